@@ -0,0 +1,175 @@
+use std::{cell::RefCell, rc::Rc};
+
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+
+type SharedBuild<T> = Shared<LocalBoxFuture<'static, Result<Rc<T>, String>>>;
+
+/// Keeps the value built for a key (a hash of the config), so it's built once and reused by later requests.
+///
+/// The build itself is shared: requests arriving while the value is being built wait for the same build, instead of
+/// starting their own.
+pub struct BuildCache<T> {
+  current: Option<(u64, SharedBuild<T>)>,
+}
+
+impl<T> Default for BuildCache<T> {
+  fn default() -> Self {
+    Self { current: None }
+  }
+}
+
+/// The result of a cache lookup.
+pub struct Cached<T> {
+  pub value: Rc<T>,
+  /// The value built for the previous key, when this lookup replaced it. It's returned only once, so it can be retired.
+  pub replaced: Option<Rc<T>>,
+}
+
+/// Returns the value built for `key`, and builds it with `build` if needed.
+///
+/// A failed build isn't kept, so the next lookup tries again.
+pub async fn get_or_build<T: 'static>(
+  cache: &RefCell<BuildCache<T>>,
+  key: u64,
+  build: impl FnOnce() -> LocalBoxFuture<'static, Result<Rc<T>, String>>,
+) -> Result<Cached<T>, String> {
+  let (shared_build, replaced) = {
+    let mut cache = cache.borrow_mut();
+
+    match &cache.current {
+      Some((current_key, shared_build)) if *current_key == key => (shared_build.clone(), None),
+      _ => {
+        let replaced = cache
+          .current
+          .take()
+          .and_then(|(_, shared_build)| shared_build.peek().cloned())
+          .and_then(Result::ok);
+        let shared_build = build().shared();
+        cache.current = Some((key, shared_build.clone()));
+
+        (shared_build, replaced)
+      }
+    }
+  };
+
+  match shared_build.await {
+    Ok(value) => Ok(Cached { value, replaced }),
+    Err(e) => {
+      let mut cache = cache.borrow_mut();
+
+      if matches!(&cache.current, Some((current_key, _)) if *current_key == key) {
+        cache.current = None;
+      }
+
+      Err(e)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+  };
+
+  use futures::executor::block_on;
+
+  use super::*;
+
+  /// Yields once to the executor, so other lookups run while a build is in progress.
+  struct YieldNow(bool);
+
+  impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+      if self.0 {
+        return Poll::Ready(());
+      }
+
+      self.0 = true;
+      cx.waker().wake_by_ref();
+
+      Poll::Pending
+    }
+  }
+
+  fn build(
+    builds: &Rc<Cell<usize>>,
+    value: Result<&'static str, &'static str>,
+  ) -> impl FnOnce() -> LocalBoxFuture<'static, Result<Rc<&'static str>, String>> {
+    let builds = builds.clone();
+
+    move || {
+      builds.set(builds.get() + 1);
+
+      async move {
+        YieldNow(false).await;
+
+        value.map(Rc::new).map_err(ToString::to_string)
+      }
+      .boxed_local()
+    }
+  }
+
+  #[test]
+  fn should_reuse_value_for_same_key() {
+    let cache = RefCell::new(BuildCache::default());
+    let builds = Rc::new(Cell::new(0));
+
+    let first = block_on(get_or_build(&cache, 1, build(&builds, Ok("a")))).unwrap();
+    let second = block_on(get_or_build(&cache, 1, build(&builds, Ok("b")))).unwrap();
+
+    assert_eq!(builds.get(), 1);
+    assert!(Rc::ptr_eq(&first.value, &second.value));
+    assert!(first.replaced.is_none());
+    assert!(second.replaced.is_none());
+  }
+
+  #[test]
+  fn should_rebuild_and_return_replaced_value_when_key_changes() {
+    let cache = RefCell::new(BuildCache::default());
+    let builds = Rc::new(Cell::new(0));
+
+    let first = block_on(get_or_build(&cache, 1, build(&builds, Ok("a")))).unwrap();
+    let second = block_on(get_or_build(&cache, 2, build(&builds, Ok("b")))).unwrap();
+    let third = block_on(get_or_build(&cache, 2, build(&builds, Ok("c")))).unwrap();
+
+    assert_eq!(builds.get(), 2);
+    assert_eq!(*second.value, "b");
+    assert!(Rc::ptr_eq(&second.replaced.unwrap(), &first.value));
+    assert!(third.replaced.is_none());
+  }
+
+  #[test]
+  fn should_share_build_between_concurrent_lookups() {
+    let cache = RefCell::new(BuildCache::default());
+    let builds = Rc::new(Cell::new(0));
+
+    let (first, second) = block_on(async {
+      futures::join!(
+        get_or_build(&cache, 1, build(&builds, Ok("a"))),
+        get_or_build(&cache, 1, build(&builds, Ok("b"))),
+      )
+    });
+
+    assert_eq!(builds.get(), 1);
+    assert!(Rc::ptr_eq(&first.unwrap().value, &second.unwrap().value));
+  }
+
+  #[test]
+  fn should_retry_failed_build() {
+    let cache = RefCell::new(BuildCache::default());
+    let builds = Rc::new(Cell::new(0));
+
+    let failed = block_on(get_or_build(&cache, 1, build(&builds, Err("failed"))));
+    let retried = block_on(get_or_build(&cache, 1, build(&builds, Ok("a")))).unwrap();
+
+    assert_eq!(failed.err(), Some("failed".to_string()));
+    assert_eq!(builds.get(), 2);
+    assert_eq!(*retried.value, "a");
+  }
+}
@@ -1,9 +1,10 @@
 use conductor_common::http::{header::*, StatusCode};
-use conductor_tracing::{otel_attrs::*, trace_id::generate_trace_id};
-use fastrace::{
-  collector::{SpanContext, SpanId},
-  Span,
+use conductor_engine::gateway::ConductorGatewayRouteData;
+use conductor_tracing::{
+  otel_attrs::*,
+  propagation::{accept_request_id, generate_request_id, PropagationConfig},
 };
+use fastrace::Span;
 use worker::*;
 
 #[inline]
@@ -12,7 +13,7 @@ fn strip_http_flavor(value: &String) -> String {
 }
 
 #[inline]
-fn build_request_properties(req: &Request) -> Vec<(&'static str, String)> {
+fn build_request_properties(req: &Request, request_id: String) -> Vec<(&'static str, String)> {
   let headers = req.headers();
   let user_agent = headers
     .get(USER_AGENT.as_str())
@@ -25,11 +26,6 @@ fn build_request_properties(req: &Request) -> Vec<(&'static str, String)> {
   let http_method = req.method().to_string();
   // @expected: it only panics if we are not running in a CF context, should be safe.
   let cf_info = req.cf().unwrap();
-  let request_id = headers
-    .get("x-request-id")
-    .unwrap()
-    .or_else(|| headers.get("cf-ray").unwrap())
-    .unwrap_or_default();
   let http_flavor = strip_http_flavor(&cf_info.http_protocol());
   let http_scheme = url.scheme().to_owned();
   // @expected: unwraps only in special cases where "data:text" is used.
@@ -63,16 +59,44 @@ fn build_request_properties(req: &Request) -> Vec<(&'static str, String)> {
   ]
 }
 
+/// Returns the id of the request, from the configured header when its value is accepted, or from the `cf-ray` header
+/// set by Cloudflare.
 #[inline]
-pub fn build_request_root_span(tenant_id: u32, endpoint_identifier: &str, req: &Request) -> Span {
+pub fn resolve_request_id(propagation: &PropagationConfig, req: &Request) -> String {
+  let headers = req.headers();
+
+  headers
+    .get(&propagation.request_id_header)
+    .ok()
+    .flatten()
+    .filter(|request_id| accept_request_id(request_id).is_some())
+    .or_else(|| headers.get("cf-ray").ok().flatten())
+    .unwrap_or_else(generate_request_id)
+}
+
+#[inline]
+pub fn build_request_root_span(
+  route_data: &ConductorGatewayRouteData,
+  request_id: String,
+  req: &Request,
+) -> Span {
   let method = req.method().to_string();
   let span_name = format!("HTTP {} {}", method, req.path());
-  let mut properties: Vec<(&str, String)> = build_request_properties(req);
-  properties.push((CONDUCTOR_ENDPOINT, endpoint_identifier.to_owned()));
+  let mut properties: Vec<(&str, String)> = build_request_properties(req, request_id);
+  properties.push((CONDUCTOR_ENDPOINT, route_data.endpoint.clone()));
 
-  let span_context = SpanContext::new(generate_trace_id(tenant_id), SpanId::default());
-
-  Span::root(span_name, span_context).with_properties(|| properties)
+  // Continues the trace of the incoming request, if any.
+  let headers = req.headers();
+  route_data
+    .propagation
+    .root_span(
+      span_name,
+      route_data.tenant_id,
+      &route_data.endpoint,
+      route_data.sampler.as_ref(),
+      |name| headers.get(name).ok().flatten(),
+    )
+    .with_properties(|| properties)
 }
 
 #[inline]
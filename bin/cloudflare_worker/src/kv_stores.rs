@@ -0,0 +1,145 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use conductor_common::kv::{
+  set_key_value_store_resolver, KeyValueBinding, KeyValueStore, KeyValueStoreError,
+};
+use worker::{kv::KvStore, send::SendWrapper, Cache, Env, Response, Url};
+
+/// The Cache API only accepts URLs as keys, so keys are added as a query parameter of this URL.
+static CACHE_KEY_BASE_URL: &str = "https://conductor.cache/";
+
+/// Makes the Workers KV namespaces and the caches of the Cache API available to the gateway, based on the bindings of the Worker.
+pub fn set_resolver(env: &Env) {
+  let env = SendWrapper::new(env.clone());
+
+  set_key_value_store_resolver(Box::new(move |binding| match binding {
+    KeyValueBinding::WorkersKv(name) => match env.kv(name) {
+      Ok(store) => Some(Arc::new(WorkersKvStore {
+        binding: name.clone(),
+        store: SendWrapper::new(store),
+      }) as Arc<dyn KeyValueStore>),
+      Err(e) => {
+        tracing::error!("failed to access Workers KV binding '{}': {}", name, e);
+
+        None
+      }
+    },
+    KeyValueBinding::CacheApi(cache_name) => Some(Arc::new(CacheApiStore {
+      cache_name: cache_name.clone(),
+    })),
+  }));
+}
+
+pub struct WorkersKvStore {
+  binding: String,
+  store: SendWrapper<KvStore>,
+}
+
+impl Debug for WorkersKvStore {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("WorkersKvStore")
+      .field("binding", &self.binding)
+      .finish()
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl KeyValueStore for WorkersKvStore {
+  async fn get(&self, key: &str) -> Result<Option<String>, KeyValueStoreError> {
+    self
+      .store
+      .get(key)
+      .text()
+      .await
+      .map_err(|e| KeyValueStoreError::ReadFailed {
+        key: key.to_string(),
+        message: e.to_string(),
+      })
+  }
+
+  async fn put(
+    &self,
+    key: &str,
+    value: &str,
+    ttl: Option<Duration>,
+  ) -> Result<(), KeyValueStoreError> {
+    let to_error = |e: worker::kv::KvError| KeyValueStoreError::WriteFailed {
+      key: key.to_string(),
+      message: e.to_string(),
+    };
+    let mut put = self.store.put(key, value).map_err(to_error)?;
+
+    if let Some(ttl) = ttl {
+      // Workers KV doesn't accept expiration TTLs shorter than 60 seconds.
+      put = put.expiration_ttl(ttl.as_secs().max(60));
+    }
+
+    put.execute().await.map_err(to_error)
+  }
+}
+
+#[derive(Debug)]
+pub struct CacheApiStore {
+  cache_name: Option<String>,
+}
+
+impl CacheApiStore {
+  async fn open(&self) -> Cache {
+    match &self.cache_name {
+      Some(cache_name) => Cache::open(cache_name.clone()).await,
+      None => Cache::default(),
+    }
+  }
+
+  fn cache_key(key: &str) -> worker::Result<String> {
+    let mut url = Url::parse(CACHE_KEY_BASE_URL)?;
+    url.query_pairs_mut().append_pair("key", key);
+
+    Ok(url.to_string())
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl KeyValueStore for CacheApiStore {
+  async fn get(&self, key: &str) -> Result<Option<String>, KeyValueStoreError> {
+    let to_error = |e: worker::Error| KeyValueStoreError::ReadFailed {
+      key: key.to_string(),
+      message: e.to_string(),
+    };
+    let cache_key = Self::cache_key(key).map_err(to_error)?;
+
+    match self.open().await.get(cache_key, false).await {
+      Ok(Some(mut response)) => response.text().await.map(Some).map_err(to_error),
+      Ok(None) => Ok(None),
+      Err(e) => Err(to_error(e)),
+    }
+  }
+
+  async fn put(
+    &self,
+    key: &str,
+    value: &str,
+    ttl: Option<Duration>,
+  ) -> Result<(), KeyValueStoreError> {
+    let to_error = |e: worker::Error| KeyValueStoreError::WriteFailed {
+      key: key.to_string(),
+      message: e.to_string(),
+    };
+    let cache_key = Self::cache_key(key).map_err(to_error)?;
+    let mut response = Response::ok(value).map_err(to_error)?;
+
+    if let Some(ttl) = ttl {
+      response
+        .headers_mut()
+        .set("Cache-Control", &format!("max-age={}", ttl.as_secs()))
+        .map_err(to_error)?;
+    }
+
+    self
+      .open()
+      .await
+      .put(cache_key, response)
+      .await
+      .map_err(to_error)
+  }
+}
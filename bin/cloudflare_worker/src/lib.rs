@@ -1,14 +1,25 @@
+mod gateway_cache;
 mod http_tracing;
-use std::str::FromStr;
+mod kv_stores;
+use std::{
+  cell::RefCell,
+  collections::hash_map::DefaultHasher,
+  hash::{Hash, Hasher},
+  rc::Rc,
+  str::FromStr,
+  time::{Duration, UNIX_EPOCH},
+};
 
 use conductor_common::http::{
   ConductorHttpRequest, ConductorHttpResponse, HeaderName, HeaderValue, HttpHeadersMap, Method,
 };
-use conductor_config::{parse_config_contents, LoggerConfig};
+use conductor_config::{try_parse_config_contents, LoggerConfig};
 use conductor_engine::gateway::{ConductorGateway, GatewayError};
 use conductor_tracing::fastrace_mgr::FastraceManager;
 use fastrace::{collector::Config, trace};
-use http_tracing::{build_request_root_span, build_response_properties};
+use futures::future::FutureExt;
+use gateway_cache::{get_or_build, BuildCache, Cached};
+use http_tracing::{build_request_root_span, build_response_properties, resolve_request_id};
 use std::panic;
 use tracing::subscriber::set_global_default;
 use tracing_subscriber::prelude::*;
@@ -55,60 +66,127 @@ fn transform_res(conductor_response: ConductorHttpResponse) -> Result<Response>
   })
 }
 
-async fn run_flow(req: Request, env: Env, minitrace_mgr: &mut FastraceManager) -> Result<Response> {
-  let conductor_config_str = env.var("CONDUCTOR_CONFIG").map(|v| v.to_string());
+/// A gateway built from the config of the worker, kept in the isolate global state so it's reused across requests.
+struct CachedGateway {
+  /// The logger built from the `logger` config, set as the default subscriber while a request is handled.
+  logger: tracing::Dispatch,
+  gateway: ConductorGateway,
+  tracing_manager: FastraceManager,
+}
+
+impl CachedGateway {
+  /// Stops the background work of a gateway that was replaced, and ships the spans it collected.
+  async fn retire(&self) {
+    self.gateway.shutdown().await;
+    self.tracing_manager.flush().await;
+  }
+}
+
+thread_local! {
+  static GATEWAY: Rc<RefCell<BuildCache<CachedGateway>>> =
+    Rc::new(RefCell::new(BuildCache::default()));
+}
+
+/// Returns the gateway built for the current config, and builds it on the first request (or after a config change).
+async fn get_or_create_gateway(env: &Env) -> std::result::Result<Cached<CachedGateway>, String> {
+  let conductor_config_str = env
+    .var("CONDUCTOR_CONFIG")
+    .map(|v| v.to_string())
+    .map_err(|e| format!("failed to read conductor config: {}", e))?;
+
+  // The hash only identifies the config within the isolate, so it doesn't need to be stable across builds.
+  let mut hasher = DefaultHasher::new();
+  conductor_config_str.hash(&mut hasher);
+  let config_hash = hasher.finish();
+
+  let cache = GATEWAY.with(Rc::clone);
+  let env = env.clone();
+
+  get_or_build(&cache, config_hash, move || {
+    create_gateway(env, conductor_config_str).boxed_local()
+  })
+  .await
+}
+
+async fn create_gateway(
+  env: Env,
+  conductor_config_str: String,
+) -> std::result::Result<Rc<CachedGateway>, String> {
   let get_env_value = |key: &str| env.var(key).map(|s| s.to_string()).ok();
+  let conductor_config = match try_parse_config_contents(
+    conductor_config_str,
+    conductor_config::ConfigFormat::Yaml,
+    get_env_value,
+  ) {
+    Ok((conductor_config, warnings)) => {
+      for warning in warnings {
+        tracing::warn!("config warning: {}", warning);
+      }
 
-  match conductor_config_str {
-    Ok(conductor_config_str) => {
-      let conductor_config = parse_config_contents(
-        conductor_config_str,
-        conductor_config::ConfigFormat::Yaml,
-        get_env_value,
-      );
-
-      let logger_config = conductor_config.logger.clone().unwrap_or_default();
-      let logger = conductor_logger::logger_layer::build_logger(
-        &logger_config.format,
-        &logger_config.filter,
-        logger_config.print_performance_info,
-      )
-      .unwrap_or_else(|e| panic!("failed to build logger: {}", e));
-
-      let result = match ConductorGateway::new(&conductor_config, minitrace_mgr).await {
-        Ok(gw) => {
-          let _guard =
-            tracing::subscriber::set_default(tracing_subscriber::registry().with(logger));
-          let root_reporter = minitrace_mgr.build_root_reporter();
-          fastrace::set_reporter(root_reporter, Config::default());
-
-          let url = req.url()?;
-
-          match gw.match_route(&url) {
-            Ok(route_data) => {
-              let root_span =
-                build_request_root_span(route_data.tenant_id, &route_data.endpoint, &req);
-              let _guard = root_span.set_local_parent();
-              let conductor_req = transform_req(&url, req).await?;
-              let conductor_response = ConductorGateway::execute(conductor_req, route_data).await;
-              let http_response = transform_res(conductor_response);
-              let res_properties = build_response_properties(&http_response);
-              let _ = root_span.with_properties(|| res_properties);
-
-              http_response
-            }
-            Err(GatewayError::MissingEndpoint(_)) => {
-              Response::error("failed to locate endpoint".to_string(), 404)
-            }
-            Err(e) => Response::error(e.to_string(), 500),
-          }
-        }
-        Err(_) => Response::error("gateway is not ready".to_string(), 500),
-      };
-
-      result
+      conductor_config
     }
-    Err(e) => Response::error(format!("failed to read conductor config: {}", e), 500),
+    Err(e) => return Err(format!("failed to load conductor config: {}", e)),
+  };
+
+  let logger_config = conductor_config.logger.clone().unwrap_or_default();
+  let logger = conductor_logger::logger_layer::build_logger(
+    &logger_config.format,
+    &logger_config.filter,
+    logger_config.print_performance_info,
+  )
+  .map_err(|e| format!("failed to build logger: {}", e))?;
+
+  // Plugins and sources resolve the KV namespaces and caches they use while the gateway is created.
+  kv_stores::set_resolver(&env);
+
+  let mut tracing_manager = FastraceManager::default();
+  let gateway = ConductorGateway::new(&conductor_config, &mut tracing_manager)
+    .await
+    .map_err(|e| {
+      tracing::error!("failed to initialize gateway: {}", e);
+
+      "gateway is not ready".to_string()
+    })?;
+
+  Ok(Rc::new(CachedGateway {
+    logger: tracing::Dispatch::new(tracing_subscriber::registry().with(logger)),
+    gateway,
+    tracing_manager,
+  }))
+}
+
+async fn run_flow(req: Request, cached: &CachedGateway) -> Result<Response> {
+  let _guard = tracing::dispatcher::set_default(&cached.logger);
+  let root_reporter = cached.tracing_manager.build_root_reporter();
+  fastrace::set_reporter(root_reporter, Config::default());
+
+  let url = req.url()?;
+
+  match cached.gateway.match_route(&url) {
+    Ok(route_data) => {
+      let request_id = resolve_request_id(&route_data.propagation, &req);
+      let root_span = build_request_root_span(route_data, request_id.clone(), &req);
+      let _guard = root_span.set_local_parent();
+      let mut conductor_req = transform_req(&url, req).await?;
+
+      // The gateway uses the same request id, even when it's not set by the client or its id isn't accepted.
+      if let (Ok(name), Ok(value)) = (
+        HeaderName::from_str(&route_data.propagation.request_id_header),
+        HeaderValue::from_str(&request_id),
+      ) {
+        conductor_req.headers.insert(name, value);
+      }
+      let conductor_response = ConductorGateway::execute(conductor_req, route_data).await;
+      let http_response = transform_res(conductor_response);
+      let res_properties = build_response_properties(&http_response);
+      let _ = root_span.with_properties(|| res_properties);
+
+      http_response
+    }
+    Err(GatewayError::MissingEndpoint(_)) => {
+      Response::error("failed to locate endpoint".to_string(), 404)
+    }
+    Err(e) => Response::error(e.to_string(), 500),
   }
 }
 
@@ -130,13 +208,28 @@ fn start() {
 
 #[event(fetch, respond_with_errors)]
 async fn main(req: Request, env: Env, context: Context) -> Result<Response> {
-  let mut minitrace_mgr = FastraceManager::default();
-  let result = run_flow(req, env, &mut minitrace_mgr).await;
+  let Cached {
+    value: cached,
+    replaced,
+  } = match get_or_create_gateway(&env).await {
+    Ok(cached) => cached,
+    Err(e) => return Response::error(e, 500),
+  };
+
+  if let Some(replaced) = replaced {
+    context.wait_until(async move { replaced.retire().await });
+  }
 
-  match result {
+  match run_flow(req, &cached).await {
     Ok(response) => {
+      let now = UNIX_EPOCH + Duration::from_millis(Date::now().as_millis());
+
+      // Schemas are reloaded (and plugins send their pending data) once the response is sent, so requests never wait
+      // for it.
       context.wait_until(async move {
-        minitrace_mgr.shutdown().await;
+        cached.gateway.refresh_schemas(now).await;
+        cached.gateway.flush_plugins().await;
+        cached.tracing_manager.flush().await;
       });
 
       Ok(response)
@@ -0,0 +1,126 @@
+use std::path::Path;
+
+use conductor_config::{
+  serialize_config, try_load_config, validate::validate_config, ConductorConfig, ConfigError,
+  ConfigFormat,
+};
+use conductor_engine::gateway::ConductorGateway;
+
+const DEFAULT_CONFIG_FILE_PATH: &str = "./config.json";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+  /// Runs the gateway server (default).
+  Run { config_file_path: String },
+  /// Loads the config file, runs all static checks and exits.
+  Validate { config_file_path: String },
+  /// Prints the config file after interpolation, and exits.
+  PrintConfig { config_file_path: String },
+}
+
+impl Command {
+  /// Parses the process arguments (without the binary name).
+  ///
+  /// `conductor [config]` keeps working as before, and runs the gateway.
+  pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+    let first = args.next();
+    let config_file_path =
+      |path: Option<String>| path.unwrap_or_else(|| DEFAULT_CONFIG_FILE_PATH.to_string());
+
+    match first.as_deref() {
+      Some("validate") => Command::Validate {
+        config_file_path: config_file_path(args.next()),
+      },
+      Some("print-config") => Command::PrintConfig {
+        config_file_path: config_file_path(args.next()),
+      },
+      _ => Command::Run {
+        config_file_path: config_file_path(first),
+      },
+    }
+  }
+}
+
+/// Loads the config file, and prints the loading errors and warnings.
+async fn load_and_report(config_file_path: &String) -> Option<ConductorConfig> {
+  match try_load_config(config_file_path, |key| std::env::var(key).ok()).await {
+    Ok((config, warnings)) => {
+      for warning in warnings {
+        eprintln!("warning: {}", warning);
+      }
+
+      Some(config)
+    }
+    Err(ConfigError::InterpolationFailed { errors }) => {
+      for error in errors {
+        eprintln!("error: {}", error);
+      }
+
+      None
+    }
+    Err(e) => {
+      eprintln!("error: {}", e);
+
+      None
+    }
+  }
+}
+
+/// Validates the config file, prints all diagnostics and returns `true` if no errors were found.
+///
+/// Schema sources are loaded in offline mode: local files and inline schemas are parsed, remote sources are skipped.
+pub async fn validate(config_file_path: &String) -> bool {
+  let config = match load_and_report(config_file_path).await {
+    Some(config) => config,
+    None => {
+      eprintln!("❌ failed to load config file \"{}\"", config_file_path);
+
+      return false;
+    }
+  };
+
+  let mut diagnostics = validate_config(&config);
+  diagnostics.extend(ConductorGateway::validate_sources_offline(&config).await);
+
+  for diagnostic in diagnostics.iter() {
+    eprintln!("{}", diagnostic);
+  }
+
+  let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+
+  if errors > 0 {
+    eprintln!(
+      "❌ config file \"{}\" is invalid: found {} error(s)",
+      config_file_path, errors
+    );
+
+    false
+  } else {
+    println!("✅ config file \"{}\" is valid", config_file_path);
+
+    true
+  }
+}
+
+/// Prints the fully interpolated config file, in the same format as the input file.
+///
+/// Returns `false` if the config file could not be loaded.
+pub async fn print_config(config_file_path: &String) -> bool {
+  let format = match ConfigFormat::try_from_path(Path::new(config_file_path)) {
+    Ok(format) => format,
+    Err(e) => {
+      eprintln!("error: {}", e);
+
+      return false;
+    }
+  };
+
+  match load_and_report(config_file_path).await {
+    Some(config) => {
+      println!("{}", serialize_config(&config, &format));
+
+      true
+    }
+    None => false,
+  }
+}
@@ -1,23 +1,30 @@
+pub mod cli;
 mod minitrace_actix;
+pub mod reload;
+pub mod shutdown;
+pub mod tls;
 
-use std::sync::Arc;
+use std::{path::Path, sync::Arc, time::SystemTime};
 
 use actix_web::{
   dev::Response,
   middleware::Compat,
   route,
   web::{self, Bytes},
-  App, HttpRequest, HttpResponse, HttpServer, Responder, Scope,
+  App, HttpMessage, HttpRequest, HttpResponse, HttpServer, Responder, Scope,
 };
 use conductor_common::http::{ConductorHttpRequest, ConductorHttpResponse, HttpHeadersMap};
 
-use crate::minitrace_actix::MinitraceTransform;
+use crate::{
+  minitrace_actix::MinitraceTransform,
+  reload::{ActiveRoute, ReloadableGateway},
+};
 
-use conductor_config::load_config;
-use conductor_engine::gateway::{ConductorGateway, ConductorGatewayRouteData};
+use conductor_config::try_load_config;
+use conductor_engine::{gateway::ConductorGateway, readiness::check_readiness};
 use conductor_tracing::fastrace_mgr::FastraceManager;
 use fastrace::{collector::Config, trace};
-use tracing::{debug, error};
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, registry};
 
 use actix_web::http::{
@@ -47,7 +54,24 @@ fn convert_status_code(status: ConductorStatusCode) -> ActixStatusCode {
 }
 
 pub async fn run_services(config_file_path: &String) -> std::io::Result<()> {
-  let config = load_config(config_file_path, |key| std::env::var(key).ok()).await;
+  let config = match try_load_config(config_file_path, |key| std::env::var(key).ok()).await {
+    Ok((config, warnings)) => {
+      for warning in warnings {
+        warn!("config warning: {}", warning);
+      }
+
+      config
+    }
+    Err(e) => {
+      error!("failed to load config file: {}", e);
+
+      return Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        e.to_string(),
+      ));
+    }
+  };
+
   let logger_config = config.logger.clone().unwrap_or_default();
   let logger = conductor_logger::logger_layer::build_logger(
     &logger_config.format,
@@ -65,35 +89,115 @@ pub async fn run_services(config_file_path: &String) -> std::io::Result<()> {
       let tracing_reporter = tracing_manager.build_root_reporter();
       fastrace::set_reporter(tracing_reporter, Config::default());
 
-      let gateway = Arc::new(gw);
+      let gateway = Arc::new(ReloadableGateway::new(
+        config_file_path.clone(),
+        config.server.clone(),
+        config.included_paths.clone(),
+        gw,
+        tracing_manager,
+      ));
+      let config_watcher = actix_web::rt::spawn(gateway.clone().watch());
+      let server_config = config.server.clone().unwrap_or_default();
+      let shutdown_timeout = server_config
+        .shutdown_timeout
+        .unwrap_or(shutdown::DEFAULT_SHUTDOWN_TIMEOUT);
+
+      let app_gateway = web::Data::from(gateway.clone());
       let http_server = HttpServer::new(move || {
-        let mut router = App::new();
+        App::new()
+          .app_data(app_gateway.clone())
+          .service(health_handler)
+          .service(readiness_handler)
+          .service(
+            Scope::new("")
+              .wrap(Compat::new(MinitraceTransform::new()))
+              .default_service(
+                web::route().to(handler), // handle all requests with this handler
+              ),
+          )
+      })
+      // Signals are handled below, so the readiness endpoint can report the shutdown before connections are closed.
+      .disable_signals()
+      .shutdown_timeout(shutdown_timeout.as_secs());
+
+      let server_address = format!("{}:{}", server_config.host, server_config.port);
+      debug!("server is trying to listen on {:?}", server_address);
 
-        for conductor_route in gateway.routes.iter() {
-          let child_router = Scope::new(conductor_route.base_path.as_str())
-            .wrap(Compat::new(MinitraceTransform::new()))
-            .app_data(web::Data::new(conductor_route.route_data.clone()))
-            .service(Scope::new("").default_service(
-              web::route().to(handler), // handle all requests with this handler
-            ));
+      let http_server = match &server_config.tls {
+        Some(tls_config) => {
+          let base_path = Path::new(config_file_path)
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+          let (rustls_config, cert_resolver) = tls::build_server_config(tls_config, base_path)
+            .map_err(|e| {
+              error!("failed to configure TLS: {}", e);
 
-          router = router.service(child_router)
+              std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
+            })?;
+          actix_web::rt::spawn(cert_resolver.watch());
+
+          // ALPN is configured by actix-web, and negotiates HTTP/2 when the client supports it.
+          http_server.bind_rustls_0_23(
+            (server_config.host.clone(), server_config.port),
+            rustls_config,
+          )?
         }
+        None => http_server.bind((server_config.host.clone(), server_config.port))?,
+      };
 
-        router.service(health_handler)
-      });
+      // The health check listener is a separate server, so it never exposes the GraphQL endpoints over plain HTTP.
+      // It serves both the health and the readiness endpoints.
+      let health_server = match server_config.health_check_port {
+        Some(health_check_port) => {
+          debug!(
+            "health check server is trying to listen on {}:{}",
+            server_config.host, health_check_port
+          );
 
-      let server_config = config.server.clone().unwrap_or_default();
+          let app_gateway = web::Data::from(gateway.clone());
+          let health_server = HttpServer::new(move || {
+            App::new()
+              .app_data(app_gateway.clone())
+              .service(health_handler)
+              .service(readiness_handler)
+          })
+          .workers(1)
+          .disable_signals()
+          .bind((server_config.host.clone(), health_check_port))?
+          .run();
+          let handle = health_server.handle();
+          actix_web::rt::spawn(health_server);
 
-      let server_address = format!("{}:{}", server_config.host, server_config.port);
-      debug!("server is trying to listen on {:?}", server_address);
+          Some(handle)
+        }
+        None => None,
+      };
+
+      let server = http_server.run();
+      let server_handle = server.handle();
+      let shutdown_gateway = gateway.clone();
+
+      actix_web::rt::spawn(async move {
+        let reason = shutdown::shutdown_signal().await;
+        info!(
+          "shutting down ({}), waiting up to {:?} for in-flight requests",
+          reason, shutdown_timeout
+        );
 
-      let server_instance = http_server
-        .bind((server_config.host, server_config.port))?
-        .run()
-        .await;
+        shutdown_gateway.begin_shutdown();
+        // Stops accepting new connections, and waits for in-flight requests to complete.
+        server_handle.stop(true).await;
+      });
+
+      let server_instance = server.await;
+      config_watcher.abort();
 
-      tracing_manager.shutdown().await;
+      if let Some(health_server) = health_server {
+        health_server.stop(true).await;
+      }
+
+      gateway.shutdown().await;
+      info!("server stopped");
 
       server_instance
     }
@@ -110,6 +214,26 @@ async fn health_handler() -> impl Responder {
   Response::ok()
 }
 
+#[route("/_readiness", method = "GET", method = "HEAD")]
+async fn readiness_handler(gateway: web::Data<ReloadableGateway>) -> impl Responder {
+  let mut report = check_readiness(
+    &gateway.current(),
+    gateway.readiness_max_staleness(),
+    SystemTime::now(),
+  );
+
+  if gateway.is_shutting_down() {
+    report.ready = false;
+    report.shutting_down = true;
+  }
+
+  if report.ready {
+    HttpResponse::Ok().json(report)
+  } else {
+    HttpResponse::ServiceUnavailable().json(report)
+  }
+}
+
 #[trace(name = "transform_request")]
 fn transform_req(req: HttpRequest, body: Bytes) -> ConductorHttpRequest {
   let mut headers_map = HttpHeadersMap::new();
@@ -143,14 +267,19 @@ fn transform_res(conductor_response: ConductorHttpResponse) -> HttpResponse {
   response.body(conductor_response.body)
 }
 
-async fn handler(
-  req: HttpRequest,
-  body: Bytes,
-  route_data: web::Data<Arc<ConductorGatewayRouteData>>,
-) -> impl Responder {
-  let conductor_request = transform_req(req, body);
-  let conductor_response: ConductorHttpResponse =
-    ConductorGateway::execute(conductor_request, &route_data).await;
+async fn handler(req: HttpRequest, body: Bytes) -> impl Responder {
+  // The route is resolved by the tracing middleware, based on the gateway instance active when the request arrived.
+  // Holding it keeps that instance running until the request is done.
+  let route = req.extensions().get::<ActiveRoute>().cloned();
+
+  match route {
+    Some(route) => {
+      let conductor_request = transform_req(req, body);
+      let conductor_response: ConductorHttpResponse =
+        ConductorGateway::execute(conductor_request, &route.route_data).await;
 
-  transform_res(conductor_response)
+      transform_res(conductor_response)
+    }
+    None => HttpResponse::NotFound().finish(),
+  }
 }
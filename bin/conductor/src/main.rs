@@ -1,4 +1,7 @@
-use conductor::run_services;
+use conductor::{
+  cli::{self, Command},
+  run_services,
+};
 use conductor_config::LoggerConfig;
 use tracing::subscriber::set_global_default;
 use tracing_subscriber::layer::SubscriberExt;
@@ -15,9 +18,21 @@ async fn main() -> std::io::Result<()> {
   set_global_default(tracing_subscriber::registry().with(global_logger))
     .expect("failed to set global default logger");
 
-  let config_file_path = std::env::args()
-    .nth(1)
-    .unwrap_or("./config.json".to_string());
+  match Command::from_args(std::env::args().skip(1)) {
+    Command::Run { config_file_path } => run_services(&config_file_path).await,
+    Command::Validate { config_file_path } => {
+      if !cli::validate(&config_file_path).await {
+        std::process::exit(1);
+      }
 
-  run_services(&config_file_path).await
+      Ok(())
+    }
+    Command::PrintConfig { config_file_path } => {
+      if !cli::print_config(&config_file_path).await {
+        std::process::exit(1);
+      }
+
+      Ok(())
+    }
+  }
 }
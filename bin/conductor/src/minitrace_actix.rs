@@ -1,21 +1,20 @@
 use actix_web::{
   dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-  http::{header::USER_AGENT, StatusCode, Version},
-  web, Error, ResponseError,
+  http::{
+    header::{HeaderName, HeaderValue, USER_AGENT},
+    StatusCode, Version,
+  },
+  web, Error, HttpMessage, ResponseError,
 };
 use conductor_engine::gateway::ConductorGatewayRouteData;
-use conductor_tracing::{otel_attrs::*, trace_id::generate_trace_id};
-use fastrace::{
-  collector::{SpanContext, SpanId},
-  Span,
-};
+use conductor_tracing::{otel_attrs::*, propagation::accept_request_id};
+use fastrace::Span;
 use futures_util::future::LocalBoxFuture;
-use std::{
-  future::{ready, Ready},
-  sync::Arc,
-};
+use std::future::{ready, Ready};
 use ulid::Ulid;
 
+use crate::reload::ReloadableGateway;
+
 pub struct MinitraceTransform;
 
 impl MinitraceTransform {
@@ -42,21 +41,61 @@ where
 }
 
 #[inline]
-fn build_request_root_span(req: &ServiceRequest) -> Span {
-  let endpoint_data = req
-    .app_data::<web::Data<Arc<ConductorGatewayRouteData>>>()
-    .expect("endpoint data not found, failed to setup tracing");
-
+fn build_request_root_span(
+  req: &ServiceRequest,
+  request_id: String,
+  endpoint_data: &ConductorGatewayRouteData,
+) -> Span {
   let span_name = format!("HTTP {} {}", req.method(), req.path());
-  let mut properties: Vec<(&str, String)> = build_request_properties(req);
+  let mut properties: Vec<(&str, String)> = build_request_properties(req, request_id);
   properties.push((CONDUCTOR_ENDPOINT, endpoint_data.endpoint.clone()));
 
-  let span_context = SpanContext::new(
-    generate_trace_id(endpoint_data.tenant_id),
-    SpanId::default(),
-  );
+  // Continues the trace of the incoming request, if any.
+  endpoint_data
+    .propagation
+    .root_span(
+      span_name,
+      endpoint_data.tenant_id,
+      &endpoint_data.endpoint,
+      endpoint_data.sampler.as_ref(),
+      |name| {
+        req
+          .headers()
+          .get(name)
+          .and_then(|value| value.to_str().ok())
+          .map(ToString::to_string)
+      },
+    )
+    .with_properties(|| properties)
+}
+
+/// Returns the id of the request, from the configured header. When the header is missing or its value isn't accepted, a
+/// new id is generated and set on the request, so the gateway uses the same id.
+fn resolve_request_id(
+  req: &mut ServiceRequest,
+  endpoint_data: &ConductorGatewayRouteData,
+) -> String {
+  let header_name = &endpoint_data.propagation.request_id_header;
+
+  if let Some(request_id) = req
+    .headers()
+    .get(header_name.as_str())
+    .and_then(|value| value.to_str().ok())
+    .and_then(accept_request_id)
+  {
+    return request_id.to_string();
+  }
+
+  let request_id = gen_request_id();
+
+  if let (Ok(name), Ok(value)) = (
+    HeaderName::from_bytes(header_name.as_bytes()),
+    HeaderValue::from_str(&request_id),
+  ) {
+    req.headers_mut().insert(name, value);
+  }
 
-  Span::root(span_name, span_context).with_properties(|| properties)
+  request_id
 }
 
 #[inline]
@@ -85,7 +124,10 @@ fn gen_request_id() -> String {
 }
 
 #[inline]
-fn build_request_properties(req: &ServiceRequest) -> Vec<(&'static str, String)> {
+fn build_request_properties(
+  req: &ServiceRequest,
+  request_id: String,
+) -> Vec<(&'static str, String)> {
   let headers = req.headers();
   let user_agent = headers
     .get(USER_AGENT)
@@ -97,10 +139,6 @@ fn build_request_properties(req: &ServiceRequest) -> Vec<(&'static str, String)>
     .unwrap_or_else(|| "default".into());
   let http_method = req.method().to_string();
   let connection_info = req.connection_info();
-  let request_id = headers
-    .get("x-request-id")
-    .map(|v| v.to_str().unwrap().to_string())
-    .unwrap_or_else(gen_request_id);
 
   vec![
     (HTTP_METHOD, http_method),
@@ -205,8 +243,20 @@ where
 
   forward_ready!(service);
 
-  fn call(&self, req: ServiceRequest) -> Self::Future {
-    let root_span = build_request_root_span(&req);
+  fn call(&self, mut req: ServiceRequest) -> Self::Future {
+    // The route is resolved once per request, so the request keeps using the same gateway instance even if the config is reloaded.
+    let route = req
+      .app_data::<web::Data<ReloadableGateway>>()
+      .and_then(|gateway| gateway.route(req.path()));
+
+    let route = match route {
+      Some(route) => route,
+      None => return Box::pin(self.service.call(req)),
+    };
+
+    let request_id = resolve_request_id(&mut req, &route.route_data);
+    let root_span = build_request_root_span(&req, request_id, &route.route_data);
+    req.extensions_mut().insert(route);
     let fut = self.service.call(req);
 
     Box::pin(async move {
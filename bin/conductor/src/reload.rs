@@ -0,0 +1,395 @@
+use std::{
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, RwLock,
+  },
+  time::{Duration, Instant, SystemTime},
+};
+
+use conductor_config::{try_load_config, validate::validate_config, ConfigError, ServerConfig};
+use conductor_engine::gateway::{ConductorGateway, ConductorGatewayRouteData, GatewayError};
+use conductor_tracing::fastrace_mgr::FastraceManager;
+use fastrace::collector::Config;
+use tracing::{debug, error, info, warn};
+
+/// How often the config file (and other watched files) is checked for changes.
+pub(crate) const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a replaced gateway is kept for the requests still using it, before it's stopped.
+const RETIRE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often a replaced gateway is checked for requests still using it.
+const RETIRE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReloadError {
+  #[error("failed to load config: {0}")]
+  ConfigLoadFailed(ConfigError),
+  #[error("config is invalid: {}", .0.join(", "))]
+  InvalidConfig(Vec<String>),
+  #[error("failed to initialize gateway: {0}")]
+  GatewayInitFailed(GatewayError),
+}
+
+/// The route matched for a request, along with the gateway instance it belongs to.
+///
+/// Requests hold it until they are done, so a replaced gateway is only stopped once its in-flight requests are done.
+#[derive(Clone)]
+pub struct ActiveRoute {
+  pub gateway: Arc<ConductorGateway>,
+  pub route_data: Arc<ConductorGatewayRouteData>,
+}
+
+/// Holds the active `ConductorGateway` instance, and replaces it when the config file changes.
+///
+/// Requests are always executed against a snapshot of the gateway taken when the request arrives,
+/// so in-flight requests keep using the previous instance until they are done. The previous instance is stopped, and
+/// its tracing reporters flushed, once these requests are done.
+pub struct ReloadableGateway {
+  config_file_path: String,
+  server_config: Option<ServerConfig>,
+  gateway: RwLock<Arc<ConductorGateway>>,
+  tracing_manager: Mutex<Option<FastraceManager>>,
+  /// The files loaded with `$include` by the active config, watched along with the config file.
+  included_paths: Mutex<Vec<PathBuf>>,
+  shutting_down: AtomicBool,
+}
+
+impl ReloadableGateway {
+  pub fn new(
+    config_file_path: String,
+    server_config: Option<ServerConfig>,
+    included_paths: Vec<PathBuf>,
+    gateway: ConductorGateway,
+    tracing_manager: FastraceManager,
+  ) -> Self {
+    Self {
+      config_file_path,
+      server_config,
+      gateway: RwLock::new(Arc::new(gateway)),
+      tracing_manager: Mutex::new(Some(tracing_manager)),
+      included_paths: Mutex::new(included_paths),
+      shutting_down: AtomicBool::new(false),
+    }
+  }
+
+  /// The staleness window for the readiness endpoint. Like the rest of the `server` configuration, it's not affected by reloads.
+  pub fn readiness_max_staleness(&self) -> Option<Duration> {
+    self
+      .server_config
+      .as_ref()
+      .and_then(|server_config| server_config.readiness_max_staleness)
+  }
+
+  pub fn current(&self) -> Arc<ConductorGateway> {
+    // @expected: the lock is never held across a panic
+    self.gateway.read().unwrap().clone()
+  }
+
+  /// Matches the path of a request against the active gateway.
+  pub fn route(&self, path: &str) -> Option<ActiveRoute> {
+    let gateway = self.current();
+    let route_data = gateway.match_path(path).ok()?.clone();
+
+    Some(ActiveRoute {
+      gateway,
+      route_data,
+    })
+  }
+
+  /// Loads the config file again and builds a new gateway from it.
+  ///
+  /// The active gateway is replaced only if the new config is valid and the gateway was built successfully.
+  pub async fn reload(&self) -> Result<(), ReloadError> {
+    let (config, warnings) = try_load_config(&self.config_file_path, |key| std::env::var(key).ok())
+      .await
+      .map_err(ReloadError::ConfigLoadFailed)?;
+
+    for warning in warnings {
+      warn!("config warning: {}", warning);
+    }
+
+    let errors = validate_config(&config)
+      .into_iter()
+      .filter(|d| d.is_error())
+      .map(|d| d.to_string())
+      .collect::<Vec<_>>();
+
+    if !errors.is_empty() {
+      return Err(ReloadError::InvalidConfig(errors));
+    }
+
+    if config.server != self.server_config {
+      warn!("changes to the \"server\" configuration are ignored until the gateway is restarted");
+    }
+
+    let mut tracing_manager = FastraceManager::default();
+    let gateway = ConductorGateway::new(&config, &mut tracing_manager)
+      .await
+      .map_err(ReloadError::GatewayInitFailed)?;
+
+    fastrace::set_reporter(tracing_manager.build_root_reporter(), Config::default());
+
+    // @expected: the locks are never held across a panic
+    let previous_gateway =
+      std::mem::replace(&mut *self.gateway.write().unwrap(), Arc::new(gateway));
+    let previous_tracing_manager = self
+      .tracing_manager
+      .lock()
+      .unwrap()
+      .replace(tracing_manager);
+    *self.included_paths.lock().unwrap() = config.included_paths;
+
+    actix_web::rt::spawn(retire(previous_gateway, previous_tracing_manager));
+
+    Ok(())
+  }
+
+  /// Marks the gateway as shutting down, so the readiness endpoint stops reporting it as ready.
+  pub fn begin_shutdown(&self) {
+    self.shutting_down.store(true, Ordering::SeqCst);
+  }
+
+  pub fn is_shutting_down(&self) -> bool {
+    self.shutting_down.load(Ordering::SeqCst)
+  }
+
+  /// Stops the background work of the active gateway (schema polling), lets its plugins send their pending data, and
+  /// flushes its tracing reporters.
+  pub async fn shutdown(&self) {
+    self.begin_shutdown();
+    self.current().shutdown().await;
+
+    // @expected: the lock is never held across a panic
+    let tracing_manager = self.tracing_manager.lock().unwrap().take();
+
+    if let Some(tracing_manager) = tracing_manager {
+      tracing_manager.shutdown().await;
+    }
+  }
+
+  async fn reload_and_report(&self, reason: &str) {
+    info!("reloading gateway configuration ({})", reason);
+
+    match self.reload().await {
+      Ok(_) => info!("gateway configuration reloaded successfully"),
+      Err(e) => error!(
+        "failed to reload gateway configuration, keeping the previous one: {}",
+        e
+      ),
+    }
+  }
+
+  /// The modification times of the config file and the files it includes, used to detect changes.
+  fn watched_files_state(&self) -> Vec<(PathBuf, Option<SystemTime>)> {
+    // @expected: the lock is never held across a panic
+    let included_paths = self.included_paths.lock().unwrap().clone();
+
+    std::iter::once(PathBuf::from(&self.config_file_path))
+      .chain(included_paths)
+      .map(|path| {
+        let modified = modified_at(&path);
+
+        (path, modified)
+      })
+      .collect()
+  }
+
+  /// Watches the config file and the files it includes for changes (and listens to `SIGHUP` on unix), and reloads the
+  /// gateway when needed.
+  pub async fn watch(self: Arc<Self>) {
+    let mut last_state = self.watched_files_state();
+    let mut interval_timer = tokio::time::interval(CONFIG_WATCH_INTERVAL);
+
+    #[cfg(unix)]
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+      Ok(signal) => Some(signal),
+      Err(e) => {
+        error!(
+          "failed to listen to SIGHUP, signal based reload is disabled: {}",
+          e
+        );
+
+        None
+      }
+    };
+
+    loop {
+      #[cfg(unix)]
+      let from_signal = tokio::select! {
+        _ = interval_timer.tick() => false,
+        Some(_) = async {
+          match hangup.as_mut() {
+            Some(signal) => signal.recv().await,
+            None => std::future::pending().await,
+          }
+        } => true,
+      };
+
+      #[cfg(not(unix))]
+      let from_signal = {
+        interval_timer.tick().await;
+        false
+      };
+
+      let state = self.watched_files_state();
+
+      if from_signal {
+        self.reload_and_report("SIGHUP").await;
+      } else if state != last_state {
+        debug!(
+          "config files were modified, previous: {:?}, current: {:?}",
+          last_state, state
+        );
+
+        self.reload_and_report("config file changed").await;
+      } else {
+        continue;
+      }
+
+      // The included files may have changed with the reload.
+      last_state = self.watched_files_state();
+    }
+  }
+}
+
+/// Waits for the requests still using a replaced gateway (or for `RETIRE_TIMEOUT`), then stops it and flushes its
+/// tracing reporters.
+async fn retire(gateway: Arc<ConductorGateway>, tracing_manager: Option<FastraceManager>) {
+  let deadline = Instant::now() + RETIRE_TIMEOUT;
+
+  while Arc::strong_count(&gateway) > 1 && Instant::now() < deadline {
+    tokio::time::sleep(RETIRE_POLL_INTERVAL).await;
+  }
+
+  gateway.shutdown().await;
+
+  if let Some(tracing_manager) = tracing_manager {
+    tracing_manager.shutdown().await;
+  }
+}
+
+pub(crate) fn modified_at(path: impl AsRef<Path>) -> Option<SystemTime> {
+  path
+    .as_ref()
+    .metadata()
+    .and_then(|metadata| metadata.modified())
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs::{write, File};
+
+  use super::*;
+
+  const SOURCES: &str = "sources:
+  - type: mock
+    id: mock
+    config:
+      response_data: response.json
+";
+
+  fn root_config(path: &str) -> String {
+    format!(
+      "$include:
+  - sources.yaml
+sources: []
+endpoints:
+  - path: {path}
+    from: mock
+"
+    )
+  }
+
+  async fn reloadable_gateway(dir: &Path) -> ReloadableGateway {
+    write(dir.join("response.json"), r#"{"data":{}}"#).unwrap();
+    write(dir.join("sources.yaml"), SOURCES).unwrap();
+    write(dir.join("config.yaml"), root_config("/a")).unwrap();
+
+    let config_file_path = dir.join("config.yaml").to_string_lossy().into_owned();
+    let (config, _) = try_load_config(&config_file_path, |_| None).await.unwrap();
+    let mut tracing_manager = FastraceManager::default();
+    let gateway = ConductorGateway::new(&config, &mut tracing_manager)
+      .await
+      .unwrap();
+
+    ReloadableGateway::new(
+      config_file_path,
+      config.server.clone(),
+      config.included_paths.clone(),
+      gateway,
+      tracing_manager,
+    )
+  }
+
+  #[actix_web::test]
+  async fn should_reload_and_keep_previous_gateway_on_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let gateway = reloadable_gateway(dir.path()).await;
+    let previous = gateway.current();
+
+    write(dir.path().join("config.yaml"), root_config("/b")).unwrap();
+    gateway.reload().await.unwrap();
+    assert!(gateway.current().match_path("/b").is_ok());
+    assert!(gateway.current().match_path("/a").is_err());
+    // Requests that started before the reload keep using the previous gateway.
+    assert!(previous.match_path("/a").is_ok());
+
+    write(
+      dir.path().join("config.yaml"),
+      "sources: []\nendpoints:\n  - path: /c\n    from: missing\n",
+    )
+    .unwrap();
+    assert!(matches!(
+      gateway.reload().await,
+      Err(ReloadError::InvalidConfig(_))
+    ));
+    assert!(gateway.current().match_path("/b").is_ok());
+
+    write(dir.path().join("config.yaml"), "endpoints: [").unwrap();
+    assert!(matches!(
+      gateway.reload().await,
+      Err(ReloadError::ConfigLoadFailed(_))
+    ));
+    assert!(gateway.current().match_path("/b").is_ok());
+  }
+
+  #[actix_web::test]
+  async fn should_stop_previous_gateway_once_requests_are_done() {
+    let dir = tempfile::tempdir().unwrap();
+    let gateway = reloadable_gateway(dir.path()).await;
+    // Like an in-flight request, only the matched route is kept.
+    let route = gateway.route("/a").unwrap();
+
+    write(dir.path().join("config.yaml"), root_config("/b")).unwrap();
+    gateway.reload().await.unwrap();
+    tokio::time::sleep(RETIRE_POLL_INTERVAL * 3).await;
+    // The previous gateway is still held by the retire task, waiting for the request.
+    assert_eq!(Arc::strong_count(&route.gateway), 2);
+
+    let previous = Arc::downgrade(&route.gateway);
+    drop(route);
+    tokio::time::sleep(RETIRE_POLL_INTERVAL * 3).await;
+    assert!(previous.upgrade().is_none());
+  }
+
+  #[actix_web::test]
+  async fn should_watch_included_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let gateway = reloadable_gateway(dir.path()).await;
+    let state = gateway.watched_files_state();
+
+    assert_eq!(state.len(), 2);
+    assert!(state[1].0.ends_with("sources.yaml"));
+
+    File::options()
+      .write(true)
+      .open(dir.path().join("sources.yaml"))
+      .unwrap()
+      .set_modified(SystemTime::now() + Duration::from_secs(60))
+      .unwrap();
+    assert_ne!(gateway.watched_files_state(), state);
+  }
+}
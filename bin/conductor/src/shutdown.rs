@@ -0,0 +1,45 @@
+use std::{sync::OnceLock, time::Duration};
+
+use tokio::sync::Notify;
+use tracing::error;
+
+/// How long to wait for in-flight requests when `server.shutdown_timeout` is not configured.
+pub(crate) const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn shutdown_requested() -> &'static Notify {
+  static SHUTDOWN_REQUESTED: OnceLock<Notify> = OnceLock::new();
+
+  SHUTDOWN_REQUESTED.get_or_init(Notify::new)
+}
+
+/// Starts the graceful shutdown of a running server, the same way `SIGTERM` does.
+///
+/// If the server is not running yet, it will shut down as soon as it starts.
+pub fn request_shutdown() {
+  shutdown_requested().notify_one();
+}
+
+/// Resolves when the server should shut down, and returns the reason.
+pub(crate) async fn shutdown_signal() -> &'static str {
+  #[cfg(unix)]
+  let terminate = async {
+    match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+      Ok(mut signal) => {
+        signal.recv().await;
+      }
+      Err(e) => {
+        error!("failed to listen to SIGTERM: {}", e);
+        std::future::pending::<()>().await;
+      }
+    }
+  };
+
+  #[cfg(not(unix))]
+  let terminate = std::future::pending::<()>();
+
+  tokio::select! {
+    _ = terminate => "SIGTERM",
+    _ = tokio::signal::ctrl_c() => "SIGINT",
+    _ = shutdown_requested().notified() => "shutdown requested",
+  }
+}
@@ -0,0 +1,481 @@
+use std::{
+  fs::File,
+  io::BufReader,
+  path::{Path, PathBuf},
+  sync::{Arc, RwLock},
+  time::SystemTime,
+};
+
+use conductor_config::ServerTlsConfig;
+use rustls::{
+  crypto::{ring, CryptoProvider},
+  pki_types::{CertificateDer, PrivateKeyDer},
+  server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier},
+  sign::CertifiedKey,
+  RootCertStore, ServerConfig,
+};
+use tracing::{debug, error, info};
+
+use crate::reload::{modified_at, CONFIG_WATCH_INTERVAL};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+  #[error("failed to read \"{0}\": {1}")]
+  ReadFailed(String, std::io::Error),
+  #[error("no certificates found in \"{0}\"")]
+  MissingCertificates(String),
+  #[error("no private key found in \"{0}\"")]
+  MissingPrivateKey(String),
+  #[error("the private key in \"{1}\" doesn't match the certificate in \"{0}\"")]
+  KeyMismatch(String, String),
+  #[error("invalid TLS configuration: {0}")]
+  InvalidConfig(rustls::Error),
+  #[error("invalid client CA certificates: {0}")]
+  InvalidClientCa(String),
+}
+
+/// Resolves the server certificate for every TLS handshake, so a new certificate can be swapped in without restarting the server.
+#[derive(Debug)]
+pub struct ReloadableCertResolver {
+  cert_file: PathBuf,
+  key_file: PathBuf,
+  provider: Arc<CryptoProvider>,
+  current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+  fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+    // @expected: the lock is never held across a panic
+    Some(self.current.read().unwrap().clone())
+  }
+}
+
+impl ReloadableCertResolver {
+  fn new(
+    cert_file: PathBuf,
+    key_file: PathBuf,
+    provider: Arc<CryptoProvider>,
+  ) -> Result<Self, TlsError> {
+    let certified_key = load_certified_key(&cert_file, &key_file, &provider)?;
+
+    Ok(Self {
+      cert_file,
+      key_file,
+      provider,
+      current: RwLock::new(Arc::new(certified_key)),
+    })
+  }
+
+  /// Watches the certificate and key files, and loads them again when one of them changes.
+  ///
+  /// If the new files are invalid (for example, only one of them was replaced so far), the previous certificate is kept.
+  pub async fn watch(self: Arc<Self>) {
+    let mut last_modified = self.modified_at();
+    let mut interval_timer = tokio::time::interval(CONFIG_WATCH_INTERVAL);
+
+    loop {
+      interval_timer.tick().await;
+      let modified = self.modified_at();
+
+      if modified == last_modified {
+        continue;
+      }
+
+      debug!(
+        "TLS certificate files were modified, previous: {:?}, current: {:?}",
+        last_modified, modified
+      );
+
+      match self.reload() {
+        Ok(()) => {
+          last_modified = modified;
+          info!("TLS certificate reloaded successfully");
+        }
+        Err(e) => error!(
+          "failed to reload TLS certificate, keeping the previous one: {}",
+          e
+        ),
+      }
+    }
+  }
+
+  /// Loads the certificate and key files, and uses them for the next handshakes. On error, the previous certificate is kept.
+  fn reload(&self) -> Result<(), TlsError> {
+    let certified_key = load_certified_key(&self.cert_file, &self.key_file, &self.provider)?;
+    // @expected: the lock is never held across a panic
+    *self.current.write().unwrap() = Arc::new(certified_key);
+
+    Ok(())
+  }
+
+  fn modified_at(&self) -> (Option<SystemTime>, Option<SystemTime>) {
+    (modified_at(&self.cert_file), modified_at(&self.key_file))
+  }
+}
+
+/// Builds the `rustls` server configuration, based on the `server.tls` section of the config file.
+///
+/// Relative paths are resolved based on `base_path` (the directory of the config file).
+pub fn build_server_config(
+  tls_config: &ServerTlsConfig,
+  base_path: &Path,
+) -> Result<(ServerConfig, Arc<ReloadableCertResolver>), TlsError> {
+  let provider = Arc::new(ring::default_provider());
+  let resolver = Arc::new(ReloadableCertResolver::new(
+    base_path.join(&tls_config.cert_file),
+    base_path.join(&tls_config.key_file),
+    provider.clone(),
+  )?);
+
+  let builder = ServerConfig::builder_with_provider(provider.clone())
+    .with_safe_default_protocol_versions()
+    .map_err(TlsError::InvalidConfig)?;
+
+  let builder = match &tls_config.client_ca_file {
+    Some(client_ca_file) => {
+      let mut roots = RootCertStore::empty();
+
+      for cert in load_certs(&base_path.join(client_ca_file))? {
+        roots
+          .add(cert)
+          .map_err(|e| TlsError::InvalidClientCa(e.to_string()))?;
+      }
+
+      let mut verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+
+      if tls_config.client_auth_optional {
+        verifier = verifier.allow_unauthenticated();
+      }
+
+      let verifier = verifier
+        .build()
+        .map_err(|e| TlsError::InvalidClientCa(e.to_string()))?;
+
+      builder.with_client_cert_verifier(verifier)
+    }
+    None => builder.with_no_client_auth(),
+  };
+
+  Ok((builder.with_cert_resolver(resolver.clone()), resolver))
+}
+
+fn load_certified_key(
+  cert_file: &Path,
+  key_file: &Path,
+  provider: &CryptoProvider,
+) -> Result<CertifiedKey, TlsError> {
+  let certs = load_certs(cert_file)?;
+  let key = load_private_key(key_file)?;
+  let signing_key = provider
+    .key_provider
+    .load_private_key(key)
+    .map_err(TlsError::InvalidConfig)?;
+
+  let certified_key = CertifiedKey::new(certs, signing_key);
+
+  // A certificate replaced without its key (or the other way around) would make all the handshakes fail.
+  certified_key.keys_match().map_err(|_| {
+    TlsError::KeyMismatch(
+      cert_file.to_string_lossy().into_owned(),
+      key_file.to_string_lossy().into_owned(),
+    )
+  })?;
+
+  Ok(certified_key)
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+  let read_failed = |e| TlsError::ReadFailed(path.to_string_lossy().into_owned(), e);
+  let mut reader = BufReader::new(File::open(path).map_err(read_failed)?);
+  let certs = rustls_pemfile::certs(&mut reader)
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(read_failed)?;
+
+  if certs.is_empty() {
+    return Err(TlsError::MissingCertificates(
+      path.to_string_lossy().into_owned(),
+    ));
+  }
+
+  Ok(certs)
+}
+
+fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, TlsError> {
+  let read_failed = |e| TlsError::ReadFailed(path.to_string_lossy().into_owned(), e);
+  let mut reader = BufReader::new(File::open(path).map_err(read_failed)?);
+
+  rustls_pemfile::private_key(&mut reader)
+    .map_err(read_failed)?
+    .ok_or_else(|| TlsError::MissingPrivateKey(path.to_string_lossy().into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs::write;
+
+  use openssl::{
+    asn1::Asn1Time,
+    bn::BigNum,
+    ec::{EcGroup, EcKey},
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{PKey, Private},
+    x509::{
+      extension::{BasicConstraints, ExtendedKeyUsage, SubjectAlternativeName},
+      X509NameBuilder, X509NameRef, X509,
+    },
+  };
+  use rustls::{
+    pki_types::ServerName, ClientConfig, ClientConnection, Connection, ServerConnection,
+  };
+
+  use super::*;
+
+  struct TestCert {
+    cert: X509,
+    key: PKey<Private>,
+  }
+
+  impl TestCert {
+    fn cert_pem(&self) -> Vec<u8> {
+      self.cert.to_pem().unwrap()
+    }
+
+    fn key_pem(&self) -> Vec<u8> {
+      self.key.private_key_to_pem_pkcs8().unwrap()
+    }
+  }
+
+  /// Generates a certificate for `localhost`, signed by `issuer`, or a self-signed CA certificate when `issuer` is `None`.
+  fn generate_cert(common_name: &str, serial: u32, issuer: Option<&TestCert>) -> TestCert {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder
+      .set_serial_number(&BigNum::from_u32(serial).unwrap().to_asn1_integer().unwrap())
+      .unwrap();
+    builder.set_subject_name(&name).unwrap();
+    let issuer_name: &X509NameRef = match issuer {
+      Some(issuer) => issuer.cert.subject_name(),
+      None => &name,
+    };
+    builder.set_issuer_name(issuer_name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+      .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+      .unwrap();
+    builder
+      .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+      .unwrap();
+
+    match issuer {
+      None => {
+        let constraints = BasicConstraints::new().critical().ca().build().unwrap();
+        builder.append_extension(constraints).unwrap();
+      }
+      Some(issuer) => {
+        let alt_name = SubjectAlternativeName::new()
+          .dns("localhost")
+          .build(&builder.x509v3_context(Some(&*issuer.cert), None))
+          .unwrap();
+        let usage = ExtendedKeyUsage::new()
+          .server_auth()
+          .client_auth()
+          .build()
+          .unwrap();
+        builder.append_extension(alt_name).unwrap();
+        builder.append_extension(usage).unwrap();
+      }
+    }
+
+    let signing_key = issuer.map_or(&key, |issuer| &issuer.key);
+    builder.sign(signing_key, MessageDigest::sha256()).unwrap();
+
+    TestCert {
+      cert: builder.build(),
+      key,
+    }
+  }
+
+  fn tls_config(client_ca_file: Option<&str>, client_auth_optional: bool) -> ServerTlsConfig {
+    ServerTlsConfig {
+      cert_file: "server.crt".to_string(),
+      key_file: "server.key".to_string(),
+      client_ca_file: client_ca_file.map(String::from),
+      client_auth_optional,
+    }
+  }
+
+  fn write_cert(dir: &Path, name: &str, cert: &TestCert) {
+    write(dir.join(format!("{}.crt", name)), cert.cert_pem()).unwrap();
+    write(dir.join(format!("{}.key", name)), cert.key_pem()).unwrap();
+  }
+
+  fn client_config(ca: &TestCert, client: Option<&TestCert>) -> ClientConfig {
+    let mut roots = RootCertStore::empty();
+    roots
+      .add(CertificateDer::from(ca.cert.to_der().unwrap()))
+      .unwrap();
+
+    let builder = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+      .with_safe_default_protocol_versions()
+      .unwrap()
+      .with_root_certificates(roots);
+
+    match client {
+      Some(client) => builder
+        .with_client_auth_cert(
+          vec![CertificateDer::from(client.cert.to_der().unwrap())],
+          rustls_pemfile::private_key(&mut client.key_pem().as_slice())
+            .unwrap()
+            .unwrap(),
+        )
+        .unwrap(),
+      None => builder.with_no_client_auth(),
+    }
+  }
+
+  /// Runs a TLS handshake in memory, and returns the first error raised by either side.
+  fn handshake(
+    server_config: ServerConfig,
+    client_config: ClientConfig,
+  ) -> Result<(), rustls::Error> {
+    let server_name = ServerName::try_from("localhost").unwrap();
+    let mut client = Connection::from(ClientConnection::new(Arc::new(client_config), server_name)?);
+    let mut server = Connection::from(ServerConnection::new(Arc::new(server_config))?);
+
+    while client.is_handshaking() || server.is_handshaking() {
+      transfer(&mut client, &mut server)?;
+      transfer(&mut server, &mut client)?;
+    }
+
+    Ok(())
+  }
+
+  fn transfer(from: &mut Connection, to: &mut Connection) -> Result<(), rustls::Error> {
+    let mut buffer = vec![];
+
+    while from.wants_write() {
+      from.write_tls(&mut buffer).unwrap();
+    }
+
+    let mut reader = buffer.as_slice();
+
+    while !reader.is_empty() {
+      to.read_tls(&mut reader).unwrap();
+      to.process_new_packets()?;
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn should_serve_certificate_and_verify_client_certificates() {
+    let dir = tempfile::tempdir().unwrap();
+    let ca = generate_cert("Test CA", 1, None);
+    let server = generate_cert("localhost", 2, Some(&ca));
+    let client = generate_cert("client", 3, Some(&ca));
+    let other_ca = generate_cert("Other CA", 4, None);
+    let other_client = generate_cert("other client", 5, Some(&other_ca));
+    write_cert(dir.path(), "server", &server);
+    write(dir.path().join("ca.crt"), ca.cert_pem()).unwrap();
+
+    let (server_config, _) = build_server_config(&tls_config(None, false), dir.path()).unwrap();
+    assert!(handshake(server_config, client_config(&ca, None)).is_ok());
+
+    let (server_config, _) = build_server_config(&tls_config(None, false), dir.path()).unwrap();
+    assert!(handshake(server_config, client_config(&other_ca, None)).is_err());
+
+    let mtls = tls_config(Some("ca.crt"), false);
+    let (server_config, _) = build_server_config(&mtls, dir.path()).unwrap();
+    assert!(handshake(server_config, client_config(&ca, Some(&client))).is_ok());
+
+    let (server_config, _) = build_server_config(&mtls, dir.path()).unwrap();
+    assert!(handshake(server_config, client_config(&ca, None)).is_err());
+
+    let (server_config, _) = build_server_config(&mtls, dir.path()).unwrap();
+    assert!(handshake(server_config, client_config(&ca, Some(&other_client))).is_err());
+
+    let optional_mtls = tls_config(Some("ca.crt"), true);
+    let (server_config, _) = build_server_config(&optional_mtls, dir.path()).unwrap();
+    assert!(handshake(server_config, client_config(&ca, None)).is_ok());
+
+    let (server_config, _) = build_server_config(&optional_mtls, dir.path()).unwrap();
+    assert!(handshake(server_config, client_config(&ca, Some(&other_client))).is_err());
+  }
+
+  #[test]
+  fn should_report_invalid_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let ca = generate_cert("Test CA", 1, None);
+    let server = generate_cert("localhost", 2, Some(&ca));
+
+    assert!(matches!(
+      build_server_config(&tls_config(None, false), dir.path()),
+      Err(TlsError::ReadFailed(..))
+    ));
+
+    write(dir.path().join("server.crt"), server.key_pem()).unwrap();
+    write(dir.path().join("server.key"), server.key_pem()).unwrap();
+    assert!(matches!(
+      build_server_config(&tls_config(None, false), dir.path()),
+      Err(TlsError::MissingCertificates(..))
+    ));
+
+    write(dir.path().join("server.crt"), server.cert_pem()).unwrap();
+    write(dir.path().join("server.key"), server.cert_pem()).unwrap();
+    assert!(matches!(
+      build_server_config(&tls_config(None, false), dir.path()),
+      Err(TlsError::MissingPrivateKey(..))
+    ));
+
+    let other = generate_cert("localhost", 3, Some(&ca));
+    write(dir.path().join("server.crt"), server.cert_pem()).unwrap();
+    write(dir.path().join("server.key"), other.key_pem()).unwrap();
+    assert!(matches!(
+      build_server_config(&tls_config(None, false), dir.path()),
+      Err(TlsError::KeyMismatch(..))
+    ));
+
+    write_cert(dir.path(), "server", &server);
+    write(dir.path().join("ca.crt"), "").unwrap();
+    assert!(matches!(
+      build_server_config(&tls_config(Some("ca.crt"), false), dir.path()),
+      Err(TlsError::MissingCertificates(..))
+    ));
+  }
+
+  #[test]
+  fn should_reload_certificate_and_keep_previous_on_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let ca = generate_cert("Test CA", 1, None);
+    let server = generate_cert("localhost", 2, Some(&ca));
+    let renewed = generate_cert("localhost", 3, Some(&ca));
+    write_cert(dir.path(), "server", &server);
+
+    let (_, resolver) = build_server_config(&tls_config(None, false), dir.path()).unwrap();
+    let current_cert = || resolver.current.read().unwrap().cert[0].clone();
+    assert_eq!(current_cert().as_ref(), server.cert.to_der().unwrap());
+
+    write_cert(dir.path(), "server", &renewed);
+    resolver.reload().unwrap();
+    assert_eq!(current_cert().as_ref(), renewed.cert.to_der().unwrap());
+
+    // The key file is still being written, so the new files can't be loaded yet.
+    write(dir.path().join("server.crt"), server.cert_pem()).unwrap();
+    write(dir.path().join("server.key"), "").unwrap();
+    assert!(resolver.reload().is_err());
+    assert_eq!(current_cert().as_ref(), renewed.cert.to_der().unwrap());
+
+    // Only the certificate was replaced so far, it doesn't match the key.
+    write(dir.path().join("server.key"), renewed.key_pem()).unwrap();
+    assert!(matches!(resolver.reload(), Err(TlsError::KeyMismatch(..))));
+    assert_eq!(current_cert().as_ref(), renewed.cert.to_der().unwrap());
+  }
+}
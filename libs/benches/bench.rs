@@ -77,21 +77,29 @@ fn criterion_benchmark(c: &mut Criterion) {
 
   c.bench_function("request hot path without HTTP server", |b| {
     let config = ConductorConfig {
+      include: vec![],
+      extends: None,
+      included_paths: vec![],
       sources: vec![SourceDefinition::GraphQL {
         id: String::from("s"),
         config: GraphQLSourceConfig {
           endpoint: String::from("http://localhost:4444/graphql"),
           schema_awareness: None,
+          http_client: None,
         },
       }],
       endpoints: vec![EndpointDefinition {
         from: String::from("s"),
         path: String::from("/"),
+        plugin_sets: vec![],
         plugins: None,
+        schema_filter: None,
       }],
       logger: None,
+      propagation: None,
       server: None,
       plugins: None,
+      plugin_sets: Default::default(),
     };
 
     let mut tracing_mgr = FastraceManager::default();
@@ -4,16 +4,36 @@ use crate::{
   vrl_utils::serde_value_to_vrl_value,
 };
 use anyhow::Result;
+use fastrace::collector::SpanContext;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use vrl::compiler::state::RuntimeState;
 
 type Context = Map<String, Value>;
 
+/// The number of errors in the GraphQL response of the request, set in the context before the response is serialized.
+///
+/// It's not set when a plugin short-circuits the request.
+pub static GRAPHQL_ERRORS_COUNT_CONTEXT_KEY: &str = "graphql:errors_count";
+
+/// The client application that sent a request, as identified by the `client_awareness` plugin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientInfo {
+  pub name: String,
+  pub version: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct RequestExecutionContext {
   pub downstream_http_request: ConductorHttpRequest,
   pub downstream_graphql_request: Option<ParsedGraphQLRequest>,
   pub short_circuit_response: Option<ConductorHttpResponse>,
+  pub client: Option<ClientInfo>,
+  /// The id of the request, used to correlate the logs and the upstream requests.
+  pub request_id: Option<String>,
+  /// The trace context of the request (continued from the incoming request, or a new one), forwarded to the upstream
+  /// services when the request isn't traced because it wasn't sampled.
+  pub trace_context: Option<SpanContext>,
   vrl_shared_state: RuntimeState,
   context: Context,
 }
@@ -24,6 +44,9 @@ impl RequestExecutionContext {
       downstream_http_request,
       downstream_graphql_request: None,
       short_circuit_response: None,
+      client: None,
+      request_id: None,
+      trace_context: None,
       vrl_shared_state: RuntimeState::default(),
       context: Context::new(),
     }
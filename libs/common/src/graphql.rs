@@ -369,6 +369,15 @@ impl GraphQLResponse {
     }
   }
 
+  pub fn new_data(data: Value) -> Self {
+    GraphQLResponse {
+      data: Some(data),
+      errors: None,
+      extensions: None,
+      downstream_http_code: None,
+    }
+  }
+
   pub fn new_error(error: &str) -> Self {
     GraphQLResponse {
       data: None,
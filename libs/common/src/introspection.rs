@@ -8,7 +8,7 @@ use graphql_tools::introspection::{
   IntrospectionInputTypeRef, IntrospectionOutputTypeRef, IntrospectionType,
 };
 
-fn serde_value_to_sdl_value(
+pub fn serde_value_to_sdl_value(
   value: serde_json::Value,
 ) -> graphql_parser::schema::Value<'static, String> {
   match value {
@@ -0,0 +1,180 @@
+use std::{
+  collections::HashMap,
+  fmt::{Debug, Display},
+  sync::{Arc, RwLock},
+  time::Duration,
+};
+
+/// A key-value store provided by the runtime, for example a Workers KV namespace on Cloudflare Workers.
+///
+/// Stores are resolved by their binding (see `resolve_key_value_store`), so plugins and sources can use them without depending on a specific runtime.
+#[async_trait::async_trait(?Send)]
+pub trait KeyValueStore: Debug + Send + Sync {
+  async fn get(&self, key: &str) -> Result<Option<String>, KeyValueStoreError>;
+  /// Stores a value. When `ttl` is set, the value expires after the given duration.
+  async fn put(
+    &self,
+    key: &str,
+    value: &str,
+    ttl: Option<Duration>,
+  ) -> Result<(), KeyValueStoreError>;
+}
+
+/// Identifies a key-value store provided by the runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeyValueBinding {
+  /// A Workers KV namespace, by the name of its binding.
+  WorkersKv(String),
+  /// A cache of the Cache API, by its name. The default cache is used when no name is set.
+  CacheApi(Option<String>),
+}
+
+impl Display for KeyValueBinding {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      KeyValueBinding::WorkersKv(binding) => write!(f, "Workers KV binding '{}'", binding),
+      KeyValueBinding::CacheApi(Some(name)) => write!(f, "Cache API cache '{}'", name),
+      KeyValueBinding::CacheApi(None) => write!(f, "default Cache API cache"),
+    }
+  }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum KeyValueStoreError {
+  #[error("{0} is not available on this runtime")]
+  MissingBinding(KeyValueBinding),
+  #[error("failed to read key '{key}': {message}")]
+  ReadFailed { key: String, message: String },
+  #[error("failed to write key '{key}': {message}")]
+  WriteFailed { key: String, message: String },
+}
+
+pub type KeyValueStoreResolver =
+  Box<dyn Fn(&KeyValueBinding) -> Option<Arc<dyn KeyValueStore>> + Send + Sync>;
+
+static REGISTERED_STORES: RwLock<Option<HashMap<KeyValueBinding, Arc<dyn KeyValueStore>>>> =
+  RwLock::new(None);
+static RESOLVER: RwLock<Option<KeyValueStoreResolver>> = RwLock::new(None);
+
+/// Registers a store for the given binding. Registered stores take precedence over the resolver.
+pub fn register_key_value_store(binding: KeyValueBinding, store: Arc<dyn KeyValueStore>) {
+  REGISTERED_STORES
+    .write()
+    .unwrap_or_else(|e| e.into_inner())
+    .get_or_insert_with(HashMap::new)
+    .insert(binding, store);
+}
+
+/// Sets the function used by the runtime to create the stores of the bindings it provides.
+pub fn set_key_value_store_resolver(resolver: KeyValueStoreResolver) {
+  *RESOLVER.write().unwrap_or_else(|e| e.into_inner()) = Some(resolver);
+}
+
+pub fn resolve_key_value_store(
+  binding: &KeyValueBinding,
+) -> Result<Arc<dyn KeyValueStore>, KeyValueStoreError> {
+  let registered = REGISTERED_STORES
+    .read()
+    .unwrap_or_else(|e| e.into_inner())
+    .as_ref()
+    .and_then(|stores| stores.get(binding).cloned());
+
+  if let Some(store) = registered {
+    return Ok(store);
+  }
+
+  RESOLVER
+    .read()
+    .unwrap_or_else(|e| e.into_inner())
+    .as_ref()
+    .and_then(|resolver| resolver(binding))
+    .ok_or_else(|| KeyValueStoreError::MissingBinding(binding.clone()))
+}
+
+/// A local in-memory stand-in of a runtime key-value store.
+#[cfg(any(test, feature = "test_utils"))]
+#[derive(Debug, Default)]
+pub struct InMemoryKeyValueStore {
+  values: std::sync::Mutex<HashMap<String, (String, Option<std::time::SystemTime>)>>,
+}
+
+#[cfg(any(test, feature = "test_utils"))]
+impl InMemoryKeyValueStore {
+  pub fn with_values(values: impl IntoIterator<Item = (String, String)>) -> Self {
+    Self {
+      values: std::sync::Mutex::new(
+        values
+          .into_iter()
+          .map(|(key, value)| (key, (value, None)))
+          .collect(),
+      ),
+    }
+  }
+}
+
+#[cfg(any(test, feature = "test_utils"))]
+#[async_trait::async_trait(?Send)]
+impl KeyValueStore for InMemoryKeyValueStore {
+  async fn get(&self, key: &str) -> Result<Option<String>, KeyValueStoreError> {
+    let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+
+    Ok(
+      values
+        .get(key)
+        .and_then(|(value, expires_at)| match expires_at {
+          Some(expires_at) if *expires_at <= std::time::SystemTime::now() => None,
+          _ => Some(value.clone()),
+        }),
+    )
+  }
+
+  async fn put(
+    &self,
+    key: &str,
+    value: &str,
+    ttl: Option<Duration>,
+  ) -> Result<(), KeyValueStoreError> {
+    let expires_at = ttl.map(|ttl| std::time::SystemTime::now() + ttl);
+    self
+      .values
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .insert(key.to_string(), (value.to_string(), expires_at));
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn resolves_registered_stores() {
+    let binding = KeyValueBinding::WorkersKv("kv_tests_registered".to_string());
+    assert!(matches!(
+      resolve_key_value_store(&binding),
+      Err(KeyValueStoreError::MissingBinding(_))
+    ));
+
+    register_key_value_store(binding.clone(), Arc::new(InMemoryKeyValueStore::default()));
+    assert!(resolve_key_value_store(&binding).is_ok());
+    assert!(resolve_key_value_store(&KeyValueBinding::CacheApi(Some(
+      "kv_tests_registered".to_string()
+    )))
+    .is_err());
+  }
+
+  #[test]
+  fn in_memory_store_expires_values() {
+    futures::executor::block_on(async {
+      let store = InMemoryKeyValueStore::default();
+      store.put("a", "1", None).await.unwrap();
+      store.put("b", "2", Some(Duration::ZERO)).await.unwrap();
+
+      assert_eq!(store.get("a").await.unwrap(), Some("1".to_string()));
+      assert_eq!(store.get("b").await.unwrap(), None);
+      assert_eq!(store.get("c").await.unwrap(), None);
+    });
+  }
+}
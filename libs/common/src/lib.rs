@@ -3,8 +3,11 @@ pub mod graphql;
 pub mod http;
 pub mod introspection;
 pub mod json;
+pub mod kv;
+pub mod normalize;
 pub mod plugin;
 pub mod plugin_manager;
+pub mod schema_diff;
 pub mod serde_utils;
 pub mod source;
 pub mod vrl_functions;
@@ -0,0 +1,468 @@
+use std::collections::{BTreeMap, HashSet};
+
+use graphql_parser::query::{
+  Definition, Directive, FragmentDefinition, OperationDefinition, Selection, SelectionSet,
+  TypeCondition, Value, VariableDefinition,
+};
+use sha2::{Digest, Sha256};
+
+use crate::graphql::ParsedGraphQLRequest;
+
+type QueryValue = Value<'static, String>;
+type QuerySelectionSet = SelectionSet<'static, String>;
+type QueryDirective = Directive<'static, String>;
+
+#[derive(Debug, Clone, Default)]
+pub struct NormalizationOptions {
+  /// Replaces the literal values of arguments and variable defaults: numbers with `0`, strings with `""`, lists with `[]` and objects with `{}`.
+  /// Operations that only differ by their literal values share the same signature.
+  pub hide_literals: bool,
+}
+
+/// The canonical form of an operation, that can be used as a stable key for the operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedOperation {
+  /// The printed operation, with the fragments it uses.
+  pub signature: String,
+  /// The SHA-256 hash of the signature, as a hex string.
+  pub hash: String,
+}
+
+/// Normalizes the operation executed by the request.
+///
+/// The signature only contains the executed operation and the fragments it uses, without comments and with a compact formatting.
+/// Fields, arguments, variables and fragments are sorted by name, so equivalent documents have the same signature.
+/// The root fields of mutations are kept in their original order, since they are executed serially.
+///
+/// Returns `None` when the request doesn't have an operation to execute.
+pub fn normalize_request(
+  request: &ParsedGraphQLRequest,
+  options: &NormalizationOptions,
+) -> Option<NormalizedOperation> {
+  let Some(Definition::Operation(operation)) = request.executable_operation() else {
+    return None;
+  };
+
+  let fragments = request
+    .parsed_operation
+    .definitions
+    .iter()
+    .filter_map(|definition| match definition {
+      Definition::Fragment(fragment) => Some((fragment.name.as_str(), fragment)),
+      _ => None,
+    })
+    .collect::<BTreeMap<_, _>>();
+
+  let printer = SignaturePrinter {
+    options,
+    fragments: &fragments,
+  };
+  let mut used_fragments = HashSet::new();
+  let mut signature = printer.operation(operation, &mut used_fragments);
+
+  // Fragments are printed after the operation, sorted by name.
+  for (name, fragment) in fragments.iter() {
+    if used_fragments.contains(*name) {
+      signature.push(' ');
+      signature.push_str(&printer.fragment(fragment));
+    }
+  }
+
+  Some(NormalizedOperation {
+    hash: hash_signature(&signature),
+    signature,
+  })
+}
+
+/// Computes the SHA-256 hash of an operation signature (or of any printed document), as a hex string.
+pub fn hash_signature(signature: &str) -> String {
+  format!("{:x}", Sha256::digest(signature.as_bytes()))
+}
+
+struct SignaturePrinter<'a> {
+  options: &'a NormalizationOptions,
+  fragments: &'a BTreeMap<&'a str, &'a FragmentDefinition<'static, String>>,
+}
+
+impl SignaturePrinter<'_> {
+  fn operation(
+    &self,
+    operation: &OperationDefinition<'static, String>,
+    used_fragments: &mut HashSet<String>,
+  ) -> String {
+    let (kind, name, variables, directives, selection_set) = match operation {
+      OperationDefinition::SelectionSet(selection_set) => {
+        ("query", None, &[][..], &[][..], selection_set)
+      }
+      OperationDefinition::Query(query) => (
+        "query",
+        query.name.as_ref(),
+        &query.variable_definitions[..],
+        &query.directives[..],
+        &query.selection_set,
+      ),
+      OperationDefinition::Mutation(mutation) => (
+        "mutation",
+        mutation.name.as_ref(),
+        &mutation.variable_definitions[..],
+        &mutation.directives[..],
+        &mutation.selection_set,
+      ),
+      OperationDefinition::Subscription(subscription) => (
+        "subscription",
+        subscription.name.as_ref(),
+        &subscription.variable_definitions[..],
+        &subscription.directives[..],
+        &subscription.selection_set,
+      ),
+    };
+
+    let mut output = kind.to_string();
+
+    if let Some(name) = name {
+      output.push(' ');
+      output.push_str(name);
+    }
+
+    output.push_str(&self.variables(variables));
+    output.push_str(&self.directives(directives));
+    output.push_str(&self.selection_set(selection_set, kind != "mutation", used_fragments));
+
+    output
+  }
+
+  fn fragment(&self, fragment: &FragmentDefinition<'static, String>) -> String {
+    let TypeCondition::On(type_name) = &fragment.type_condition;
+
+    format!(
+      "fragment {} on {}{}{}",
+      fragment.name,
+      type_name,
+      self.directives(&fragment.directives),
+      // Fragments spread by this fragment are already collected from the operation.
+      self.selection_set(&fragment.selection_set, true, &mut HashSet::new())
+    )
+  }
+
+  fn variables(&self, variables: &[VariableDefinition<'static, String>]) -> String {
+    if variables.is_empty() {
+      return String::new();
+    }
+
+    let mut variables = variables
+      .iter()
+      .map(|variable| {
+        let mut output = format!("${}:{}", variable.name, variable.var_type);
+
+        if let Some(default_value) = &variable.default_value {
+          output.push('=');
+          output.push_str(&self.value(default_value));
+        }
+
+        output
+      })
+      .collect::<Vec<_>>();
+    variables.sort();
+
+    format!("({})", variables.join(","))
+  }
+
+  fn directives(&self, directives: &[QueryDirective]) -> String {
+    directives
+      .iter()
+      .map(|directive| {
+        format!(
+          "@{}{}",
+          directive.name,
+          self.arguments(&directive.arguments)
+        )
+      })
+      .collect::<Vec<_>>()
+      .join("")
+  }
+
+  fn arguments(&self, arguments: &[(String, QueryValue)]) -> String {
+    if arguments.is_empty() {
+      return String::new();
+    }
+
+    let mut arguments = arguments
+      .iter()
+      .map(|(name, value)| format!("{}:{}", name, self.value(value)))
+      .collect::<Vec<_>>();
+    arguments.sort();
+
+    format!("({})", arguments.join(","))
+  }
+
+  /// Prints a selection set. Fields are printed first, then fragment spreads and inline fragments.
+  fn selection_set(
+    &self,
+    selection_set: &QuerySelectionSet,
+    sort_fields: bool,
+    used_fragments: &mut HashSet<String>,
+  ) -> String {
+    let mut fields = vec![];
+    let mut spreads = vec![];
+    let mut inline_fragments = vec![];
+
+    for selection in &selection_set.items {
+      match selection {
+        Selection::Field(field) => {
+          let mut output = match &field.alias {
+            Some(alias) => format!("{}:{}", alias, field.name),
+            None => field.name.clone(),
+          };
+          output.push_str(&self.arguments(&field.arguments));
+          output.push_str(&self.directives(&field.directives));
+
+          if !field.selection_set.items.is_empty() {
+            output.push_str(&self.selection_set(&field.selection_set, true, used_fragments));
+          }
+
+          fields.push(output);
+        }
+        Selection::FragmentSpread(spread) => {
+          self.collect_fragment(&spread.fragment_name, used_fragments);
+          spreads.push(format!(
+            "...{}{}",
+            spread.fragment_name,
+            self.directives(&spread.directives)
+          ));
+        }
+        Selection::InlineFragment(fragment) => {
+          let mut output = "...".to_string();
+
+          if let Some(TypeCondition::On(type_name)) = &fragment.type_condition {
+            output.push_str("on ");
+            output.push_str(type_name);
+          }
+
+          output.push_str(&self.directives(&fragment.directives));
+          output.push_str(&self.selection_set(&fragment.selection_set, true, used_fragments));
+          inline_fragments.push(output);
+        }
+      }
+    }
+
+    if sort_fields {
+      fields.sort();
+    }
+
+    spreads.sort();
+    inline_fragments.sort();
+
+    let items = fields
+      .into_iter()
+      .chain(spreads)
+      .chain(inline_fragments)
+      .collect::<Vec<_>>();
+
+    format!("{{{}}}", items.join(" "))
+  }
+
+  /// Marks a fragment, and the fragments it spreads, as used by the operation.
+  fn collect_fragment(&self, name: &str, used_fragments: &mut HashSet<String>) {
+    if !used_fragments.insert(name.to_string()) {
+      return;
+    }
+
+    if let Some(fragment) = self.fragments.get(name) {
+      self.selection_set(&fragment.selection_set, true, used_fragments);
+    }
+  }
+
+  fn value(&self, value: &QueryValue) -> String {
+    match value {
+      QueryValue::Variable(name) => format!("${}", name),
+      QueryValue::Int(_) | QueryValue::Float(_) if self.options.hide_literals => "0".to_string(),
+      QueryValue::Int(number) => number
+        .as_i64()
+        .map(|number| number.to_string())
+        .unwrap_or_default(),
+      QueryValue::Float(number) => format!("{:?}", number),
+      QueryValue::String(_) if self.options.hide_literals => "\"\"".to_string(),
+      // JSON string escapes are a subset of the GraphQL ones.
+      QueryValue::String(value) => serde_json::to_string(value).unwrap_or_default(),
+      QueryValue::Boolean(value) => value.to_string(),
+      QueryValue::Null => "null".to_string(),
+      QueryValue::Enum(name) => name.clone(),
+      QueryValue::List(_) if self.options.hide_literals => "[]".to_string(),
+      QueryValue::List(items) => format!(
+        "[{}]",
+        items
+          .iter()
+          .map(|item| self.value(item))
+          .collect::<Vec<_>>()
+          .join(",")
+      ),
+      QueryValue::Object(_) if self.options.hide_literals => "{}".to_string(),
+      // Object fields are stored in a `BTreeMap`, so they are already sorted by name.
+      QueryValue::Object(fields) => format!(
+        "{{{}}}",
+        fields
+          .iter()
+          .map(|(name, value)| format!("{}:{}", name, self.value(value)))
+          .collect::<Vec<_>>()
+          .join(",")
+      ),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::graphql::GraphQLRequest;
+
+  fn normalize(operation: &str, operation_name: Option<&str>, hide_literals: bool) -> String {
+    let request = ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
+      operation: operation.to_string(),
+      operation_name: operation_name.map(|name| name.to_string()),
+      variables: None,
+      extensions: None,
+    })
+    .unwrap();
+
+    normalize_request(&request, &NormalizationOptions { hide_literals })
+      .unwrap()
+      .signature
+  }
+
+  #[test]
+  fn should_remove_comments_and_formatting() {
+    // Example from the "Language" section of the GraphQL spec.
+    let signature = normalize(
+      r#"
+        # a comment
+        {
+          likeStory(storyID: 12345) {
+            story {
+              likeCount
+            }
+          }
+        }
+      "#,
+      None,
+      false,
+    );
+
+    assert_eq!(
+      signature,
+      "query{likeStory(storyID:12345){story{likeCount}}}"
+    );
+    assert_eq!(
+      signature,
+      normalize(
+        "query { likeStory(storyID: 12345) { story { likeCount } } }",
+        None,
+        false
+      )
+    );
+  }
+
+  #[test]
+  fn should_sort_fields_arguments_and_fragments() {
+    // Example from the "Fragments" section of the GraphQL spec.
+    let signature = normalize(
+      r#"
+        query withNestedFragments {
+          user(id: 4) {
+            friends(first: 10) {
+              ...friendFields
+            }
+            mutualFriends(first: 10) {
+              ...friendFields
+            }
+          }
+        }
+
+        fragment standardProfilePic on User {
+          profilePic(size: 50, format: "png")
+        }
+
+        fragment friendFields on User {
+          name
+          id
+          ...standardProfilePic
+          ... on Page { likers { count } }
+        }
+
+        fragment unused on User {
+          id
+        }
+
+        query other {
+          __typename
+        }
+      "#,
+      Some("withNestedFragments"),
+      false,
+    );
+
+    assert_eq!(
+      signature,
+      concat!(
+        "query withNestedFragments{user(id:4){friends(first:10){...friendFields} mutualFriends(first:10){...friendFields}}} ",
+        "fragment friendFields on User{id name ...standardProfilePic ...on Page{likers{count}}} ",
+        "fragment standardProfilePic on User{profilePic(format:\"png\",size:50)}"
+      )
+    );
+  }
+
+  #[test]
+  fn should_hide_literals() {
+    let signature = normalize(
+      r#"
+        query search($first: Int = 10, $after: String) {
+          search(text: "conductor", first: $first, after: $after, filter: { tags: ["a"] }, kind: USER, exact: true)
+        }
+      "#,
+      None,
+      true,
+    );
+
+    assert_eq!(
+      signature,
+      "query search($after:String,$first:Int=0){search(after:$after,exact:true,filter:{},first:$first,kind:USER,text:\"\")}"
+    );
+  }
+
+  #[test]
+  fn should_keep_mutation_root_fields_order() {
+    let first = normalize(
+      "mutation { b: setName(name: \"b\") a: setName(name: \"a\") }",
+      None,
+      false,
+    );
+    let second = normalize(
+      "mutation { a: setName(name: \"a\") b: setName(name: \"b\") }",
+      None,
+      false,
+    );
+
+    assert_eq!(
+      first,
+      "mutation{b:setName(name:\"b\") a:setName(name:\"a\")}"
+    );
+    assert_ne!(first, second);
+  }
+
+  #[test]
+  fn should_hash_signatures() {
+    let request = ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
+      operation: "{ a }".to_string(),
+      operation_name: None,
+      variables: None,
+      extensions: None,
+    })
+    .unwrap();
+    let normalized = normalize_request(&request, &Default::default()).unwrap();
+
+    assert_eq!(normalized.hash, hash_signature("query{a}"));
+    assert_eq!(
+      hash_signature(""),
+      "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+  }
+}
@@ -3,6 +3,7 @@ use std::{fmt::Debug, sync::Arc};
 use crate::{
   graphql::GraphQLRequest,
   http::{ConductorHttpRequest, ConductorHttpResponse},
+  schema_diff::SchemaDiff,
   source::SourceRuntime,
 };
 use reqwest::Response;
@@ -60,4 +61,12 @@ pub trait Plugin: Sync + Send + Debug {
     _response: &mut ConductorHttpResponse,
   ) {
   }
+  // Called when a source used by the endpoint reloads a schema that differs from the previous one, for example to clear
+  // caches that depend on the schema.
+  fn on_schema_change(&self, _source_id: &str, _diff: &SchemaDiff) {}
+  // Called on WASM runtime once the response is sent, since background tasks are not available there, so the plugin
+  // can send the data it collected (for example, usage reports) when it's due.
+  async fn on_flush(&self) {}
+  // Called when the gateway shuts down, or is replaced after a config reload, to send the data that is still pending.
+  async fn on_shutdown(&self) {}
 }
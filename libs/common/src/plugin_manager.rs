@@ -4,6 +4,7 @@ use crate::{
   execute::RequestExecutionContext,
   graphql::GraphQLRequest,
   http::{ConductorHttpRequest, ConductorHttpResponse},
+  schema_diff::SchemaDiff,
   source::SourceRuntime,
 };
 use reqwest::Response;
@@ -32,4 +33,7 @@ pub trait PluginManager: std::fmt::Debug + Send + Sync {
     ctx: &mut RequestExecutionContext,
     response: &Result<Response, reqwest_middleware::Error>,
   );
+  fn on_schema_change(&self, source_id: &str, diff: &SchemaDiff);
+  async fn on_flush(&self);
+  async fn on_shutdown(&self);
 }
@@ -0,0 +1,519 @@
+use std::{
+  collections::HashMap,
+  fmt::{Display, Formatter},
+};
+
+use graphql_parser::schema::{Definition, EnumValue, Field, InputValue, Type, TypeDefinition};
+
+use crate::graphql::ParsedGraphQLSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaChangeKind {
+  Added,
+  Removed,
+  Changed,
+}
+
+impl Display for SchemaChangeKind {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Added => write!(f, "added"),
+      Self::Removed => write!(f, "removed"),
+      Self::Changed => write!(f, "changed"),
+    }
+  }
+}
+
+/// A single structural change between two versions of a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaChange {
+  pub kind: SchemaChangeKind,
+  /// The schema coordinate of the changed element, for example `User`, `User.name`, `Role.ADMIN` or `Query.user(id:)`.
+  pub path: String,
+  /// The name of the added or removed interface (for object and interface types) or member (for unions). The path is
+  /// the coordinate of the type, since these don't have their own coordinate.
+  pub member: Option<String>,
+  /// A human readable description of the change.
+  pub message: String,
+  /// Whether the change can break operations that were valid with the previous schema.
+  pub breaking: bool,
+}
+
+impl Display for SchemaChange {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} {}: {}", self.kind, self.path, self.message)?;
+
+    if let Some(member) = &self.member {
+      write!(f, " ({})", member)?;
+    }
+
+    if self.breaking {
+      write!(f, " (breaking)")?;
+    }
+
+    Ok(())
+  }
+}
+
+/// The structural changes between two versions of a schema. Descriptions and directives usage are not compared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+  pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+
+  pub fn has_breaking_changes(&self) -> bool {
+    self.changes.iter().any(|c| c.breaking)
+  }
+
+  pub fn count(&self, kind: SchemaChangeKind) -> usize {
+    self.changes.iter().filter(|c| c.kind == kind).count()
+  }
+
+  pub fn breaking_count(&self) -> usize {
+    self.changes.iter().filter(|c| c.breaking).count()
+  }
+
+  fn push(&mut self, kind: SchemaChangeKind, path: String, message: String, breaking: bool) {
+    self.changes.push(SchemaChange {
+      kind,
+      path,
+      member: None,
+      message,
+      breaking,
+    });
+  }
+
+  fn push_member(&mut self, kind: SchemaChangeKind, path: &str, member: &str, message: String) {
+    self.changes.push(SchemaChange {
+      kind,
+      path: path.to_string(),
+      member: Some(member.to_string()),
+      message,
+      breaking: kind == SchemaChangeKind::Removed,
+    });
+  }
+}
+
+type SchemaTypeDefinition = TypeDefinition<'static, String>;
+type SchemaType = Type<'static, String>;
+
+/// Computes the changes of types and fields between the `previous` and the `current` schema.
+pub fn diff_schemas(previous: &ParsedGraphQLSchema, current: &ParsedGraphQLSchema) -> SchemaDiff {
+  let mut diff = SchemaDiff::default();
+  let previous_types = type_definitions(previous);
+  let current_types = type_definitions(current);
+  let previous_by_name = previous_types.iter().copied().collect::<HashMap<_, _>>();
+  let current_by_name = current_types.iter().copied().collect::<HashMap<_, _>>();
+
+  for (name, previous_type) in &previous_types {
+    match current_by_name.get(name) {
+      Some(current_type) => diff_types(&mut diff, name, previous_type, current_type),
+      None => diff.push(
+        SchemaChangeKind::Removed,
+        name.to_string(),
+        format!("{} was removed", type_kind(previous_type)),
+        true,
+      ),
+    }
+  }
+
+  for (name, current_type) in &current_types {
+    if !previous_by_name.contains_key(name) {
+      diff.push(
+        SchemaChangeKind::Added,
+        name.to_string(),
+        format!("{} was added", type_kind(current_type)),
+        false,
+      );
+    }
+  }
+
+  diff
+}
+
+fn type_definitions(schema: &ParsedGraphQLSchema) -> Vec<(&str, &SchemaTypeDefinition)> {
+  schema
+    .definitions
+    .iter()
+    .filter_map(|definition| match definition {
+      Definition::TypeDefinition(type_definition) => {
+        Some((type_name(type_definition), type_definition))
+      }
+      _ => None,
+    })
+    .collect()
+}
+
+fn type_name(type_definition: &SchemaTypeDefinition) -> &str {
+  match type_definition {
+    TypeDefinition::Scalar(t) => &t.name,
+    TypeDefinition::Object(t) => &t.name,
+    TypeDefinition::Interface(t) => &t.name,
+    TypeDefinition::Union(t) => &t.name,
+    TypeDefinition::Enum(t) => &t.name,
+    TypeDefinition::InputObject(t) => &t.name,
+  }
+}
+
+fn type_kind(type_definition: &SchemaTypeDefinition) -> &'static str {
+  match type_definition {
+    TypeDefinition::Scalar(_) => "scalar",
+    TypeDefinition::Object(_) => "object type",
+    TypeDefinition::Interface(_) => "interface",
+    TypeDefinition::Union(_) => "union",
+    TypeDefinition::Enum(_) => "enum",
+    TypeDefinition::InputObject(_) => "input object type",
+  }
+}
+
+fn diff_types(
+  diff: &mut SchemaDiff,
+  name: &str,
+  previous: &SchemaTypeDefinition,
+  current: &SchemaTypeDefinition,
+) {
+  match (previous, current) {
+    (TypeDefinition::Scalar(_), TypeDefinition::Scalar(_)) => {}
+    (TypeDefinition::Object(p), TypeDefinition::Object(c)) => {
+      diff_members(
+        diff,
+        name,
+        "interface",
+        &p.implements_interfaces,
+        &c.implements_interfaces,
+      );
+      diff_fields(diff, name, &p.fields, &c.fields);
+    }
+    (TypeDefinition::Interface(p), TypeDefinition::Interface(c)) => {
+      diff_members(
+        diff,
+        name,
+        "interface",
+        &p.implements_interfaces,
+        &c.implements_interfaces,
+      );
+      diff_fields(diff, name, &p.fields, &c.fields);
+    }
+    (TypeDefinition::Union(p), TypeDefinition::Union(c)) => {
+      diff_members(diff, name, "member", &p.types, &c.types)
+    }
+    (TypeDefinition::Enum(p), TypeDefinition::Enum(c)) => {
+      diff_enum_values(diff, name, &p.values, &c.values)
+    }
+    (TypeDefinition::InputObject(p), TypeDefinition::InputObject(c)) => {
+      diff_input_values(diff, name, "input field", &p.fields, &c.fields)
+    }
+    _ => diff.push(
+      SchemaChangeKind::Changed,
+      name.to_string(),
+      format!(
+        "kind changed from {} to {}",
+        type_kind(previous),
+        type_kind(current)
+      ),
+      true,
+    ),
+  }
+}
+
+/// Compares a list of names: implemented interfaces or union members. Removing one of them is always breaking.
+fn diff_members(
+  diff: &mut SchemaDiff,
+  name: &str,
+  member_kind: &str,
+  previous: &[String],
+  current: &[String],
+) {
+  for member in previous.iter().filter(|m| !current.contains(m)) {
+    diff.push_member(
+      SchemaChangeKind::Removed,
+      name,
+      member,
+      format!("{} was removed", member_kind),
+    );
+  }
+
+  for member in current.iter().filter(|m| !previous.contains(m)) {
+    diff.push_member(
+      SchemaChangeKind::Added,
+      name,
+      member,
+      format!("{} was added", member_kind),
+    );
+  }
+}
+
+fn diff_enum_values(
+  diff: &mut SchemaDiff,
+  name: &str,
+  previous: &[EnumValue<'static, String>],
+  current: &[EnumValue<'static, String>],
+) {
+  for value in previous
+    .iter()
+    .filter(|p| !current.iter().any(|c| c.name == p.name))
+  {
+    diff.push(
+      SchemaChangeKind::Removed,
+      format!("{}.{}", name, value.name),
+      String::from("value was removed"),
+      true,
+    );
+  }
+
+  for value in current
+    .iter()
+    .filter(|c| !previous.iter().any(|p| p.name == c.name))
+  {
+    diff.push(
+      SchemaChangeKind::Added,
+      format!("{}.{}", name, value.name),
+      String::from("value was added"),
+      false,
+    );
+  }
+}
+
+fn diff_fields(
+  diff: &mut SchemaDiff,
+  type_name: &str,
+  previous: &[Field<'static, String>],
+  current: &[Field<'static, String>],
+) {
+  for previous_field in previous {
+    let path = format!("{}.{}", type_name, previous_field.name);
+
+    match current.iter().find(|f| f.name == previous_field.name) {
+      Some(current_field) => {
+        if previous_field.field_type != current_field.field_type {
+          diff.push(
+            SchemaChangeKind::Changed,
+            path.clone(),
+            format!(
+              "type changed from {} to {}",
+              previous_field.field_type, current_field.field_type
+            ),
+            !is_safe_output_type_change(&previous_field.field_type, &current_field.field_type),
+          );
+        }
+
+        diff_input_values(
+          diff,
+          &path,
+          "argument",
+          &previous_field.arguments,
+          &current_field.arguments,
+        );
+      }
+      None => diff.push(
+        SchemaChangeKind::Removed,
+        path,
+        String::from("field was removed"),
+        true,
+      ),
+    }
+  }
+
+  for current_field in current {
+    if !previous.iter().any(|f| f.name == current_field.name) {
+      diff.push(
+        SchemaChangeKind::Added,
+        format!("{}.{}", type_name, current_field.name),
+        String::from("field was added"),
+        false,
+      );
+    }
+  }
+}
+
+/// Compares arguments (of a field) or fields (of an input object type).
+fn diff_input_values(
+  diff: &mut SchemaDiff,
+  parent_path: &str,
+  value_kind: &str,
+  previous: &[InputValue<'static, String>],
+  current: &[InputValue<'static, String>],
+) {
+  let path = |name: &str| match value_kind {
+    "argument" => format!("{}({}:)", parent_path, name),
+    _ => format!("{}.{}", parent_path, name),
+  };
+
+  for previous_value in previous {
+    match current.iter().find(|v| v.name == previous_value.name) {
+      Some(current_value) => {
+        if previous_value.value_type != current_value.value_type {
+          diff.push(
+            SchemaChangeKind::Changed,
+            path(&previous_value.name),
+            format!(
+              "type changed from {} to {}",
+              previous_value.value_type, current_value.value_type
+            ),
+            !is_safe_input_type_change(&previous_value.value_type, &current_value.value_type),
+          );
+        }
+
+        if previous_value.default_value != current_value.default_value {
+          diff.push(
+            SchemaChangeKind::Changed,
+            path(&previous_value.name),
+            String::from("default value changed"),
+            false,
+          );
+        }
+      }
+      None => diff.push(
+        SchemaChangeKind::Removed,
+        path(&previous_value.name),
+        format!("{} was removed", value_kind),
+        true,
+      ),
+    }
+  }
+
+  for current_value in current {
+    if !previous.iter().any(|v| v.name == current_value.name) {
+      let required = matches!(current_value.value_type, Type::NonNullType(_))
+        && current_value.default_value.is_none();
+
+      diff.push(
+        SchemaChangeKind::Added,
+        path(&current_value.name),
+        if required {
+          format!("required {} was added", value_kind)
+        } else {
+          format!("{} was added", value_kind)
+        },
+        required,
+      );
+    }
+  }
+}
+
+/// Making an output type non-nullable is safe, since clients already handle non-null values.
+fn is_safe_output_type_change(previous: &SchemaType, current: &SchemaType) -> bool {
+  match (previous, current) {
+    (Type::NonNullType(p), Type::NonNullType(c)) => is_safe_output_type_change(p, c),
+    (p, Type::NonNullType(c)) => is_safe_output_type_change(p, c),
+    (Type::ListType(p), Type::ListType(c)) => is_safe_output_type_change(p, c),
+    (Type::NamedType(p), Type::NamedType(c)) => p == c,
+    _ => false,
+  }
+}
+
+/// Making an input type nullable is safe, since clients already send non-null values.
+fn is_safe_input_type_change(previous: &SchemaType, current: &SchemaType) -> bool {
+  match (previous, current) {
+    (Type::NonNullType(p), Type::NonNullType(c)) => is_safe_input_type_change(p, c),
+    (Type::NonNullType(p), c) => is_safe_input_type_change(p, c),
+    (Type::ListType(p), Type::ListType(c)) => is_safe_input_type_change(p, c),
+    (Type::NamedType(p), Type::NamedType(c)) => p == c,
+    _ => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::graphql::parse_graphql_schema;
+
+  fn diff(previous: &str, current: &str) -> SchemaDiff {
+    diff_schemas(
+      &parse_graphql_schema(previous).unwrap(),
+      &parse_graphql_schema(current).unwrap(),
+    )
+  }
+
+  #[test]
+  fn should_not_report_changes_for_identical_schemas() {
+    let schema = "type Query { user(id: ID!): User } type User { id: ID! name: String }";
+
+    assert!(diff(schema, schema).is_empty());
+  }
+
+  #[test]
+  fn should_report_breaking_and_safe_changes() {
+    let result = diff(
+      r#"
+        type Query { user(id: ID!): User, legacy: String }
+        type User { id: ID! name: String }
+        enum Role { ADMIN USER }
+      "#,
+      r#"
+        type Query { user(id: ID, locale: String!): User }
+        type User { id: ID! name: String! email: String }
+        enum Role { ADMIN }
+        input Filter { name: String }
+      "#,
+    );
+
+    let changes = result
+      .changes
+      .iter()
+      .map(|c| (c.path.as_str(), c.kind, c.breaking))
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      changes,
+      vec![
+        ("Query.user(id:)", SchemaChangeKind::Changed, false),
+        ("Query.user(locale:)", SchemaChangeKind::Added, true),
+        ("Query.legacy", SchemaChangeKind::Removed, true),
+        ("User.name", SchemaChangeKind::Changed, false),
+        ("User.email", SchemaChangeKind::Added, false),
+        ("Role.USER", SchemaChangeKind::Removed, true),
+        ("Filter", SchemaChangeKind::Added, false),
+      ]
+    );
+    assert!(result.has_breaking_changes());
+    assert_eq!(result.breaking_count(), 3);
+  }
+
+  #[test]
+  fn should_report_interfaces_and_union_members() {
+    let result = diff(
+      r#"
+        interface Node { id: ID! }
+        interface Entity { id: ID! }
+        type User implements Node { id: ID! }
+        type Group implements Node & Entity { id: ID! }
+        union Member = User | Group
+        enum Role { ADMIN }
+      "#,
+      r#"
+        interface Node { id: ID! }
+        interface Entity { id: ID! }
+        type User implements Node & Entity { id: ID! }
+        type Group implements Node { id: ID! }
+        union Member = User
+        enum Role { ADMIN USER }
+      "#,
+    );
+
+    let changes = result
+      .changes
+      .iter()
+      .map(|c| (c.path.as_str(), c.member.as_deref(), c.kind, c.breaking))
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      changes,
+      vec![
+        ("User", Some("Entity"), SchemaChangeKind::Added, false),
+        ("Group", Some("Entity"), SchemaChangeKind::Removed, true),
+        ("Member", Some("Group"), SchemaChangeKind::Removed, true),
+        ("Role.USER", None, SchemaChangeKind::Added, false),
+      ]
+    );
+    assert_eq!(
+      result.changes[2].to_string(),
+      "removed Member: member was removed (Group) (breaking)"
+    );
+  }
+}
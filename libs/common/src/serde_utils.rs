@@ -84,6 +84,20 @@ impl<'de> Deserialize<'de> for LocalFileReference {
   }
 }
 
+/// Deserializes an optional file path, and resolves it relative to the config file (like `LocalFileReference`), without reading the file.
+pub fn deserialize_optional_relative_path<'de, D>(
+  deserializer: D,
+) -> Result<Option<String>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let path = Option::<String>::deserialize(deserializer)?;
+
+  Ok(path.map(|path| {
+    BASE_PATH.with(|base_path| base_path.borrow().join(path).to_string_lossy().into_owned())
+  }))
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct JsonSchemaExample<T: Serialize> {
   #[serde(rename = "$metadata")]
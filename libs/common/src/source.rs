@@ -1,10 +1,11 @@
-use std::{fmt::Debug, future::Future, pin::Pin, sync::Arc};
+use std::{fmt::Debug, future::Future, pin::Pin, sync::Arc, time::SystemTime};
 
 use crate::{
   execute::RequestExecutionContext,
   graphql::{GraphQLResponse, ParsedGraphQLSchema},
   http::StatusCode,
   plugin_manager::PluginManager,
+  schema_diff::SchemaDiff,
 };
 
 #[derive(thiserror::Error, Debug)]
@@ -25,6 +26,49 @@ pub trait SourceRuntime: Debug + Send + Sync + 'static {
   fn name(&self) -> &str;
   fn schema(&self) -> Option<Arc<ParsedGraphQLSchema>>;
   fn sdl(&self) -> Option<Arc<String>>;
+
+  /// The loading state of the source's schema, or `None` if the source doesn't load a schema.
+  fn schema_health(&self) -> Option<SchemaHealth> {
+    None
+  }
+
+  /// The sources combined by this source (for example, the sources of a merge source), or an empty list.
+  fn merged_sources(&self) -> Vec<Arc<Box<dyn SourceRuntime>>> {
+    vec![]
+  }
+
+  /// Stops the background work of the source (for example, schema polling) as part of the gateway shutdown.
+  fn shutdown(&self) {}
+
+  /// Registers a listener that is called every time the source reloads a schema that differs from the previous one, or
+  /// loads a schema after the initial load failed.
+  ///
+  /// Sources that don't load a schema never call the listener.
+  fn subscribe_schema_changes(&self, _listener: SchemaChangeListener) {}
+
+  /// Reloads the schema of the source if its polling interval elapsed since the last reload.
+  ///
+  /// Used on WASM runtime, where the schema can't be polled in the background. `now` is provided by the caller, since
+  /// the system clock is not available there.
+  fn refresh_schema<'a>(&'a self, _now: SystemTime) -> Pin<Box<(dyn Future<Output = ()> + 'a)>> {
+    Box::pin(async {})
+  }
+}
+
+/// Called with the id of the source and the changes between its previous and current schema.
+pub type SchemaChangeListener = Arc<dyn Fn(&str, &SchemaDiff) + Send + Sync>;
+
+/// The loading state of a source's schema, used to report the readiness of the gateway.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaHealth {
+  /// A hash of the schema currently in use, if a schema was loaded.
+  pub schema_hash: Option<String>,
+  /// When the schema currently in use was loaded. Not available on WASM runtime.
+  pub last_loaded_at: Option<SystemTime>,
+  /// The error of the last attempt to load the schema, if it failed after the last successful load.
+  pub last_error: Option<String>,
+  /// When the last attempt to load the schema failed. Not available on WASM runtime.
+  pub last_failed_at: Option<SystemTime>,
 }
 
 #[derive(thiserror::Error, Debug)]
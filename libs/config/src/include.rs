@@ -0,0 +1,531 @@
+use std::{
+  collections::{BTreeMap, HashMap},
+  fs::{read_dir, read_to_string},
+  path::{Path, PathBuf},
+};
+
+use conductor_common::serde_utils::BASE_PATH;
+use serde::Deserialize;
+
+use crate::{
+  interpolate::interpolate, override_plugins, parse_config_with_includes, ConductorConfig,
+  ConfigError, ConfigFormat, EndpointDefinition, PluginDefinition, SourceDefinition,
+};
+
+type Warnings = Vec<String>;
+
+/// A config file loaded using `$include`.
+///
+/// Fragments can only contribute sources, endpoints, plugins and plugin sets: top-level settings like `server` and `logger` must be declared in the root config file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ConductorConfigFragment {
+  #[serde(rename = "$schema", default)]
+  #[allow(dead_code)]
+  schema: Option<String>,
+  #[serde(rename = "$include", default)]
+  include: Vec<String>,
+  #[serde(default)]
+  sources: Vec<SourceDefinition>,
+  #[serde(default)]
+  endpoints: Vec<EndpointDefinition>,
+  #[serde(default)]
+  plugins: Vec<PluginDefinition>,
+  #[serde(default)]
+  plugin_sets: BTreeMap<String, Vec<PluginDefinition>>,
+}
+
+struct IncludeResolver<'a> {
+  get_env_value: &'a dyn Fn(&str) -> Option<String>,
+  stack: Vec<PathBuf>,
+  source_origins: HashMap<String, String>,
+  endpoint_origins: HashMap<String, String>,
+  plugin_set_origins: HashMap<String, String>,
+  included_paths: Vec<PathBuf>,
+  warnings: Warnings,
+}
+
+/// Loads all files referenced by `$include` (recursively), and merges them into the given config.
+///
+/// `root` is the path of the file the config was loaded from, if any. It's used for reporting and cycles detection.
+/// Relative paths are resolved based on the current `BASE_PATH`.
+pub(crate) fn resolve_includes(
+  config: &mut ConductorConfig,
+  root: Option<&Path>,
+  get_env_value: &dyn Fn(&str) -> Option<String>,
+) -> Result<Warnings, ConfigError> {
+  let root_name = root
+    .map(|p| p.to_string_lossy().into_owned())
+    .unwrap_or_else(|| String::from("root config"));
+
+  let mut resolver = IncludeResolver {
+    get_env_value,
+    stack: root
+      .and_then(|p| p.canonicalize().ok())
+      .into_iter()
+      .collect(),
+    source_origins: config
+      .sources
+      .iter()
+      .map(|s| (s.id().to_string(), root_name.clone()))
+      .collect(),
+    endpoint_origins: config
+      .endpoints
+      .iter()
+      .map(|e| (e.path.clone(), root_name.clone()))
+      .collect(),
+    plugin_set_origins: config
+      .plugin_sets
+      .keys()
+      .map(|name| (name.clone(), root_name.clone()))
+      .collect(),
+    included_paths: vec![],
+    warnings: vec![],
+  };
+
+  let base_path = BASE_PATH.with(|bp| bp.borrow().clone());
+
+  for include in std::mem::take(&mut config.include) {
+    resolver.include(config, &base_path.join(include))?;
+  }
+
+  config.included_paths = resolver.included_paths;
+
+  Ok(resolver.warnings)
+}
+
+/// Loads the config file referenced by `extends` (and the files it includes or extends), and merges the given config
+/// over it.
+///
+/// `root` is the path of the file the config was loaded from, if any. `extended_by` is the chain of files that extend
+/// it, used to detect cycles.
+pub(crate) fn resolve_extends(
+  config: &mut ConductorConfig,
+  declares_server: bool,
+  root: Option<&Path>,
+  get_env_value: &dyn Fn(&str) -> Option<String>,
+  extended_by: &[PathBuf],
+) -> Result<Warnings, ConfigError> {
+  let Some(extends) = config.extends.take() else {
+    return Ok(vec![]);
+  };
+
+  let base_path = BASE_PATH.with(|bp| bp.borrow().clone());
+  let path = base_path.join(extends);
+  let path_name = path.to_string_lossy().into_owned();
+  let read_failed = |source| ConfigError::ReadFailed {
+    path: path_name.clone(),
+    source,
+  };
+  let canonical_path = path.canonicalize().map_err(read_failed)?;
+
+  let mut chain = extended_by.to_vec();
+  chain.extend(root.and_then(|p| p.canonicalize().ok()));
+
+  if chain.contains(&canonical_path) {
+    return Err(ConfigError::IncludeCycle { path: path_name });
+  }
+
+  let format = ConfigFormat::try_from_path(&path)?;
+  let contents = read_to_string(&path).map_err(read_failed)?;
+
+  // File references inside the base config are relative to the base config itself.
+  let parent_path = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+  let previous_base_path = BASE_PATH.with(|bp| bp.replace(parent_path));
+  let base = parse_config_with_includes(contents, format, Some(&path), get_env_value, &chain);
+  BASE_PATH.with(|bp| bp.replace(previous_base_path));
+  let (base, warnings) = base.map_err(|e| e.with_file(&path_name))?;
+
+  let extending = std::mem::replace(config, base);
+  config.included_paths.insert(0, path);
+  config.included_paths.extend(extending.included_paths);
+
+  if declares_server {
+    config.server = extending.server;
+  }
+
+  config.logger = extending.logger.or(config.logger.take());
+  config.propagation = extending.propagation.or(config.propagation.take());
+
+  for source in extending.sources {
+    match config.sources.iter_mut().find(|s| s.id() == source.id()) {
+      Some(base_source) => *base_source = source,
+      None => config.sources.push(source),
+    }
+  }
+
+  for endpoint in extending.endpoints {
+    match config
+      .endpoints
+      .iter_mut()
+      .find(|e| e.path == endpoint.path)
+    {
+      Some(base_endpoint) => *base_endpoint = endpoint,
+      None => config.endpoints.push(endpoint),
+    }
+  }
+
+  if let Some(plugins) = extending.plugins {
+    override_plugins(config.plugins.get_or_insert_with(Vec::new), 0, &plugins);
+  }
+
+  config.plugin_sets.extend(extending.plugin_sets);
+
+  Ok(warnings)
+}
+
+impl<'a> IncludeResolver<'a> {
+  fn include(&mut self, config: &mut ConductorConfig, path: &Path) -> Result<(), ConfigError> {
+    if !path.is_dir() {
+      return self.include_file(config, path);
+    }
+
+    // The directory itself is watched, so added and removed files are detected.
+    self.included_paths.push(path.to_path_buf());
+
+    let mut files = read_dir(path)
+      .map_err(|source| ConfigError::ReadFailed {
+        path: path.to_string_lossy().into_owned(),
+        source,
+      })?
+      .filter_map(|entry| entry.ok().map(|e| e.path()))
+      .filter(|p| p.is_file() && ConfigFormat::try_from_path(p).is_ok())
+      .collect::<Vec<_>>();
+    files.sort();
+
+    for file in files {
+      self.include_file(config, &file)?;
+    }
+
+    Ok(())
+  }
+
+  fn include_file(&mut self, config: &mut ConductorConfig, path: &Path) -> Result<(), ConfigError> {
+    let path_name = path.to_string_lossy().into_owned();
+    let read_failed = |source| ConfigError::ReadFailed {
+      path: path_name.clone(),
+      source,
+    };
+    let canonical_path = path.canonicalize().map_err(read_failed)?;
+
+    if self.stack.contains(&canonical_path) {
+      return Err(ConfigError::IncludeCycle {
+        path: path_name.clone(),
+      });
+    }
+
+    let format = ConfigFormat::try_from_path(path)?;
+    let contents = read_to_string(path).map_err(read_failed)?;
+    self.included_paths.push(path.to_path_buf());
+    let (contents, warnings) = interpolate(&contents, self.get_env_value)
+      .map_err(|errors| ConfigError::InterpolationFailed { errors })?;
+    self.warnings.extend(warnings);
+
+    // File references inside the fragment are relative to the fragment itself.
+    let parent_path = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+    let previous_base_path = BASE_PATH.with(|bp| bp.replace(parent_path.clone()));
+    let fragment = match format {
+      ConfigFormat::Json => {
+        serde_json::from_str::<ConductorConfigFragment>(&contents).map_err(ConfigError::from)
+      }
+      ConfigFormat::Yaml => {
+        serde_yaml::from_str::<ConductorConfigFragment>(&contents).map_err(ConfigError::from)
+      }
+    };
+    BASE_PATH.with(|bp| bp.replace(previous_base_path));
+    let fragment = fragment.map_err(|e| e.with_file(&path_name))?;
+
+    for source in fragment.sources {
+      if let Some(first) = self
+        .source_origins
+        .insert(source.id().to_string(), path_name.clone())
+      {
+        return Err(ConfigError::IncludeConflict {
+          kind: "source",
+          key: source.id().to_string(),
+          first,
+          second: path_name,
+        });
+      }
+
+      config.sources.push(source);
+    }
+
+    for endpoint in fragment.endpoints {
+      if let Some(first) = self
+        .endpoint_origins
+        .insert(endpoint.path.clone(), path_name.clone())
+      {
+        return Err(ConfigError::IncludeConflict {
+          kind: "endpoint",
+          key: endpoint.path,
+          first,
+          second: path_name,
+        });
+      }
+
+      config.endpoints.push(endpoint);
+    }
+
+    for (name, plugins) in fragment.plugin_sets {
+      if let Some(first) = self
+        .plugin_set_origins
+        .insert(name.clone(), path_name.clone())
+      {
+        return Err(ConfigError::IncludeConflict {
+          kind: "plugin set",
+          key: name,
+          first,
+          second: path_name,
+        });
+      }
+
+      config.plugin_sets.insert(name, plugins);
+    }
+
+    if !fragment.plugins.is_empty() {
+      config
+        .plugins
+        .get_or_insert_with(Vec::new)
+        .extend(fragment.plugins);
+    }
+
+    self.stack.push(canonical_path);
+
+    for include in fragment.include {
+      self.include(config, &parent_path.join(include))?;
+    }
+
+    self.stack.pop();
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs::{create_dir_all, write};
+
+  use tempfile::TempDir;
+
+  use super::*;
+  use crate::try_load_config;
+
+  fn fixture_dir() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    create_dir_all(dir.path().join("fragments")).unwrap();
+
+    dir
+  }
+
+  #[tokio::test]
+  async fn should_merge_included_files_and_directories() {
+    let fixture = fixture_dir();
+    let dir = fixture.path();
+    write(
+      dir.join("config.yaml"),
+      r#"
+$include:
+  - sources.yaml
+  - fragments
+sources: []
+endpoints:
+  - path: /graphql
+    from: countries
+"#,
+    )
+    .unwrap();
+    write(
+      dir.join("sources.yaml"),
+      r#"
+sources:
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+"#,
+    )
+    .unwrap();
+    write(
+      dir.join("fragments/a.yaml"),
+      "endpoints:\n  - path: /a\n    from: countries\n",
+    )
+    .unwrap();
+    write(
+      dir.join("fragments/b.json"),
+      r#"{ "endpoints": [{ "path": "/b", "from": "countries" }], "plugins": [{ "type": "cors" }] }"#,
+    )
+    .unwrap();
+
+    let (config, _) = try_load_config(
+      &dir.join("config.yaml").to_string_lossy().into_owned(),
+      |_| None,
+    )
+    .await
+    .unwrap();
+
+    assert!(config.include.is_empty());
+    assert_eq!(config.sources.len(), 1);
+    assert_eq!(
+      config
+        .endpoints
+        .iter()
+        .map(|e| e.path.as_str())
+        .collect::<Vec<_>>(),
+      vec!["/graphql", "/a", "/b"]
+    );
+    assert_eq!(config.plugins.map(|p| p.len()), Some(1));
+  }
+
+  #[tokio::test]
+  async fn should_report_conflicts_between_files() {
+    let fixture = fixture_dir();
+    let dir = fixture.path();
+    write(
+      dir.join("config.yaml"),
+      "$include: [fragments/a.yaml]\nsources: []\nendpoints:\n  - path: /a\n    from: countries\n",
+    )
+    .unwrap();
+    write(
+      dir.join("fragments/a.yaml"),
+      "endpoints:\n  - path: /a\n    from: countries\n",
+    )
+    .unwrap();
+
+    let result = try_load_config(
+      &dir.join("config.yaml").to_string_lossy().into_owned(),
+      |_| None,
+    )
+    .await;
+
+    assert!(matches!(
+      result,
+      Err(ConfigError::IncludeConflict {
+        kind: "endpoint",
+        ..
+      })
+    ));
+  }
+
+  #[tokio::test]
+  async fn should_detect_include_cycles() {
+    let fixture = fixture_dir();
+    let dir = fixture.path();
+    write(
+      dir.join("config.yaml"),
+      "$include: [fragments/a.yaml]\nsources: []\nendpoints: []\n",
+    )
+    .unwrap();
+    write(dir.join("fragments/a.yaml"), "$include: [../config.yaml]\n").unwrap();
+
+    let result = try_load_config(
+      &dir.join("config.yaml").to_string_lossy().into_owned(),
+      |_| None,
+    )
+    .await;
+
+    assert!(matches!(result, Err(ConfigError::IncludeCycle { .. })));
+  }
+
+  #[tokio::test]
+  async fn should_merge_config_over_extended_file() {
+    let fixture = fixture_dir();
+    let dir = fixture.path();
+    write(
+      dir.join("base.yaml"),
+      r#"
+server:
+  port: 8000
+logger:
+  filter: debug
+sources:
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+endpoints:
+  - path: /graphql
+    from: countries
+  - path: /public
+    from: countries
+plugins:
+  - type: cors
+  - type: graphiql
+"#,
+    )
+    .unwrap();
+    write(
+      dir.join("config.yaml"),
+      r#"
+extends: base.yaml
+sources: []
+endpoints:
+  - path: /public
+    from: countries
+    plugins:
+      - type: http_get
+  - path: /internal
+    from: countries
+plugins:
+  - type: graphiql
+    enabled: false
+"#,
+    )
+    .unwrap();
+
+    let (config, _) = try_load_config(
+      &dir.join("config.yaml").to_string_lossy().into_owned(),
+      |_| None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(config.extends, None);
+    assert_eq!(config.server.map(|s| s.port), Some(8000));
+    assert!(config.logger.is_some());
+    assert_eq!(config.sources.len(), 1);
+    assert_eq!(
+      config
+        .endpoints
+        .iter()
+        .map(|e| (e.path.as_str(), e.plugins.is_some()))
+        .collect::<Vec<_>>(),
+      vec![("/graphql", false), ("/public", true), ("/internal", false)]
+    );
+    assert!(matches!(
+      config.plugins.as_deref(),
+      Some([
+        PluginDefinition::CorsPlugin { .. },
+        PluginDefinition::GraphiQLPlugin {
+          enabled: Some(false),
+          ..
+        }
+      ])
+    ));
+    assert_eq!(config.included_paths, vec![dir.join("base.yaml")]);
+  }
+
+  #[tokio::test]
+  async fn should_detect_extends_cycles() {
+    let fixture = fixture_dir();
+    let dir = fixture.path();
+    write(
+      dir.join("config.yaml"),
+      "extends: fragments/base.yaml\nsources: []\nendpoints: []\n",
+    )
+    .unwrap();
+    write(
+      dir.join("fragments/base.yaml"),
+      "extends: ../config.yaml\nsources: []\nendpoints: []\n",
+    )
+    .unwrap();
+
+    let result = try_load_config(
+      &dir.join("config.yaml").to_string_lossy().into_owned(),
+      |_| None,
+    )
+    .await;
+
+    assert!(matches!(result, Err(ConfigError::IncludeCycle { .. })));
+  }
+}
@@ -1,14 +1,25 @@
+pub mod include;
 pub mod interpolate;
+pub mod validate;
 
 use conductor_common::{
   http::{HttpHeadersMap, Method, ToHeadersMap},
-  serde_utils::{JsonSchemaExample, JsonSchemaExampleMetadata, LocalFileReference, BASE_PATH},
+  serde_utils::{
+    deserialize_optional_relative_path, JsonSchemaExample, JsonSchemaExampleMetadata,
+    LocalFileReference, BASE_PATH,
+  },
 };
 use conductor_logger::config::LoggerConfigFormat;
+pub use conductor_tracing::propagation::PropagationConfig;
 use interpolate::interpolate;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs::read_to_string, path::Path, time::Duration};
+use std::{
+  collections::{BTreeMap, HashMap},
+  fs::read_to_string,
+  path::{Path, PathBuf},
+  time::Duration,
+};
 
 /// This section describes the top-level configuration object for Conductor gateway.
 ///
@@ -30,6 +41,44 @@ use std::{collections::HashMap, fs::read_to_string, path::Path, time::Duration};
 ///
 /// > By default, Conductor will look for a file named `config.json` in the current directory.
 ///
+/// To check a configuration file without starting the server, use the `validate` command. It reports missing sources, duplicate endpoint paths and plugins, and parses local schema files:
+///
+/// ```sh
+///
+/// conductor validate my-config-file.json
+///
+/// ```
+///
+/// To print the configuration after environment variables interpolation, use `conductor print-config my-config-file.json`.
+///
+/// #### Reloading the configuration
+///
+/// The binary watches the configuration file for changes, and reloads the gateway without restarting the process. You can also trigger a reload by sending a `SIGHUP` signal to the process.
+///
+/// In-flight requests are completed using the previous configuration. If the new configuration is invalid, it's rejected and the previous configuration is kept.
+///
+/// > Changes to the `server` and `logger` sections require a restart.
+///
+/// #### Health and readiness
+///
+/// The binary exposes two endpoints for health checks: `/_health` always responds with `200` while the process is running, and `/_readiness` responds with `200` only when all sources have loaded their schema (see `server.readiness_max_staleness`). The readiness response body lists the state, schema hash (SHA-256) and last load time of every source, including the sources combined by a `merge` source.
+///
+/// On `SIGTERM` (or `SIGINT`), the server stops accepting new connections, `/_readiness` starts responding with `503`, and in-flight requests are given `server.shutdown_timeout` to complete before telemetry is flushed and the process exits.
+///
+/// #### TLS
+///
+/// The binary can terminate TLS (with HTTP/2 support) using the `server.tls` configuration. Certificate files are reloaded when they change on disk, so renewed certificates are picked up without a restart:
+///
+/// ```yaml
+/// server:
+///   port: 443
+///   health_check_port: 8080
+///   tls:
+///     cert_file: certs/server.crt
+///     key_file: certs/server.key
+///     client_ca_file: certs/clients-ca.crt # optional, enables mTLS
+/// ```
+///
 /// ### Docker
 ///
 /// If you are using Docker environment, you can mount the configuration file into the container, and then point the Conductor binary to it:
@@ -86,12 +135,42 @@ use std::{collections::HashMap, fs::read_to_string, path::Path, time::Duration};
 /// - `endpoint: ${API_ENDPOINT:https://api.example.com/}` - Uses the `API_ENDPOINT` variable or defaults to the provided URL.
 /// - `name: \$super` - Results in the literal string `name: \$super` in the configuration.
 ///
+/// ### Splitting the configuration into multiple files
+///
+/// Large configurations can be split into multiple files using the top-level `$include` property. Each entry is a path to a file (or a directory of files) that contributes `sources`, `endpoints`, `plugins` and `plugin_sets` to the main configuration:
+///
+/// ```yaml
+/// $include:
+///   - sources/countries.yaml
+///   - endpoints/
+/// ```
+///
+/// Environment variables are interpolated in included files as well. Defining the same source `id`, endpoint `path` or plugin set name in more than one file is an error.
+///
+/// ### Extending a configuration file
+///
+/// A configuration file can reuse another one with the top-level `extends` property, for example to share the sources and plugins of a base configuration between environments:
+///
+/// ```yaml
+/// extends: ./base.yaml
+/// sources: []
+/// endpoints:
+///   - path: /internal
+///     from: countries
+/// ```
+///
+/// The settings declared in the extending file take precedence: sources, endpoints and plugin sets replace the ones of the base file with the same `id`, `path` or name, global plugins replace the base plugins of the same type, and `server`, `logger` and `propagation` replace the base settings.
+///
 
 #[cfg(not(target_arch = "wasm32"))]
 fn default_server_config() -> Option<ServerConfig> {
   Some(ServerConfig {
     port: default_server_port(),
     host: default_server_host(),
+    tls: None,
+    health_check_port: None,
+    readiness_max_staleness: None,
+    shutdown_timeout: None,
   })
 }
 
@@ -102,6 +181,22 @@ fn default_server_config() -> Option<ServerConfig> {
 
 #[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
 pub struct ConductorConfig {
+  /// A list of additional config files (or directories of config files) to merge into this config.
+  ///
+  /// Paths are resolved relative to the file that includes them. Included files can declare `sources`, `endpoints`, `plugins`, `plugin_sets` and nested `$include` entries.
+  ///
+  /// When a directory is included, all `.json`, `.yaml` and `.yml` files in it are loaded, sorted by name.
+  #[serde(rename = "$include", default, skip_serializing_if = "Vec::is_empty")]
+  pub include: Vec<String>,
+  /// A base config file, whose settings are used unless they are declared in this config.
+  ///
+  /// Sources, endpoints and plugin sets are merged by `id`, `path` and name, and global plugins replace the base plugins of the same type. The path is resolved relative to this file.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub extends: Option<String>,
+  /// The files and directories loaded with `$include`, set when the config is loaded. They're watched for changes, along with the config file.
+  #[serde(skip)]
+  #[schemars(skip)]
+  pub included_paths: Vec<PathBuf>,
   #[serde(
     default = "default_server_config",
     skip_serializing_if = "Option::is_none"
@@ -113,6 +208,11 @@ pub struct ConductorConfig {
   #[serde(default, skip_serializing_if = "Option::is_none")]
   /// Conductor logger configuration.
   pub logger: Option<LoggerConfig>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  /// Configuration for the correlation of requests: request ids, and propagation of the trace context to the upstream services.
+  ///
+  /// When not specified, the `x-request-id` header is used for request ids, and the W3C trace context is propagated.
+  pub propagation: Option<PropagationConfig>,
   /// List of sources to be used by the gateway. Each source is a GraphQL endpoint or multiple endpoints grouped using a federated implementation.
   ///
   /// For additional information, please refer to the [Sources section](./sources/graphql).
@@ -125,6 +225,63 @@ pub struct ConductorConfig {
   /// List of global plugins to be applied to all endpoints. Global plugins are applied before endpoint-specific plugins.
   #[serde(skip_serializing_if = "Option::is_none")]
   pub plugins: Option<Vec<PluginDefinition>>,
+  /// Named groups of plugins that can be shared between endpoints.
+  ///
+  /// An endpoint can use one or more of these groups by listing their names in its `plugin_sets` field.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub plugin_sets: BTreeMap<String, Vec<PluginDefinition>>,
+}
+
+impl ConductorConfig {
+  /// Returns the list of plugins to apply to the given endpoint: the global plugins, then the plugins of the endpoint's plugin sets (in the order they are listed), and finally the endpoint's own plugins.
+  ///
+  /// A plugin from a plugin set replaces a plugin of the same type declared by a previous set, and the endpoint's own plugins replace plugins of the same type declared by its sets.
+  /// Repeatable plugins (`vrl`) are never replaced: all their declarations are applied.
+  /// Returns the name of the first plugin set that is not defined in `plugin_sets`, if any.
+  pub fn endpoint_plugins(
+    &self,
+    endpoint: &EndpointDefinition,
+  ) -> Result<Vec<PluginDefinition>, String> {
+    let mut plugins = self.plugins.clone().unwrap_or_default();
+    let scoped_start = plugins.len();
+
+    for name in &endpoint.plugin_sets {
+      let plugin_set = self.plugin_sets.get(name).ok_or_else(|| name.clone())?;
+
+      override_plugins(&mut plugins, scoped_start, plugin_set);
+    }
+
+    if let Some(endpoint_plugins) = &endpoint.plugins {
+      override_plugins(&mut plugins, scoped_start, endpoint_plugins);
+    }
+
+    Ok(plugins)
+  }
+}
+
+/// Appends `overrides` to `plugins`, replacing in place the plugins of the same type found after `from`. Repeatable
+/// plugins are always appended.
+fn override_plugins(
+  plugins: &mut Vec<PluginDefinition>,
+  from: usize,
+  overrides: &[PluginDefinition],
+) {
+  let end = plugins.len();
+
+  for plugin in overrides {
+    if plugin.is_repeatable() {
+      plugins.push(plugin.clone());
+      continue;
+    }
+
+    match plugins[from..end]
+      .iter()
+      .position(|p| p.type_name() == plugin.type_name())
+    {
+      Some(index) => plugins[from + index] = plugin.clone(),
+      None => plugins.push(plugin.clone()),
+    }
+  }
 }
 
 /// The `Endpoint` object exposes a GraphQL source with set of plugins applied to it.
@@ -143,11 +300,62 @@ pub struct EndpointDefinition {
   ///
   /// This must match the `id` field of a `Source` definition.
   pub from: String,
-  /// A list of unique plugins to be applied to this endpoint. These plugins will be applied after the global plugins.
+  /// Names of plugin sets (declared in the top-level `plugin_sets` field) to be applied to this endpoint.
+  ///
+  /// Plugin sets are applied after the global plugins, in the order they are listed. When more than one set declares a plugin of the same type, the last one wins (except for `vrl` plugins, which are all applied).
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub plugin_sets: Vec<String>,
+  /// A list of unique plugins to be applied to this endpoint. These plugins will be applied after the global plugins and the plugin sets.
   ///
   /// Order of plugins is important: plugins are applied in the order they are defined.
+  /// A plugin declared here replaces a plugin of the same type coming from the endpoint's plugin sets (except for `vrl` plugins, which are all applied).
   #[serde(skip_serializing_if = "Option::is_none")]
   pub plugins: Option<Vec<PluginDefinition>>,
+  /// Restricts the schema exposed by this endpoint to a subset of the source's schema (also known as a "contract").
+  ///
+  /// The filtered schema is used for validation and introspection: operations that select removed types or fields are rejected. This requires the source to have a schema (for example, using `schema_awareness`).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub schema_filter: Option<SchemaFilterConfig>,
+}
+
+/// Selects the types and fields of a source's schema that are exposed by an endpoint.
+///
+/// Fields can be selected by the `@tag(name: "...")` directive (set on the field or on its parent type), or by schema coordinates (`Type`, `Type.field` or `Type.field(argument:)`), where `*` matches any sequence of characters.
+///
+/// Fields, arguments and types that reference a removed type are removed as well, and types that are no longer reachable from the root types are removed from the schema.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema, Default, PartialEq)]
+#[schemars(example = "schema_filter_example")]
+pub struct SchemaFilterConfig {
+  /// When set, only the fields of object and interface types tagged with one of these tags (or declared in a tagged type) are exposed, in addition to the fields matching `include`.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub include_tags: Vec<String>,
+  /// Types, fields, arguments and enum values tagged with one of these tags are removed.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub exclude_tags: Vec<String>,
+  /// When set, only the fields of object and interface types matching one of these coordinates are exposed, in addition to the fields selected by `include_tags`.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub include: Vec<String>,
+  /// Types, fields, arguments and enum values matching one of these coordinates are removed.
+  ///
+  /// Removing a required argument removes its field as well, since it can't be called anymore.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub exclude: Vec<String>,
+}
+
+fn schema_filter_example() -> JsonSchemaExample<SchemaFilterConfig> {
+  JsonSchemaExample {
+    metadata: JsonSchemaExampleMetadata::new(
+      "Public API",
+      Some("This example exposes the fields tagged with `public`, without the internal fields and the `Admin*` types."),
+    ),
+    wrapper: None,
+    example: SchemaFilterConfig {
+      include_tags: vec!["public".to_string()],
+      exclude_tags: vec!["internal".to_string()],
+      include: vec![],
+      exclude: vec!["Admin*".to_string(), "User.email".to_string()],
+    },
+  }
 }
 
 fn endpoint_definition_example1() -> JsonSchemaExample<ConductorConfig> {
@@ -155,20 +363,28 @@ fn endpoint_definition_example1() -> JsonSchemaExample<ConductorConfig> {
         metadata: JsonSchemaExampleMetadata::new("Basic Example", Some("This example demonstrate how to declare a GraphQL source, and expose it as a GraphQL endpoint. The endpoint also exposes a GraphiQL interface.")),
         wrapper: None,
         example: ConductorConfig {
+            include: vec![],
+            extends: None,
+            included_paths: vec![],
             server: None,
             logger: None,
+            propagation: None,
             plugins: None,
+            plugin_sets: Default::default(),
             sources: vec![SourceDefinition::GraphQL {
                 id: "my-source".to_string(),
                 config: GraphQLSourceConfig {
                     endpoint: "https://my-source.com/graphql".to_string(),
                     schema_awareness: None,
+                    http_client: None,
                 },
             }],
             endpoints: vec![EndpointDefinition {
                 path: "/graphql".to_string(),
                 from: "my-source".to_string(),
+                plugin_sets: vec![],
                 plugins: Some(vec![PluginDefinition::GraphiQLPlugin { enabled: Default::default(), config: None }]),
+                schema_filter: None,
             }],
         },
     }
@@ -179,24 +395,32 @@ fn endpoint_definition_example2() -> JsonSchemaExample<ConductorConfig> {
         metadata: JsonSchemaExampleMetadata::new("Multiple Endpoints", Some("This example shows how to expose a single GraphQL source with different plugins applied to it. In this example, we expose the same, one time with persised operations, and one time with HTTP GET for arbitrary queries.")),
         wrapper: None,
         example: ConductorConfig {
+            include: vec![],
+            extends: None,
+            included_paths: vec![],
             server: None,
             logger: None,
+            propagation: None,
             plugins: None,
+            plugin_sets: Default::default(),
             sources: vec![SourceDefinition::GraphQL {
                 id: "my-source".to_string(),
                 config: GraphQLSourceConfig {
                     endpoint: "https://my-source.com/graphql".to_string(),
                     schema_awareness: None,
+                    http_client: None,
                 },
             }],
             endpoints: vec![EndpointDefinition {
                 path: "/trusted".to_string(),
                 from: "my-source".to_string(),
+                plugin_sets: vec![],
                 plugins: Some(vec![
                     PluginDefinition::TrustedDocumentsPlugin {
                         enabled: Default::default(),
                         config: trusted_documents_plugin::Config {
                             allow_untrusted: Some(false),
+                            learn: None,
                             store: trusted_documents_plugin::Store::File { file: LocalFileReference { path: "store.json".to_string(), contents: "".to_string()}, format: trusted_documents_plugin::FileFormat::JsonKeyValue },
                             protocols: vec![
                                 trusted_documents_plugin::Protocol::DocumentId { field_name: Default::default() },
@@ -204,14 +428,17 @@ fn endpoint_definition_example2() -> JsonSchemaExample<ConductorConfig> {
                         }
                     }
                 ]),
+                schema_filter: None,
             }, EndpointDefinition {
                 path: "/data".to_string(),
                 from: "my-source".to_string(),
+                plugin_sets: vec![],
                 plugins: Some(vec![
                     PluginDefinition::HttpGetPlugin { enabled: Default::default(), config: Some(http_get_plugin::Config {
                         mutations: Some(false)
                     }) }
                 ]),
+                schema_filter: None,
             }],
         },
     }
@@ -318,6 +545,51 @@ pub enum PluginDefinition {
     enabled: Option<bool>,
     config: telemetry_plugin::Config,
   },
+
+  #[serde(rename = "usage_reporting")]
+  UsageReportingPlugin {
+    #[serde(
+      default = "default_plugin_enabled",
+      skip_serializing_if = "Option::is_none"
+    )]
+    enabled: Option<bool>,
+    config: usage_reporting_plugin::Config,
+  },
+
+  #[serde(rename = "client_awareness")]
+  ClientAwarenessPlugin {
+    #[serde(
+      default = "default_plugin_enabled",
+      skip_serializing_if = "Option::is_none"
+    )]
+    enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config: Option<client_awareness_plugin::Config>,
+  },
+}
+
+impl PluginDefinition {
+  /// The `type` identifier of the plugin, as used in the configuration file.
+  pub fn type_name(&self) -> &'static str {
+    match self {
+      PluginDefinition::GraphiQLPlugin { .. } => "graphiql",
+      PluginDefinition::CorsPlugin { .. } => "cors",
+      PluginDefinition::DisableItrospectionPlugin { .. } => "disable_introspection",
+      PluginDefinition::HttpGetPlugin { .. } => "http_get",
+      PluginDefinition::VrlPluginConfig { .. } => "vrl",
+      PluginDefinition::TrustedDocumentsPlugin { .. } => "trusted_documents",
+      PluginDefinition::JwtAuthPlugin { .. } => "jwt_auth",
+      PluginDefinition::GraphQLValidation { .. } => "graphql_validation",
+      PluginDefinition::TelemetryPlugin { .. } => "telemetry",
+      PluginDefinition::UsageReportingPlugin { .. } => "usage_reporting",
+      PluginDefinition::ClientAwarenessPlugin { .. } => "client_awareness",
+    }
+  }
+
+  /// Whether the plugin is expected to be declared more than once, for example to run several VRL programs.
+  pub fn is_repeatable(&self) -> bool {
+    matches!(self, PluginDefinition::VrlPluginConfig { .. })
+  }
 }
 
 #[derive(Deserialize, Serialize, Default, Debug, Clone, Copy, JsonSchema)]
@@ -407,7 +679,7 @@ fn default_log_filter() -> String {
   "info".to_string()
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema, Default, PartialEq)]
 pub struct ServerConfig {
   #[serde(default = "default_server_port")]
   /// The port to listen on, default to 9000
@@ -415,6 +687,56 @@ pub struct ServerConfig {
   #[serde(default = "default_server_host")]
   /// The host to listen on, default to 127.0.0.1
   pub host: String,
+  /// TLS configuration for the server. When set, the server only accepts HTTPS connections, and HTTP/2 is negotiated using ALPN.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub tls: Option<ServerTlsConfig>,
+  /// An additional port to listen on with plain HTTP, serving only the health check endpoint (`/_health`).
+  ///
+  /// This is useful when TLS is enabled, and the load balancer performs health checks over plain HTTP.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub health_check_port: Option<u16>,
+  /// How long a source can keep serving a previously loaded schema after reloading it started to fail, before the readiness endpoint (`/_readiness`) reports the gateway as not ready.
+  ///
+  /// When not set, failing reloads never affect the readiness of the gateway.
+  #[serde(
+    deserialize_with = "humantime_serde::deserialize",
+    serialize_with = "humantime_serde::serialize",
+    default,
+    skip_serializing_if = "Option::is_none"
+  )]
+  #[schemars(with = "Option<String>")]
+  pub readiness_max_staleness: Option<Duration>,
+  /// How long to wait for in-flight requests to complete when the server is shutting down (on `SIGTERM` or `SIGINT`), default to 30s.
+  ///
+  /// Once the shutdown starts, the server stops accepting new connections and the readiness endpoint reports the gateway as not ready.
+  /// Requests that are still running after this timeout are dropped.
+  #[serde(
+    deserialize_with = "humantime_serde::deserialize",
+    serialize_with = "humantime_serde::serialize",
+    default,
+    skip_serializing_if = "Option::is_none"
+  )]
+  #[schemars(with = "Option<String>")]
+  pub shutdown_timeout: Option<Duration>,
+}
+
+/// TLS configuration for the HTTP server.
+///
+/// File paths are resolved relative to the config file. The certificate and key files are watched, and reloaded when they change.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema, PartialEq)]
+pub struct ServerTlsConfig {
+  /// Path to a PEM file containing the certificate chain of the server.
+  pub cert_file: String,
+  /// Path to a PEM file containing the private key of the server.
+  pub key_file: String,
+  /// Path to a PEM file containing the CA certificates used to verify client certificates.
+  ///
+  /// When set, clients are required to present a valid certificate (mTLS).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub client_ca_file: Option<String>,
+  /// When `client_ca_file` is set, allow clients that do not present a certificate at all. Certificates that are presented are still verified.
+  #[serde(default)]
+  pub client_auth_optional: bool,
 }
 
 fn default_server_port() -> u16 {
@@ -453,6 +775,30 @@ pub enum SourceDefinition {
     /// The configuration for the GraphQL source.
     config: FederationSourceConfig,
   },
+  #[serde(rename = "merge")]
+  /// Merges the schemas of other sources into a single schema (schema stitching)
+  Merge {
+    /// The identifier of the source. This is used to reference the source in the `from` field of an endpoint definition.
+    id: String,
+    /// The configuration for the merge source.
+    config: MergeSourceConfig,
+  },
+  #[serde(rename = "openapi")]
+  /// A REST API, described by an OpenAPI document
+  OpenApi {
+    /// The identifier of the source. This is used to reference the source in the `from` field of an endpoint definition.
+    id: String,
+    /// The configuration for the OpenAPI source.
+    config: OpenApiSourceConfig,
+  },
+  #[serde(rename = "schema_mock")]
+  /// Mocked responses, generated from a GraphQL schema
+  SchemaMock {
+    /// The identifier of the source. This is used to reference the source in the `from` field of an endpoint definition.
+    id: String,
+    /// The configuration for the schema mock source.
+    config: SchemaMockSourceConfig,
+  },
 }
 
 impl SourceDefinition {
@@ -461,6 +807,9 @@ impl SourceDefinition {
       SourceDefinition::GraphQL { id, .. } => id,
       SourceDefinition::Mock { id, .. } => id,
       SourceDefinition::Federation { id, .. } => id,
+      SourceDefinition::Merge { id, .. } => id,
+      SourceDefinition::OpenApi { id, .. } => id,
+      SourceDefinition::SchemaMock { id, .. } => id,
     }
   }
 }
@@ -487,9 +836,43 @@ pub struct GraphQLSourceConfig {
   /// Schema Awareness configuration for the source.
   /// Enabling this configuration will configure the gateway to load the upstream GraphQL schema and use that information in other plugins.
   ///
+  /// Introspection fields (`__schema` and `__type`) are resolved by the gateway from the loaded schema, and only the other fields of the operation are sent to the upstream.
+  ///
   /// When this configuration is not specified, Schema Awareness is disabled, and plugins will not have access to the upstream schema.
   /// In that case, the gateway will act as a simple proxy, without any knowledge of the upstream schema.
   pub schema_awareness: Option<SchemaAwarenessConfig>,
+  /// Configuration for the HTTP client used to send requests to the upstream (TLS settings, HTTP/2).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub http_client: Option<UpstreamHttpClientConfig>,
+}
+
+/// Configuration for the HTTP client used to reach an upstream service.
+///
+/// > Note: these settings are not supported on WASM runtime, and are ignored there.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct UpstreamHttpClientConfig {
+  /// A client certificate to present to the upstream (mTLS).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub client_certificate: Option<UpstreamClientCertificateConfig>,
+  /// Additional CA certificates (PEM files) to trust, on top of the system's root certificates.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub root_certificates: Vec<LocalFileReference>,
+  /// Disables the verification of the upstream certificate and hostname.
+  ///
+  /// This is insecure, and should only be used for local development.
+  #[serde(default)]
+  pub insecure_skip_verify: bool,
+  /// Use HTTP/2 without negotiating it first (HTTP/2 "prior knowledge"). The upstream must support HTTP/2.
+  #[serde(default)]
+  pub http2_prior_knowledge: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct UpstreamClientCertificateConfig {
+  /// Path to a PEM file containing the client certificate chain.
+  pub cert: LocalFileReference,
+  /// Path to a PEM file containing the private key of the client certificate, in PKCS#8 format.
+  pub key: LocalFileReference,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
@@ -520,7 +903,7 @@ pub struct SchemaAwarenessConfig {
   #[schemars(with = "Option<String>")]
   /// Polling interval for reloading the schema awareness.
   ///
-  /// This field is ignored on WASM runtime.
+  /// On WASM runtime, the schema is not polled in the background: it's reloaded after handling a request, once the interval elapsed since the last reload.
   pub polling_interval: Option<Duration>,
   /// What to do in case of a failure to load the schema awareness.
   #[serde(default = "default_schema_awareness_on_error")]
@@ -570,11 +953,27 @@ pub enum SchemaAwarenessSource {
   #[schemars(title = "inline")]
   Inline { content: String },
   /// Loads schema awareness from a remote endpoint.
+  ///
+  /// When the endpoint responds with an `ETag` header, the next requests are sent with `If-None-Match`, so an unchanged schema is not downloaded and processed again.
   #[serde(rename = "remote")]
   #[schemars(title = "remote")]
   Remote {
     /// Endpoint to load the schema awareness from.
     url: String,
+    /// Additional endpoints serving the same schema. When loading from `url` fails, the mirrors are tried in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    mirrors: Vec<String>,
+    /// Path to a local file where the last successfully loaded schema is stored (as SDL).
+    ///
+    /// When the schema can't be loaded from the remote endpoints while the gateway starts, the schema stored in this file is used instead.
+    ///
+    /// This field is ignored on WASM runtime.
+    #[serde(
+      default,
+      deserialize_with = "deserialize_optional_relative_path",
+      skip_serializing_if = "Option::is_none"
+    )]
+    cache_file: Option<String>,
     #[serde(
       deserialize_with = "http_serde::header_map::deserialize",
       serialize_with = "http_serde::header_map::serialize",
@@ -595,6 +994,20 @@ pub enum SchemaAwarenessSource {
     )]
     #[schemars(with = "String")]
     method: Method,
+    /// Configuration for the HTTP client used to fetch the schema (TLS settings, HTTP/2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    http_client: Option<UpstreamHttpClientConfig>,
+  },
+  /// Loads schema awareness from a value stored in a Workers KV namespace.
+  ///
+  /// This source is only available on Cloudflare Worker runtime, where local files can't be read. When `polling_interval` is set, the value is read again to pick up schema changes.
+  #[serde(rename = "workers_kv")]
+  #[schemars(title = "workers_kv")]
+  WorkersKv {
+    /// The name of the KV namespace binding, as configured for the Worker.
+    binding: String,
+    /// The key of the value holding the schema.
+    key: String,
   },
 }
 
@@ -608,6 +1021,81 @@ pub struct MockedSourceConfig {
   pub response_data: LocalFileReference,
 }
 
+/// A mocked upstream that generates responses from a GraphQL schema, so clients can be developed before the upstream exists.
+///
+/// Unlike the `mock` source, the response matches the selection set of each operation: every selected field gets a value of the right type. Values are generated from the `seed`, the type and the path of each field, so the same operation always gets the same response.
+///
+/// Built-in scalars and enums get generated values, and custom scalars are generated as strings, unless a value is set in `overrides`.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[schemars(example = "schema_mock_source_definition_example")]
+pub struct SchemaMockSourceConfig {
+  /// The GraphQL schema used to generate the responses, loaded from SDL or an introspection result.
+  pub schema: SchemaAwarenessConfig,
+  /// The seed used to generate values. Changing it changes all the generated values.
+  #[serde(default)]
+  pub seed: u64,
+  /// The number of items generated for list fields, default to 2.
+  #[serde(default = "default_schema_mock_list_length")]
+  pub list_length: usize,
+  /// Values used instead of the generated ones, by type name (for example: `DateTime`) or field coordinate (for example: `User.email`). A field coordinate takes precedence over the type of the field.
+  ///
+  /// For object types, the value can set only some of the fields, the other selected fields are still generated.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub overrides: HashMap<String, serde_json::Value>,
+  /// The ratio of operations that get an error response instead of data, between `0` and `1`. Use this to test how clients handle errors.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error_rate: Option<f64>,
+  /// A delay added before every response, to simulate a slow upstream.
+  ///
+  /// This field is ignored on WASM runtime.
+  #[serde(
+    deserialize_with = "humantime_serde::deserialize",
+    serialize_with = "humantime_serde::serialize",
+    default,
+    skip_serializing_if = "Option::is_none"
+  )]
+  #[schemars(with = "Option<String>")]
+  pub latency: Option<Duration>,
+}
+
+fn default_schema_mock_list_length() -> usize {
+  2
+}
+
+fn schema_mock_source_definition_example() -> JsonSchemaExample<SourceDefinition> {
+  JsonSchemaExample {
+    wrapper: None,
+    metadata: JsonSchemaExampleMetadata::new(
+      "From a local schema",
+      Some("This example generates responses from a local SDL file, with a fixed value for the `DateTime` custom scalar, a 200ms delay, and an error for 10% of the operations."),
+    ),
+    example: SourceDefinition::SchemaMock {
+      id: "my-source".to_string(),
+      config: SchemaMockSourceConfig {
+        schema: SchemaAwarenessConfig {
+          polling_interval: None,
+          on_error: SchemaAwarenessConfigOnError::Terminate,
+          format: SchemaAwarenessFormat::Sdl,
+          source: SchemaAwarenessSource::File {
+            file: LocalFileReference {
+              path: "./schema.graphql".to_string(),
+              contents: "".to_string(),
+            },
+          },
+        },
+        seed: 0,
+        list_length: 2,
+        overrides: HashMap::from([(
+          "DateTime".to_string(),
+          serde_json::Value::from("2024-01-01T00:00:00Z"),
+        )]),
+        error_rate: Some(0.1),
+        latency: Some(Duration::from_millis(200)),
+      },
+    },
+  }
+}
+
 fn graphql_source_definition_example1() -> JsonSchemaExample<SourceDefinition> {
   JsonSchemaExample {
     wrapper: None,
@@ -617,6 +1105,7 @@ fn graphql_source_definition_example1() -> JsonSchemaExample<SourceDefinition> {
       config: GraphQLSourceConfig {
         endpoint: "https://my-source.com/graphql".to_string(),
         schema_awareness: None,
+        http_client: None,
       },
     },
   }
@@ -635,10 +1124,14 @@ fn graphql_source_definition_example2() -> JsonSchemaExample<SourceDefinition> {
           format: SchemaAwarenessFormat::Introspection,
           source: SchemaAwarenessSource::Remote {
             url: "https://my-source.com/graphql".to_string(),
+            mirrors: vec![],
+            cache_file: None,
             headers: vec![("Authorization", "Bearer TOKEN")].to_headers_map().unwrap(),
             method: Method::POST,
+            http_client: None,
           },
         }),
+        http_client: None,
       },
     },
   }
@@ -658,6 +1151,7 @@ fn graphql_source_definition_example3() -> JsonSchemaExample<SourceDefinition> {
           format: SchemaAwarenessFormat::Sdl,
           source: SchemaAwarenessSource::File { file: LocalFileReference { path: "./introspection.json".to_string(), contents: "".to_string() } },
         }),
+        http_client: None,
       },
     },
   }
@@ -677,6 +1171,7 @@ fn graphql_source_definition_example4() -> JsonSchemaExample<SourceDefinition> {
           format: SchemaAwarenessFormat::Sdl,
           source: SchemaAwarenessSource::Inline { content: String::from("type Query { noop: String }") }
         }),
+        http_client: None,
       },
     },
   }
@@ -698,6 +1193,12 @@ pub struct FederationSourceConfig {
   /// Exposes the query plan as JSON under "extensions"
   #[serde(default = "default_expose_query_plan")]
   pub expose_query_plan: bool,
+  /// Attaches the query plan as JSON to the span of the federation execution, so it's visible in the traces of the request.
+  #[serde(default)]
+  pub trace_query_plan: bool,
+  /// Configuration for the HTTP client used to send requests to the subgraphs (TLS settings, HTTP/2).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub http_client: Option<UpstreamHttpClientConfig>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
@@ -712,8 +1213,11 @@ pub struct SchemaAwarenessSupergraphConfig {
   #[schemars(with = "Option<String>")]
   /// Polling interval for reloading the schema awareness.
   ///
-  /// This field is ignored on WASM runtime.
+  /// On WASM runtime, the schema is not polled in the background: it's reloaded after handling a request, once the interval elapsed since the last reload.
   pub polling_interval: Option<Duration>,
+  /// What to do in case of a failure to load the supergraph while the gateway starts.
+  #[serde(default = "default_schema_awareness_on_error")]
+  pub on_error: SchemaAwarenessConfigOnError,
 }
 
 fn default_expose_query_plan() -> bool {
@@ -734,15 +1238,21 @@ fn federation_definition_example1() -> JsonSchemaExample<SourceDefinition> {
       config: FederationSourceConfig {
         supergraph: SchemaAwarenessSupergraphConfig {
           polling_interval: Some(Duration::from_secs(60)),
+          on_error: SchemaAwarenessConfigOnError::Terminate,
           source: SchemaAwarenessSource::Remote {
             url: "https://cdn.graphql-hive.com/artifacts/v1/TARGET_ID/supergraph".to_string(),
+            mirrors: vec![],
+            cache_file: None,
             headers: vec![("X-Hive-CDN-Key", "CDN_TOKEN")]
               .to_headers_map()
               .unwrap(),
             method: Method::GET,
+            http_client: None,
           },
         },
         expose_query_plan: false,
+        trace_query_plan: false,
+        http_client: None,
       },
     },
   }
@@ -757,6 +1267,7 @@ fn federation_definition_example2() -> JsonSchemaExample<SourceDefinition> {
       config: FederationSourceConfig {
         supergraph: SchemaAwarenessSupergraphConfig {
           polling_interval: None,
+          on_error: SchemaAwarenessConfigOnError::Terminate,
           source: SchemaAwarenessSource::File {
             file: LocalFileReference {
               contents: "".into(),
@@ -765,6 +1276,185 @@ fn federation_definition_example2() -> JsonSchemaExample<SourceDefinition> {
           },
         },
         expose_query_plan: false,
+        trace_query_plan: false,
+        http_client: None,
+      },
+    },
+  }
+}
+
+/// A source that combines other sources, that are not aware of each other, into a single schema (also known as "schema stitching").
+///
+/// The root fields (`Query`, `Mutation` and `Subscription`) of all merged sources are exposed together, and each root field is executed by the source that declares it. The merged sources must have a schema: a `graphql` source with `schema_awareness`, or a `federation` source.
+///
+/// When two sources declare a type with the same name, the first source wins, unless the type is listed in `type_merging`. Use `type_prefix` to expose the types of a source under different names instead.
+///
+/// > Note: subscriptions are not supported by this source.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[schemars(example = "merge_source_definition_example")]
+pub struct MergeSourceConfig {
+  /// The sources to merge. When more than one source declares the same root field, the first one in this list wins.
+  pub sources: Vec<MergedSourceConfig>,
+  /// Types that are declared by more than one source, and should be exposed as a single type with the fields of all sources.
+  ///
+  /// When an operation selects fields of a merged type that are declared by another source, the gateway fetches them from that source, using the key of the object.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub type_merging: Vec<TypeMergingConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct MergedSourceConfig {
+  /// The identifier of the source to merge, as declared in `sources`.
+  pub id: String,
+  /// A prefix added to the names of all the types of this source, to avoid conflicts with the types of the other sources.
+  ///
+  /// Root types, built-in scalars and merged types (see `type_merging`) are not renamed.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub type_prefix: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct TypeMergingConfig {
+  /// The name of the object type, as declared by the merged sources.
+  pub type_name: String,
+  /// The field that identifies an object of this type in all sources, for example `id`.
+  pub key: String,
+  /// The root `Query` fields used to fetch an object of this type by its key, for each source that declares the type.
+  ///
+  /// Fields of the type can be fetched only from sources that have a resolver.
+  pub resolvers: Vec<TypeMergingResolverConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct TypeMergingResolverConfig {
+  /// The identifier of the merged source.
+  pub source: String,
+  /// A root `Query` field of the source, that returns an object of the merged type by its key (for example, `userById`).
+  pub field: String,
+  /// The argument of `field` that receives the key. Defaults to the name of the key field.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub argument: Option<String>,
+}
+
+fn merge_source_definition_example() -> JsonSchemaExample<SourceDefinition> {
+  JsonSchemaExample {
+    wrapper: None,
+    metadata: JsonSchemaExampleMetadata::new(
+      "Merging two services",
+      Some("This example merges the `accounts` and `reviews` sources, and resolves the `User` type across both of them by its `id` field. The types of the `reviews` source are prefixed to avoid conflicts."),
+    ),
+    example: SourceDefinition::Merge {
+      id: "my-source".to_string(),
+      config: MergeSourceConfig {
+        sources: vec![
+          MergedSourceConfig {
+            id: "accounts".to_string(),
+            type_prefix: None,
+          },
+          MergedSourceConfig {
+            id: "reviews".to_string(),
+            type_prefix: Some("Reviews".to_string()),
+          },
+        ],
+        type_merging: vec![TypeMergingConfig {
+          type_name: "User".to_string(),
+          key: "id".to_string(),
+          resolvers: vec![
+            TypeMergingResolverConfig {
+              source: "accounts".to_string(),
+              field: "user".to_string(),
+              argument: None,
+            },
+            TypeMergingResolverConfig {
+              source: "reviews".to_string(),
+              field: "userById".to_string(),
+              argument: Some("userId".to_string()),
+            },
+          ],
+        }],
+      },
+    },
+  }
+}
+
+/// A source based on a REST API, described by an [OpenAPI 3](https://spec.openapis.org/oas/v3.0.3) document (JSON or YAML).
+///
+/// The gateway generates a GraphQL schema from the document: `GET` operations are exposed as `Query` fields, and all other operations as `Mutation` fields, named after their `operationId`. The path, query and header parameters of an operation are exposed as arguments, and its JSON request body is exposed as the `input` argument.
+///
+/// Fields are resolved by calling the HTTP endpoint of the operation, and selecting the requested fields from its JSON response.
+///
+/// > Note: subscriptions are not supported by this source.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[schemars(example = "openapi_source_definition_example")]
+pub struct OpenApiSourceConfig {
+  /// The OpenAPI document describing the REST API.
+  pub spec: OpenApiSpecSource,
+  /// The base URL of the REST API. By default, the first entry of the `servers` field of the document is used.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub endpoint: Option<String>,
+  /// Headers of the downstream request that are passed to the REST API (for example: `authorization`).
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub forward_headers: Vec<String>,
+  #[serde(
+    deserialize_with = "http_serde::header_map::deserialize",
+    serialize_with = "http_serde::header_map::serialize",
+    default
+  )]
+  /// Additional headers to include in all requests sent to the REST API.
+  #[schemars(with = "HashMap<String, String>")]
+  pub headers: HttpHeadersMap,
+  /// Configuration for the HTTP client used to call the REST API (TLS settings, HTTP/2).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub http_client: Option<UpstreamHttpClientConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[serde(tag = "type")]
+pub enum OpenApiSpecSource {
+  #[serde(rename = "file")]
+  #[schemars(title = "file")]
+  /// Loads the OpenAPI document from a local file.
+  File {
+    #[serde(rename = "path")]
+    file: LocalFileReference,
+  },
+  /// Loads the OpenAPI document from a remote endpoint, once while the gateway starts.
+  #[serde(rename = "remote")]
+  #[schemars(title = "remote")]
+  Remote {
+    /// Endpoint to load the OpenAPI document from.
+    url: String,
+    #[serde(
+      deserialize_with = "http_serde::header_map::deserialize",
+      serialize_with = "http_serde::header_map::serialize",
+      default
+    )]
+    /// Optional headers to include in the request (for example: authentication).
+    #[schemars(with = "HashMap<String, String>")]
+    headers: HttpHeadersMap,
+  },
+}
+
+fn openapi_source_definition_example() -> JsonSchemaExample<SourceDefinition> {
+  JsonSchemaExample {
+    wrapper: None,
+    metadata: JsonSchemaExampleMetadata::new(
+      "From a file",
+      Some("This example loads the OpenAPI document from a local file, and passes the `authorization` header of the downstream requests to the REST API."),
+    ),
+    example: SourceDefinition::OpenApi {
+      id: "my-source".to_string(),
+      config: OpenApiSourceConfig {
+        spec: OpenApiSpecSource::File {
+          file: LocalFileReference {
+            contents: "".into(),
+            path: "./openapi.yaml".into(),
+          },
+        },
+        endpoint: Some("https://api.example.com/v1".to_string()),
+        forward_headers: vec!["authorization".to_string()],
+        headers: Default::default(),
+        http_client: None,
       },
     },
   }
@@ -804,80 +1494,277 @@ fn default_polling_interval() -> Option<Duration> {
   Some(Duration::from_secs(60))
 }
 
+type Warnings = Vec<String>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+  #[error("failed to read config file \"{path}\": {source}")]
+  ReadFailed {
+    path: String,
+    source: std::io::Error,
+  },
+  #[error("config file \"{path}\" has no extension, expected one of: .json, .yaml, .yml")]
+  MissingExtension { path: String },
+  #[error(
+    "config file \"{path}\" has an unsupported extension, expected one of: .json, .yaml, .yml"
+  )]
+  UnsupportedExtension { path: String },
+  #[error("failed to interpolate config file: {}", .errors.join(", "))]
+  InterpolationFailed { errors: Vec<String> },
+  #[error(
+    "failed to parse {format} config file{}: {message}",
+    format_location(.file, .line, .column)
+  )]
+  ParseFailed {
+    format: ConfigFormat,
+    file: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+    message: String,
+  },
+  #[error("config file \"{path}\" is included recursively")]
+  IncludeCycle { path: String },
+  #[error("{kind} \"{key}\" is defined in both \"{first}\" and \"{second}\"")]
+  IncludeConflict {
+    kind: &'static str,
+    key: String,
+    first: String,
+    second: String,
+  },
+}
+
+fn format_location(file: &Option<String>, line: &Option<usize>, column: &Option<usize>) -> String {
+  let position = match (line, column) {
+    (Some(line), Some(column)) => format!(":{}:{}", line, column),
+    (Some(line), None) => format!(":{}", line),
+    _ => String::new(),
+  };
+
+  match file {
+    Some(file) => format!(" \"{}{}\"", file, position),
+    None if !position.is_empty() => format!(" at{}", position),
+    None => String::new(),
+  }
+}
+
+impl ConfigError {
+  /// Attaches the file path to parse errors, unless it was already set by an included file.
+  fn with_file(self, path: &str) -> Self {
+    match self {
+      ConfigError::ParseFailed {
+        format,
+        line,
+        column,
+        message,
+        file: None,
+      } => ConfigError::ParseFailed {
+        format,
+        file: Some(path.to_string()),
+        line,
+        column,
+        message,
+      },
+      other => other,
+    }
+  }
+}
+
+impl From<serde_json::Error> for ConfigError {
+  fn from(e: serde_json::Error) -> Self {
+    // serde_json reports line 0 for errors that are not related to the input position
+    let (line, column) = match e.line() {
+      0 => (None, None),
+      line => (Some(line), Some(e.column())),
+    };
+
+    ConfigError::ParseFailed {
+      format: ConfigFormat::Json,
+      file: None,
+      line,
+      column,
+      message: e.to_string(),
+    }
+  }
+}
+
+impl From<serde_yaml::Error> for ConfigError {
+  fn from(e: serde_yaml::Error) -> Self {
+    let location = e.location();
+
+    ConfigError::ParseFailed {
+      format: ConfigFormat::Yaml,
+      file: None,
+      line: location.as_ref().map(|l| l.line()),
+      column: location.as_ref().map(|l| l.column()),
+      message: e.to_string(),
+    }
+  }
+}
+
+/// Loads, interpolates and parses the config file from the given path.
+///
+/// On success, the warnings emitted during interpolation are returned along with the config object.
 #[tracing::instrument(level = "trace", skip(get_env_value))]
-pub async fn load_config(
+pub async fn try_load_config(
   file_path: &String,
   get_env_value: impl Fn(&str) -> Option<String>,
-) -> ConductorConfig {
+) -> Result<(ConductorConfig, Warnings), ConfigError> {
   let path = Path::new(file_path);
-
-  // @expected: 👇
-  let raw_contents = read_to_string(file_path)
-    .unwrap_or_else(|e| panic!("Failed to read config file \"{}\": {}", file_path, e));
+  let format = ConfigFormat::try_from_path(path)?;
+  let raw_contents = read_to_string(file_path).map_err(|source| ConfigError::ReadFailed {
+    path: file_path.clone(),
+    source,
+  })?;
 
   let base_path = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
   BASE_PATH.with(|bp| {
     *bp.borrow_mut() = base_path;
   });
 
-  parse_config_contents(raw_contents, ConfigFormat::from_path(path), get_env_value)
+  parse_config_with_includes(raw_contents, format, Some(path), &get_env_value, &[])
+    .map_err(|e| e.with_file(file_path))
 }
 
-pub fn parse_config_contents(
+/// Interpolates and parses the config from a string.
+///
+/// Files referenced with `$include` and `extends` are resolved relative to the current `BASE_PATH`.
+pub fn try_parse_config_contents(
   contents: String,
   format: ConfigFormat,
   get_env_value: impl Fn(&str) -> Option<String>,
+) -> Result<(ConductorConfig, Warnings), ConfigError> {
+  parse_config_with_includes(contents, format, None, &get_env_value, &[])
+}
+
+/// `extended_by` is the chain of files that extend this config, used to detect cycles.
+fn parse_config_with_includes(
+  contents: String,
+  format: ConfigFormat,
+  root: Option<&Path>,
+  get_env_value: &dyn Fn(&str) -> Option<String>,
+  extended_by: &[PathBuf],
+) -> Result<(ConductorConfig, Warnings), ConfigError> {
+  let (config_string, mut warnings) = interpolate(&contents, get_env_value)
+    .map_err(|errors| ConfigError::InterpolationFailed { errors })?;
+
+  let mut config = match format {
+    ConfigFormat::Json => parse_config_from_json(&config_string)?,
+    ConfigFormat::Yaml => parse_config_from_yaml(&config_string)?,
+  };
+
+  if !config.include.is_empty() {
+    warnings.extend(include::resolve_includes(&mut config, root, get_env_value)?);
+  }
+
+  if config.extends.is_some() {
+    // The server settings have a default value, so we check whether they are declared before they are overridden.
+    let declares_server = match format {
+      ConfigFormat::Json => serde_json::from_str::<DeclaredSettings>(&config_string)
+        .is_ok_and(|declared| declared.server.is_some()),
+      ConfigFormat::Yaml => serde_yaml::from_str::<DeclaredSettings>(&config_string)
+        .is_ok_and(|declared| declared.server.is_some()),
+    };
+
+    warnings.extend(include::resolve_extends(
+      &mut config,
+      declares_server,
+      root,
+      get_env_value,
+      extended_by,
+    )?);
+  }
+
+  Ok((config, warnings))
+}
+
+/// The top-level settings that are declared in a config file, regardless of their default values.
+#[derive(Deserialize)]
+struct DeclaredSettings {
+  #[serde(default)]
+  server: Option<serde::de::IgnoredAny>,
+}
+
+#[tracing::instrument(level = "trace", skip(get_env_value))]
+pub async fn load_config(
+  file_path: &String,
+  get_env_value: impl Fn(&str) -> Option<String>,
 ) -> ConductorConfig {
-  let mut config_string = contents;
+  unwrap_config_result(try_load_config(file_path, get_env_value).await)
+}
 
-  match interpolate(&config_string, get_env_value) {
-    Ok((interpolated_content, warnings)) => {
-      config_string = interpolated_content;
+pub fn parse_config_contents(
+  contents: String,
+  format: ConfigFormat,
+  get_env_value: impl Fn(&str) -> Option<String>,
+) -> ConductorConfig {
+  unwrap_config_result(try_parse_config_contents(contents, format, get_env_value))
+}
 
+fn unwrap_config_result(
+  result: Result<(ConductorConfig, Warnings), ConfigError>,
+) -> ConductorConfig {
+  match result {
+    Ok((config, warnings)) => {
       for warning in warnings {
         println!("warning: {}", warning);
       }
-    }
-    Err(errors) => {
-      for error in errors {
-        println!("error: {:?}", error);
-      }
 
-      // @expected: 👇
-      panic!("Failed to interpolate config file, please resolve the above errors");
+      config
     }
+    // @expected: 👇
+    Err(e) => panic!("{}", e),
   }
+}
 
+/// Serializes a config object back to a string, after interpolation and file references resolution.
+pub fn serialize_config(config: &ConductorConfig, format: &ConfigFormat) -> String {
   match format {
     ConfigFormat::Json => {
       // @expected: 👇
-      parse_config_from_json(&config_string).expect("Failed to parse JSON config file")
+      serde_json::to_string_pretty(config).expect("Failed to serialize config to JSON")
     }
     ConfigFormat::Yaml => {
       // @expected: 👇
-      parse_config_from_yaml(&config_string).expect("Failed to parse YAML config file")
+      serde_yaml::to_string(config).expect("Failed to serialize config to YAML")
     }
   }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfigFormat {
   Json,
   Yaml,
 }
 
+impl std::fmt::Display for ConfigFormat {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ConfigFormat::Json => write!(f, "JSON"),
+      ConfigFormat::Yaml => write!(f, "YAML"),
+    }
+  }
+}
+
 impl ConfigFormat {
-  pub fn from_path(path: &Path) -> Self {
+  pub fn try_from_path(path: &Path) -> Result<Self, ConfigError> {
     match path.extension() {
       Some(ext) => match ext.to_str() {
-        Some("json") => ConfigFormat::Json,
-        Some("yaml") | Some("yml") => ConfigFormat::Yaml,
-        // @expected: 👇
-        _ => panic!("Unsupported config file extension"),
+        Some("json") => Ok(ConfigFormat::Json),
+        Some("yaml") | Some("yml") => Ok(ConfigFormat::Yaml),
+        _ => Err(ConfigError::UnsupportedExtension {
+          path: path.to_string_lossy().into_owned(),
+        }),
       },
-      // @expected: 👇
-      None => panic!("Config file has no extension"),
+      None => Err(ConfigError::MissingExtension {
+        path: path.to_string_lossy().into_owned(),
+      }),
     }
   }
+
+  pub fn from_path(path: &Path) -> Self {
+    // @expected: 👇
+    Self::try_from_path(path).unwrap_or_else(|e| panic!("{}", e))
+  }
 }
 
 fn parse_config_from_yaml(contents: &str) -> Result<ConductorConfig, serde_yaml::Error> {
@@ -887,3 +1774,196 @@ fn parse_config_from_yaml(contents: &str) -> Result<ConductorConfig, serde_yaml:
 fn parse_config_from_json(contents: &str) -> Result<ConductorConfig, serde_json::Error> {
   serde_json::from_str::<ConductorConfig>(contents)
 }
+
+#[cfg(test)]
+mod tests {
+  use conductor_common::vrl_utils::VrlConfigReference;
+
+  use super::*;
+
+  #[test]
+  fn should_report_yaml_error_location() {
+    let result = try_parse_config_contents(
+      "sources: []\nendpoints:\n  - path: /graphql\n".to_string(),
+      ConfigFormat::Yaml,
+      |_| None,
+    );
+
+    match result {
+      Err(ConfigError::ParseFailed {
+        format,
+        line,
+        file,
+        message,
+        ..
+      }) => {
+        assert_eq!(format, ConfigFormat::Yaml);
+        assert!(line.is_some());
+        assert_eq!(file, None);
+        assert!(message.contains("missing field `from`"));
+      }
+      other => panic!("unexpected result: {:?}", other),
+    }
+  }
+
+  #[test]
+  fn should_report_json_error_location() {
+    let result = try_parse_config_contents(
+      "{\n  \"sources\": [],\n  \"endpoints\": [\n}".to_string(),
+      ConfigFormat::Json,
+      |_| None,
+    );
+
+    match result {
+      Err(ConfigError::ParseFailed { format, line, .. }) => {
+        assert_eq!(format, ConfigFormat::Json);
+        assert_eq!(line, Some(4));
+      }
+      other => panic!("unexpected result: {:?}", other),
+    }
+  }
+
+  #[test]
+  fn should_return_interpolation_errors() {
+    let result = try_parse_config_contents(
+      "sources: []\nendpoints: []\nserver:\n  host: ${HOST}\n".to_string(),
+      ConfigFormat::Yaml,
+      |_| None,
+    );
+
+    match result {
+      Err(ConfigError::InterpolationFailed { errors, .. }) => {
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("`HOST`"));
+      }
+      other => panic!("unexpected result: {:?}", other),
+    }
+  }
+
+  #[test]
+  fn should_reject_unsupported_extension() {
+    assert!(matches!(
+      ConfigFormat::try_from_path(Path::new("config.toml")),
+      Err(ConfigError::UnsupportedExtension { .. })
+    ));
+    assert!(matches!(
+      ConfigFormat::try_from_path(Path::new("config")),
+      Err(ConfigError::MissingExtension { .. })
+    ));
+  }
+
+  #[test]
+  fn should_resolve_endpoint_plugin_sets_in_order() {
+    let config = parse_config_contents(
+      r#"
+sources: []
+plugins:
+  - type: cors
+plugin_sets:
+  public:
+    - type: http_get
+    - type: disable_introspection
+  internal:
+    - type: graphiql
+    - type: disable_introspection
+      enabled: false
+endpoints:
+  - path: /graphql
+    from: countries
+    plugin_sets: [public, internal]
+    plugins:
+      - type: http_get
+        enabled: false
+"#
+      .to_string(),
+      ConfigFormat::Yaml,
+      |_| None,
+    );
+
+    let plugins = config.endpoint_plugins(&config.endpoints[0]).unwrap();
+
+    assert_eq!(
+      plugins.iter().map(|p| p.type_name()).collect::<Vec<_>>(),
+      vec!["cors", "http_get", "disable_introspection", "graphiql"]
+    );
+    assert!(matches!(
+      plugins[1],
+      PluginDefinition::HttpGetPlugin {
+        enabled: Some(false),
+        ..
+      }
+    ));
+    assert!(matches!(
+      plugins[2],
+      PluginDefinition::DisableItrospectionPlugin {
+        enabled: Some(false),
+        ..
+      }
+    ));
+  }
+
+  #[test]
+  fn should_apply_all_repeated_vrl_plugins() {
+    let config = parse_config_contents(
+      r#"
+sources: []
+plugin_sets:
+  scripts:
+    - type: vrl
+      config:
+        on_downstream_http_request:
+          from: inline
+          content: .set
+endpoints:
+  - path: /graphql
+    from: countries
+    plugin_sets: [scripts]
+    plugins:
+      - type: vrl
+        config:
+          on_downstream_http_request:
+            from: inline
+            content: .first
+      - type: vrl
+        config:
+          on_downstream_http_request:
+            from: inline
+            content: .second
+"#
+      .to_string(),
+      ConfigFormat::Yaml,
+      |_| None,
+    );
+
+    let plugins = config.endpoint_plugins(&config.endpoints[0]).unwrap();
+    let contents = plugins
+      .iter()
+      .map(|plugin| match plugin {
+        PluginDefinition::VrlPluginConfig { config, .. } => {
+          match &config.on_downstream_http_request {
+            Some(VrlConfigReference::Inline { content }) => content.as_str(),
+            _ => panic!("unexpected VRL reference"),
+          }
+        }
+        _ => panic!("unexpected plugin"),
+      })
+      .collect::<Vec<_>>();
+
+    assert_eq!(contents, vec![".set", ".first", ".second"]);
+  }
+
+  #[test]
+  fn should_report_unknown_plugin_set() {
+    let config = parse_config_contents(
+      "sources: []\nendpoints:\n  - path: /graphql\n    from: countries\n    plugin_sets: [missing]\n"
+        .to_string(),
+      ConfigFormat::Yaml,
+      |_| None,
+    );
+
+    assert!(matches!(
+      config.endpoint_plugins(&config.endpoints[0]),
+      Err(name) if name == "missing"
+    ));
+  }
+}
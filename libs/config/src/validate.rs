@@ -0,0 +1,645 @@
+use std::{
+  collections::{HashMap, HashSet},
+  fmt,
+};
+
+use crate::{ConductorConfig, MergeSourceConfig, PluginDefinition, SourceDefinition};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+  Error,
+  Warning,
+}
+
+impl fmt::Display for DiagnosticSeverity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      DiagnosticSeverity::Error => write!(f, "error"),
+      DiagnosticSeverity::Warning => write!(f, "warning"),
+    }
+  }
+}
+
+/// A single problem found while validating a config file.
+///
+/// `path` points to the config node that caused the problem, for example `endpoints[1].from`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDiagnostic {
+  pub severity: DiagnosticSeverity,
+  pub path: String,
+  pub message: String,
+}
+
+impl ConfigDiagnostic {
+  pub fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+    Self {
+      severity: DiagnosticSeverity::Error,
+      path: path.into(),
+      message: message.into(),
+    }
+  }
+
+  pub fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+    Self {
+      severity: DiagnosticSeverity::Warning,
+      path: path.into(),
+      message: message.into(),
+    }
+  }
+
+  pub fn is_error(&self) -> bool {
+    self.severity == DiagnosticSeverity::Error
+  }
+}
+
+impl fmt::Display for ConfigDiagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}: {}", self.severity, self.path, self.message)
+  }
+}
+
+/// Runs the static checks on a parsed config object: references between endpoints and sources,
+/// duplicated identifiers/paths and duplicated plugins.
+///
+/// This function does not load any schema or perform any I/O.
+pub fn validate_config(config: &ConductorConfig) -> Vec<ConfigDiagnostic> {
+  let mut diagnostics = vec![];
+  let mut source_ids: HashSet<&str> = HashSet::new();
+
+  for (index, source) in config.sources.iter().enumerate() {
+    if !source_ids.insert(source.id()) {
+      diagnostics.push(ConfigDiagnostic::error(
+        format!("sources[{}].id", index),
+        format!("source \"{}\" is defined more than once", source.id()),
+      ));
+    }
+  }
+
+  if let Some(server) = &config.server {
+    if server.health_check_port == Some(server.port) {
+      diagnostics.push(ConfigDiagnostic::error(
+        "server.health_check_port",
+        format!("port {} is already used by the main listener", server.port),
+      ));
+    }
+
+    if let Some(tls) = &server.tls {
+      if tls.client_auth_optional && tls.client_ca_file.is_none() {
+        diagnostics.push(ConfigDiagnostic::warning(
+          "server.tls.client_auth_optional",
+          "has no effect without \"client_ca_file\"",
+        ));
+      }
+    }
+  }
+
+  if config.endpoints.is_empty() {
+    diagnostics.push(ConfigDiagnostic::warning(
+      "endpoints",
+      "no endpoints are defined, the gateway will not serve any requests",
+    ));
+  }
+
+  let global_plugins = config.plugins.as_deref().unwrap_or_default();
+  diagnostics.extend(validate_plugins("plugins", global_plugins, &[]));
+
+  for (name, plugins) in &config.plugin_sets {
+    diagnostics.extend(validate_plugins(
+      &format!("plugin_sets.{}", name),
+      plugins,
+      global_plugins,
+    ));
+  }
+
+  let mut used_plugin_sets: HashSet<&str> = HashSet::new();
+
+  let mut endpoint_paths: HashMap<&str, usize> = HashMap::new();
+  let mut used_sources: HashSet<&str> = HashSet::new();
+
+  for (index, endpoint) in config.endpoints.iter().enumerate() {
+    let first_index = *endpoint_paths
+      .entry(endpoint.path.as_str())
+      .or_insert(index);
+
+    if first_index != index {
+      diagnostics.push(ConfigDiagnostic::error(
+        format!("endpoints[{}].path", index),
+        format!(
+          "path \"{}\" is already used by endpoints[{}]",
+          endpoint.path, first_index
+        ),
+      ));
+    }
+
+    if !endpoint.path.starts_with('/') {
+      diagnostics.push(ConfigDiagnostic::error(
+        format!("endpoints[{}].path", index),
+        format!("path \"{}\" must start with \"/\"", endpoint.path),
+      ));
+    }
+
+    if source_ids.contains(endpoint.from.as_str()) {
+      used_sources.insert(endpoint.from.as_str());
+    } else {
+      diagnostics.push(ConfigDiagnostic::error(
+        format!("endpoints[{}].from", index),
+        format!("source \"{}\" is not defined in \"sources\"", endpoint.from),
+      ));
+    }
+
+    if endpoint.schema_filter.is_some() {
+      let source_has_schema = config
+        .sources
+        .iter()
+        .find(|source| source.id() == endpoint.from)
+        .map(has_schema);
+
+      if source_has_schema == Some(false) {
+        diagnostics.push(ConfigDiagnostic::error(
+          format!("endpoints[{}].schema_filter", index),
+          format!(
+            "source \"{}\" does not have a schema to filter, all operations would be rejected",
+            endpoint.from
+          ),
+        ));
+      }
+    }
+
+    for (set_index, name) in endpoint.plugin_sets.iter().enumerate() {
+      if config.plugin_sets.contains_key(name) {
+        used_plugin_sets.insert(name.as_str());
+      } else {
+        diagnostics.push(ConfigDiagnostic::error(
+          format!("endpoints[{}].plugin_sets[{}]", index, set_index),
+          format!("plugin set \"{}\" is not defined in \"plugin_sets\"", name),
+        ));
+      }
+    }
+
+    diagnostics.extend(validate_plugins(
+      &format!("endpoints[{}].plugins", index),
+      endpoint.plugins.as_deref().unwrap_or_default(),
+      global_plugins,
+    ));
+
+    if let Ok(plugins) = config.endpoint_plugins(endpoint) {
+      if client_claim_read_before_jwt_auth(&plugins) {
+        diagnostics.push(ConfigDiagnostic::error(
+          format!("endpoints[{}].plugins", index),
+          "plugin \"client_awareness\" reads the client from a JWT claim, but the \"jwt_auth\" plugin is not declared before it",
+        ));
+      }
+    }
+  }
+
+  for (index, source) in config.sources.iter().enumerate() {
+    if let SourceDefinition::Merge { id, config: merge } = source {
+      diagnostics.extend(validate_merge_source(
+        &format!("sources[{}].config", index),
+        id,
+        merge,
+        &config.sources,
+      ));
+
+      used_sources.extend(merge.sources.iter().map(|merged| merged.id.as_str()));
+    }
+
+    if let SourceDefinition::SchemaMock { config: mock, .. } = source {
+      if let Some(error_rate) = mock.error_rate {
+        if !(0.0..=1.0).contains(&error_rate) {
+          diagnostics.push(ConfigDiagnostic::error(
+            format!("sources[{}].config.error_rate", index),
+            "error rate must be between 0 and 1",
+          ));
+        }
+      }
+    }
+  }
+
+  for (index, source) in config.sources.iter().enumerate() {
+    if !used_sources.contains(source.id()) {
+      diagnostics.push(ConfigDiagnostic::warning(
+        format!("sources[{}]", index),
+        format!("source \"{}\" is not used by any endpoint", source.id()),
+      ));
+    }
+  }
+
+  for name in config.plugin_sets.keys() {
+    if !used_plugin_sets.contains(name.as_str()) {
+      diagnostics.push(ConfigDiagnostic::warning(
+        format!("plugin_sets.{}", name),
+        format!("plugin set \"{}\" is not used by any endpoint", name),
+      ));
+    }
+  }
+
+  diagnostics
+}
+
+fn has_schema(source: &SourceDefinition) -> bool {
+  match source {
+    SourceDefinition::GraphQL { config, .. } => config.schema_awareness.is_some(),
+    SourceDefinition::Federation { .. } => true,
+    SourceDefinition::Mock { .. } => false,
+    SourceDefinition::Merge { .. } => true,
+    SourceDefinition::OpenApi { .. } => true,
+    SourceDefinition::SchemaMock { .. } => true,
+  }
+}
+
+fn validate_merge_source(
+  path: &str,
+  id: &str,
+  config: &MergeSourceConfig,
+  sources: &[SourceDefinition],
+) -> Vec<ConfigDiagnostic> {
+  let mut diagnostics = vec![];
+
+  if config.sources.is_empty() {
+    diagnostics.push(ConfigDiagnostic::error(
+      format!("{}.sources", path),
+      "at least one source must be merged",
+    ));
+  }
+
+  for (index, merged) in config.sources.iter().enumerate() {
+    let merged_path = format!("{}.sources[{}].id", path, index);
+
+    match sources.iter().find(|source| source.id() == merged.id) {
+      None => diagnostics.push(ConfigDiagnostic::error(
+        merged_path,
+        format!("source \"{}\" is not defined in \"sources\"", merged.id),
+      )),
+      Some(SourceDefinition::Merge { .. }) => diagnostics.push(ConfigDiagnostic::error(
+        merged_path,
+        format!(
+          "source \"{}\" is a merge source, and can't be merged into \"{}\"",
+          merged.id, id
+        ),
+      )),
+      Some(source) if !has_schema(source) => diagnostics.push(ConfigDiagnostic::error(
+        merged_path,
+        format!(
+          "source \"{}\" does not have a schema, and can't be merged",
+          merged.id
+        ),
+      )),
+      Some(_) => {}
+    }
+  }
+
+  for (index, rule) in config.type_merging.iter().enumerate() {
+    for (resolver_index, resolver) in rule.resolvers.iter().enumerate() {
+      if !config
+        .sources
+        .iter()
+        .any(|merged| merged.id == resolver.source)
+      {
+        diagnostics.push(ConfigDiagnostic::error(
+          format!(
+            "{}.type_merging[{}].resolvers[{}].source",
+            path, index, resolver_index
+          ),
+          format!("source \"{}\" is not merged by \"{}\"", resolver.source, id),
+        ));
+      }
+    }
+  }
+
+  diagnostics
+}
+
+/// Checks whether an enabled `client_awareness` plugin reads a JWT claim without an enabled `jwt_auth` plugin running
+/// before it, in which case the claims are never available.
+fn client_claim_read_before_jwt_auth(plugins: &[PluginDefinition]) -> bool {
+  let is_enabled = |enabled: &Option<bool>| *enabled != Some(false);
+  let is_claim = |source: &client_awareness_plugin::Source| {
+    matches!(source, client_awareness_plugin::Source::JwtClaim { .. })
+  };
+
+  for plugin in plugins {
+    match plugin {
+      PluginDefinition::JwtAuthPlugin { enabled, .. } if is_enabled(enabled) => return false,
+      PluginDefinition::ClientAwarenessPlugin {
+        enabled,
+        config: Some(config),
+      } if is_enabled(enabled) && (is_claim(&config.name) || is_claim(&config.version)) => {
+        return true
+      }
+      _ => {}
+    }
+  }
+
+  false
+}
+
+/// Reports the plugins that are declared more than once. All the declarations are executed, so this is a warning: it's
+/// often a copy/paste mistake, but it can be intended (for example, to run the same plugin with different settings).
+fn validate_plugins(
+  path: &str,
+  plugins: &[PluginDefinition],
+  inherited: &[PluginDefinition],
+) -> Vec<ConfigDiagnostic> {
+  let mut diagnostics = vec![];
+  let mut seen: HashSet<&str> = HashSet::new();
+
+  for (index, plugin) in plugins.iter().enumerate() {
+    let type_name = plugin.type_name();
+
+    if plugin.is_repeatable() {
+      continue;
+    }
+
+    if !seen.insert(type_name) {
+      diagnostics.push(ConfigDiagnostic::warning(
+        format!("{}[{}]", path, index),
+        format!(
+          "plugin \"{}\" is defined more than once, and will run more than once",
+          type_name
+        ),
+      ));
+    } else if inherited.iter().any(|p| p.type_name() == type_name) {
+      diagnostics.push(ConfigDiagnostic::warning(
+        format!("{}[{}]", path, index),
+        format!(
+          "plugin \"{}\" is already defined as a global plugin, and will run twice",
+          type_name
+        ),
+      ));
+    }
+  }
+
+  diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{parse_config_contents, ConfigFormat};
+
+  fn parse(yaml: &str) -> ConductorConfig {
+    parse_config_contents(yaml.to_string(), ConfigFormat::Yaml, |_| None)
+  }
+
+  #[test]
+  fn should_pass_valid_config() {
+    let config = parse(
+      r#"
+sources:
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+endpoints:
+  - path: /graphql
+    from: countries
+    plugins:
+      - type: graphiql
+"#,
+    );
+
+    assert_eq!(validate_config(&config), vec![]);
+  }
+
+  #[test]
+  fn should_report_unknown_and_unused_plugin_sets() {
+    let config = parse(
+      r#"
+sources:
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+plugin_sets:
+  public:
+    - type: cors
+  internal:
+    - type: graphiql
+endpoints:
+  - path: /graphql
+    from: countries
+    plugin_sets: [public, missing]
+"#,
+    );
+
+    assert_eq!(
+      validate_config(&config),
+      vec![
+        ConfigDiagnostic::error(
+          "endpoints[0].plugin_sets[1]",
+          "plugin set \"missing\" is not defined in \"plugin_sets\""
+        ),
+        ConfigDiagnostic::warning(
+          "plugin_sets.internal",
+          "plugin set \"internal\" is not used by any endpoint"
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn should_report_missing_source() {
+    let config = parse(
+      r#"
+sources:
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+endpoints:
+  - path: /graphql
+    from: countries
+  - path: /other
+    from: missing
+"#,
+    );
+
+    assert_eq!(
+      validate_config(&config),
+      vec![ConfigDiagnostic::error(
+        "endpoints[1].from",
+        "source \"missing\" is not defined in \"sources\""
+      )]
+    );
+  }
+
+  #[test]
+  fn should_report_duplicate_paths_and_sources() {
+    let config = parse(
+      r#"
+sources:
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+endpoints:
+  - path: /graphql
+    from: countries
+  - path: /graphql
+    from: countries
+  - path: /graphql
+    from: countries
+"#,
+    );
+
+    let diagnostics = validate_config(&config);
+    assert!(diagnostics.contains(&ConfigDiagnostic::error(
+      "sources[1].id",
+      "source \"countries\" is defined more than once"
+    )));
+    assert!(diagnostics.contains(&ConfigDiagnostic::error(
+      "endpoints[1].path",
+      "path \"/graphql\" is already used by endpoints[0]"
+    )));
+    assert!(diagnostics.contains(&ConfigDiagnostic::error(
+      "endpoints[2].path",
+      "path \"/graphql\" is already used by endpoints[0]"
+    )));
+  }
+
+  #[test]
+  fn should_report_invalid_merged_sources() {
+    let config = parse(
+      r#"
+sources:
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+  - id: merged
+    type: merge
+    config:
+      sources:
+        - id: countries
+        - id: missing
+        - id: merged
+      type_merging:
+        - type_name: Country
+          key: code
+          resolvers:
+            - source: other
+              field: country
+endpoints:
+  - path: /graphql
+    from: merged
+"#,
+    );
+
+    assert_eq!(
+      validate_config(&config),
+      vec![
+        ConfigDiagnostic::error(
+          "sources[1].config.sources[0].id",
+          "source \"countries\" does not have a schema, and can't be merged"
+        ),
+        ConfigDiagnostic::error(
+          "sources[1].config.sources[1].id",
+          "source \"missing\" is not defined in \"sources\""
+        ),
+        ConfigDiagnostic::error(
+          "sources[1].config.sources[2].id",
+          "source \"merged\" is a merge source, and can't be merged into \"merged\""
+        ),
+        ConfigDiagnostic::error(
+          "sources[1].config.type_merging[0].resolvers[0].source",
+          "source \"other\" is not merged by \"merged\""
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn should_report_duplicate_plugins() {
+    let config = parse(
+      r#"
+sources:
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+plugins:
+  - type: cors
+  - type: vrl
+    config:
+      on_downstream_http_request:
+        from: inline
+        content: .
+endpoints:
+  - path: /graphql
+    from: countries
+    plugins:
+      - type: graphiql
+      - type: graphiql
+      - type: cors
+      - type: vrl
+        config:
+          on_downstream_http_request:
+            from: inline
+            content: .
+"#,
+    );
+
+    assert_eq!(
+      validate_config(&config),
+      vec![
+        ConfigDiagnostic::warning(
+          "endpoints[0].plugins[1]",
+          "plugin \"graphiql\" is defined more than once, and will run more than once"
+        ),
+        ConfigDiagnostic::warning(
+          "endpoints[0].plugins[2]",
+          "plugin \"cors\" is already defined as a global plugin, and will run twice"
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn should_report_client_claim_read_before_jwt_auth() {
+    let config = parse(
+      r#"
+sources:
+  - id: countries
+    type: graphql
+    config:
+      endpoint: https://countries.trevorblades.com/
+endpoints:
+  - path: /graphql
+    from: countries
+    plugins:
+      - type: client_awareness
+        config:
+          name:
+            from: jwt_claim
+            claim: client_name
+      - type: jwt_auth
+        config:
+          jwks_providers: []
+  - path: /other
+    from: countries
+    plugins:
+      - type: jwt_auth
+        config:
+          jwks_providers: []
+      - type: client_awareness
+        config:
+          name:
+            from: jwt_claim
+            claim: client_name
+"#,
+    );
+
+    assert_eq!(
+      validate_config(&config),
+      vec![ConfigDiagnostic::error(
+        "endpoints[0].plugins",
+        "plugin \"client_awareness\" reads the client from a JWT claim, but the \"jwt_auth\" plugin is not declared before it"
+      )]
+    );
+  }
+}
@@ -30,6 +30,7 @@ impl TestSuite {
       GraphQLSourceConfig {
         endpoint: mock_server.url("/graphql"),
         schema_awareness: None,
+        http_client: None,
       },
     )
     .await
@@ -66,6 +67,7 @@ impl TestSuite {
       GraphQLSourceConfig {
         endpoint: mock_server.url("/graphql"),
         schema_awareness: None,
+        http_client: None,
       },
     )
     .await
@@ -1,4 +1,5 @@
 pub mod plugin_cors;
 pub mod plugin_disable_introspection;
 pub mod plugin_telemetry;
+pub mod plugin_usage_reporting;
 pub mod plugin_vrl;
@@ -3,13 +3,14 @@ pub mod telemetry {
   use conductor_tracing::reporters::TracingReporter;
   use conductor_tracing::routed_reporter::test_utils::TestReporter;
   use conductor_tracing::{
-    fastrace_mgr::FastraceManager, otel_attrs::*, trace_id::generate_trace_id,
+    fastrace_mgr::FastraceManager,
+    otel_attrs::*,
+    trace_id::{generate_trace_id, tenant_root_span},
   };
   use e2e::suite::TestSuite;
   use fastrace::{
     collector::{Config, SpanContext, SpanId},
     future::FutureExt,
-    Span,
   };
   use tokio::test;
 
@@ -37,7 +38,7 @@ pub mod telemetry {
     };
 
     let span_context = SpanContext::new(generate_trace_id(0), SpanId::default());
-    let root_span = Span::root("root", span_context);
+    let root_span = tenant_root_span("root", span_context, 0);
     test
       .run_graphql_request(GraphQLRequest::default())
       .in_span(root_span)
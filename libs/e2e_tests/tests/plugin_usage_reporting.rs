@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use conductor_common::{
+  http::{ConductorHttpRequest, HeaderValue, HttpHeadersMap, Method, StatusCode},
+  plugin::CreatablePlugin,
+};
+use e2e::suite::TestSuite;
+use httpmock::prelude::*;
+use tokio::test;
+
+#[test]
+async fn reports_usage_to_registry() {
+  let registry = MockServer::start_async().await;
+  let registry_mock = registry
+    .mock_async(|when, then| {
+      when
+        .method(POST)
+        .path("/usage")
+        .header("authorization", "Bearer test-token")
+        .header("x-usage-api-version", "2")
+        .body_contains("\"size\":1")
+        .body_contains("\"operationName\":\"Test\"")
+        .body_contains("\"errorsTotal\":0")
+        .body_contains("\"client\":{\"name\":\"web\",\"version\":\"1.0.0\"}");
+      then.status(200);
+    })
+    .await;
+
+  let plugin = usage_reporting_plugin::Plugin::create(usage_reporting_plugin::Config {
+    endpoint: registry.url("/usage"),
+    token: "test-token".to_string(),
+    sample_rate: 1.0,
+    exclude: vec![],
+    max_batch_size: 100,
+    flush_interval: Duration::from_secs(60),
+    client_name_header: "apollographql-client-name".to_string(),
+    client_version_header: "apollographql-client-version".to_string(),
+  })
+  .await
+  .unwrap();
+  let reporter = plugin.reporter();
+
+  let mut headers = HttpHeadersMap::default();
+  headers.append("content-type", HeaderValue::from_static("application/json"));
+  headers.append("apollographql-client-name", HeaderValue::from_static("web"));
+  headers.append(
+    "apollographql-client-version",
+    HeaderValue::from_static("1.0.0"),
+  );
+
+  let test = TestSuite {
+    plugins: vec![plugin],
+    ..Default::default()
+  };
+  let response = test
+    .run_http_request(ConductorHttpRequest {
+      body: "{\"query\": \"query Test { __typename }\"}".into(),
+      uri: String::from("/graphql"),
+      query_string: String::from(""),
+      method: Method::POST,
+      headers,
+    })
+    .await;
+  assert_eq!(response.status, StatusCode::OK);
+
+  // Reports are sent in the background, so we send the pending report explicitly.
+  reporter.flush().await;
+  registry_mock.assert_async().await;
+}
@@ -1,28 +1,47 @@
-use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use std::{
+  collections::{HashMap, HashSet},
+  fmt::Debug,
+  sync::Arc,
+  time::SystemTime,
+};
 
 use conductor_common::{
-  execute::RequestExecutionContext,
+  execute::{RequestExecutionContext, GRAPHQL_ERRORS_COUNT_CONTEXT_KEY},
   graphql::{ExtractGraphQLOperationError, GraphQLRequest, GraphQLResponse, ParsedGraphQLRequest},
-  http::{ConductorHttpRequest, ConductorHttpResponse, Url},
+  http::{ConductorHttpRequest, ConductorHttpResponse, HeaderName, HeaderValue, Url},
   plugin::PluginError,
   plugin_manager::PluginManager,
-  source::{GraphQLSourceInitError, SourceError, SourceRuntime},
+  source::{GraphQLSourceInitError, SchemaChangeListener, SourceError, SourceRuntime},
+};
+use conductor_config::{
+  validate::ConfigDiagnostic, ConductorConfig, EndpointDefinition, OpenApiSpecSource,
+  PropagationConfig, SchemaAwarenessFormat, SourceDefinition,
 };
-use conductor_config::{ConductorConfig, EndpointDefinition, SourceDefinition};
 use conductor_tracing::{
   fastrace_mgr::FastraceManager,
   otel_attrs::CONDUCTOR_SOURCE,
   otel_utils::{create_graphql_error_span_properties, create_graphql_span},
+  propagation::{accept_request_id, generate_request_id},
+  sampling::TraceSampler,
 };
 use fastrace::{future::FutureExt, trace, Span};
+use federation_query_planner::supergraph::{parse_supergraph, Supergraph};
 use reqwest::{Method, StatusCode};
-use tracing::error;
+use tracing::{error, Instrument};
 
 use crate::{
+  introspection::{
+    execute_introspection, has_introspection_fields, is_introspection_only,
+    merge_introspection_response, remove_introspection_fields,
+  },
+  openapi::build_openapi_schema,
   plugin_manager::PluginManagerImpl,
+  schema_awareness::SchemaAwareness,
   source::{
-    federation_source::FederationSourceRuntime, graphql_source::GraphQLSourceRuntime,
-    mock_source::MockedSourceRuntime,
+    federation_source::FederationSourceRuntime, filtered_source::FilteredSourceRuntime,
+    graphql_source::GraphQLSourceRuntime, merge_source::MergeSourceRuntime,
+    mock_source::MockedSourceRuntime, openapi_source::OpenApiSourceRuntime,
+    schema_mock_source::SchemaMockSourceRuntime,
   },
 };
 
@@ -32,6 +51,9 @@ pub struct ConductorGatewayRouteData {
   pub tenant_id: u32,
   pub plugin_manager: Arc<Box<dyn PluginManager>>,
   pub to: Arc<Box<dyn SourceRuntime>>,
+  pub propagation: PropagationConfig,
+  /// Decides which requests of the endpoint are traced, when its telemetry is sampled.
+  pub sampler: Option<TraceSampler>,
 }
 
 #[derive(Debug)]
@@ -55,28 +77,38 @@ pub enum GatewayError {
   MissingSource(String),
   #[error("failed to initialize source '{0}': {1}")]
   SourceInitFailed(String, GraphQLSourceInitError),
+  #[error("failed to locate plugin set named \"{0}\"")]
+  MissingPluginSet(String),
 }
 
 impl ConductorGateway {
   pub fn match_route(&self, route: &Url) -> Result<&ConductorGatewayRouteData, GatewayError> {
+    self
+      .match_path(route.path())
+      .map(|route_data| route_data.as_ref())
+  }
+
+  pub fn match_path(&self, path: &str) -> Result<&Arc<ConductorGatewayRouteData>, GatewayError> {
     // TODO: This function should probably use a more sophisticated matching algorithm.
     for conductor_route in &self.routes {
-      if route.path() == conductor_route.base_path {
+      if path == conductor_route.base_path {
         return Ok(&conductor_route.route_data);
       }
     }
 
     for conductor_route in &self.routes {
-      if route.path().starts_with(&conductor_route.base_path) {
+      if path.starts_with(&conductor_route.base_path) {
         return Ok(&conductor_route.route_data);
       }
     }
 
-    Err(GatewayError::MissingEndpoint(route.path().to_string()))
+    Err(GatewayError::MissingEndpoint(path.to_string()))
   }
 
+  /// Creates the runtime of a source. `sources` holds the runtimes that were already created, used by merge sources.
   async fn create_source(
     def: &SourceDefinition,
+    sources: &HashMap<String, Arc<Box<dyn SourceRuntime>>>,
   ) -> Result<Box<dyn SourceRuntime>, GraphQLSourceInitError> {
     Ok(match def {
       SourceDefinition::GraphQL { id, config } => {
@@ -88,9 +120,118 @@ impl ConductorGateway {
       SourceDefinition::Mock { id, config } => {
         Box::new(MockedSourceRuntime::new(id.clone(), config.clone()))
       }
+      SourceDefinition::OpenApi { id, config } => {
+        Box::new(OpenApiSourceRuntime::new(id.clone(), config.clone()).await?)
+      }
+      SourceDefinition::SchemaMock { id, config } => {
+        Box::new(SchemaMockSourceRuntime::new(id.clone(), config.clone()).await?)
+      }
+      SourceDefinition::Merge { id, config } => {
+        let merged_sources = config
+          .sources
+          .iter()
+          .map(|merged| {
+            sources.get(&merged.id).cloned().ok_or_else(|| {
+              GraphQLSourceInitError::SourceInitFailed {
+                source: anyhow::anyhow!("merged source \"{}\" is not defined", merged.id),
+              }
+            })
+          })
+          .collect::<Result<Vec<_>, _>>()?;
+
+        Box::new(MergeSourceRuntime::new(
+          id.clone(),
+          config.clone(),
+          merged_sources,
+        ))
+      }
     })
   }
 
+  /// Loads and parses the schema of every source that can be loaded without network access.
+  ///
+  /// Remote schema sources are skipped and reported as warnings.
+  pub async fn validate_sources_offline(config_object: &ConductorConfig) -> Vec<ConfigDiagnostic> {
+    let mut diagnostics = vec![];
+
+    for (index, source_config) in config_object.sources.iter().enumerate() {
+      let path = format!("sources[{}]", index);
+
+      let result = match source_config {
+        SourceDefinition::GraphQL { config, .. } => match config.schema_awareness.as_ref() {
+          Some(schema_awareness) => SchemaAwareness::<()>::load_offline(
+            &schema_awareness.format,
+            &schema_awareness.source,
+            |_, _| Ok(()),
+          )
+          .await
+          .map(|record| record.is_some()),
+          None => continue,
+        },
+        SourceDefinition::SchemaMock { config, .. } => SchemaAwareness::<()>::load_offline(
+          &config.schema.format,
+          &config.schema.source,
+          |_, _| Ok(()),
+        )
+        .await
+        .map(|record| record.is_some()),
+        SourceDefinition::Federation { config, .. } => SchemaAwareness::<Supergraph>::load_offline(
+          &SchemaAwarenessFormat::Sdl,
+          &config.supergraph.source,
+          |_, parsed| parse_supergraph(parsed),
+        )
+        .await
+        .map(|record| record.is_some()),
+        SourceDefinition::Mock { config, .. } => {
+          if let Err(e) = serde_json::from_str::<GraphQLResponse>(&config.response_data.contents) {
+            diagnostics.push(ConfigDiagnostic::error(
+              format!("{}.config.response_data", path),
+              format!("failed to parse mocked response: {}", e),
+            ));
+          }
+
+          continue;
+        }
+        SourceDefinition::OpenApi { config, .. } => match &config.spec {
+          OpenApiSpecSource::File { file } => {
+            if let Err(e) = build_openapi_schema(&file.contents) {
+              diagnostics.push(ConfigDiagnostic::error(
+                format!("{}.config.spec.path", path),
+                format!("failed to build schema from OpenAPI document: {}", e),
+              ));
+            }
+
+            continue;
+          }
+          OpenApiSpecSource::Remote { .. } => Ok(false),
+        },
+        // Merge sources don't load a schema, they use the schemas of the merged sources.
+        SourceDefinition::Merge { .. } => continue,
+      };
+
+      match result {
+        Ok(true) => {}
+        Ok(false) => diagnostics.push(ConfigDiagnostic::warning(
+          path,
+          format!(
+            "source \"{}\" loads its schema from a remote endpoint, skipped in offline mode",
+            source_config.id()
+          ),
+        )),
+        Err(e) => diagnostics.push(ConfigDiagnostic::error(
+          path,
+          format!(
+            "failed to load schema for source \"{}\": {:?}",
+            source_config.id(),
+            e
+          ),
+        )),
+      }
+    }
+
+    diagnostics
+  }
+
   async fn construct_endpoint(
     tenant_id: u32,
     config_object: &ConductorConfig,
@@ -98,24 +239,47 @@ impl ConductorGateway {
     source_runtime: Arc<Box<dyn SourceRuntime>>,
     tracing_manager: &mut FastraceManager,
   ) -> Result<ConductorGatewayRouteData, GatewayError> {
-    let global_plugins = &config_object.plugins;
-    let combined_plugins = global_plugins
-      .iter()
-      .chain(&endpoint_config.plugins)
-      .flat_map(|vec| vec.iter())
-      .cloned()
-      .collect::<Vec<_>>();
+    let combined_plugins = config_object
+      .endpoint_plugins(endpoint_config)
+      .map_err(GatewayError::MissingPluginSet)?;
+
+    let propagation = config_object.propagation.clone().unwrap_or_default();
+    let plugin_manager = PluginManagerImpl::new(
+      &Some(combined_plugins),
+      propagation.clone(),
+      tracing_manager,
+      tenant_id,
+    )
+    .await
+    .map_err(GatewayError::PluginManagerInitError)?;
+
+    // The sampler is set by the telemetry plugin, when it's configured for the endpoint.
+    let sampler = tracing_manager.sampler(tenant_id).cloned();
+
+    let source_runtime: Arc<Box<dyn SourceRuntime>> = match &endpoint_config.schema_filter {
+      Some(schema_filter) => Arc::new(Box::new(FilteredSourceRuntime::new(
+        source_runtime,
+        schema_filter.clone(),
+      ))),
+      None => source_runtime,
+    };
 
-    let plugin_manager =
-      PluginManagerImpl::new(&Some(combined_plugins), tracing_manager, tenant_id)
-        .await
-        .map_err(GatewayError::PluginManagerInitError)?;
+    let plugin_manager: Arc<Box<dyn PluginManager>> = Arc::new(Box::new(plugin_manager));
+    // The source can be shared by several endpoints, so its listener doesn't keep the plugins of this endpoint alive.
+    let schema_change_plugins = Arc::downgrade(&plugin_manager);
+    source_runtime.subscribe_schema_changes(Arc::new(move |source_id, diff| {
+      if let Some(plugin_manager) = schema_change_plugins.upgrade() {
+        plugin_manager.on_schema_change(source_id, diff);
+      }
+    }));
 
     let route_data = ConductorGatewayRouteData {
       endpoint: endpoint_config.path.clone(),
       to: source_runtime,
-      plugin_manager: Arc::new(Box::new(plugin_manager)),
+      plugin_manager,
       tenant_id,
+      propagation,
+      sampler,
     };
 
     Ok(route_data)
@@ -128,8 +292,14 @@ impl ConductorGateway {
     let mut route_mapping: Vec<ConductorGatewayRoute> = vec![];
     let mut sources: HashMap<String, Arc<Box<dyn SourceRuntime>>> = HashMap::new();
 
-    for source_config in config_object.sources.iter() {
-      let source = ConductorGateway::create_source(source_config)
+    // Merge sources are created last, since they use the runtimes of the sources they merge.
+    let (merge_sources, other_sources): (Vec<_>, Vec<_>) = config_object
+      .sources
+      .iter()
+      .partition(|source_config| matches!(source_config, SourceDefinition::Merge { .. }));
+
+    for source_config in other_sources.into_iter().chain(merge_sources) {
+      let source = ConductorGateway::create_source(source_config, &sources)
         .await
         .map_err(|source| GatewayError::SourceInitFailed(source_config.id().to_owned(), source))?;
 
@@ -141,24 +311,19 @@ impl ConductorGateway {
         .get(&endpoint_config.from)
         .ok_or_else(|| GatewayError::MissingSource(endpoint_config.from.clone()))?;
 
-      let route_data = match Self::construct_endpoint(
+      let route_data = Self::construct_endpoint(
         index.try_into().unwrap(),
         config_object,
         endpoint_config,
         upstream_source.clone(),
         tracing_manager,
       )
-      .await
-      {
-        Ok(route_data) => ConductorGatewayRoute {
-          base_path: endpoint_config.path.clone(),
-          route_data: Arc::new(route_data),
-        },
-        // @expected: if we are unable to construct the endpoints and attach them onto the gateway's http server, we have to exit
-        Err(e) => panic!("failed to construct endpoint: {:?}", e),
-      };
+      .await?;
 
-      route_mapping.push(route_data);
+      route_mapping.push(ConductorGatewayRoute {
+        base_path: endpoint_config.path.clone(),
+        route_data: Arc::new(route_data),
+      });
     }
 
     Ok(Self {
@@ -166,6 +331,52 @@ impl ConductorGateway {
     })
   }
 
+  /// Stops the background work of all sources used by the gateway endpoints, and lets their plugins send the data that
+  /// is still pending.
+  pub async fn shutdown(&self) {
+    for route in &self.routes {
+      route.route_data.to.shutdown();
+      route.route_data.plugin_manager.on_shutdown().await;
+    }
+  }
+
+  /// Lets the plugins of all endpoints send the data they collected, when it's due.
+  ///
+  /// On WASM runtime, plugins can't send data in background tasks, so the runtime calls this after handling requests.
+  pub async fn flush_plugins(&self) {
+    for route in &self.routes {
+      route.route_data.plugin_manager.on_flush().await;
+    }
+  }
+
+  /// Reloads the schemas of the sources used by the gateway endpoints, if their polling interval elapsed.
+  ///
+  /// On WASM runtime, schemas can't be polled in the background, so the runtime calls this after handling requests.
+  pub async fn refresh_schemas(&self, now: SystemTime) {
+    let mut seen = HashSet::new();
+
+    for route in &self.routes {
+      let source = &route.route_data.to;
+
+      if seen.insert(source.name()) {
+        source.refresh_schema(now).await;
+      }
+    }
+  }
+
+  /// Registers a listener for the schema changes of all sources used by the gateway endpoints.
+  pub fn subscribe_schema_changes(&self, listener: SchemaChangeListener) {
+    let mut seen = HashSet::new();
+
+    for route in &self.routes {
+      let source = &route.route_data.to;
+
+      if seen.insert(source.name()) {
+        source.subscribe_schema_changes(listener.clone());
+      }
+    }
+  }
+
   #[cfg(feature = "test_utils")]
   pub async fn execute_test(
     source: Arc<Box<dyn SourceRuntime>>,
@@ -178,6 +389,8 @@ impl ConductorGateway {
       plugin_manager: Arc::new(Box::new(plugin_manager)),
       to: source,
       tenant_id: 0,
+      propagation: Default::default(),
+      sampler: None,
     };
     let gw = Self {
       routes: vec![ConductorGatewayRoute {
@@ -190,13 +403,103 @@ impl ConductorGateway {
     ConductorGateway::execute(request, &gw.routes[0].route_data).await
   }
 
+  /// Executes the GraphQL request with the source of the route.
+  ///
+  /// When the source has a schema, introspection fields are resolved from it, and only the other root fields (if any)
+  /// are executed by the source.
+  async fn execute_source(
+    route_data: &ConductorGatewayRouteData,
+    request_ctx: &mut RequestExecutionContext,
+    graphql_span: &Span,
+  ) -> Result<GraphQLResponse, SourceError> {
+    let introspection_response = match (
+      route_data.to.schema(),
+      request_ctx.downstream_graphql_request.as_mut(),
+    ) {
+      (Some(schema), Some(request)) if has_introspection_fields(request) => {
+        let response = execute_introspection(&schema, request);
+
+        if is_introspection_only(request) {
+          return Ok(response);
+        }
+
+        *request = remove_introspection_fields(request);
+
+        Some(response)
+      }
+      _ => None,
+    };
+
+    let upstream_span = Span::enter_with_parent("upstream_call", graphql_span)
+      .with_property(|| (CONDUCTOR_SOURCE, route_data.to.name().to_string()));
+
+    let mut response = route_data
+      .to
+      .execute(route_data.plugin_manager.clone(), request_ctx)
+      .in_span(upstream_span)
+      .await?;
+
+    if let Some(introspection_response) = introspection_response {
+      merge_introspection_response(&mut response, introspection_response);
+    }
+
+    Ok(response)
+  }
+
   #[trace(name = "execute")]
   pub async fn execute(
     request: ConductorHttpRequest,
     route_data: &ConductorGatewayRouteData,
   ) -> ConductorHttpResponse {
+    let request_id_header = &route_data.propagation.request_id_header;
+    let request_id = request
+      .headers
+      .get(request_id_header.as_str())
+      .and_then(|value| value.to_str().ok())
+      .and_then(accept_request_id)
+      .map(ToString::to_string)
+      .unwrap_or_else(generate_request_id);
+
+    // The request id, and the client once it's identified, are attached to all the logs of the request.
+    let request_span = tracing::info_span!(
+      "downstream_request",
+      request_id = request_id.as_str(),
+      client.name = tracing::field::Empty,
+      client.version = tracing::field::Empty,
+    );
+
+    let trace_context = route_data
+      .propagation
+      .request_span_context(route_data.tenant_id, |name| {
+        request
+          .headers
+          .get(name)
+          .and_then(|value| value.to_str().ok())
+          .map(ToString::to_string)
+      });
+
     let mut request_ctx = RequestExecutionContext::new(request);
+    request_ctx.request_id = Some(request_id.clone());
+    request_ctx.trace_context = Some(trace_context);
 
+    let mut response = ConductorGateway::execute_request(request_ctx, route_data)
+      .instrument(request_span)
+      .await;
+
+    if let (Ok(name), Ok(value)) = (
+      HeaderName::from_bytes(request_id_header.as_bytes()),
+      HeaderValue::from_str(&request_id),
+    ) {
+      response.headers.insert(name, value);
+    }
+
+    response
+  }
+
+  async fn execute_request(
+    mut request_ctx: RequestExecutionContext,
+    route_data: &ConductorGatewayRouteData,
+  ) -> ConductorHttpResponse {
     // Step 1: Trigger "on_downstream_http_request" on all plugins
     route_data
       .plugin_manager
@@ -216,6 +519,15 @@ impl ConductorGateway {
       }
     }
 
+    if let Some(client) = &request_ctx.client {
+      let span = tracing::Span::current();
+      span.record("client.name", client.name.as_str());
+
+      if let Some(version) = &client.version {
+        span.record("client.version", version.as_str());
+      }
+    }
+
     // Step 2: Default handling flow for GraphQL request using POST
     // If plugins didn't extract anything from the request, we can try to do that here.
     // Plugins might have set it before, so we can avoid extraction.
@@ -259,7 +571,7 @@ impl ConductorGateway {
     // Verify that we have a GraphQL request at this point.
     match request_ctx.downstream_graphql_request.as_ref() {
       Some(gql_operation) => {
-        let mut _graphql_span = create_graphql_span(gql_operation);
+        let mut _graphql_span = create_graphql_span(gql_operation, request_ctx.client.as_ref());
 
         // Step 3: Execute plugins on the extracted GraphQL request.
         route_data
@@ -280,14 +592,8 @@ impl ConductorGateway {
           }
         }
 
-        let upstream_span = Span::enter_with_parent("upstream_call", &_graphql_span)
-          .with_property(|| (CONDUCTOR_SOURCE, route_data.to.name().to_string()));
-
-        let upstream_response = route_data
-          .to
-          .execute(route_data.plugin_manager.clone(), &mut request_ctx)
-          .in_span(upstream_span)
-          .await;
+        let upstream_response =
+          ConductorGateway::execute_source(route_data, &mut request_ctx, &_graphql_span).await;
 
         let final_response = match upstream_response {
           Ok(response) => response,
@@ -309,6 +615,15 @@ impl ConductorGateway {
             _graphql_span.with_properties(|| create_graphql_error_span_properties(errors));
         }
 
+        request_ctx.ctx_insert(
+          GRAPHQL_ERRORS_COUNT_CONTEXT_KEY,
+          final_response
+            .errors
+            .as_ref()
+            .map(Vec::len)
+            .unwrap_or_default(),
+        );
+
         let mut http_response: ConductorHttpResponse = final_response.into();
 
         route_data
@@ -0,0 +1,303 @@
+use conductor_config::UpstreamHttpClientConfig;
+
+/// Creates the HTTP client used to reach an upstream, with the TLS and HTTP/2 settings of the source applied.
+///
+/// The `fetch` based client used on WASM runtime doesn't support these settings, so they are ignored there.
+pub fn create_upstream_http_client(
+  config: Option<&UpstreamHttpClientConfig>,
+) -> Result<reqwest::Client, reqwest::Error> {
+  let builder = wasm_polyfills::create_http_client();
+
+  #[cfg(not(target_arch = "wasm32"))]
+  let builder = match config {
+    Some(config) => apply_config(builder, config)?,
+    None => builder,
+  };
+
+  #[cfg(target_arch = "wasm32")]
+  if config.is_some() {
+    tracing::warn!("\"http_client\" configuration is not supported on WASM runtime, ignoring it");
+  }
+
+  builder.build()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn apply_config(
+  mut builder: reqwest::ClientBuilder,
+  config: &UpstreamHttpClientConfig,
+) -> Result<reqwest::ClientBuilder, reqwest::Error> {
+  use reqwest::{Certificate, Identity};
+
+  for root_certificate in &config.root_certificates {
+    builder =
+      builder.add_root_certificate(Certificate::from_pem(root_certificate.contents.as_bytes())?);
+  }
+
+  if let Some(client_certificate) = &config.client_certificate {
+    builder = builder.identity(Identity::from_pkcs8_pem(
+      client_certificate.cert.contents.as_bytes(),
+      client_certificate.key.contents.as_bytes(),
+    )?);
+  }
+
+  if config.insecure_skip_verify {
+    tracing::warn!("upstream certificate verification is disabled, this should only be used for local development");
+    builder = builder.danger_accept_invalid_certs(true);
+  }
+
+  if config.http2_prior_knowledge {
+    builder = builder.http2_prior_knowledge();
+  }
+
+  Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    sync::mpsc,
+    thread,
+  };
+
+  use conductor_common::serde_utils::LocalFileReference;
+  use conductor_config::UpstreamClientCertificateConfig;
+  use openssl::{
+    asn1::Asn1Time,
+    bn::BigNum,
+    ec::{EcGroup, EcKey},
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{PKey, Private},
+    ssl::{SslAcceptor, SslMethod, SslVerifyMode},
+    x509::{
+      extension::{BasicConstraints, ExtendedKeyUsage, SubjectAlternativeName},
+      X509NameBuilder, X509NameRef, X509,
+    },
+  };
+
+  use super::*;
+
+  struct TestCert {
+    cert: X509,
+    key: PKey<Private>,
+  }
+
+  /// Generates a certificate for `localhost`, signed by `issuer`, or a self-signed CA certificate when `issuer` is `None`.
+  fn generate_cert(common_name: &str, serial: u32, issuer: Option<&TestCert>) -> TestCert {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder
+      .set_serial_number(&BigNum::from_u32(serial).unwrap().to_asn1_integer().unwrap())
+      .unwrap();
+    builder.set_subject_name(&name).unwrap();
+    let issuer_name: &X509NameRef = match issuer {
+      Some(issuer) => issuer.cert.subject_name(),
+      None => &name,
+    };
+    builder.set_issuer_name(issuer_name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+      .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+      .unwrap();
+    builder
+      .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+      .unwrap();
+
+    match issuer {
+      None => {
+        let constraints = BasicConstraints::new().critical().ca().build().unwrap();
+        builder.append_extension(constraints).unwrap();
+      }
+      Some(issuer) => {
+        let alt_name = SubjectAlternativeName::new()
+          .dns("localhost")
+          .build(&builder.x509v3_context(Some(&*issuer.cert), None))
+          .unwrap();
+        let usage = ExtendedKeyUsage::new()
+          .server_auth()
+          .client_auth()
+          .build()
+          .unwrap();
+        builder.append_extension(alt_name).unwrap();
+        builder.append_extension(usage).unwrap();
+      }
+    }
+
+    let signing_key = issuer.map_or(&key, |issuer| &issuer.key);
+    builder.sign(signing_key, MessageDigest::sha256()).unwrap();
+
+    TestCert {
+      cert: builder.build(),
+      key,
+    }
+  }
+
+  fn pem_file(contents: Vec<u8>) -> LocalFileReference {
+    LocalFileReference {
+      path: "test.pem".to_string(),
+      contents: String::from_utf8(contents).unwrap(),
+    }
+  }
+
+  fn client_config() -> UpstreamHttpClientConfig {
+    UpstreamHttpClientConfig {
+      client_certificate: None,
+      root_certificates: vec![],
+      insecure_skip_verify: false,
+      http2_prior_knowledge: false,
+    }
+  }
+
+  /// Starts an HTTPS server on a random port, that requires a client certificate signed by `client_ca` when set.
+  fn start_tls_server(server: &TestCert, client_ca: Option<&TestCert>) -> u16 {
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_private_key(&server.key).unwrap();
+    acceptor.set_certificate(&server.cert).unwrap();
+
+    if let Some(client_ca) = client_ca {
+      acceptor
+        .cert_store_mut()
+        .add_cert(client_ca.cert.clone())
+        .unwrap();
+      acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    }
+
+    let acceptor = acceptor.build();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    thread::spawn(move || {
+      for stream in listener.incoming().flatten() {
+        if let Ok(mut stream) = acceptor.accept(stream) {
+          let mut buffer = [0; 4096];
+          let _ = stream.read(&mut buffer);
+          let _ =
+            stream.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+        }
+      }
+    });
+
+    port
+  }
+
+  async fn request(
+    client: &reqwest::Client,
+    port: u16,
+  ) -> Result<reqwest::Response, reqwest::Error> {
+    client
+      .get(format!("https://localhost:{}/", port))
+      .send()
+      .await
+  }
+
+  #[tokio::test]
+  async fn should_trust_root_certificates_and_skip_verification() {
+    let ca = generate_cert("Test CA", 1, None);
+    let server = generate_cert("localhost", 2, Some(&ca));
+    let port = start_tls_server(&server, None);
+
+    let client = create_upstream_http_client(None).unwrap();
+    assert!(request(&client, port).await.is_err());
+
+    let config = UpstreamHttpClientConfig {
+      root_certificates: vec![pem_file(ca.cert.to_pem().unwrap())],
+      ..client_config()
+    };
+    let client = create_upstream_http_client(Some(&config)).unwrap();
+    assert_eq!(request(&client, port).await.unwrap().status(), 200);
+
+    let config = UpstreamHttpClientConfig {
+      insecure_skip_verify: true,
+      ..client_config()
+    };
+    let client = create_upstream_http_client(Some(&config)).unwrap();
+    assert_eq!(request(&client, port).await.unwrap().status(), 200);
+  }
+
+  #[tokio::test]
+  async fn should_present_client_certificate() {
+    let ca = generate_cert("Test CA", 1, None);
+    let server = generate_cert("localhost", 2, Some(&ca));
+    let client_cert = generate_cert("client", 3, Some(&ca));
+    let port = start_tls_server(&server, Some(&ca));
+
+    let config = UpstreamHttpClientConfig {
+      root_certificates: vec![pem_file(ca.cert.to_pem().unwrap())],
+      ..client_config()
+    };
+    let client = create_upstream_http_client(Some(&config)).unwrap();
+    assert!(request(&client, port).await.is_err());
+
+    let config = UpstreamHttpClientConfig {
+      client_certificate: Some(UpstreamClientCertificateConfig {
+        cert: pem_file(client_cert.cert.to_pem().unwrap()),
+        key: pem_file(client_cert.key.private_key_to_pem_pkcs8().unwrap()),
+      }),
+      ..config
+    };
+    let client = create_upstream_http_client(Some(&config)).unwrap();
+    assert_eq!(request(&client, port).await.unwrap().status(), 200);
+  }
+
+  #[tokio::test]
+  async fn should_use_http2_prior_knowledge() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+      for mut stream in listener.incoming().flatten() {
+        let mut preface = [0; 24];
+        let _ = stream.read_exact(&mut preface);
+        let _ = sender.send(preface);
+      }
+    });
+
+    let config = UpstreamHttpClientConfig {
+      http2_prior_knowledge: true,
+      ..client_config()
+    };
+
+    for (config, expected) in [
+      (None, &b"GET / HTTP/1.1\r\n"[..]),
+      (Some(&config), &b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n"[..]),
+    ] {
+      let client = create_upstream_http_client(config).unwrap();
+      let _ = client
+        .get(format!("http://127.0.0.1:{}/", port))
+        .send()
+        .await;
+
+      assert!(receiver.recv().unwrap().starts_with(expected));
+    }
+  }
+
+  #[test]
+  fn should_fail_on_invalid_certificates() {
+    let config = UpstreamHttpClientConfig {
+      root_certificates: vec![pem_file(b"not a certificate".to_vec())],
+      ..client_config()
+    };
+    assert!(create_upstream_http_client(Some(&config)).is_err());
+
+    let ca = generate_cert("Test CA", 1, None);
+    let config = UpstreamHttpClientConfig {
+      client_certificate: Some(UpstreamClientCertificateConfig {
+        cert: pem_file(ca.cert.to_pem().unwrap()),
+        key: pem_file(b"not a key".to_vec()),
+      }),
+      ..client_config()
+    };
+    assert!(create_upstream_http_client(Some(&config)).is_err());
+  }
+}
@@ -0,0 +1,1123 @@
+use std::{
+  collections::{HashMap, HashSet},
+  sync::OnceLock,
+};
+
+use conductor_common::graphql::{
+  parse_graphql_schema, GraphQLRequest, GraphQLResponse, ParsedGraphQLDocument,
+  ParsedGraphQLRequest, ParsedGraphQLSchema,
+};
+use graphql_parser::{
+  query::{
+    Definition as QueryDefinition, Field as QueryField, FragmentDefinition, OperationDefinition,
+    Selection, SelectionSet, TypeCondition,
+  },
+  schema::{
+    Definition, Directive, DirectiveDefinition, EnumValue, Field, InputValue, Type, TypeDefinition,
+    Value as GraphQLValue,
+  },
+};
+use serde_json::{Map, Value};
+
+type SchemaTypeDefinition = TypeDefinition<'static, String>;
+type SchemaType = Type<'static, String>;
+
+/// The types and directives every GraphQL schema has, even when they are not declared in the SDL.
+const BUILTIN_SDL: &str = r#"
+scalar String
+scalar Int
+scalar Float
+scalar Boolean
+scalar ID
+
+type __Schema {
+  description: String
+  types: [__Type!]!
+  queryType: __Type!
+  mutationType: __Type
+  subscriptionType: __Type
+  directives: [__Directive!]!
+}
+
+type __Type {
+  kind: __TypeKind!
+  name: String
+  description: String
+  specifiedByURL: String
+  fields(includeDeprecated: Boolean = false): [__Field!]
+  interfaces: [__Type!]
+  possibleTypes: [__Type!]
+  enumValues(includeDeprecated: Boolean = false): [__EnumValue!]
+  inputFields(includeDeprecated: Boolean = false): [__InputValue!]
+  ofType: __Type
+  isOneOf: Boolean
+}
+
+enum __TypeKind {
+  SCALAR
+  OBJECT
+  INTERFACE
+  UNION
+  ENUM
+  INPUT_OBJECT
+  LIST
+  NON_NULL
+}
+
+type __Field {
+  name: String!
+  description: String
+  args(includeDeprecated: Boolean = false): [__InputValue!]!
+  type: __Type!
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+type __InputValue {
+  name: String!
+  description: String
+  type: __Type!
+  defaultValue: String
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+type __EnumValue {
+  name: String!
+  description: String
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+type __Directive {
+  name: String!
+  description: String
+  locations: [__DirectiveLocation!]!
+  args(includeDeprecated: Boolean = false): [__InputValue!]!
+  isRepeatable: Boolean!
+}
+
+enum __DirectiveLocation {
+  QUERY
+  MUTATION
+  SUBSCRIPTION
+  FIELD
+  FRAGMENT_DEFINITION
+  FRAGMENT_SPREAD
+  INLINE_FRAGMENT
+  VARIABLE_DEFINITION
+  SCHEMA
+  SCALAR
+  OBJECT
+  FIELD_DEFINITION
+  ARGUMENT_DEFINITION
+  INTERFACE
+  UNION
+  ENUM
+  ENUM_VALUE
+  INPUT_OBJECT
+  INPUT_FIELD_DEFINITION
+}
+
+directive @skip(if: Boolean!) on FIELD | FRAGMENT_SPREAD | INLINE_FRAGMENT
+directive @include(if: Boolean!) on FIELD | FRAGMENT_SPREAD | INLINE_FRAGMENT
+directive @deprecated(reason: String = "No longer supported") on FIELD_DEFINITION | ARGUMENT_DEFINITION | INPUT_FIELD_DEFINITION | ENUM_VALUE
+directive @specifiedBy(url: String!) on SCALAR
+"#;
+
+const DEFAULT_DEPRECATION_REASON: &str = "No longer supported";
+
+fn builtin_schema() -> &'static ParsedGraphQLSchema {
+  static BUILTIN_SCHEMA: OnceLock<ParsedGraphQLSchema> = OnceLock::new();
+
+  // @expected: the built-in SDL is static and valid
+  BUILTIN_SCHEMA.get_or_init(|| parse_graphql_schema(BUILTIN_SDL).unwrap())
+}
+
+/// Checks if the operation selects introspection fields (`__schema` or `__type`) at its root.
+pub fn has_introspection_fields(request: &ParsedGraphQLRequest) -> bool {
+  root_field_names(request)
+    .iter()
+    .any(|name| *name == "__schema" || *name == "__type")
+}
+
+/// Checks if all root fields of the operation can be resolved by `execute_introspection`.
+pub fn is_introspection_only(request: &ParsedGraphQLRequest) -> bool {
+  let names = root_field_names(request);
+
+  !names.is_empty()
+    && names
+      .iter()
+      .all(|name| matches!(*name, "__schema" | "__type" | "__typename"))
+}
+
+/// The names of the root fields selected by the operation, including the ones selected through fragments.
+fn root_field_names(request: &ParsedGraphQLRequest) -> Vec<&str> {
+  let Some(selection_set) = query_selection_set(request) else {
+    return vec![];
+  };
+
+  let mut names = vec![];
+  let mut pending = vec![selection_set];
+  let mut visited_fragments = HashSet::new();
+
+  while let Some(selection_set) = pending.pop() {
+    for selection in &selection_set.items {
+      match selection {
+        Selection::Field(field) => names.push(field.name.as_str()),
+        Selection::InlineFragment(fragment) => pending.push(&fragment.selection_set),
+        Selection::FragmentSpread(spread) => {
+          if !visited_fragments.insert(spread.fragment_name.as_str()) {
+            continue;
+          }
+
+          if let Some(fragment) = find_fragment(request, &spread.fragment_name) {
+            pending.push(&fragment.selection_set);
+          }
+        }
+      }
+    }
+  }
+
+  names
+}
+
+fn query_selection_set(request: &ParsedGraphQLRequest) -> Option<&SelectionSet<'static, String>> {
+  match request.executable_operation() {
+    Some(QueryDefinition::Operation(OperationDefinition::Query(query))) => {
+      Some(&query.selection_set)
+    }
+    Some(QueryDefinition::Operation(OperationDefinition::SelectionSet(selection_set))) => {
+      Some(selection_set)
+    }
+    _ => None,
+  }
+}
+
+fn find_fragment<'a>(
+  request: &'a ParsedGraphQLRequest,
+  name: &str,
+) -> Option<&'a FragmentDefinition<'static, String>> {
+  request
+    .parsed_operation
+    .definitions
+    .iter()
+    .find_map(|definition| match definition {
+      QueryDefinition::Fragment(fragment) if fragment.name == name => Some(fragment),
+      _ => None,
+    })
+}
+
+/// Builds a copy of the request without the `__schema` and `__type` root fields, so the other root fields of an
+/// operation that mixes both can be executed by the source, while the introspection fields are resolved locally.
+///
+/// Fragments and variables that are no longer used by the operation are removed as well.
+pub fn remove_introspection_fields(request: &ParsedGraphQLRequest) -> ParsedGraphQLRequest {
+  let mut document = request.parsed_operation.clone();
+  let operation_index = request.executable_operation().and_then(|operation| {
+    request
+      .parsed_operation
+      .definitions
+      .iter()
+      .position(|definition| std::ptr::eq(definition, operation))
+  });
+
+  // Fragments spread at the root of the operation select root fields too, and a fragment left without fields
+  // can't be spread anymore, so we repeat until nothing changes.
+  let mut root_fragments = HashSet::new();
+  let mut empty_fragments = HashSet::new();
+
+  loop {
+    let counts_before = (root_fragments.len(), empty_fragments.len());
+
+    for (index, definition) in document.definitions.iter_mut().enumerate() {
+      match definition {
+        QueryDefinition::Operation(operation) if Some(index) == operation_index => {
+          if let Some(selection_set) = query_selection_set_mut(operation) {
+            remove_root_introspection_fields(selection_set, &mut root_fragments, &empty_fragments);
+          }
+        }
+        QueryDefinition::Fragment(fragment) if root_fragments.contains(&fragment.name) => {
+          remove_root_introspection_fields(
+            &mut fragment.selection_set,
+            &mut root_fragments,
+            &empty_fragments,
+          );
+
+          if fragment.selection_set.items.is_empty() {
+            empty_fragments.insert(fragment.name.clone());
+          }
+        }
+        _ => {}
+      }
+    }
+
+    if (root_fragments.len(), empty_fragments.len()) == counts_before {
+      break;
+    }
+  }
+
+  remove_unused_definitions(&mut document, operation_index);
+
+  ParsedGraphQLRequest {
+    request: GraphQLRequest {
+      operation: document.to_string(),
+      ..request.request.clone()
+    },
+    parsed_operation: document,
+  }
+}
+
+fn query_selection_set_mut<'a>(
+  operation: &'a mut OperationDefinition<'static, String>,
+) -> Option<&'a mut SelectionSet<'static, String>> {
+  match operation {
+    OperationDefinition::Query(query) => Some(&mut query.selection_set),
+    OperationDefinition::SelectionSet(selection_set) => Some(selection_set),
+    _ => None,
+  }
+}
+
+fn remove_root_introspection_fields(
+  selection_set: &mut SelectionSet<'static, String>,
+  root_fragments: &mut HashSet<String>,
+  empty_fragments: &HashSet<String>,
+) {
+  selection_set.items.retain_mut(|selection| match selection {
+    Selection::Field(field) => !matches!(field.name.as_str(), "__schema" | "__type"),
+    Selection::InlineFragment(fragment) => {
+      remove_root_introspection_fields(
+        &mut fragment.selection_set,
+        root_fragments,
+        empty_fragments,
+      );
+
+      !fragment.selection_set.items.is_empty()
+    }
+    Selection::FragmentSpread(spread) => {
+      root_fragments.insert(spread.fragment_name.clone());
+
+      !empty_fragments.contains(&spread.fragment_name)
+    }
+  });
+}
+
+/// Removes the fragments that are not spread by any operation, and the variables not used by the executed operation.
+fn remove_unused_definitions(document: &mut ParsedGraphQLDocument, operation_index: Option<usize>) {
+  let fragments = document
+    .definitions
+    .iter()
+    .filter_map(|definition| match definition {
+      QueryDefinition::Fragment(fragment) => Some((fragment.name.clone(), fragment)),
+      _ => None,
+    })
+    .collect::<HashMap<_, _>>();
+  let mut used_fragments = HashSet::new();
+  let mut operation_variables = HashSet::new();
+
+  for (index, definition) in document.definitions.iter().enumerate() {
+    let QueryDefinition::Operation(operation) = definition else {
+      continue;
+    };
+
+    let mut variables = HashSet::new();
+    let (directives, selection_set) = match operation {
+      OperationDefinition::Query(query) => (&query.directives[..], &query.selection_set),
+      OperationDefinition::Mutation(mutation) => {
+        (&mutation.directives[..], &mutation.selection_set)
+      }
+      OperationDefinition::Subscription(subscription) => {
+        (&subscription.directives[..], &subscription.selection_set)
+      }
+      OperationDefinition::SelectionSet(selection_set) => (&[][..], selection_set),
+    };
+    let mut pending = vec![];
+    collect_directive_variables(directives, &mut variables);
+    collect_usages(selection_set, &mut pending, &mut variables);
+
+    let mut visited = HashSet::new();
+
+    while let Some(name) = pending.pop() {
+      if !visited.insert(name.clone()) {
+        continue;
+      }
+
+      if let Some(fragment) = fragments.get(&name) {
+        collect_directive_variables(&fragment.directives, &mut variables);
+        collect_usages(&fragment.selection_set, &mut pending, &mut variables);
+      }
+    }
+
+    used_fragments.extend(visited);
+
+    if Some(index) == operation_index {
+      operation_variables = variables;
+    }
+  }
+
+  document
+    .definitions
+    .retain_mut(|definition| match definition {
+      QueryDefinition::Fragment(fragment) => used_fragments.contains(&fragment.name),
+      QueryDefinition::Operation(OperationDefinition::Query(query)) => {
+        query
+          .variable_definitions
+          .retain(|variable| operation_variables.contains(&variable.name));
+
+        true
+      }
+      QueryDefinition::Operation(_) => true,
+    });
+}
+
+/// Collects the fragments spread and the variables used in a selection set.
+fn collect_usages(
+  selection_set: &SelectionSet<'static, String>,
+  fragments: &mut Vec<String>,
+  variables: &mut HashSet<String>,
+) {
+  for selection in &selection_set.items {
+    match selection {
+      Selection::Field(field) => {
+        for (_, value) in &field.arguments {
+          collect_value_variables(value, variables);
+        }
+
+        collect_directive_variables(&field.directives, variables);
+        collect_usages(&field.selection_set, fragments, variables);
+      }
+      Selection::InlineFragment(fragment) => {
+        collect_directive_variables(&fragment.directives, variables);
+        collect_usages(&fragment.selection_set, fragments, variables);
+      }
+      Selection::FragmentSpread(spread) => {
+        collect_directive_variables(&spread.directives, variables);
+        fragments.push(spread.fragment_name.clone());
+      }
+    }
+  }
+}
+
+fn collect_directive_variables(
+  directives: &[Directive<'static, String>],
+  variables: &mut HashSet<String>,
+) {
+  for directive in directives {
+    for (_, value) in &directive.arguments {
+      collect_value_variables(value, variables);
+    }
+  }
+}
+
+fn collect_value_variables(value: &GraphQLValue<'static, String>, variables: &mut HashSet<String>) {
+  match value {
+    GraphQLValue::Variable(name) => {
+      variables.insert(name.clone());
+    }
+    GraphQLValue::List(items) => {
+      for item in items {
+        collect_value_variables(item, variables);
+      }
+    }
+    GraphQLValue::Object(fields) => {
+      for item in fields.values() {
+        collect_value_variables(item, variables);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// Adds the root fields resolved by `execute_introspection` to the response of the source, for an operation that was
+/// split with `remove_introspection_fields`.
+pub fn merge_introspection_response(
+  response: &mut GraphQLResponse,
+  introspection: GraphQLResponse,
+) {
+  if let (Some(Value::Object(data)), Some(Value::Object(introspection_data))) =
+    (response.data.as_mut(), introspection.data)
+  {
+    // The fields executed by the source are already set, the others are the introspection fields.
+    for (key, value) in introspection_data {
+      data.entry(key).or_insert(value);
+    }
+  }
+
+  if let Some(errors) = introspection.errors {
+    response.errors.get_or_insert_with(Vec::new).extend(errors);
+  }
+}
+
+/// Resolves the introspection fields (`__schema`, `__type` and `__typename`) of a query, based on the given schema.
+///
+/// Other root fields are resolved as `null`, so this should only be used when `is_introspection_only` is true.
+pub fn execute_introspection(
+  schema: &ParsedGraphQLSchema,
+  request: &ParsedGraphQLRequest,
+) -> GraphQLResponse {
+  let Some(selection_set) = query_selection_set(request) else {
+    return GraphQLResponse::new_error("introspection is only supported for query operations");
+  };
+
+  let executor = IntrospectionExecutor::new(schema, request);
+  let root_type_name = executor.query_type.unwrap_or("Query");
+  let mut data = Map::new();
+
+  for (key, fields) in executor.collect_fields(root_type_name, &[selection_set]) {
+    let field = fields[0];
+    let selection_sets = fields.iter().map(|f| &f.selection_set).collect::<Vec<_>>();
+
+    let value = match field.name.as_str() {
+      "__typename" => Value::String(root_type_name.to_string()),
+      "__schema" => executor.resolve_object(Node::Schema, &selection_sets),
+      "__type" => executor.resolve_optional(
+        executor
+          .string_argument(field, "name")
+          .and_then(|name| executor.named_type(&name)),
+        &selection_sets,
+      ),
+      _ => Value::Null,
+    };
+
+    data.insert(key, value);
+  }
+
+  GraphQLResponse::new_data(Value::Object(data))
+}
+
+/// An object of the introspection schema.
+#[derive(Clone, Copy)]
+enum Node<'a> {
+  Schema,
+  NamedType(&'a SchemaTypeDefinition),
+  /// A `LIST` or `NON_NULL` type.
+  WrappingType(&'a SchemaType),
+  Field(&'a Field<'static, String>),
+  InputValue(&'a InputValue<'static, String>),
+  EnumValue(&'a EnumValue<'static, String>),
+  Directive(&'a DirectiveDefinition<'static, String>),
+}
+
+impl Node<'_> {
+  fn typename(&self) -> &'static str {
+    match self {
+      Node::Schema => "__Schema",
+      Node::NamedType(_) | Node::WrappingType(_) => "__Type",
+      Node::Field(_) => "__Field",
+      Node::InputValue(_) => "__InputValue",
+      Node::EnumValue(_) => "__EnumValue",
+      Node::Directive(_) => "__Directive",
+    }
+  }
+}
+
+struct IntrospectionExecutor<'a> {
+  types: Vec<&'a SchemaTypeDefinition>,
+  types_by_name: HashMap<&'a str, &'a SchemaTypeDefinition>,
+  directives: Vec<&'a DirectiveDefinition<'static, String>>,
+  request: &'a ParsedGraphQLRequest,
+  query_type: Option<&'a str>,
+  mutation_type: Option<&'a str>,
+  subscription_type: Option<&'a str>,
+}
+
+impl<'a> IntrospectionExecutor<'a> {
+  fn new(schema: &'a ParsedGraphQLSchema, request: &'a ParsedGraphQLRequest) -> Self {
+    let mut types: Vec<&'a SchemaTypeDefinition> = vec![];
+    let mut types_by_name = HashMap::new();
+    let mut directives: Vec<&'a DirectiveDefinition<'static, String>> = vec![];
+    let mut schema_definition = None;
+
+    // The types and directives declared in the schema take precedence over the built-in ones.
+    for definition in schema
+      .definitions
+      .iter()
+      .chain(builtin_schema().definitions.iter())
+    {
+      match definition {
+        Definition::TypeDefinition(t) => {
+          if !types_by_name.contains_key(type_name(t)) {
+            types_by_name.insert(type_name(t), t);
+            types.push(t);
+          }
+        }
+        Definition::DirectiveDefinition(d) => {
+          if !directives.iter().any(|existing| existing.name == d.name) {
+            directives.push(d);
+          }
+        }
+        Definition::SchemaDefinition(s) => {
+          schema_definition.get_or_insert(s);
+        }
+        Definition::TypeExtension(_) => {}
+      }
+    }
+
+    let root_type = |declared: Option<&'a String>, default_name: &'static str| match declared {
+      Some(name) => Some(name.as_str()),
+      None => types_by_name
+        .contains_key(default_name)
+        .then_some(default_name),
+    };
+
+    Self {
+      query_type: root_type(schema_definition.and_then(|s| s.query.as_ref()), "Query"),
+      mutation_type: root_type(
+        schema_definition.and_then(|s| s.mutation.as_ref()),
+        "Mutation",
+      ),
+      subscription_type: root_type(
+        schema_definition.and_then(|s| s.subscription.as_ref()),
+        "Subscription",
+      ),
+      types,
+      types_by_name,
+      directives,
+      request,
+    }
+  }
+
+  fn named_type(&self, name: &str) -> Option<Node<'a>> {
+    self.types_by_name.get(name).copied().map(Node::NamedType)
+  }
+
+  fn type_node(&self, t: &'a SchemaType) -> Option<Node<'a>> {
+    match t {
+      Type::NamedType(name) => self.named_type(name),
+      Type::ListType(_) | Type::NonNullType(_) => Some(Node::WrappingType(t)),
+    }
+  }
+
+  /// Collects the fields of the selection sets by their response key, following fragments that apply to `type_name`.
+  fn collect_fields(
+    &self,
+    type_name: &str,
+    selection_sets: &[&'a SelectionSet<'static, String>],
+  ) -> Vec<(String, Vec<&'a QueryField<'static, String>>)> {
+    let mut result = vec![];
+    let mut visited_fragments = HashSet::new();
+
+    for selection_set in selection_sets {
+      self.collect_fields_into(
+        type_name,
+        selection_set,
+        &mut visited_fragments,
+        &mut result,
+      );
+    }
+
+    result
+  }
+
+  fn collect_fields_into(
+    &self,
+    type_name: &str,
+    selection_set: &'a SelectionSet<'static, String>,
+    visited_fragments: &mut HashSet<&'a str>,
+    result: &mut Vec<(String, Vec<&'a QueryField<'static, String>>)>,
+  ) {
+    for selection in &selection_set.items {
+      match selection {
+        Selection::Field(field) if self.is_included(&field.directives) => {
+          let key = field.alias.as_ref().unwrap_or(&field.name);
+
+          match result.iter_mut().find(|(k, _)| k == key) {
+            Some((_, fields)) => fields.push(field),
+            None => result.push((key.clone(), vec![field])),
+          }
+        }
+        Selection::InlineFragment(fragment)
+          if self.is_included(&fragment.directives)
+            && applies_to(fragment.type_condition.as_ref(), type_name) =>
+        {
+          self.collect_fields_into(
+            type_name,
+            &fragment.selection_set,
+            visited_fragments,
+            result,
+          );
+        }
+        Selection::FragmentSpread(spread)
+          if self.is_included(&spread.directives)
+            && visited_fragments.insert(spread.fragment_name.as_str()) =>
+        {
+          if let Some(fragment) = find_fragment(self.request, &spread.fragment_name) {
+            if applies_to(Some(&fragment.type_condition), type_name) {
+              self.collect_fields_into(
+                type_name,
+                &fragment.selection_set,
+                visited_fragments,
+                result,
+              );
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  fn resolve_object(
+    &self,
+    node: Node<'a>,
+    selection_sets: &[&'a SelectionSet<'static, String>],
+  ) -> Value {
+    let mut result = Map::new();
+
+    for (key, fields) in self.collect_fields(node.typename(), selection_sets) {
+      let field = fields[0];
+      let selection_sets = fields.iter().map(|f| &f.selection_set).collect::<Vec<_>>();
+
+      result.insert(key, self.resolve_field(node, field, &selection_sets));
+    }
+
+    Value::Object(result)
+  }
+
+  fn resolve_optional(
+    &self,
+    node: Option<Node<'a>>,
+    selection_sets: &[&'a SelectionSet<'static, String>],
+  ) -> Value {
+    node
+      .map(|node| self.resolve_object(node, selection_sets))
+      .unwrap_or(Value::Null)
+  }
+
+  fn resolve_list(
+    &self,
+    nodes: impl Iterator<Item = Node<'a>>,
+    selection_sets: &[&'a SelectionSet<'static, String>],
+  ) -> Value {
+    Value::Array(
+      nodes
+        .map(|node| self.resolve_object(node, selection_sets))
+        .collect(),
+    )
+  }
+
+  fn resolve_field(
+    &self,
+    node: Node<'a>,
+    field: &'a QueryField<'static, String>,
+    selection_sets: &[&'a SelectionSet<'static, String>],
+  ) -> Value {
+    if field.name == "__typename" {
+      return Value::String(node.typename().to_string());
+    }
+
+    let include_deprecated = self.bool_argument(field, "includeDeprecated");
+
+    match (node, field.name.as_str()) {
+      (Node::Schema, "types") => self.resolve_list(
+        self.types.iter().copied().map(Node::NamedType),
+        selection_sets,
+      ),
+      (Node::Schema, "queryType") => self.resolve_optional(
+        self.query_type.and_then(|name| self.named_type(name)),
+        selection_sets,
+      ),
+      (Node::Schema, "mutationType") => self.resolve_optional(
+        self.mutation_type.and_then(|name| self.named_type(name)),
+        selection_sets,
+      ),
+      (Node::Schema, "subscriptionType") => self.resolve_optional(
+        self
+          .subscription_type
+          .and_then(|name| self.named_type(name)),
+        selection_sets,
+      ),
+      (Node::Schema, "directives") => self.resolve_list(
+        self.directives.iter().copied().map(Node::Directive),
+        selection_sets,
+      ),
+      (Node::NamedType(t), _) => {
+        self.resolve_type_field(t, field, include_deprecated, selection_sets)
+      }
+      (Node::WrappingType(t), "kind") => match t {
+        Type::ListType(_) => Value::from("LIST"),
+        _ => Value::from("NON_NULL"),
+      },
+      (Node::WrappingType(t), "ofType") => match t {
+        Type::ListType(inner) | Type::NonNullType(inner) => {
+          self.resolve_optional(self.type_node(inner), selection_sets)
+        }
+        Type::NamedType(_) => Value::Null,
+      },
+      (Node::Field(f), "name") => Value::from(f.name.as_str()),
+      (Node::Field(f), "description") => optional_string(&f.description),
+      (Node::Field(f), "args") => self.resolve_list(
+        f.arguments
+          .iter()
+          .filter(|arg| include_deprecated || deprecation_reason(&arg.directives).is_none())
+          .map(Node::InputValue),
+        selection_sets,
+      ),
+      (Node::Field(f), "type") => {
+        self.resolve_optional(self.type_node(&f.field_type), selection_sets)
+      }
+      (Node::Field(f), "isDeprecated") => Value::Bool(deprecation_reason(&f.directives).is_some()),
+      (Node::Field(f), "deprecationReason") => deprecation_reason(&f.directives).into(),
+      (Node::InputValue(v), "name") => Value::from(v.name.as_str()),
+      (Node::InputValue(v), "description") => optional_string(&v.description),
+      (Node::InputValue(v), "type") => {
+        self.resolve_optional(self.type_node(&v.value_type), selection_sets)
+      }
+      (Node::InputValue(v), "defaultValue") => v
+        .default_value
+        .as_ref()
+        .map(|value| Value::String(value.to_string()))
+        .unwrap_or(Value::Null),
+      (Node::InputValue(v), "isDeprecated") => {
+        Value::Bool(deprecation_reason(&v.directives).is_some())
+      }
+      (Node::InputValue(v), "deprecationReason") => deprecation_reason(&v.directives).into(),
+      (Node::EnumValue(v), "name") => Value::from(v.name.as_str()),
+      (Node::EnumValue(v), "description") => optional_string(&v.description),
+      (Node::EnumValue(v), "isDeprecated") => {
+        Value::Bool(deprecation_reason(&v.directives).is_some())
+      }
+      (Node::EnumValue(v), "deprecationReason") => deprecation_reason(&v.directives).into(),
+      (Node::Directive(d), "name") => Value::from(d.name.as_str()),
+      (Node::Directive(d), "description") => optional_string(&d.description),
+      (Node::Directive(d), "locations") => Value::Array(
+        d.locations
+          .iter()
+          .map(|location| Value::from(location.as_str()))
+          .collect(),
+      ),
+      (Node::Directive(d), "args") => self.resolve_list(
+        d.arguments
+          .iter()
+          .filter(|arg| include_deprecated || deprecation_reason(&arg.directives).is_none())
+          .map(Node::InputValue),
+        selection_sets,
+      ),
+      (Node::Directive(d), "isRepeatable") => Value::Bool(d.repeatable),
+      _ => Value::Null,
+    }
+  }
+
+  fn resolve_type_field(
+    &self,
+    t: &'a SchemaTypeDefinition,
+    field: &'a QueryField<'static, String>,
+    include_deprecated: bool,
+    selection_sets: &[&'a SelectionSet<'static, String>],
+  ) -> Value {
+    let fields = |fields: &'a [Field<'static, String>]| {
+      self.resolve_list(
+        fields
+          .iter()
+          .filter(|f| !f.name.starts_with("__"))
+          .filter(|f| include_deprecated || deprecation_reason(&f.directives).is_none())
+          .map(Node::Field),
+        selection_sets,
+      )
+    };
+    let named_types = |names: &'a [String]| {
+      self.resolve_list(
+        names.iter().filter_map(|name| self.named_type(name)),
+        selection_sets,
+      )
+    };
+
+    match (t, field.name.as_str()) {
+      (_, "kind") => Value::from(type_kind(t)),
+      (_, "name") => Value::from(type_name(t)),
+      (_, "description") => optional_string(type_description(t)),
+      (TypeDefinition::Scalar(s), "specifiedByURL") => s
+        .directives
+        .iter()
+        .find(|d| d.name == "specifiedBy")
+        .and_then(|d| string_value(&d.arguments, "url"))
+        .map(Value::String)
+        .unwrap_or(Value::Null),
+      (TypeDefinition::Object(o), "fields") => fields(&o.fields),
+      (TypeDefinition::Interface(i), "fields") => fields(&i.fields),
+      (TypeDefinition::Object(o), "interfaces") => named_types(&o.implements_interfaces),
+      (TypeDefinition::Interface(i), "interfaces") => named_types(&i.implements_interfaces),
+      (TypeDefinition::Interface(i), "possibleTypes") => self.resolve_list(
+        self
+          .types
+          .iter()
+          .filter(|candidate| match candidate {
+            TypeDefinition::Object(o) => o.implements_interfaces.contains(&i.name),
+            _ => false,
+          })
+          .copied()
+          .map(Node::NamedType),
+        selection_sets,
+      ),
+      (TypeDefinition::Union(u), "possibleTypes") => named_types(&u.types),
+      (TypeDefinition::Enum(e), "enumValues") => self.resolve_list(
+        e.values
+          .iter()
+          .filter(|v| include_deprecated || deprecation_reason(&v.directives).is_none())
+          .map(Node::EnumValue),
+        selection_sets,
+      ),
+      (TypeDefinition::InputObject(i), "inputFields") => self.resolve_list(
+        i.fields
+          .iter()
+          .filter(|f| include_deprecated || deprecation_reason(&f.directives).is_none())
+          .map(Node::InputValue),
+        selection_sets,
+      ),
+      (TypeDefinition::InputObject(i), "isOneOf") => {
+        Value::Bool(i.directives.iter().any(|d| d.name == "oneOf"))
+      }
+      _ => Value::Null,
+    }
+  }
+
+  /// Checks the `@skip` and `@include` directives of a selection.
+  fn is_included(&self, directives: &[Directive<'static, String>]) -> bool {
+    directives.iter().all(|directive| {
+      let condition = directive
+        .arguments
+        .iter()
+        .find(|(name, _)| name == "if")
+        .and_then(|(_, value)| self.resolve_value(value))
+        .and_then(|value| value.as_bool());
+
+      match directive.name.as_str() {
+        "skip" => condition != Some(true),
+        "include" => condition != Some(false),
+        _ => true,
+      }
+    })
+  }
+
+  fn bool_argument(&self, field: &QueryField<'static, String>, name: &str) -> bool {
+    field
+      .arguments
+      .iter()
+      .find(|(arg_name, _)| arg_name == name)
+      .and_then(|(_, value)| self.resolve_value(value))
+      .and_then(|value| value.as_bool())
+      .unwrap_or(false)
+  }
+
+  fn string_argument(&self, field: &QueryField<'static, String>, name: &str) -> Option<String> {
+    field
+      .arguments
+      .iter()
+      .find(|(arg_name, _)| arg_name == name)
+      .and_then(|(_, value)| self.resolve_value(value))
+      .and_then(|value| value.as_str().map(String::from))
+  }
+
+  /// Converts an argument value to JSON, using the request variables for variable references.
+  fn resolve_value(&self, value: &GraphQLValue<'static, String>) -> Option<Value> {
+    match value {
+      GraphQLValue::Variable(name) => self
+        .request
+        .request
+        .variables
+        .as_ref()
+        .and_then(|variables| variables.get(name))
+        .cloned(),
+      GraphQLValue::Boolean(b) => Some(Value::Bool(*b)),
+      GraphQLValue::String(s) | GraphQLValue::Enum(s) => Some(Value::String(s.clone())),
+      _ => None,
+    }
+  }
+}
+
+fn applies_to(type_condition: Option<&TypeCondition<'static, String>>, type_name: &str) -> bool {
+  match type_condition {
+    Some(TypeCondition::On(name)) => name == type_name,
+    None => true,
+  }
+}
+
+fn type_name(t: &SchemaTypeDefinition) -> &str {
+  match t {
+    TypeDefinition::Scalar(t) => &t.name,
+    TypeDefinition::Object(t) => &t.name,
+    TypeDefinition::Interface(t) => &t.name,
+    TypeDefinition::Union(t) => &t.name,
+    TypeDefinition::Enum(t) => &t.name,
+    TypeDefinition::InputObject(t) => &t.name,
+  }
+}
+
+fn type_description(t: &SchemaTypeDefinition) -> &Option<String> {
+  match t {
+    TypeDefinition::Scalar(t) => &t.description,
+    TypeDefinition::Object(t) => &t.description,
+    TypeDefinition::Interface(t) => &t.description,
+    TypeDefinition::Union(t) => &t.description,
+    TypeDefinition::Enum(t) => &t.description,
+    TypeDefinition::InputObject(t) => &t.description,
+  }
+}
+
+fn type_kind(t: &SchemaTypeDefinition) -> &'static str {
+  match t {
+    TypeDefinition::Scalar(_) => "SCALAR",
+    TypeDefinition::Object(_) => "OBJECT",
+    TypeDefinition::Interface(_) => "INTERFACE",
+    TypeDefinition::Union(_) => "UNION",
+    TypeDefinition::Enum(_) => "ENUM",
+    TypeDefinition::InputObject(_) => "INPUT_OBJECT",
+  }
+}
+
+fn optional_string(value: &Option<String>) -> Value {
+  value
+    .as_ref()
+    .map(|v| Value::String(v.clone()))
+    .unwrap_or(Value::Null)
+}
+
+fn string_value(
+  arguments: &[(String, GraphQLValue<'static, String>)],
+  name: &str,
+) -> Option<String> {
+  arguments.iter().find_map(|(arg_name, value)| match value {
+    GraphQLValue::String(s) if arg_name == name => Some(s.clone()),
+    _ => None,
+  })
+}
+
+fn deprecation_reason(directives: &[Directive<'static, String>]) -> Option<String> {
+  directives.iter().find(|d| d.name == "deprecated").map(|d| {
+    string_value(&d.arguments, "reason").unwrap_or_else(|| DEFAULT_DEPRECATION_REASON.to_string())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use conductor_common::graphql::{GraphQLRequest, ParsedGraphQLRequest};
+  use serde_json::json;
+
+  use super::*;
+
+  fn execute(sdl: &str, operation: &str) -> Value {
+    let schema = parse_graphql_schema(sdl).unwrap();
+    let request = ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
+      operation: operation.to_string(),
+      operation_name: None,
+      variables: None,
+      extensions: None,
+    })
+    .unwrap();
+
+    execute_introspection(&schema, &request).data.unwrap()
+  }
+
+  #[test]
+  fn should_resolve_types_with_fragments_and_aliases() {
+    let data = execute(
+      r#"
+        type Query { me: User, old: String @deprecated(reason: "use me") }
+        type User { id: ID! }
+      "#,
+      r#"
+        query {
+          __typename
+          schema: __schema { queryType { name } mutationType { name } }
+          __type(name: "Query") { ...TypeFields }
+        }
+
+        fragment TypeFields on __Type {
+          kind
+          fields { name type { kind ofType { name } } }
+          all: fields(includeDeprecated: true) { name isDeprecated deprecationReason }
+        }
+      "#,
+    );
+
+    assert_eq!(
+      data,
+      json!({
+        "__typename": "Query",
+        "schema": { "queryType": { "name": "Query" }, "mutationType": null },
+        "__type": {
+          "kind": "OBJECT",
+          "fields": [
+            { "name": "me", "type": { "kind": "OBJECT", "ofType": null } }
+          ],
+          "all": [
+            { "name": "me", "isDeprecated": false, "deprecationReason": null },
+            { "name": "old", "isDeprecated": true, "deprecationReason": "use me" }
+          ]
+        }
+      })
+    );
+  }
+
+  #[test]
+  fn should_include_builtin_types() {
+    let data = execute(
+      "type Query { id: ID }",
+      "{ __schema { types { name } directives { name } } }",
+    );
+    let names = |key: &str| {
+      data["__schema"][key]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>()
+    };
+
+    assert!(names("types").contains(&"String".to_string()));
+    assert!(names("types").contains(&"__Schema".to_string()));
+    assert_eq!(
+      names("directives"),
+      vec!["skip", "include", "deprecated", "specifiedBy"]
+    );
+  }
+
+  #[test]
+  fn should_split_mixed_operations() {
+    let request = ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
+      operation: r#"
+        query ($id: ID!, $name: String!) {
+          ...Root
+          user(id: $id) { name }
+        }
+
+        fragment Root on Query {
+          __type(name: $name) { name }
+          ... on Query { __schema { queryType { name } } }
+        }
+      "#
+      .to_string(),
+      operation_name: None,
+      variables: None,
+      extensions: None,
+    })
+    .unwrap();
+
+    let source_request = remove_introspection_fields(&request);
+    let operation = source_request
+      .request
+      .operation
+      .split_whitespace()
+      .collect::<Vec<_>>()
+      .join(" ");
+
+    assert_eq!(operation, "query($id: ID!) { user(id: $id) { name } }");
+
+    let mut response = GraphQLResponse::new_data(json!({ "user": { "name": "Dotan" } }));
+    merge_introspection_response(
+      &mut response,
+      GraphQLResponse::new_data(
+        json!({ "__type": null, "__schema": { "queryType": { "name": "Query" } } }),
+      ),
+    );
+
+    assert_eq!(
+      response.data,
+      Some(json!({
+        "user": { "name": "Dotan" },
+        "__type": null,
+        "__schema": { "queryType": { "name": "Query" } }
+      }))
+    );
+  }
+}
@@ -1,4 +1,10 @@
 pub mod gateway;
+pub mod http_client;
+pub mod introspection;
+pub mod openapi;
 pub mod plugin_manager;
+pub mod readiness;
 pub mod schema_awareness;
+pub mod schema_filter;
+pub mod schema_merge;
 pub mod source;
@@ -0,0 +1,679 @@
+use std::collections::{HashMap, HashSet};
+
+use conductor_common::{
+  graphql::{parse_graphql_schema, ParsedGraphQLSchema},
+  http::Method,
+};
+use serde_json::Value;
+
+#[derive(thiserror::Error, Debug)]
+pub enum OpenApiError {
+  #[error("failed to parse OpenAPI document: {0}")]
+  InvalidDocument(serde_yaml::Error),
+  #[error("unsupported OpenAPI version \"{0}\", only OpenAPI 3 documents are supported")]
+  UnsupportedVersion(String),
+  #[error("the OpenAPI document does not describe any GET operation, at least one is required to build the Query type")]
+  MissingQueryOperations,
+  #[error("failed to parse the generated GraphQL schema: {0}")]
+  InvalidGeneratedSchema(String),
+}
+
+const HTTP_METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+/// The argument that receives the JSON request body of an operation.
+pub const BODY_ARGUMENT: &str = "input";
+/// The scalar used for values that can't be described with GraphQL types (free-form objects, `oneOf`, ...).
+const JSON_SCALAR: &str = "JSON";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterLocation {
+  Path,
+  Query,
+  Header,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenApiParameter {
+  /// The name of the parameter in the OpenAPI document.
+  pub name: String,
+  /// The name of the GraphQL argument.
+  pub argument: String,
+  pub location: ParameterLocation,
+}
+
+/// The HTTP operation that resolves a root field.
+#[derive(Debug, Clone)]
+pub struct OpenApiOperation {
+  pub method: Method,
+  /// The path template of the operation, for example `/users/{id}`.
+  pub path: String,
+  pub parameters: Vec<OpenApiParameter>,
+  /// The named type of the `input` argument, when the operation has a JSON request body.
+  pub body_type: Option<String>,
+  /// The named type returned by the field.
+  pub type_name: String,
+}
+
+/// A field of a generated object or input type, and the JSON property it maps to.
+#[derive(Debug, Clone)]
+pub struct ObjectField {
+  pub property: String,
+  pub type_name: String,
+}
+
+/// A GraphQL schema generated from an OpenAPI document.
+#[derive(Debug)]
+pub struct OpenApiSchema {
+  pub schema: ParsedGraphQLSchema,
+  pub sdl: String,
+  /// The URL of the first entry of the `servers` field of the document.
+  pub server_url: Option<String>,
+  /// The operation of each root field, by root type and field name.
+  operations: HashMap<(String, String), OpenApiOperation>,
+  /// The fields of the generated object and input types, by type and field name.
+  fields: HashMap<String, HashMap<String, ObjectField>>,
+}
+
+impl OpenApiSchema {
+  pub fn operation(&self, root_type: &str, field_name: &str) -> Option<&OpenApiOperation> {
+    self
+      .operations
+      .get(&(root_type.to_string(), field_name.to_string()))
+  }
+
+  pub fn object_field(&self, type_name: &str, field_name: &str) -> Option<&ObjectField> {
+    self
+      .fields
+      .get(type_name)
+      .and_then(|fields| fields.get(field_name))
+  }
+}
+
+/// Generates a GraphQL schema from an OpenAPI 3 document (JSON or YAML).
+///
+/// `GET` operations are exposed as `Query` fields, and other operations as `Mutation` fields, named after their
+/// `operationId`. Path, query and header parameters are exposed as arguments, and the JSON request body as the `input`
+/// argument. The schemas of the document are exposed as object types (and input types, with an `Input` suffix).
+pub fn build_openapi_schema(document: &str) -> Result<OpenApiSchema, OpenApiError> {
+  let document: Value = serde_yaml::from_str(document).map_err(OpenApiError::InvalidDocument)?;
+  let version = document
+    .get("openapi")
+    .and_then(Value::as_str)
+    .unwrap_or_default();
+
+  if !version.starts_with("3.") {
+    return Err(OpenApiError::UnsupportedVersion(version.to_string()));
+  }
+
+  let mut builder = SchemaBuilder {
+    document: &document,
+    definitions: vec![],
+    generated: HashSet::new(),
+    fields: HashMap::new(),
+  };
+  let mut root_fields: HashMap<&str, Vec<String>> = HashMap::new();
+  let mut operations = HashMap::new();
+  let empty = serde_json::Map::new();
+
+  for (path, path_item) in document
+    .get("paths")
+    .and_then(Value::as_object)
+    .unwrap_or(&empty)
+  {
+    let path_item = builder.resolve(path_item);
+
+    for method in HTTP_METHODS {
+      let Some(operation) = path_item.get(method) else {
+        continue;
+      };
+
+      let root_type = if method == "get" { "Query" } else { "Mutation" };
+      let field_name = operation
+        .get("operationId")
+        .and_then(Value::as_str)
+        .map(sanitize_name)
+        .unwrap_or_else(|| derived_field_name(method, path));
+
+      if operations.contains_key(&(root_type.to_string(), field_name.clone())) {
+        tracing::warn!(
+          "OpenAPI operation \"{} {}\" is skipped, field \"{}\" is already defined",
+          method.to_uppercase(),
+          path,
+          field_name
+        );
+        continue;
+      }
+
+      let mut arguments = vec![];
+      let mut parameters = vec![];
+
+      // Parameters declared on the path apply to all of its operations.
+      for parameter in [path_item.get("parameters"), operation.get("parameters")]
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_array)
+        .flatten()
+      {
+        let parameter = builder.resolve(parameter);
+        let Some(name) = parameter.get("name").and_then(Value::as_str) else {
+          continue;
+        };
+        let location = match parameter.get("in").and_then(Value::as_str) {
+          Some("path") => ParameterLocation::Path,
+          Some("query") => ParameterLocation::Query,
+          Some("header") => ParameterLocation::Header,
+          _ => continue,
+        };
+        let argument = sanitize_name(name);
+        let is_required = location == ParameterLocation::Path
+          || parameter
+            .get("required")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let (argument_type, _) = builder.type_reference(
+          parameter.get("schema").unwrap_or(&Value::Null),
+          &format!("{}{}", pascal_case(&field_name), pascal_case(&argument)),
+          true,
+        );
+
+        arguments.push(format!(
+          "{}{}: {}{}",
+          description(parameter),
+          argument,
+          argument_type,
+          if is_required { "!" } else { "" }
+        ));
+        parameters.push(OpenApiParameter {
+          name: name.to_string(),
+          argument,
+          location,
+        });
+      }
+
+      let request_body = operation
+        .get("requestBody")
+        .map(|body| builder.resolve(body));
+      let body_type = match request_body.and_then(json_schema) {
+        Some(schema) => {
+          let (body_type, body_type_name) =
+            builder.type_reference(schema, &format!("{}Input", pascal_case(&field_name)), true);
+          let is_required = request_body
+            .and_then(|body| body.get("required"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+          arguments.push(format!(
+            "{}: {}{}",
+            BODY_ARGUMENT,
+            body_type,
+            if is_required { "!" } else { "" }
+          ));
+
+          Some(body_type_name)
+        }
+        None => None,
+      };
+
+      let (field_type, type_name) = match success_response_schema(&builder, operation) {
+        Some(schema) => builder.type_reference(
+          schema,
+          &format!("{}Response", pascal_case(&field_name)),
+          false,
+        ),
+        None => (JSON_SCALAR.to_string(), JSON_SCALAR.to_string()),
+      };
+
+      root_fields.entry(root_type).or_default().push(format!(
+        "{}{}{}: {}",
+        description(operation),
+        field_name,
+        if arguments.is_empty() {
+          "".to_string()
+        } else {
+          format!("({})", arguments.join(", "))
+        },
+        field_type
+      ));
+
+      operations.insert(
+        (root_type.to_string(), field_name),
+        OpenApiOperation {
+          method: match method {
+            "post" => Method::POST,
+            "put" => Method::PUT,
+            "patch" => Method::PATCH,
+            "delete" => Method::DELETE,
+            _ => Method::GET,
+          },
+          path: path.to_string(),
+          parameters,
+          body_type,
+          type_name,
+        },
+      );
+    }
+  }
+
+  if !root_fields.contains_key("Query") {
+    return Err(OpenApiError::MissingQueryOperations);
+  }
+
+  let mut sdl = format!("scalar {}\n", JSON_SCALAR);
+
+  for root_type in ["Query", "Mutation"] {
+    if let Some(fields) = root_fields.get(root_type) {
+      sdl.push_str(&format!(
+        "\ntype {} {{\n  {}\n}}\n",
+        root_type,
+        fields.join("\n  ")
+      ));
+    }
+  }
+
+  for definition in &builder.definitions {
+    sdl.push_str(&format!("\n{}\n", definition));
+  }
+
+  let schema =
+    parse_graphql_schema(&sdl).map_err(|e| OpenApiError::InvalidGeneratedSchema(e.to_string()))?;
+
+  Ok(OpenApiSchema {
+    schema,
+    sdl,
+    server_url: document
+      .pointer("/servers/0/url")
+      .and_then(Value::as_str)
+      .map(String::from),
+    operations,
+    fields: builder.fields,
+  })
+}
+
+struct SchemaBuilder<'a> {
+  document: &'a Value,
+  /// The SDL of the generated object, input and enum types.
+  definitions: Vec<String>,
+  generated: HashSet<String>,
+  fields: HashMap<String, HashMap<String, ObjectField>>,
+}
+
+impl<'a> SchemaBuilder<'a> {
+  /// Follows the `$ref` pointers of a value, within the document.
+  fn resolve(&self, value: &'a Value) -> &'a Value {
+    let mut value = value;
+
+    // Limits the number of followed pointers, in case of a cycle.
+    for _ in 0..32 {
+      let target = value
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| self.document.pointer(pointer));
+
+      match target {
+        Some(target) => value = target,
+        None => break,
+      }
+    }
+
+    value
+  }
+
+  /// Returns the GraphQL type reference for a JSON schema, and its named type, generating the types it needs.
+  ///
+  /// Schemas declared under `components` are named after their key, inline schemas are named after `name_hint`.
+  fn type_reference(
+    &mut self,
+    schema: &'a Value,
+    name_hint: &str,
+    input: bool,
+  ) -> (String, String) {
+    let mut name = schema
+      .get("$ref")
+      .and_then(Value::as_str)
+      .and_then(|reference| reference.rsplit('/').next())
+      .map(pascal_case)
+      .unwrap_or_else(|| name_hint.to_string());
+    let schema = self.resolve(schema);
+
+    if input && !name.ends_with("Input") {
+      name.push_str("Input");
+    }
+
+    let schema_type = match schema.get("type") {
+      Some(Value::String(schema_type)) => Some(schema_type.as_str()),
+      // OpenAPI 3.1 declares nullable types as `["string", "null"]`.
+      Some(Value::Array(types)) => types
+        .iter()
+        .filter_map(Value::as_str)
+        .find(|schema_type| *schema_type != "null"),
+      _ => None,
+    };
+
+    let named_type = match schema_type {
+      Some("array") => {
+        let (item_type, item_type_name) = self.type_reference(
+          schema.get("items").unwrap_or(&Value::Null),
+          &format!("{}Item", name.trim_end_matches("Input")),
+          input,
+        );
+
+        return (format!("[{}]", item_type), item_type_name);
+      }
+      Some("integer") => "Int".to_string(),
+      Some("number") => "Float".to_string(),
+      Some("boolean") => "Boolean".to_string(),
+      Some("string") => match schema.get("enum").and_then(Value::as_array) {
+        Some(values) if self.enum_type(name.trim_end_matches("Input"), values) => {
+          name.trim_end_matches("Input").to_string()
+        }
+        _ => "String".to_string(),
+      },
+      Some("object") | None if !self.properties(schema).is_empty() => {
+        self.object_type(&name, schema, input);
+        name
+      }
+      _ => JSON_SCALAR.to_string(),
+    };
+
+    (named_type.clone(), named_type)
+  }
+
+  /// The properties of an object schema, including the ones of its `allOf` schemas.
+  fn properties(&self, schema: &'a Value) -> Vec<(&'a str, &'a Value, bool)> {
+    let schema = self.resolve(schema);
+    let mut properties: Vec<(&'a str, &'a Value, bool)> = vec![];
+
+    for part in schema
+      .get("allOf")
+      .and_then(Value::as_array)
+      .into_iter()
+      .flatten()
+    {
+      properties.extend(self.properties(part));
+    }
+
+    let required = schema
+      .get("required")
+      .and_then(Value::as_array)
+      .map(|required| {
+        required
+          .iter()
+          .filter_map(Value::as_str)
+          .collect::<Vec<_>>()
+      })
+      .unwrap_or_default();
+
+    for (name, property) in schema
+      .get("properties")
+      .and_then(Value::as_object)
+      .into_iter()
+      .flatten()
+    {
+      if !properties.iter().any(|(existing, _, _)| existing == name) {
+        properties.push((name, property, required.contains(&name.as_str())));
+      }
+    }
+
+    properties
+  }
+
+  fn object_type(&mut self, name: &str, schema: &'a Value, input: bool) {
+    if !self.generated.insert(name.to_string()) {
+      return;
+    }
+
+    let mut fields = vec![];
+    let mut object_fields = HashMap::new();
+
+    for (property, property_schema, is_required) in self.properties(schema) {
+      let field_name = sanitize_name(property);
+      let (field_type, field_type_name) = self.type_reference(
+        property_schema,
+        &format!(
+          "{}{}",
+          name.trim_end_matches("Input"),
+          pascal_case(&field_name)
+        ),
+        input,
+      );
+
+      // Output fields are nullable, since REST APIs often omit properties that are declared as required.
+      fields.push(format!(
+        "{}{}: {}{}",
+        description(self.resolve(property_schema)),
+        field_name,
+        field_type,
+        if input && is_required { "!" } else { "" }
+      ));
+      object_fields.insert(
+        field_name,
+        ObjectField {
+          property: property.to_string(),
+          type_name: field_type_name,
+        },
+      );
+    }
+
+    self.definitions.push(format!(
+      "{}{} {} {{\n  {}\n}}",
+      description(schema),
+      if input { "input" } else { "type" },
+      name,
+      fields.join("\n  ")
+    ));
+    self.fields.insert(name.to_string(), object_fields);
+  }
+
+  /// Generates an enum type if all values are valid GraphQL enum values.
+  fn enum_type(&mut self, name: &str, values: &[Value]) -> bool {
+    let values = values.iter().filter_map(Value::as_str).collect::<Vec<_>>();
+    let is_valid = !values.is_empty()
+      && values.iter().all(|value| {
+        sanitize_name(value) == *value && !matches!(*value, "true" | "false" | "null")
+      });
+
+    if is_valid && self.generated.insert(name.to_string()) {
+      self
+        .definitions
+        .push(format!("enum {} {{\n  {}\n}}", name, values.join("\n  ")));
+    }
+
+    is_valid
+  }
+}
+
+/// The schema of the `application/json` content of a request body or a response.
+fn json_schema(value: &Value) -> Option<&Value> {
+  value
+    .get("content")
+    .and_then(Value::as_object)?
+    .iter()
+    .find(|(content_type, _)| content_type.starts_with("application/json"))
+    .and_then(|(_, content)| content.get("schema"))
+}
+
+/// The JSON schema of the first successful response of an operation.
+fn success_response_schema<'a>(
+  builder: &SchemaBuilder<'a>,
+  operation: &'a Value,
+) -> Option<&'a Value> {
+  let responses = operation.get("responses").and_then(Value::as_object)?;
+
+  responses
+    .iter()
+    .filter(|(status, _)| status.starts_with('2'))
+    .chain(responses.iter().filter(|(status, _)| *status == "default"))
+    .find_map(|(_, response)| json_schema(builder.resolve(response)))
+}
+
+fn description(value: &Value) -> String {
+  value
+    .get("description")
+    .or_else(|| value.get("summary"))
+    .and_then(Value::as_str)
+    .map(|description| format!("{} ", Value::from(description)))
+    .unwrap_or_default()
+}
+
+/// Replaces the characters that are not allowed in GraphQL names.
+fn sanitize_name(name: &str) -> String {
+  let name = name
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+    .collect::<String>();
+
+  match name.chars().next() {
+    Some(c) if !c.is_ascii_digit() => name,
+    _ => format!("_{}", name),
+  }
+}
+
+fn pascal_case(name: &str) -> String {
+  let name = name
+    .split(|c: char| !c.is_ascii_alphanumeric())
+    .map(|part| {
+      let mut chars = part.chars();
+
+      match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+      }
+    })
+    .collect::<String>();
+
+  sanitize_name(&name)
+}
+
+/// The field name of an operation without `operationId`, for example `get_users_by_id` for `GET /users/{id}`.
+fn derived_field_name(method: &str, path: &str) -> String {
+  let parts = std::iter::once(method.to_string())
+    .chain(
+      path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| match segment.strip_prefix('{') {
+          Some(parameter) => format!("by_{}", parameter.trim_end_matches('}')),
+          None => segment.to_string(),
+        }),
+    )
+    .collect::<Vec<_>>();
+
+  sanitize_name(&parts.join("_"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const DOCUMENT: &str = r##"
+openapi: 3.0.3
+servers:
+  - url: https://api.example.com/v1
+paths:
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        schema:
+          type: integer
+    get:
+      operationId: getPet
+      summary: Returns a pet
+      responses:
+        "200":
+          description: The pet
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+  /pets:
+    get:
+      parameters:
+        - name: status
+          in: query
+          schema:
+            type: string
+            enum: [available, sold]
+      responses:
+        "200":
+          description: The pets
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: "#/components/schemas/Pet"
+    post:
+      operationId: createPet
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Pet"
+      responses:
+        "201":
+          description: The created pet
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        id:
+          type: integer
+        name:
+          type: string
+        photo-urls:
+          type: array
+          items:
+            type: string
+"##;
+
+  #[test]
+  fn should_build_schema_from_openapi_document() {
+    let schema = build_openapi_schema(DOCUMENT).unwrap();
+
+    assert_eq!(
+      schema.server_url.as_deref(),
+      Some("https://api.example.com/v1")
+    );
+
+    let get_pet = schema.operation("Query", "getPet").unwrap();
+    assert_eq!(get_pet.method, Method::GET);
+    assert_eq!(get_pet.path, "/pets/{petId}");
+    assert_eq!(get_pet.parameters[0].location, ParameterLocation::Path);
+    assert_eq!(get_pet.type_name, "Pet");
+
+    let list_pets = schema.operation("Query", "get_pets").unwrap();
+    assert_eq!(list_pets.parameters[0].location, ParameterLocation::Query);
+
+    let create_pet = schema.operation("Mutation", "createPet").unwrap();
+    assert_eq!(create_pet.method, Method::POST);
+    assert_eq!(create_pet.body_type.as_deref(), Some("PetInput"));
+
+    assert_eq!(
+      schema.object_field("Pet", "photo_urls").unwrap().property,
+      "photo-urls"
+    );
+    assert!(schema.sdl.contains("getPet(petId: Int!): Pet"));
+    assert!(schema
+      .sdl
+      .contains("get_pets(status: GetPetsStatus): [Pet]"));
+    assert!(schema.sdl.contains("createPet(input: PetInput!): Pet"));
+    assert!(schema.sdl.contains("input PetInput {"));
+    assert!(schema.sdl.contains("name: String!"));
+  }
+
+  #[test]
+  fn should_reject_swagger_documents() {
+    assert!(matches!(
+      build_openapi_schema(r#"{ "swagger": "2.0", "paths": {} }"#),
+      Err(OpenApiError::UnsupportedVersion(_))
+    ));
+  }
+}
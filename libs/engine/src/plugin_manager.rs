@@ -6,20 +6,25 @@ use conductor_common::{
   http::{ConductorHttpRequest, ConductorHttpResponse},
   plugin::{CreatablePlugin, Plugin, PluginError},
   plugin_manager::PluginManager,
+  schema_diff::SchemaDiff,
   source::SourceRuntime,
 };
-use conductor_config::PluginDefinition;
+use conductor_config::{PluginDefinition, PropagationConfig};
 use conductor_tracing::fastrace_mgr::FastraceManager;
 use reqwest::Response;
 
 #[derive(Debug, Default)]
 pub struct PluginManagerImpl {
   plugins: Vec<Box<dyn Plugin>>,
+  propagation: PropagationConfig,
 }
 
 impl PluginManagerImpl {
   pub fn new_from_vec(plugins: Vec<Box<dyn Plugin>>) -> Self {
-    let mut pm = Self { plugins };
+    let mut pm = Self {
+      plugins,
+      propagation: Default::default(),
+    };
 
     // We want to make sure to register default plugins last, in order to ensure it's setting the value correctly
     for p in PluginManagerImpl::default_plugins() {
@@ -35,10 +40,14 @@ impl PluginManagerImpl {
 
   pub async fn new(
     plugins_config: &Option<Vec<PluginDefinition>>,
+    propagation: PropagationConfig,
     tracing_manager: &mut FastraceManager,
     tenant_id: u32,
   ) -> Result<Self, PluginError> {
-    let mut instance = PluginManagerImpl::default();
+    let mut instance = PluginManagerImpl {
+      plugins: vec![],
+      propagation,
+    };
 
     if let Some(config_defs) = plugins_config {
       for plugin_def in config_defs.iter() {
@@ -102,6 +111,19 @@ impl PluginManagerImpl {
 
             plugin
           }
+          PluginDefinition::UsageReportingPlugin {
+            enabled: Some(true),
+            config,
+          } => Self::create_plugin::<usage_reporting_plugin::Plugin>(config.clone()).await?,
+          PluginDefinition::ClientAwarenessPlugin {
+            enabled: Some(true),
+            config,
+          } => {
+            Self::create_plugin::<client_awareness_plugin::Plugin>(
+              config.clone().unwrap_or_default(),
+            )
+            .await?
+          }
           // In case plugin is not enabled, we are skipping it. Also when we don't have a match, so watch out for this one if you add a new plugin.
           _ => continue,
         };
@@ -218,6 +240,9 @@ impl PluginManager for PluginManagerImpl {
     ctx: &mut RequestExecutionContext,
     request: &mut ConductorHttpRequest,
   ) {
+    // Correlation headers are added first, so plugins are able to override them.
+    self.propagation.inject_upstream_headers(ctx, request);
+
     let p = &self.plugins;
 
     for plugin in p.iter() {
@@ -250,4 +275,25 @@ impl PluginManager for PluginManagerImpl {
       }
     }
   }
+
+  #[tracing::instrument(level = "debug", skip(self, diff), name = "on_schema_change")]
+  fn on_schema_change(&self, source_id: &str, diff: &SchemaDiff) {
+    for plugin in self.plugins.iter() {
+      plugin.on_schema_change(source_id, diff);
+    }
+  }
+
+  #[tracing::instrument(level = "debug", skip(self), name = "on_flush")]
+  async fn on_flush(&self) {
+    for plugin in self.plugins.iter() {
+      plugin.on_flush().await;
+    }
+  }
+
+  #[tracing::instrument(level = "debug", skip(self), name = "on_shutdown")]
+  async fn on_shutdown(&self) {
+    for plugin in self.plugins.iter() {
+      plugin.on_shutdown().await;
+    }
+  }
 }
@@ -0,0 +1,165 @@
+use std::{
+  collections::HashSet,
+  time::{Duration, SystemTime},
+};
+
+use conductor_common::source::SchemaHealth;
+use serde::Serialize;
+
+use crate::gateway::ConductorGateway;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceReadinessState {
+  /// The source doesn't load a schema, so it's always ready.
+  NoSchema,
+  /// The schema was never loaded successfully.
+  Loading,
+  /// The schema is loaded, and the last attempt to reload it (if any) succeeded or is still within the staleness window.
+  Ready,
+  /// The last attempts to reload the schema failed, and the schema in use is older than the staleness window.
+  Stale,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceReadiness {
+  pub id: String,
+  pub state: SourceReadinessState,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub schema_hash: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub last_loaded_at: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+  pub ready: bool,
+  #[serde(skip_serializing_if = "std::ops::Not::not")]
+  pub shutting_down: bool,
+  pub sources: Vec<SourceReadiness>,
+}
+
+/// Checks the state of all sources used by the gateway endpoints, including the sources combined by a merge source.
+///
+/// The gateway is ready once every source that loads a schema has loaded it successfully. When `max_staleness` is set,
+/// a source whose last reload failed becomes unready once its schema is older than `max_staleness`.
+pub fn check_readiness(
+  gateway: &ConductorGateway,
+  max_staleness: Option<Duration>,
+  now: SystemTime,
+) -> ReadinessReport {
+  let mut seen: HashSet<String> = HashSet::new();
+  let mut sources = vec![];
+
+  for route in &gateway.routes {
+    let source = &route.route_data.to;
+    let merged = source.merged_sources();
+
+    for source in std::iter::once(source).chain(merged.iter()) {
+      if !seen.insert(source.name().to_string()) {
+        continue;
+      }
+
+      let health = source.schema_health();
+      sources.push(SourceReadiness {
+        id: source.name().to_string(),
+        state: source_state(health.as_ref(), max_staleness, now),
+        schema_hash: health.as_ref().and_then(|h| h.schema_hash.clone()),
+        last_loaded_at: health
+          .as_ref()
+          .and_then(|h| h.last_loaded_at)
+          .map(|t| humantime::format_rfc3339_seconds(t).to_string()),
+        last_error: health.and_then(|h| h.last_error),
+      });
+    }
+  }
+
+  ReadinessReport {
+    ready: sources.iter().all(|s| {
+      matches!(
+        s.state,
+        SourceReadinessState::Ready | SourceReadinessState::NoSchema
+      )
+    }),
+    shutting_down: false,
+    sources,
+  }
+}
+
+fn source_state(
+  health: Option<&SchemaHealth>,
+  max_staleness: Option<Duration>,
+  now: SystemTime,
+) -> SourceReadinessState {
+  let health = match health {
+    Some(health) => health,
+    None => return SourceReadinessState::NoSchema,
+  };
+
+  if health.schema_hash.is_none() {
+    return SourceReadinessState::Loading;
+  }
+
+  let is_stale = match (&health.last_error, health.last_loaded_at, max_staleness) {
+    (Some(_), Some(last_loaded_at), Some(max_staleness)) => now
+      .duration_since(last_loaded_at)
+      .map(|age| age > max_staleness)
+      .unwrap_or(false),
+    _ => false,
+  };
+
+  if is_stale {
+    SourceReadinessState::Stale
+  } else {
+    SourceReadinessState::Ready
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn loaded_at(now: SystemTime, age_secs: u64) -> SchemaHealth {
+    SchemaHealth {
+      schema_hash: Some("abc".to_string()),
+      last_loaded_at: Some(now - Duration::from_secs(age_secs)),
+      last_error: None,
+      last_failed_at: None,
+    }
+  }
+
+  #[test]
+  fn should_report_source_states() {
+    let now = SystemTime::now();
+    let max_staleness = Some(Duration::from_secs(60));
+
+    assert_eq!(
+      source_state(None, max_staleness, now),
+      SourceReadinessState::NoSchema
+    );
+    assert_eq!(
+      source_state(Some(&SchemaHealth::default()), max_staleness, now),
+      SourceReadinessState::Loading
+    );
+    assert_eq!(
+      source_state(Some(&loaded_at(now, 120)), max_staleness, now),
+      SourceReadinessState::Ready
+    );
+
+    let failing = SchemaHealth {
+      last_error: Some("failed to fetch remote schema".to_string()),
+      last_failed_at: Some(now),
+      ..loaded_at(now, 120)
+    };
+    assert_eq!(
+      source_state(Some(&failing), max_staleness, now),
+      SourceReadinessState::Stale
+    );
+    assert_eq!(
+      source_state(Some(&failing), None, now),
+      SourceReadinessState::Ready
+    );
+  }
+}
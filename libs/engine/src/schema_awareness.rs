@@ -1,19 +1,29 @@
 use std::{
   ops::Deref,
   sync::{Arc, RwLock, RwLockReadGuard},
+  time::SystemTime,
 };
 
 use conductor_common::{
   graphql::{parse_graphql_schema, GraphQLRequest, GraphQLResponse, ParsedGraphQLSchema},
-  http::HttpHeadersMap,
+  http::{HttpHeadersMap, Method},
   introspection::{introspection_to_sdl, IntrospectionQueryResponse, INTROSPECTION_QUERY},
-  parse_introspection_str, SchemaParseError,
+  kv::{resolve_key_value_store, KeyValueBinding, KeyValueStoreError},
+  normalize::hash_signature,
+  parse_introspection_str,
+  schema_diff::{diff_schemas, SchemaChangeKind},
+  source::{SchemaChangeListener, SchemaHealth},
+  SchemaParseError,
 };
 use conductor_config::{
   SchemaAwarenessConfig, SchemaAwarenessConfigOnError, SchemaAwarenessFormat, SchemaAwarenessSource,
 };
-use reqwest::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
-use wasm_polyfills::create_http_client;
+use reqwest::{
+  header::{HeaderValue, ACCEPT, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+  StatusCode,
+};
+
+use crate::http_client::create_upstream_http_client;
 
 #[derive(Debug)]
 pub struct SchemaAwarenessRecord<ProcessedValue> {
@@ -42,10 +52,19 @@ type ProcessorFn<ProcessedValue> =
 #[derive(Debug)]
 pub struct SchemaAwareness<ProcessedValue = ()> {
   schema: Arc<RwLock<Option<Arc<SchemaAwarenessRecord<ProcessedValue>>>>>,
+  health: Arc<RwLock<SchemaHealth>>,
+  listeners: Arc<SchemaChangeListeners>,
+  #[cfg(not(target_arch = "wasm32"))]
+  polling_task: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
+  /// Background tasks are not available on WASM runtime, so the schema is reloaded on demand, see `refresh_if_stale`.
+  #[cfg(target_arch = "wasm32")]
+  lazy_poller: Option<futures::lock::Mutex<LazyPoller<ProcessedValue>>>,
 }
 
 #[derive(thiserror::Error, Debug)]
 pub enum SchemaAwarenessError {
+  #[error("failed to create http client")]
+  FailedToCreateHttpClient { source: reqwest::Error },
   #[error("failed to fetch remote schema")]
   FailedToFetchRemoteSchema { source: reqwest::Error },
   #[error("failed to read remote body")]
@@ -56,6 +75,10 @@ pub enum SchemaAwarenessError {
   FailedToProcessSchema { source: anyhow::Error },
   #[error("failed to load introspection")]
   FailedToParseIntrospection { source: Option<serde_json::Error> },
+  #[error("failed to read schema from key-value store")]
+  FailedToReadKeyValueStore { source: KeyValueStoreError },
+  #[error("key '{key}' was not found in key-value store")]
+  MissingKeyValueEntry { key: String },
 }
 
 impl<ProcessedValue> SchemaAwareness<ProcessedValue>
@@ -68,137 +91,143 @@ where
     processor: ProcessorFn<ProcessedValue>,
   ) -> Result<Self, SchemaAwarenessError> {
     tracing::info!("Initializing schema awareness for source '{}'", source_id);
-    let initial_schema = match Self::load_schema(&config.format, &config.source, processor).await {
-      Ok(schema) => Some(Arc::new(schema)),
-      Err(e) => {
-        tracing::error!(
-          "Failed to load initial schema awareness for id '{}': {:?}",
-          source_id,
-          e
-        );
+    let mut health = SchemaHealth::default();
+    let mut remote_state = RemoteSchemaState::default();
+    let initial_schema =
+      match Self::load_schema(&config.format, &config.source, &mut remote_state, processor).await {
+        Ok(Some(schema)) => {
+          record_success(&mut health, &schema.raw);
+          #[cfg(not(target_arch = "wasm32"))]
+          write_cache_file(&source_id, &config.source, &schema.raw).await;
 
-        match config.on_error {
-          SchemaAwarenessConfigOnError::Ignore => {
-            tracing::error!(
-              "Failed to load schema awareness for source '{}'. Ignoring.",
-              source_id
-            );
+          Some(Arc::new(schema))
+        }
+        // There is no ETag before the first load, so the schema is always returned here.
+        Ok(None) => None,
+        Err(e) => {
+          tracing::error!(
+            "Failed to load initial schema awareness for id '{}': {:?}",
+            source_id,
+            e
+          );
 
-            None
-          }
-          SchemaAwarenessConfigOnError::Terminate => {
-            return Err(e);
+          #[cfg(not(target_arch = "wasm32"))]
+          let cached_schema =
+            Self::load_cache_file(&source_id, &config.source, &mut health, processor).await;
+          #[cfg(target_arch = "wasm32")]
+          let cached_schema = None;
+
+          record_failure(&mut health, &e);
+
+          match (cached_schema, &config.on_error) {
+            (Some(schema), _) => Some(Arc::new(schema)),
+            (None, SchemaAwarenessConfigOnError::Ignore) => {
+              tracing::error!(
+                "Failed to load schema awareness for source '{}'. Ignoring.",
+                source_id
+              );
+
+              None
+            }
+            (None, SchemaAwarenessConfigOnError::Terminate) => {
+              return Err(e);
+            }
           }
         }
-      }
-    };
+      };
 
-    let instance = Self {
-      schema: Arc::new(RwLock::new(initial_schema)),
-    };
-
-    if let Some(_polling_interval_duration) = config.polling_interval {
-      #[cfg(target_arch = "wasm32")]
-      {
-        tracing::error!(
-          "Schema awareness polling interval is not supported in WASM runtime, ignoring."
-        )
-      }
+    let schema = Arc::new(RwLock::new(initial_schema));
+    let health = Arc::new(RwLock::new(health));
+    let listeners: Arc<SchemaChangeListeners> = Default::default();
+    let poller = config.polling_interval.map(|polling_interval| {
+      (
+        polling_interval,
+        SchemaPoller {
+          source_id: source_id.clone(),
+          format: config.format.clone(),
+          source: config.source.clone(),
+          schema: Arc::downgrade(&schema),
+          health: health.clone(),
+          listeners: listeners.clone(),
+          remote_state,
+          processor,
+        },
+      )
+    });
 
+    Ok(Self {
+      schema,
+      health,
+      listeners,
       #[cfg(not(target_arch = "wasm32"))]
-      {
-        let source_id = source_id.clone();
-        let source = config.source.clone();
-        let format = config.format.clone();
-        let handle = instance.schema.clone();
-
-        tokio::spawn(async move {
-          Self::fetch_periodically(
-            source_id,
-            format,
-            source,
-            handle,
-            processor,
-            _polling_interval_duration,
-          )
-          .await;
-        });
-      }
-    }
-
-    Ok(instance)
+      polling_task: std::sync::Mutex::new(poller.map(|(polling_interval, poller)| {
+        tokio::spawn(poller.fetch_periodically(polling_interval))
+      })),
+      #[cfg(target_arch = "wasm32")]
+      lazy_poller: poller.map(|(polling_interval, poller)| {
+        futures::lock::Mutex::new(LazyPoller {
+          poller,
+          polling_interval,
+          next_refresh_at: None,
+        })
+      }),
+    })
   }
 
-  #[cfg(not(target_arch = "wasm32"))]
-  async fn fetch_periodically(
-    source_id: String,
-    format: SchemaAwarenessFormat,
-    source: SchemaAwarenessSource,
-    handle: Arc<RwLock<Option<Arc<SchemaAwarenessRecord<ProcessedValue>>>>>,
-    processor: ProcessorFn<ProcessedValue>,
-    duration: std::time::Duration,
-  ) {
-    let mut interval_timer = tokio::time::interval(duration);
+  /// Reloads the schema if the `polling_interval` elapsed since the last reload.
+  ///
+  /// This only has an effect on WASM runtime, where the schema can't be polled in a background task and is reloaded
+  /// while handling requests instead. `now` is provided by the caller, since the system clock is not available there.
+  pub async fn refresh_if_stale(&self, _now: SystemTime) {
+    #[cfg(target_arch = "wasm32")]
+    if let Some(lazy_poller) = &self.lazy_poller {
+      // Another request is already reloading the schema.
+      let Some(mut lazy_poller) = lazy_poller.try_lock() else {
+        return;
+      };
 
-    loop {
-      interval_timer.tick().await;
-      Self::load_and_update_schema(
-        source_id.clone(),
-        format.clone(),
-        source.clone(),
-        handle.clone(),
-        processor,
-      )
-      .await;
+      match lazy_poller.next_refresh_at {
+        Some(next_refresh_at) if _now < next_refresh_at => return,
+        // The first call starts the interval, since the schema was just loaded with the gateway.
+        None => {
+          lazy_poller.next_refresh_at = Some(_now + lazy_poller.polling_interval);
+          return;
+        }
+        Some(_) => {}
+      }
+
+      lazy_poller.next_refresh_at = Some(_now + lazy_poller.polling_interval);
+      lazy_poller
+        .poller
+        .load_and_update_schema(self.schema.clone())
+        .await;
     }
   }
 
-  #[cfg(not(target_arch = "wasm32"))]
-  async fn load_and_update_schema(
-    source_id: String,
-    format: SchemaAwarenessFormat,
-    source: SchemaAwarenessSource,
-    handle: Arc<RwLock<Option<Arc<SchemaAwarenessRecord<ProcessedValue>>>>>,
+  /// Loads the schema from a local source (`file` or `inline`) without performing any network calls.
+  ///
+  /// Returns `Ok(None)` for `remote` and `workers_kv` sources, since these can't be checked offline.
+  pub async fn load_offline(
+    format: &SchemaAwarenessFormat,
+    source: &SchemaAwarenessSource,
     processor: ProcessorFn<ProcessedValue>,
-  ) {
-    tracing::debug!(
-      "fetching schema awareness for source '{}', format: {:?} source config: {:?}",
-      source_id,
-      format,
-      source
-    );
-    let schema = Self::load_schema(&format, &source, processor).await;
-
-    match schema {
-      Ok(schema) => match handle.write() {
-        Ok(mut t) => {
-          tracing::debug!(
-            "successfully loaded schema awareness for source '{}', updating local record",
-            source_id
-          );
-          t.replace(Arc::new(schema));
-        }
-        Err(e) => {
-          tracing::error!(
-            "Failed to accquire lock for schema awareness for source '{}': {:?}",
-            source_id,
-            e
-          );
-        }
-      },
-      Err(e) => tracing::error!(
-        "Failed to load schema awareness for id '{}': {:?}",
-        source_id,
-        e
-      ),
-    };
+  ) -> Result<Option<SchemaAwarenessRecord<ProcessedValue>>, SchemaAwarenessError> {
+    match source {
+      SchemaAwarenessSource::Remote { .. } | SchemaAwarenessSource::WorkersKv { .. } => Ok(None),
+      _ => Self::load_schema(format, source, &mut RemoteSchemaState::default(), processor).await,
+    }
   }
 
+  /// Loads the schema from the configured source.
+  ///
+  /// Returns `Ok(None)` when the remote endpoint reports that the schema was not modified since the last load.
   async fn load_schema<'a>(
     format: &'a SchemaAwarenessFormat,
     source: &'a SchemaAwarenessSource,
+    remote_state: &mut RemoteSchemaState,
     processor: ProcessorFn<ProcessedValue>,
-  ) -> Result<SchemaAwarenessRecord<ProcessedValue>, SchemaAwarenessError> {
+  ) -> Result<Option<SchemaAwarenessRecord<ProcessedValue>>, SchemaAwarenessError> {
+    let mut etag = None;
     let result = match (format, source) {
       (SchemaAwarenessFormat::Sdl, SchemaAwarenessSource::File { file }) => {
         parse_graphql_schema(&file.contents).map(|schema| (file.contents.clone(), schema))
@@ -206,62 +235,30 @@ where
       (SchemaAwarenessFormat::Sdl, SchemaAwarenessSource::Inline { content }) => {
         parse_graphql_schema(&content).map(|schema| (content.clone(), schema))
       }
-      (
-        SchemaAwarenessFormat::Sdl,
-        SchemaAwarenessSource::Remote {
-          url,
-          headers,
-          method,
-        },
-      ) => {
-        let http_client = create_http_client().build().unwrap();
-        let res = http_client
-          .request(method.clone(), url)
-          .headers(headers.clone())
-          .send()
-          .await
-          .map_err(|source| SchemaAwarenessError::FailedToFetchRemoteSchema { source })?
-          .text()
-          .await
-          .map_err(|source| SchemaAwarenessError::FailedToReadRemoteBody { source })?;
+      (SchemaAwarenessFormat::Sdl, SchemaAwarenessSource::Remote { .. }) => {
+        let Some(response) = fetch_remote_schema(format, source, remote_state).await? else {
+          return Ok(None);
+        };
+        etag = response.etag;
 
-        parse_graphql_schema(&res).map(|schema| (res, schema))
+        parse_graphql_schema(&response.body).map(|schema| (response.body, schema))
       }
-      (
-        SchemaAwarenessFormat::Introspection,
-        SchemaAwarenessSource::Remote {
-          url,
-          headers,
-          method,
-        },
-      ) => {
-        let http_client = create_http_client().build().unwrap();
-        let mut headers = HttpHeadersMap::from(headers.clone());
-        headers
-          .entry(CONTENT_TYPE)
-          .or_insert(HeaderValue::from_static("application/json"));
-        headers
-          .entry(ACCEPT)
-          .or_insert(HeaderValue::from_static("application/json"));
-
-        let gql_response = http_client
-          .request(method.clone(), url)
-          .headers(headers.clone())
-          .body(
-            GraphQLRequest {
-              operation: INTROSPECTION_QUERY.to_string(),
-              operation_name: Some(String::from("IntrospectionQuery")),
-              extensions: None,
-              variables: None,
-            }
-            .to_string(),
-          )
-          .send()
-          .await
-          .map_err(|source| SchemaAwarenessError::FailedToFetchRemoteSchema { source })?
-          .json::<GraphQLResponse<IntrospectionQueryResponse>>()
-          .await
-          .map_err(|source| SchemaAwarenessError::FailedToReadRemoteBody { source })?;
+      (SchemaAwarenessFormat::Sdl, SchemaAwarenessSource::WorkersKv { binding, key }) => {
+        let content = read_key_value_schema(binding, key).await?;
+
+        parse_graphql_schema(&content).map(|schema| (content, schema))
+      }
+      (SchemaAwarenessFormat::Introspection, SchemaAwarenessSource::Remote { .. }) => {
+        let Some(response) = fetch_remote_schema(format, source, remote_state).await? else {
+          return Ok(None);
+        };
+        etag = response.etag;
+
+        let gql_response =
+          serde_json::from_str::<GraphQLResponse<IntrospectionQueryResponse>>(&response.body)
+            .map_err(|source| SchemaAwarenessError::FailedToParseIntrospection {
+              source: Some(source),
+            })?;
 
         match gql_response.data {
           None => return Err(SchemaAwarenessError::FailedToParseIntrospection { source: None }),
@@ -283,6 +280,18 @@ where
 
         Ok((as_sdl_obj.to_string(), as_sdl_obj))
       }
+      (SchemaAwarenessFormat::Introspection, SchemaAwarenessSource::WorkersKv { binding, key }) => {
+        let content = read_key_value_schema(binding, key).await?;
+        let introspection = parse_introspection_str(&content).map_err(|source| {
+          SchemaAwarenessError::FailedToParseIntrospection {
+            source: Some(source),
+          }
+        })?;
+
+        let as_sdl_obj = introspection_to_sdl(introspection);
+
+        Ok((as_sdl_obj.to_string(), as_sdl_obj))
+      }
       (SchemaAwarenessFormat::Introspection, SchemaAwarenessSource::Inline { content }) => {
         let introspection = parse_introspection_str(&content).map_err(|source| {
           SchemaAwarenessError::FailedToParseIntrospection {
@@ -300,11 +309,82 @@ where
     let processed = processor(&result.0, &result.1)
       .map_err(|source| SchemaAwarenessError::FailedToProcessSchema { source })?;
 
-    Ok(SchemaAwarenessRecord {
+    // The ETag is only kept once the schema was processed successfully, so an invalid schema is fetched again on the next attempt.
+    remote_state.etag = etag;
+
+    Ok(Some(SchemaAwarenessRecord {
       raw: Arc::new(result.0),
       schema: Arc::new(result.1),
       processed: Arc::new(processed),
-    })
+    }))
+  }
+
+  /// Loads the schema stored in the `cache_file` of a remote source, when the remote endpoints can't be reached.
+  #[cfg(not(target_arch = "wasm32"))]
+  async fn load_cache_file(
+    source_id: &str,
+    source: &SchemaAwarenessSource,
+    health: &mut SchemaHealth,
+    processor: ProcessorFn<ProcessedValue>,
+  ) -> Option<SchemaAwarenessRecord<ProcessedValue>> {
+    let SchemaAwarenessSource::Remote {
+      cache_file: Some(cache_file),
+      ..
+    } = source
+    else {
+      return None;
+    };
+
+    let content = match tokio::fs::read_to_string(cache_file).await {
+      Ok(content) => content,
+      Err(e) => {
+        tracing::error!(
+          "Failed to read schema cache file '{}' for source '{}': {}",
+          cache_file,
+          source_id,
+          e
+        );
+
+        return None;
+      }
+    };
+
+    // The cache file always contains SDL, even when the remote source uses introspection.
+    let record = Self::load_schema(
+      &SchemaAwarenessFormat::Sdl,
+      &SchemaAwarenessSource::Inline { content },
+      &mut RemoteSchemaState::default(),
+      processor,
+    )
+    .await;
+
+    match record {
+      Ok(Some(record)) => {
+        tracing::warn!(
+          "Using the schema stored in '{}' for source '{}'",
+          cache_file,
+          source_id
+        );
+        record_success(health, &record.raw);
+        health.last_loaded_at = tokio::fs::metadata(cache_file)
+          .await
+          .and_then(|metadata| metadata.modified())
+          .ok();
+
+        Some(record)
+      }
+      Ok(None) => None,
+      Err(e) => {
+        tracing::error!(
+          "Failed to load schema cache file '{}' for source '{}': {:?}",
+          cache_file,
+          source_id,
+          e
+        );
+
+        None
+      }
+    }
   }
 
   fn record(&self) -> RwLockReadGuard<Option<Arc<SchemaAwarenessRecord<ProcessedValue>>>> {
@@ -334,4 +414,626 @@ where
 
     None
   }
+
+  pub fn health(&self) -> SchemaHealth {
+    // @expected: the lock is never held across a panic
+    self.health.read().unwrap().clone()
+  }
+
+  /// Registers a listener that is called every time polling loads a schema that differs from the previous one, or loads
+  /// a schema after the initial load failed.
+  pub fn subscribe_changes(&self, listener: SchemaChangeListener) {
+    self.listeners.subscribe(listener);
+  }
+}
+
+impl<ProcessedValue> SchemaAwareness<ProcessedValue> {
+  /// Stops reloading the schema periodically. The schema that was loaded last remains available.
+  pub fn stop_polling(&self) {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(polling_task) = self
+      .polling_task
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .take()
+    {
+      polling_task.abort();
+    }
+  }
+}
+
+impl<ProcessedValue> Drop for SchemaAwareness<ProcessedValue> {
+  fn drop(&mut self) {
+    self.stop_polling();
+  }
+}
+
+/// Reloads the schema of a source, periodically in a background task, or on demand on WASM runtime.
+#[derive(Debug)]
+struct SchemaPoller<ProcessedValue> {
+  source_id: String,
+  format: SchemaAwarenessFormat,
+  source: SchemaAwarenessSource,
+  schema: std::sync::Weak<RwLock<Option<Arc<SchemaAwarenessRecord<ProcessedValue>>>>>,
+  health: Arc<RwLock<SchemaHealth>>,
+  listeners: Arc<SchemaChangeListeners>,
+  remote_state: RemoteSchemaState,
+  processor: ProcessorFn<ProcessedValue>,
+}
+
+impl<ProcessedValue> SchemaPoller<ProcessedValue>
+where
+  ProcessedValue: Send + Sync + 'static,
+{
+  #[cfg(not(target_arch = "wasm32"))]
+  async fn fetch_periodically(mut self, duration: std::time::Duration) {
+    let mut interval_timer = tokio::time::interval(duration);
+
+    loop {
+      interval_timer.tick().await;
+
+      // The source was dropped (for example, after a config reload), so we can stop polling.
+      let Some(handle) = self.schema.upgrade() else {
+        tracing::debug!(
+          "schema awareness for source '{}' was dropped, stopping polling",
+          self.source_id
+        );
+
+        return;
+      };
+
+      self.load_and_update_schema(handle).await;
+    }
+  }
+
+  async fn load_and_update_schema(
+    &mut self,
+    handle: Arc<RwLock<Option<Arc<SchemaAwarenessRecord<ProcessedValue>>>>>,
+  ) {
+    let source_id = &self.source_id;
+    let health = &self.health;
+
+    tracing::debug!(
+      "fetching schema awareness for source '{}', format: {:?} source config: {:?}",
+      source_id,
+      self.format,
+      self.source
+    );
+    let schema = SchemaAwareness::<ProcessedValue>::load_schema(
+      &self.format,
+      &self.source,
+      &mut self.remote_state,
+      self.processor,
+    )
+    .await;
+
+    match schema {
+      Ok(None) => {
+        tracing::debug!(
+          "schema awareness for source '{}' was not modified, keeping local record",
+          source_id
+        );
+        // @expected: the lock is never held across a panic
+        record_not_modified(&mut health.write().unwrap());
+      }
+      Ok(Some(schema)) => {
+        // The file is written before the lock is acquired, so the lock is not held across an await point.
+        #[cfg(not(target_arch = "wasm32"))]
+        write_cache_file(source_id, &self.source, &schema.raw).await;
+
+        match handle.write() {
+          Ok(mut t) => {
+            tracing::debug!(
+              "successfully loaded schema awareness for source '{}', updating local record",
+              source_id
+            );
+            // @expected: the lock is never held across a panic
+            record_success(&mut health.write().unwrap(), &schema.raw);
+
+            let current = Arc::new(schema);
+            let previous = t.replace(current.clone());
+            drop(t);
+
+            self.listeners.notify(
+              source_id,
+              previous.as_ref().map(|previous| previous.schema.as_ref()),
+              &current.schema,
+            );
+          }
+          Err(e) => {
+            tracing::error!(
+              "Failed to accquire lock for schema awareness for source '{}': {:?}",
+              source_id,
+              e
+            );
+          }
+        }
+      }
+      Err(e) => {
+        tracing::error!(
+          "Failed to load schema awareness for id '{}': {:?}",
+          source_id,
+          e
+        );
+        // @expected: the lock is never held across a panic
+        record_failure(&mut health.write().unwrap(), &e);
+      }
+    };
+  }
+}
+
+/// The state of the on-demand reloads of a schema, on WASM runtime.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug)]
+struct LazyPoller<ProcessedValue> {
+  poller: SchemaPoller<ProcessedValue>,
+  polling_interval: std::time::Duration,
+  /// When the schema should be reloaded next, set by the first call to `refresh_if_stale`.
+  next_refresh_at: Option<SystemTime>,
+}
+
+/// The listeners registered with `SchemaAwareness::subscribe_changes`.
+#[derive(Default)]
+struct SchemaChangeListeners(RwLock<Vec<SchemaChangeListener>>);
+
+impl std::fmt::Debug for SchemaChangeListeners {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("SchemaChangeListeners")
+      // @expected: the lock is never held across a panic
+      .field("count", &self.0.read().unwrap().len())
+      .finish()
+  }
+}
+
+impl SchemaChangeListeners {
+  fn subscribe(&self, listener: SchemaChangeListener) {
+    // @expected: the lock is never held across a panic
+    self.0.write().unwrap().push(listener);
+  }
+
+  /// Logs the changes between the previous and the current schema, and notifies the listeners if there are any.
+  ///
+  /// When there is no previous schema (the first load failed), the whole current schema is reported as added.
+  fn notify(
+    &self,
+    source_id: &str,
+    previous: Option<&ParsedGraphQLSchema>,
+    current: &ParsedGraphQLSchema,
+  ) {
+    let empty_schema = ParsedGraphQLSchema {
+      definitions: vec![],
+    };
+    let diff = diff_schemas(previous.unwrap_or(&empty_schema), current);
+
+    if diff.is_empty() {
+      tracing::debug!(
+        "schema of source '{}' was reloaded without changes",
+        source_id
+      );
+
+      return;
+    }
+
+    tracing::info!(
+      source_id = %source_id,
+      added = diff.count(SchemaChangeKind::Added),
+      removed = diff.count(SchemaChangeKind::Removed),
+      changed = diff.count(SchemaChangeKind::Changed),
+      breaking = diff.breaking_count(),
+      "schema of source '{}' changed",
+      source_id
+    );
+
+    for change in &diff.changes {
+      if change.breaking {
+        tracing::warn!("schema of source '{}': {}", source_id, change);
+      } else {
+        tracing::info!("schema of source '{}': {}", source_id, change);
+      }
+    }
+
+    // @expected: the lock is never held across a panic
+    let listeners = self.0.read().unwrap().clone();
+
+    for listener in listeners {
+      listener(source_id, &diff);
+    }
+  }
+}
+
+/// Reads the schema stored under `key` in the Workers KV namespace bound as `binding`.
+async fn read_key_value_schema(binding: &str, key: &str) -> Result<String, SchemaAwarenessError> {
+  let store = resolve_key_value_store(&KeyValueBinding::WorkersKv(binding.to_string()))
+    .map_err(|source| SchemaAwarenessError::FailedToReadKeyValueStore { source })?;
+
+  store
+    .get(key)
+    .await
+    .map_err(|source| SchemaAwarenessError::FailedToReadKeyValueStore { source })?
+    .ok_or_else(|| SchemaAwarenessError::MissingKeyValueEntry {
+      key: key.to_string(),
+    })
+}
+
+/// State kept between loads of a remote schema.
+#[derive(Debug, Default)]
+struct RemoteSchemaState {
+  etag: Option<RemoteSchemaEtag>,
+}
+
+#[derive(Debug)]
+struct RemoteSchemaEtag {
+  /// The endpoint that responded with the `ETag`, since mirrors might use different values.
+  url: String,
+  value: HeaderValue,
+}
+
+struct RemoteSchemaResponse {
+  body: String,
+  etag: Option<RemoteSchemaEtag>,
+}
+
+/// Fetches the schema from the `url` of a remote source, and then from its `mirrors` (in order) until one of them succeeds.
+///
+/// Returns `Ok(None)` when the endpoint responds with `304 Not Modified`.
+async fn fetch_remote_schema(
+  format: &SchemaAwarenessFormat,
+  source: &SchemaAwarenessSource,
+  remote_state: &RemoteSchemaState,
+) -> Result<Option<RemoteSchemaResponse>, SchemaAwarenessError> {
+  let SchemaAwarenessSource::Remote {
+    url,
+    mirrors,
+    headers,
+    method,
+    http_client,
+    ..
+  } = source
+  else {
+    return Ok(None);
+  };
+
+  let http_client = create_upstream_http_client(http_client.as_ref())
+    .map_err(|source| SchemaAwarenessError::FailedToCreateHttpClient { source })?;
+  let mut result =
+    fetch_remote_schema_from(&http_client, format, url, headers, method, remote_state).await;
+
+  for mirror in mirrors {
+    let Err(e) = &result else {
+      break;
+    };
+
+    tracing::warn!(
+      "Failed to load schema from '{}', trying mirror '{}': {:?}",
+      url,
+      mirror,
+      e
+    );
+    result =
+      fetch_remote_schema_from(&http_client, format, mirror, headers, method, remote_state).await;
+  }
+
+  result
+}
+
+async fn fetch_remote_schema_from(
+  http_client: &reqwest::Client,
+  format: &SchemaAwarenessFormat,
+  url: &str,
+  headers: &HttpHeadersMap,
+  method: &Method,
+  remote_state: &RemoteSchemaState,
+) -> Result<Option<RemoteSchemaResponse>, SchemaAwarenessError> {
+  let mut headers = headers.clone();
+  let mut request = http_client.request(method.clone(), url);
+
+  if let Some(etag) = remote_state.etag.as_ref().filter(|etag| etag.url == url) {
+    headers.insert(IF_NONE_MATCH, etag.value.clone());
+  }
+
+  if let SchemaAwarenessFormat::Introspection = format {
+    headers
+      .entry(CONTENT_TYPE)
+      .or_insert(HeaderValue::from_static("application/json"));
+    headers
+      .entry(ACCEPT)
+      .or_insert(HeaderValue::from_static("application/json"));
+    request = request.body(
+      GraphQLRequest {
+        operation: INTROSPECTION_QUERY.to_string(),
+        operation_name: Some(String::from("IntrospectionQuery")),
+        extensions: None,
+        variables: None,
+      }
+      .to_string(),
+    );
+  }
+
+  let response = request
+    .headers(headers)
+    .send()
+    .await
+    .map_err(|source| SchemaAwarenessError::FailedToFetchRemoteSchema { source })?;
+
+  if response.status() == StatusCode::NOT_MODIFIED {
+    return Ok(None);
+  }
+
+  let response = response
+    .error_for_status()
+    .map_err(|source| SchemaAwarenessError::FailedToFetchRemoteSchema { source })?;
+  let etag = response.headers().get(ETAG).map(|value| RemoteSchemaEtag {
+    url: url.to_string(),
+    value: value.clone(),
+  });
+  let body = response
+    .text()
+    .await
+    .map_err(|source| SchemaAwarenessError::FailedToReadRemoteBody { source })?;
+
+  Ok(Some(RemoteSchemaResponse { body, etag }))
+}
+
+/// Stores the last successfully loaded schema in the `cache_file` of a remote source.
+#[cfg(not(target_arch = "wasm32"))]
+async fn write_cache_file(source_id: &str, source: &SchemaAwarenessSource, raw: &str) {
+  if let SchemaAwarenessSource::Remote {
+    cache_file: Some(cache_file),
+    ..
+  } = source
+  {
+    if let Err(e) = tokio::fs::write(cache_file, raw).await {
+      tracing::error!(
+        "Failed to write schema cache file '{}' for source '{}': {}",
+        cache_file,
+        source_id,
+        e
+      );
+    }
+  }
+}
+
+fn record_success(health: &mut SchemaHealth, raw: &str) {
+  health.schema_hash = Some(hash_signature(raw));
+  health.last_loaded_at = now();
+  health.last_error = None;
+  health.last_failed_at = None;
+}
+
+fn record_not_modified(health: &mut SchemaHealth) {
+  health.last_loaded_at = now();
+  health.last_error = None;
+  health.last_failed_at = None;
+}
+
+fn record_failure(health: &mut SchemaHealth, error: &SchemaAwarenessError) {
+  health.last_error = Some(error.to_string());
+  health.last_failed_at = now();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> Option<SystemTime> {
+  Some(SystemTime::now())
+}
+
+// The system clock is not available on WASM runtime.
+#[cfg(target_arch = "wasm32")]
+fn now() -> Option<SystemTime> {
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use conductor_common::kv::{register_key_value_store, InMemoryKeyValueStore};
+  use httpmock::MockServer;
+
+  const SDL: &str = "type Query { products: [String] }";
+
+  fn remote_source(
+    url: String,
+    mirrors: Vec<String>,
+    cache_file: Option<String>,
+  ) -> SchemaAwarenessSource {
+    SchemaAwarenessSource::Remote {
+      url,
+      mirrors,
+      cache_file,
+      headers: HttpHeadersMap::new(),
+      method: Method::GET,
+      http_client: None,
+    }
+  }
+
+  async fn load_remote(
+    source: &SchemaAwarenessSource,
+    remote_state: &mut RemoteSchemaState,
+  ) -> Result<Option<SchemaAwarenessRecord<()>>, SchemaAwarenessError> {
+    SchemaAwareness::<()>::load_schema(&SchemaAwarenessFormat::Sdl, source, remote_state, |_, _| {
+      Ok(())
+    })
+    .await
+  }
+
+  fn load_from_kv(
+    binding: &str,
+    key: &str,
+  ) -> Result<Option<SchemaAwarenessRecord<()>>, SchemaAwarenessError> {
+    futures::executor::block_on(SchemaAwareness::<()>::load_schema(
+      &SchemaAwarenessFormat::Sdl,
+      &SchemaAwarenessSource::WorkersKv {
+        binding: binding.to_string(),
+        key: key.to_string(),
+      },
+      &mut RemoteSchemaState::default(),
+      |_, _| Ok(()),
+    ))
+  }
+
+  #[test]
+  fn should_load_schema_from_workers_kv() {
+    register_key_value_store(
+      KeyValueBinding::WorkersKv("SCHEMAS".to_string()),
+      Arc::new(InMemoryKeyValueStore::with_values([(
+        "products".to_string(),
+        "type Query { products: [String] }".to_string(),
+      )])),
+    );
+
+    let record = load_from_kv("SCHEMAS", "products").unwrap().unwrap();
+    assert!(record.raw().contains("products: [String]"));
+
+    assert!(matches!(
+      load_from_kv("SCHEMAS", "reviews"),
+      Err(SchemaAwarenessError::MissingKeyValueEntry { .. })
+    ));
+    assert!(matches!(
+      load_from_kv("MISSING_BINDING", "products"),
+      Err(SchemaAwarenessError::FailedToReadKeyValueStore { .. })
+    ));
+  }
+
+  #[tokio::test]
+  async fn should_skip_schema_that_was_not_modified() {
+    let server = MockServer::start_async().await;
+    let initial = server
+      .mock_async(|when, then| {
+        when.path("/schema").header_missing("if-none-match");
+        then.status(200).header("etag", "\"v1\"").body(SDL);
+      })
+      .await;
+    let not_modified = server
+      .mock_async(|when, then| {
+        when.path("/schema").header("if-none-match", "\"v1\"");
+        then.status(304);
+      })
+      .await;
+    let source = remote_source(server.url("/schema"), vec![], None);
+    let mut remote_state = RemoteSchemaState::default();
+
+    let record = load_remote(&source, &mut remote_state)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(record.raw().as_str(), SDL);
+    assert!(load_remote(&source, &mut remote_state)
+      .await
+      .unwrap()
+      .is_none());
+
+    initial.assert_async().await;
+    not_modified.assert_async().await;
+  }
+
+  #[tokio::test]
+  async fn should_load_schema_from_mirror_when_url_fails() {
+    let server = MockServer::start_async().await;
+    let primary = server
+      .mock_async(|when, then| {
+        when.path("/schema");
+        then.status(500);
+      })
+      .await;
+    let mirror = server
+      .mock_async(|when, then| {
+        when.path("/mirror");
+        then.status(200).header("etag", "\"v1\"").body(SDL);
+      })
+      .await;
+    let source = remote_source(server.url("/schema"), vec![server.url("/mirror")], None);
+    let mut remote_state = RemoteSchemaState::default();
+
+    let record = load_remote(&source, &mut remote_state)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(record.raw().as_str(), SDL);
+    assert_eq!(remote_state.etag.unwrap().url, server.url("/mirror"));
+
+    primary.assert_async().await;
+    mirror.assert_async().await;
+  }
+
+  #[tokio::test]
+  async fn should_use_cache_file_when_remote_fails() {
+    let server = MockServer::start_async().await;
+    server
+      .mock_async(|when, then| {
+        when.path("/schema");
+        then.status(200).body(SDL);
+      })
+      .await;
+    server
+      .mock_async(|when, then| {
+        when.path("/missing");
+        then.status(404);
+      })
+      .await;
+    let dir = tempfile::tempdir().unwrap();
+    let cache_file = dir
+      .path()
+      .join("schema.graphql")
+      .to_string_lossy()
+      .to_string();
+    let config = |url: String| SchemaAwarenessConfig {
+      format: SchemaAwarenessFormat::Sdl,
+      source: remote_source(url, vec![], Some(cache_file.clone())),
+      polling_interval: None,
+      on_error: SchemaAwarenessConfigOnError::Terminate,
+    };
+
+    SchemaAwareness::<()>::new(
+      "products".to_string(),
+      config(server.url("/schema")),
+      |_, _| Ok(()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read_to_string(&cache_file).unwrap(), SDL);
+
+    let schema_awareness = SchemaAwareness::<()>::new(
+      "products".to_string(),
+      config(server.url("/missing")),
+      |_, _| Ok(()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(schema_awareness.raw().unwrap().as_str(), SDL);
+    assert!(schema_awareness.health().last_error.is_some());
+  }
+
+  #[test]
+  fn should_notify_listeners_when_schema_loads_after_failed_initial_load() {
+    let listeners: Arc<SchemaChangeListeners> = Default::default();
+    let notified = Arc::new(RwLock::new(vec![]));
+    let recorded = notified.clone();
+    listeners.subscribe(Arc::new(move |source_id, diff| {
+      recorded
+        .write()
+        .unwrap()
+        .push((source_id.to_string(), diff.count(SchemaChangeKind::Added)));
+    }));
+
+    let handle = Arc::new(RwLock::new(None));
+    let mut poller = SchemaPoller::<()> {
+      source_id: "products".to_string(),
+      format: SchemaAwarenessFormat::Sdl,
+      source: SchemaAwarenessSource::Inline {
+        content: SDL.to_string(),
+      },
+      schema: Arc::downgrade(&handle),
+      health: Default::default(),
+      listeners,
+      remote_state: RemoteSchemaState::default(),
+      processor: |_, _| Ok(()),
+    };
+
+    futures::executor::block_on(poller.load_and_update_schema(handle.clone()));
+
+    assert!(handle.read().unwrap().is_some());
+    assert_eq!(
+      notified.read().unwrap().as_slice(),
+      &[("products".to_string(), 1)]
+    );
+  }
 }
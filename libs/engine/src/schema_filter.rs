@@ -0,0 +1,494 @@
+use std::collections::{HashMap, HashSet};
+
+use conductor_common::graphql::ParsedGraphQLSchema;
+use conductor_config::SchemaFilterConfig;
+use graphql_parser::schema::{Definition, Directive, Field, Type, TypeDefinition, Value};
+
+type SchemaTypeDefinition = TypeDefinition<'static, String>;
+type SchemaType = Type<'static, String>;
+type SchemaField = Field<'static, String>;
+
+/// Builds the schema exposed by an endpoint, based on its `schema_filter` configuration.
+pub fn filter_schema(
+  schema: &ParsedGraphQLSchema,
+  config: &SchemaFilterConfig,
+) -> ParsedGraphQLSchema {
+  let filter = SchemaFilter { config };
+  let mut schema = schema.clone();
+
+  for definition in schema.definitions.iter_mut() {
+    if let Definition::TypeDefinition(type_definition) = definition {
+      filter.apply(type_definition);
+    }
+  }
+
+  let mut removed = HashSet::new();
+  schema.definitions.retain(|definition| match definition {
+    Definition::TypeDefinition(type_definition) if filter.is_type_excluded(type_definition) => {
+      removed.insert(type_name(type_definition).to_string());
+      false
+    }
+    _ => true,
+  });
+
+  // Removing a type can leave other fields or types without a valid type, so we repeat until nothing changes.
+  loop {
+    let removed_before = removed.len();
+
+    for definition in schema.definitions.iter_mut() {
+      if let Definition::TypeDefinition(type_definition) = definition {
+        remove_references(type_definition, &removed);
+      }
+    }
+
+    schema.definitions.retain(|definition| match definition {
+      Definition::TypeDefinition(type_definition) if is_empty_type(type_definition) => {
+        removed.insert(type_name(type_definition).to_string());
+        false
+      }
+      _ => true,
+    });
+
+    if removed.len() == removed_before {
+      break;
+    }
+  }
+
+  remove_unreachable_types(&mut schema);
+
+  schema
+}
+
+struct SchemaFilter<'a> {
+  config: &'a SchemaFilterConfig,
+}
+
+impl SchemaFilter<'_> {
+  fn has_tag(&self, directives: &[Directive<'static, String>], tags: &[String]) -> bool {
+    !tags.is_empty()
+      && directives.iter().any(|directive| {
+        directive.name == "tag"
+          && directive.arguments.iter().any(|(name, value)| {
+            name == "name" && matches!(value, Value::String(tag) if tags.contains(tag))
+          })
+      })
+  }
+
+  fn matches(&self, patterns: &[String], coordinate: &str) -> bool {
+    patterns
+      .iter()
+      .any(|pattern| glob_matches(pattern, coordinate))
+  }
+
+  fn is_excluded(&self, directives: &[Directive<'static, String>], coordinate: &str) -> bool {
+    self.has_tag(directives, &self.config.exclude_tags)
+      || self.matches(&self.config.exclude, coordinate)
+  }
+
+  fn is_type_excluded(&self, type_definition: &SchemaTypeDefinition) -> bool {
+    self.is_excluded(type_directives(type_definition), type_name(type_definition))
+  }
+
+  /// Checks the include rules for a field of an object or interface type.
+  fn is_field_included(
+    &self,
+    type_directives: &[Directive<'static, String>],
+    field_directives: &[Directive<'static, String>],
+    type_name: &str,
+    coordinate: &str,
+  ) -> bool {
+    if self.config.include_tags.is_empty() && self.config.include.is_empty() {
+      return true;
+    }
+
+    self.has_tag(type_directives, &self.config.include_tags)
+      || self.has_tag(field_directives, &self.config.include_tags)
+      || self.matches(&self.config.include, type_name)
+      || self.matches(&self.config.include, coordinate)
+  }
+
+  fn filter_fields(
+    &self,
+    type_name: &str,
+    type_directives: &[Directive<'static, String>],
+    fields: &mut Vec<SchemaField>,
+  ) {
+    fields.retain(|field| {
+      let coordinate = format!("{}.{}", type_name, field.name);
+
+      // A field with a required argument that is removed can't be called anymore.
+      self.is_field_included(type_directives, &field.directives, type_name, &coordinate)
+        && !self.is_excluded(&field.directives, &coordinate)
+        && !field.arguments.iter().any(|arg| {
+          is_required(&arg.value_type)
+            && self.is_excluded(
+              &arg.directives,
+              &argument_coordinate(&coordinate, &arg.name),
+            )
+        })
+    });
+
+    for field in fields.iter_mut() {
+      let coordinate = format!("{}.{}", type_name, field.name);
+
+      field.arguments.retain(|arg| {
+        !self.is_excluded(
+          &arg.directives,
+          &argument_coordinate(&coordinate, &arg.name),
+        )
+      });
+    }
+  }
+
+  fn apply(&self, type_definition: &mut SchemaTypeDefinition) {
+    match type_definition {
+      TypeDefinition::Object(t) => self.filter_fields(&t.name, &t.directives, &mut t.fields),
+      TypeDefinition::Interface(t) => self.filter_fields(&t.name, &t.directives, &mut t.fields),
+      TypeDefinition::InputObject(t) => {
+        let name = &t.name;
+
+        t.fields.retain(|field| {
+          !self.is_excluded(&field.directives, &format!("{}.{}", name, field.name))
+        });
+      }
+      TypeDefinition::Enum(t) => {
+        let name = &t.name;
+
+        t.values.retain(|value| {
+          !self.is_excluded(&value.directives, &format!("{}.{}", name, value.name))
+        });
+      }
+      TypeDefinition::Scalar(_) | TypeDefinition::Union(_) => {}
+    }
+  }
+}
+
+/// The schema coordinate of a field argument, for example `Query.search(filter:)`.
+fn argument_coordinate(field_coordinate: &str, argument: &str) -> String {
+  format!("{}({}:)", field_coordinate, argument)
+}
+
+/// Matches a schema coordinate against a pattern, where `*` matches any sequence of characters.
+fn glob_matches(pattern: &str, value: &str) -> bool {
+  let mut parts = pattern.split('*');
+  // @expected: `split` always returns at least one item
+  let first = parts.next().unwrap();
+
+  let Some(mut rest) = value.strip_prefix(first) else {
+    return false;
+  };
+
+  let parts = parts.collect::<Vec<_>>();
+
+  for (index, part) in parts.iter().enumerate() {
+    if index == parts.len() - 1 {
+      return rest.ends_with(part);
+    }
+
+    match rest.find(part) {
+      Some(position) => rest = &rest[position + part.len()..],
+      None => return false,
+    }
+  }
+
+  // No wildcard in the pattern, so the value must match it exactly.
+  rest.is_empty()
+}
+
+pub(crate) fn type_name(type_definition: &SchemaTypeDefinition) -> &str {
+  match type_definition {
+    TypeDefinition::Scalar(t) => &t.name,
+    TypeDefinition::Object(t) => &t.name,
+    TypeDefinition::Interface(t) => &t.name,
+    TypeDefinition::Union(t) => &t.name,
+    TypeDefinition::Enum(t) => &t.name,
+    TypeDefinition::InputObject(t) => &t.name,
+  }
+}
+
+fn type_directives(type_definition: &SchemaTypeDefinition) -> &[Directive<'static, String>] {
+  match type_definition {
+    TypeDefinition::Scalar(t) => &t.directives,
+    TypeDefinition::Object(t) => &t.directives,
+    TypeDefinition::Interface(t) => &t.directives,
+    TypeDefinition::Union(t) => &t.directives,
+    TypeDefinition::Enum(t) => &t.directives,
+    TypeDefinition::InputObject(t) => &t.directives,
+  }
+}
+
+pub(crate) fn named_type(t: &SchemaType) -> &str {
+  match t {
+    Type::NamedType(name) => name,
+    Type::ListType(inner) | Type::NonNullType(inner) => named_type(inner),
+  }
+}
+
+/// Removes the fields, arguments and members that use one of the `removed` types.
+///
+/// A field with a required argument of a removed type is removed, since it can't be called anymore.
+fn remove_references(type_definition: &mut SchemaTypeDefinition, removed: &HashSet<String>) {
+  let is_removed = |t: &SchemaType| removed.contains(named_type(t));
+  let remove_field_references = |fields: &mut Vec<SchemaField>| {
+    fields.retain(|field| {
+      !is_removed(&field.field_type)
+        && !field
+          .arguments
+          .iter()
+          .any(|arg| is_removed(&arg.value_type) && is_required(&arg.value_type))
+    });
+
+    for field in fields.iter_mut() {
+      field.arguments.retain(|arg| !is_removed(&arg.value_type));
+    }
+  };
+
+  match type_definition {
+    TypeDefinition::Object(t) => {
+      t.implements_interfaces.retain(|i| !removed.contains(i));
+      remove_field_references(&mut t.fields);
+    }
+    TypeDefinition::Interface(t) => {
+      t.implements_interfaces.retain(|i| !removed.contains(i));
+      remove_field_references(&mut t.fields);
+    }
+    TypeDefinition::InputObject(t) => {
+      // An input type with a required field of a removed type can't be used anymore.
+      if t
+        .fields
+        .iter()
+        .any(|field| is_removed(&field.value_type) && is_required(&field.value_type))
+      {
+        t.fields.clear();
+      } else {
+        t.fields.retain(|field| !is_removed(&field.value_type));
+      }
+    }
+    TypeDefinition::Union(t) => t.types.retain(|member| !removed.contains(member)),
+    TypeDefinition::Scalar(_) | TypeDefinition::Enum(_) => {}
+  }
+}
+
+fn is_required(t: &SchemaType) -> bool {
+  matches!(t, Type::NonNullType(_))
+}
+
+fn is_empty_type(type_definition: &SchemaTypeDefinition) -> bool {
+  match type_definition {
+    TypeDefinition::Object(t) => t.fields.is_empty(),
+    TypeDefinition::Interface(t) => t.fields.is_empty(),
+    TypeDefinition::InputObject(t) => t.fields.is_empty(),
+    TypeDefinition::Union(t) => t.types.is_empty(),
+    TypeDefinition::Enum(t) => t.values.is_empty(),
+    TypeDefinition::Scalar(_) => false,
+  }
+}
+
+/// Removes the types that can't be reached from the root types, and the root operation types that were removed.
+fn remove_unreachable_types(schema: &mut ParsedGraphQLSchema) {
+  let defined_names = schema
+    .definitions
+    .iter()
+    .filter_map(|definition| match definition {
+      Definition::TypeDefinition(t) => Some(type_name(t).to_string()),
+      _ => None,
+    })
+    .collect::<HashSet<_>>();
+
+  let mut root_types = vec![];
+
+  for definition in schema.definitions.iter_mut() {
+    if let Definition::SchemaDefinition(schema_definition) = definition {
+      for root in [
+        &mut schema_definition.query,
+        &mut schema_definition.mutation,
+        &mut schema_definition.subscription,
+      ] {
+        if root
+          .as_ref()
+          .is_some_and(|name| !defined_names.contains(name))
+        {
+          *root = None;
+        }
+
+        root_types.extend(root.clone());
+      }
+    }
+  }
+
+  if root_types.is_empty() {
+    root_types = vec![
+      "Query".to_string(),
+      "Mutation".to_string(),
+      "Subscription".to_string(),
+    ];
+  }
+
+  let defined_types = schema
+    .definitions
+    .iter()
+    .filter_map(|definition| match definition {
+      Definition::TypeDefinition(t) => Some((type_name(t), t)),
+      _ => None,
+    })
+    .collect::<HashMap<_, _>>();
+  let mut reachable: HashSet<String> = HashSet::new();
+  let mut queue = root_types;
+
+  // Types used by directive arguments are reachable as well.
+  for definition in &schema.definitions {
+    if let Definition::DirectiveDefinition(directive) = definition {
+      queue.extend(
+        directive
+          .arguments
+          .iter()
+          .map(|arg| named_type(&arg.value_type).to_string()),
+      );
+    }
+  }
+
+  while let Some(name) = queue.pop() {
+    if !reachable.insert(name.clone()) {
+      continue;
+    }
+
+    let Some(type_definition) = defined_types.get(name.as_str()) else {
+      continue;
+    };
+
+    match type_definition {
+      TypeDefinition::Object(t) => {
+        queue.extend(t.implements_interfaces.iter().cloned());
+        queue.extend(t.fields.iter().flat_map(field_types));
+      }
+      TypeDefinition::Interface(t) => {
+        queue.extend(t.implements_interfaces.iter().cloned());
+        queue.extend(t.fields.iter().flat_map(field_types));
+        // The implementations of a reachable interface can be returned by its fields.
+        queue.extend(
+          defined_types
+            .values()
+            .filter(|candidate| implements(candidate, &name))
+            .map(|candidate| type_name(candidate).to_string()),
+        );
+      }
+      TypeDefinition::Union(t) => queue.extend(t.types.iter().cloned()),
+      TypeDefinition::InputObject(t) => queue.extend(
+        t.fields
+          .iter()
+          .map(|field| named_type(&field.value_type).to_string()),
+      ),
+      TypeDefinition::Scalar(_) | TypeDefinition::Enum(_) => {}
+    }
+  }
+
+  schema.definitions.retain(|definition| match definition {
+    Definition::TypeDefinition(t) => reachable.contains(type_name(t)),
+    Definition::TypeExtension(_) => false,
+    _ => true,
+  });
+}
+
+/// The names of the types used by a field: its own type, and the types of its arguments.
+fn field_types(field: &SchemaField) -> Vec<String> {
+  std::iter::once(&field.field_type)
+    .chain(field.arguments.iter().map(|arg| &arg.value_type))
+    .map(|t| named_type(t).to_string())
+    .collect()
+}
+
+fn implements(type_definition: &SchemaTypeDefinition, interface: &str) -> bool {
+  match type_definition {
+    TypeDefinition::Object(t) => t.implements_interfaces.iter().any(|i| i == interface),
+    TypeDefinition::Interface(t) => t.implements_interfaces.iter().any(|i| i == interface),
+    _ => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use conductor_common::graphql::parse_graphql_schema;
+
+  use super::*;
+
+  const SCHEMA: &str = r#"
+    type Query {
+      me: User @tag(name: "public")
+      admin: AdminPanel
+      search(filter: SearchFilter): [User!]! @tag(name: "public")
+    }
+
+    type User @tag(name: "public") {
+      id: ID!
+      name: String
+      email: String @tag(name: "internal")
+    }
+
+    type AdminPanel {
+      users: [User!]!
+      user(id: ID!): User
+    }
+
+    input SearchFilter {
+      name: String
+      role: Role
+    }
+
+    enum Role {
+      ADMIN
+      USER
+    }
+  "#;
+
+  fn filtered_sdl(config: SchemaFilterConfig) -> String {
+    filter_schema(&parse_graphql_schema(SCHEMA).unwrap(), &config)
+      .to_string()
+      .split_whitespace()
+      .collect::<Vec<_>>()
+      .join(" ")
+  }
+
+  #[test]
+  fn should_match_coordinates_with_globs() {
+    assert!(glob_matches("User", "User"));
+    assert!(!glob_matches("User", "UserInput"));
+    assert!(glob_matches("User*", "UserInput"));
+    assert!(glob_matches("*.email", "User.email"));
+    assert!(glob_matches("Query.*", "Query.me"));
+    assert!(glob_matches("A*m*n", "AdminPanel.admin"));
+    assert!(!glob_matches("*.email", "User.name"));
+  }
+
+  #[test]
+  fn should_filter_schema_by_tags_and_coordinates() {
+    let sdl = filtered_sdl(SchemaFilterConfig {
+      include_tags: vec!["public".to_string()],
+      exclude_tags: vec!["internal".to_string()],
+      include: vec![],
+      exclude: vec!["Role".to_string()],
+    });
+
+    assert!(sdl.contains("type Query { me: User @tag(name: \"public\") search(filter: SearchFilter): [User!]! @tag(name: \"public\") }"));
+    assert!(sdl.contains("type User @tag(name: \"public\") { id: ID! name: String }"));
+    assert!(sdl.contains("input SearchFilter { name: String }"));
+    assert!(!sdl.contains("AdminPanel"));
+    assert!(!sdl.contains("Role"));
+  }
+
+  #[test]
+  fn should_exclude_arguments_by_coordinates() {
+    let sdl = filtered_sdl(SchemaFilterConfig {
+      exclude: vec![
+        "Query.search(filter:)".to_string(),
+        "*.user(id:)".to_string(),
+      ],
+      ..Default::default()
+    });
+
+    assert!(sdl.contains("search: [User!]!"));
+    assert!(!sdl.contains("SearchFilter"));
+    assert!(!sdl.contains("Role"));
+    // `AdminPanel.user` requires the excluded argument, so it's removed with it.
+    assert!(sdl.contains("type AdminPanel { users: [User!]! }"));
+  }
+}
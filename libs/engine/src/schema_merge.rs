@@ -0,0 +1,364 @@
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
+
+use conductor_common::graphql::ParsedGraphQLSchema;
+use graphql_parser::schema::{Definition, Document, Field, InputValue, Type, TypeDefinition};
+
+use crate::schema_filter::{named_type, type_name};
+
+type SchemaTypeDefinition = TypeDefinition<'static, String>;
+type SchemaType = Type<'static, String>;
+
+const BUILTIN_SCALARS: [&str; 5] = ["String", "Int", "Float", "Boolean", "ID"];
+
+/// A schema built from the schemas of several sources, as configured by a `merge` source.
+#[derive(Debug)]
+pub struct MergedSchema {
+  pub schema: ParsedGraphQLSchema,
+  pub sources: Vec<MergedSourceSchema>,
+  /// The index of the source that resolves each root field, by root type and field name.
+  root_fields: HashMap<(String, String), usize>,
+  /// The named type of each field of the object and interface types, by type and field name.
+  field_types: HashMap<String, HashMap<String, String>>,
+}
+
+/// The schema of a single merged source, and the names of its types in the merged schema.
+#[derive(Debug)]
+pub struct MergedSourceSchema {
+  pub schema: Arc<ParsedGraphQLSchema>,
+  /// The types that are exposed under a different name, by their name in the source schema.
+  pub exposed_names: HashMap<String, String>,
+  /// The names of the renamed types in the source schema, by their name in the merged schema.
+  original_names: HashMap<String, String>,
+  /// The fields declared by the source for each object and interface type, by the name of the type in the merged schema.
+  fields: HashMap<String, HashSet<String>>,
+}
+
+impl MergedSourceSchema {
+  /// The name of a type of the merged schema, in the source schema.
+  pub fn original_name<'a>(&'a self, name: &'a str) -> &'a str {
+    self
+      .original_names
+      .get(name)
+      .map(|original| original.as_str())
+      .unwrap_or(name)
+  }
+
+  /// Checks if the source declares a field on a type of the merged schema.
+  pub fn has_field(&self, type_name: &str, field_name: &str) -> bool {
+    self
+      .fields
+      .get(type_name)
+      .map(|fields| fields.contains(field_name))
+      .unwrap_or(false)
+  }
+}
+
+impl MergedSchema {
+  /// The index of the source that resolves a field of a root type (`Query`, `Mutation` or `Subscription`).
+  pub fn root_field_source(&self, root_type: &str, field_name: &str) -> Option<usize> {
+    self
+      .root_fields
+      .get(&(root_type.to_string(), field_name.to_string()))
+      .copied()
+  }
+
+  /// The named type returned by a field of an object or interface type.
+  pub fn field_type(&self, type_name: &str, field_name: &str) -> Option<&str> {
+    self
+      .field_types
+      .get(type_name)
+      .and_then(|fields| fields.get(field_name))
+      .map(|t| t.as_str())
+  }
+}
+
+/// Merges the schemas of several sources, each with an optional prefix for the names of its types.
+///
+/// The root types of all sources are merged into `Query`, `Mutation` and `Subscription`, and the first source that
+/// declares a root field resolves it. Object types listed in `merged_types` are exposed with the fields of all
+/// sources. For any other type declared by more than one source (after prefixing), the first source wins.
+pub fn merge_schemas(
+  sources: &[(Arc<ParsedGraphQLSchema>, Option<String>)],
+  merged_types: &HashSet<String>,
+) -> MergedSchema {
+  let mut definitions: Vec<Definition<'static, String>> = vec![];
+  let mut type_positions: HashMap<String, usize> = HashMap::new();
+  let mut directive_names: HashSet<String> = HashSet::new();
+  let mut root_fields = HashMap::new();
+  let mut merged_sources = vec![];
+
+  for (index, (schema, type_prefix)) in sources.iter().enumerate() {
+    let root_types = root_types(schema);
+    let mut exposed_names = HashMap::new();
+
+    for definition in &schema.definitions {
+      if let Definition::TypeDefinition(t) = definition {
+        let name = type_name(t);
+        let exposed_name = match (root_types.get(name), type_prefix) {
+          (Some(root_type), _) => root_type.to_string(),
+          (None, _) if keeps_name(name, merged_types) => continue,
+          (None, Some(prefix)) => format!("{}{}", prefix, name),
+          (None, None) => continue,
+        };
+
+        if exposed_name != name {
+          exposed_names.insert(name.to_string(), exposed_name);
+        }
+      }
+    }
+
+    let mut fields = HashMap::new();
+
+    for definition in &schema.definitions {
+      match definition {
+        Definition::TypeDefinition(t) => {
+          let mut t = t.clone();
+          rename_type_definition(&mut t, &exposed_names);
+
+          let name = type_name(&t).to_string();
+          let is_root = root_types.values().any(|root_type| *root_type == name);
+
+          if let Some(type_fields) = object_fields(&t) {
+            fields.insert(
+              name.clone(),
+              type_fields.iter().map(|field| field.name.clone()).collect(),
+            );
+
+            if is_root {
+              for field in type_fields {
+                root_fields
+                  .entry((name.clone(), field.name.clone()))
+                  .or_insert(index);
+              }
+            }
+          }
+
+          match type_positions.get(&name) {
+            None => {
+              type_positions.insert(name, definitions.len());
+              definitions.push(Definition::TypeDefinition(t));
+            }
+            Some(position) if is_root || merged_types.contains(&name) => {
+              if let (
+                Definition::TypeDefinition(TypeDefinition::Object(existing)),
+                TypeDefinition::Object(merged),
+              ) = (&mut definitions[*position], t)
+              {
+                for field in merged.fields {
+                  if !existing.fields.iter().any(|f| f.name == field.name) {
+                    existing.fields.push(field);
+                  }
+                }
+              }
+            }
+            Some(_) => {
+              tracing::warn!(
+                "type \"{}\" is declared by more than one merged source, only the first declaration is used",
+                name
+              );
+            }
+          }
+        }
+        Definition::DirectiveDefinition(d) => {
+          if directive_names.insert(d.name.clone()) {
+            let mut d = d.clone();
+            rename_input_values(&mut d.arguments, &exposed_names);
+            definitions.push(Definition::DirectiveDefinition(d));
+          }
+        }
+        // The root types are always exposed as `Query`, `Mutation` and `Subscription`, and extensions are not supported.
+        Definition::SchemaDefinition(_) | Definition::TypeExtension(_) => {}
+      }
+    }
+
+    merged_sources.push(MergedSourceSchema {
+      schema: schema.clone(),
+      original_names: exposed_names
+        .iter()
+        .map(|(original, exposed)| (exposed.clone(), original.clone()))
+        .collect(),
+      exposed_names,
+      fields,
+    });
+  }
+
+  let field_types = definitions
+    .iter()
+    .filter_map(|definition| match definition {
+      Definition::TypeDefinition(t) => object_fields(t).map(|fields| {
+        (
+          type_name(t).to_string(),
+          fields
+            .iter()
+            .map(|field| {
+              (
+                field.name.clone(),
+                named_type(&field.field_type).to_string(),
+              )
+            })
+            .collect(),
+        )
+      }),
+      _ => None,
+    })
+    .collect();
+
+  MergedSchema {
+    schema: Document { definitions },
+    sources: merged_sources,
+    root_fields,
+    field_types,
+  }
+}
+
+/// Maps the names of the root types of a schema to `Query`, `Mutation` and `Subscription`.
+fn root_types(schema: &ParsedGraphQLSchema) -> HashMap<&str, &'static str> {
+  let schema_definition = schema
+    .definitions
+    .iter()
+    .find_map(|definition| match definition {
+      Definition::SchemaDefinition(schema_definition) => Some(schema_definition),
+      _ => None,
+    });
+
+  match schema_definition {
+    Some(schema_definition) => [
+      (schema_definition.query.as_deref(), "Query"),
+      (schema_definition.mutation.as_deref(), "Mutation"),
+      (schema_definition.subscription.as_deref(), "Subscription"),
+    ]
+    .into_iter()
+    .filter_map(|(name, root_type)| name.map(|name| (name, root_type)))
+    .collect(),
+    None => schema
+      .definitions
+      .iter()
+      .filter_map(|definition| match definition {
+        Definition::TypeDefinition(t) => ["Query", "Mutation", "Subscription"]
+          .into_iter()
+          .find(|root_type| *root_type == type_name(t))
+          .map(|root_type| (root_type, root_type)),
+        _ => None,
+      })
+      .collect(),
+  }
+}
+
+/// Checks if a type keeps its name, even when the source has a type prefix.
+fn keeps_name(name: &str, merged_types: &HashSet<String>) -> bool {
+  BUILTIN_SCALARS.contains(&name) || name.starts_with("__") || merged_types.contains(name)
+}
+
+fn object_fields(t: &SchemaTypeDefinition) -> Option<&[Field<'static, String>]> {
+  match t {
+    TypeDefinition::Object(o) => Some(&o.fields),
+    TypeDefinition::Interface(i) => Some(&i.fields),
+    _ => None,
+  }
+}
+
+fn rename(name: &mut String, names: &HashMap<String, String>) {
+  if let Some(exposed) = names.get(name.as_str()) {
+    *name = exposed.clone();
+  }
+}
+
+fn rename_type(t: &mut SchemaType, names: &HashMap<String, String>) {
+  match t {
+    Type::NamedType(name) => rename(name, names),
+    Type::ListType(inner) | Type::NonNullType(inner) => rename_type(inner, names),
+  }
+}
+
+fn rename_input_values(
+  values: &mut [InputValue<'static, String>],
+  names: &HashMap<String, String>,
+) {
+  for value in values {
+    rename_type(&mut value.value_type, names);
+  }
+}
+
+fn rename_fields(fields: &mut [Field<'static, String>], names: &HashMap<String, String>) {
+  for field in fields {
+    rename_type(&mut field.field_type, names);
+    rename_input_values(&mut field.arguments, names);
+  }
+}
+
+/// Renames a type, and all the types it references, to their names in the merged schema.
+fn rename_type_definition(t: &mut SchemaTypeDefinition, names: &HashMap<String, String>) {
+  match t {
+    TypeDefinition::Scalar(s) => rename(&mut s.name, names),
+    TypeDefinition::Object(o) => {
+      rename(&mut o.name, names);
+      o.implements_interfaces
+        .iter_mut()
+        .for_each(|name| rename(name, names));
+      rename_fields(&mut o.fields, names);
+    }
+    TypeDefinition::Interface(i) => {
+      rename(&mut i.name, names);
+      i.implements_interfaces
+        .iter_mut()
+        .for_each(|name| rename(name, names));
+      rename_fields(&mut i.fields, names);
+    }
+    TypeDefinition::Union(u) => {
+      rename(&mut u.name, names);
+      u.types.iter_mut().for_each(|name| rename(name, names));
+    }
+    TypeDefinition::Enum(e) => rename(&mut e.name, names),
+    TypeDefinition::InputObject(i) => {
+      rename(&mut i.name, names);
+      rename_input_values(&mut i.fields, names);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use conductor_common::graphql::parse_graphql_schema;
+
+  use super::*;
+
+  fn source(sdl: &str, type_prefix: Option<&str>) -> (Arc<ParsedGraphQLSchema>, Option<String>) {
+    (
+      Arc::new(parse_graphql_schema(sdl).unwrap()),
+      type_prefix.map(String::from),
+    )
+  }
+
+  #[test]
+  fn should_merge_root_fields_and_prefix_types() {
+    let merged = merge_schemas(
+      &[
+        source(
+          "type Query { me: User, status: String } type User { id: ID! name: String }",
+          None,
+        ),
+        source(
+          "schema { query: Root } type Root { reviews: [Review] status: Int } type Review { id: ID! author: User } type User { id: ID! reviews: [Review] }",
+          Some("Reviews"),
+        ),
+      ],
+      &HashSet::from(["User".to_string()]),
+    );
+
+    assert_eq!(merged.root_field_source("Query", "me"), Some(0));
+    assert_eq!(merged.root_field_source("Query", "status"), Some(0));
+    assert_eq!(merged.root_field_source("Query", "reviews"), Some(1));
+    assert_eq!(merged.field_type("Query", "reviews"), Some("ReviewsReview"));
+    assert_eq!(merged.field_type("User", "reviews"), Some("ReviewsReview"));
+    assert_eq!(merged.field_type("ReviewsReview", "author"), Some("User"));
+
+    assert!(merged.sources[1].has_field("User", "reviews"));
+    assert!(!merged.sources[1].has_field("User", "name"));
+    assert_eq!(merged.sources[1].original_name("Query"), "Root");
+    assert_eq!(merged.sources[1].original_name("ReviewsReview"), "Review");
+    assert_eq!(merged.sources[1].original_name("User"), "User");
+  }
+}
@@ -1,23 +1,36 @@
+use crate::http_client::create_upstream_http_client;
 use crate::schema_awareness::SchemaAwareness;
+use crate::schema_filter::{filter_schema, type_name};
 use conductor_common::execute::RequestExecutionContext;
-use conductor_common::graphql::GraphQLResponse;
+use conductor_common::graphql::{GraphQLResponse, ParsedGraphQLSchema};
 use conductor_common::plugin_manager::PluginManager;
-use conductor_common::source::{GraphQLSourceInitError, SourceError, SourceRuntime};
-use conductor_config::{FederationSourceConfig, SchemaAwarenessConfig};
+use conductor_common::source::{
+  GraphQLSourceInitError, SchemaChangeListener, SchemaHealth, SourceError, SourceRuntime,
+};
+use conductor_config::{FederationSourceConfig, SchemaAwarenessConfig, SchemaFilterConfig};
 use federation_query_planner::supergraph::parse_supergraph;
 use federation_query_planner::supergraph::Supergraph;
 use federation_query_planner::FederationExecutor;
 use futures::lock::Mutex;
+use graphql_parser::schema::{Definition, Directive, TypeDefinition};
 use minitrace_reqwest::{traced_reqwest, TracedHttpClient};
 use std::sync::Arc;
-use std::{future::Future, pin::Pin};
+use std::{future::Future, pin::Pin, time::SystemTime};
 
 #[derive(Debug)]
 pub struct FederationSourceRuntime {
   pub client: TracedHttpClient,
   pub identifier: String,
   pub config: FederationSourceConfig,
-  pub schema_awareness: SchemaAwareness<Supergraph>,
+  pub schema_awareness: SchemaAwareness<FederatedSchema>,
+}
+
+/// The processed supergraph of a federation source.
+#[derive(Debug)]
+pub struct FederatedSchema {
+  pub supergraph: Supergraph,
+  /// The schema exposed to clients, without the federation types, directives and `@inaccessible` elements.
+  pub api_schema: Arc<ParsedGraphQLSchema>,
 }
 
 impl FederationSourceRuntime {
@@ -32,20 +45,24 @@ impl FederationSourceRuntime {
     );
 
     let client = traced_reqwest(
-      wasm_polyfills::create_http_client()
-        .build()
+      create_upstream_http_client(config.http_client.as_ref())
         .map_err(|source| GraphQLSourceInitError::FetcherError { source })?,
     );
 
-    let schema_awareness = SchemaAwareness::<Supergraph>::new(
+    let schema_awareness = SchemaAwareness::<FederatedSchema>::new(
       identifier.clone(),
       SchemaAwarenessConfig {
         format: conductor_config::SchemaAwarenessFormat::Sdl,
-        on_error: conductor_config::SchemaAwarenessConfigOnError::Terminate,
+        on_error: config.supergraph.on_error.clone(),
         polling_interval: config.supergraph.polling_interval,
         source: config.supergraph.source.clone(),
       },
-      |_, parsed| parse_supergraph(parsed),
+      |_, parsed| {
+        Ok(FederatedSchema {
+          supergraph: parse_supergraph(parsed)?,
+          api_schema: Arc::new(build_api_schema(parsed)),
+        })
+      },
     )
     .await
     .map_err(|source| GraphQLSourceInitError::SourceInitFailed {
@@ -66,14 +83,33 @@ impl SourceRuntime for FederationSourceRuntime {
     &self.identifier
   }
 
-  fn schema(&self) -> Option<Arc<conductor_common::graphql::ParsedGraphQLSchema>> {
-    self.schema_awareness.schema()
+  fn schema(&self) -> Option<Arc<ParsedGraphQLSchema>> {
+    self
+      .schema_awareness
+      .processed()
+      .map(|federated_schema| federated_schema.api_schema.clone())
   }
 
   fn sdl(&self) -> Option<Arc<String>> {
     self.schema_awareness.raw()
   }
 
+  fn schema_health(&self) -> Option<SchemaHealth> {
+    Some(self.schema_awareness.health())
+  }
+
+  fn shutdown(&self) {
+    self.schema_awareness.stop_polling();
+  }
+
+  fn subscribe_schema_changes(&self, listener: SchemaChangeListener) {
+    self.schema_awareness.subscribe_changes(listener);
+  }
+
+  fn refresh_schema<'a>(&'a self, now: SystemTime) -> Pin<Box<(dyn Future<Output = ()> + 'a)>> {
+    Box::pin(self.schema_awareness.refresh_if_stale(now))
+  }
+
   fn execute<'a>(
     &'a self,
     plugin_manager: Arc<Box<dyn PluginManager>>,
@@ -88,11 +124,12 @@ impl SourceRuntime for FederationSourceRuntime {
       let operation = downstream_request.parsed_operation;
 
       match self.schema_awareness.processed().as_ref() {
-        Some(supergraph) => {
+        Some(federated_schema) => {
           let executor = FederationExecutor {
             client: &self.client,
             plugin_manager: plugin_manager.clone(),
-            supergraph,
+            supergraph: &federated_schema.supergraph,
+            trace_query_plan: self.config.trace_query_plan,
           };
 
           match executor
@@ -124,3 +161,150 @@ impl SourceRuntime for FederationSourceRuntime {
     }))
   }
 }
+
+/// Directives of the supergraph that are only used for planning, and aren't part of the API schema.
+static FEDERATION_DIRECTIVES: &[&str] = &["link", "core", "inaccessible", "tag"];
+
+/// Builds the API schema of a supergraph: the schema clients can see in introspection queries.
+pub fn build_api_schema(supergraph: &ParsedGraphQLSchema) -> ParsedGraphQLSchema {
+  let is_inaccessible = |directives: &[Directive<'static, String>]| {
+    directives
+      .iter()
+      .any(|directive| directive.name == "inaccessible")
+  };
+
+  let mut exclude = vec![
+    "join__*".to_string(),
+    "link__*".to_string(),
+    "core__*".to_string(),
+  ];
+
+  for definition in &supergraph.definitions {
+    if let Definition::TypeDefinition(type_definition) = definition {
+      let name = type_name(type_definition);
+      let (directives, members) = match type_definition {
+        TypeDefinition::Scalar(t) => (&t.directives, vec![]),
+        TypeDefinition::Union(t) => (&t.directives, vec![]),
+        TypeDefinition::Object(t) => (
+          &t.directives,
+          t.fields.iter().map(|f| (&f.name, &f.directives)).collect(),
+        ),
+        TypeDefinition::Interface(t) => (
+          &t.directives,
+          t.fields.iter().map(|f| (&f.name, &f.directives)).collect(),
+        ),
+        TypeDefinition::InputObject(t) => (
+          &t.directives,
+          t.fields.iter().map(|f| (&f.name, &f.directives)).collect(),
+        ),
+        TypeDefinition::Enum(t) => (
+          &t.directives,
+          t.values.iter().map(|v| (&v.name, &v.directives)).collect(),
+        ),
+      };
+
+      if is_inaccessible(directives) {
+        exclude.push(name.to_string());
+      }
+
+      exclude.extend(
+        members
+          .into_iter()
+          .filter(|(_, directives)| is_inaccessible(directives))
+          .map(|(member, _)| format!("{}.{}", name, member)),
+      );
+    }
+  }
+
+  let mut api_schema = filter_schema(
+    supergraph,
+    &SchemaFilterConfig {
+      exclude,
+      ..Default::default()
+    },
+  );
+
+  api_schema
+    .definitions
+    .retain_mut(|definition| match definition {
+      Definition::DirectiveDefinition(directive) => {
+        !FEDERATION_DIRECTIVES.contains(&directive.name.as_str())
+          && !directive.name.starts_with("join__")
+      }
+      Definition::TypeDefinition(TypeDefinition::Object(t)) => {
+        for field in t.fields.iter_mut() {
+          field
+            .arguments
+            .retain(|arg| !is_inaccessible(&arg.directives));
+        }
+
+        true
+      }
+      Definition::TypeDefinition(TypeDefinition::Interface(t)) => {
+        for field in t.fields.iter_mut() {
+          field
+            .arguments
+            .retain(|arg| !is_inaccessible(&arg.directives));
+        }
+
+        true
+      }
+      _ => true,
+    });
+
+  api_schema
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use conductor_common::graphql::parse_graphql_schema;
+
+  #[test]
+  fn should_build_api_schema_from_supergraph() {
+    let supergraph = parse_graphql_schema(
+      r#"
+        schema @link(url: "https://specs.apollo.dev/link/v1.0") {
+          query: Query
+        }
+
+        directive @join__type(graph: join__Graph!, key: join__FieldSet) repeatable on OBJECT
+        directive @inaccessible on FIELD_DEFINITION | OBJECT | ARGUMENT_DEFINITION
+        directive @link(url: String, import: [link__Import]) repeatable on SCHEMA
+
+        scalar join__FieldSet
+        scalar link__Import
+
+        enum join__Graph {
+          PRODUCTS @join__graph(name: "products", url: "http://products")
+        }
+
+        type Query @join__type(graph: PRODUCTS) {
+          products(first: Int, internal: Boolean @inaccessible): [Product]
+          internal: Internal
+        }
+
+        type Product @join__type(graph: PRODUCTS, key: "upc") {
+          upc: String!
+          cost: Int @inaccessible
+        }
+
+        type Internal @inaccessible {
+          id: ID
+        }
+      "#,
+    )
+    .unwrap();
+
+    let sdl = build_api_schema(&supergraph).to_string();
+
+    assert!(sdl.contains("products(first: Int): [Product]"));
+    assert!(sdl.contains("upc: String!"));
+    assert!(!sdl.contains("cost"));
+    assert!(!sdl.contains("Internal"));
+    assert!(!sdl.contains("join__Graph"));
+    assert!(!sdl.contains("join__FieldSet"));
+    assert!(!sdl.contains("link__Import"));
+    assert!(!sdl.contains("directive @"));
+  }
+}
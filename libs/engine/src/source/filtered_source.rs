@@ -0,0 +1,133 @@
+use std::{
+  future::Future,
+  pin::Pin,
+  sync::{Arc, RwLock},
+  time::SystemTime,
+};
+
+use conductor_common::{
+  execute::RequestExecutionContext,
+  graphql::{validate_graphql_operation, GraphQLResponse, ParsedGraphQLSchema},
+  plugin_manager::PluginManager,
+  source::{SchemaChangeListener, SchemaHealth, SourceError, SourceRuntime},
+};
+use conductor_config::SchemaFilterConfig;
+
+use crate::schema_filter::filter_schema;
+
+/// Wraps a source and exposes only the part of its schema selected by an endpoint's `schema_filter`.
+///
+/// Operations are validated against the filtered schema before they reach the wrapped source, and introspection is
+/// resolved by the gateway, so removed types and fields can't be queried or discovered.
+#[derive(Debug)]
+pub struct FilteredSourceRuntime {
+  inner: Arc<Box<dyn SourceRuntime>>,
+  filter: SchemaFilterConfig,
+  cache: RwLock<Option<FilteredSchema>>,
+}
+
+#[derive(Debug, Clone)]
+struct FilteredSchema {
+  /// The schema of the wrapped source that was filtered, used to detect schema reloads.
+  source_schema: Arc<ParsedGraphQLSchema>,
+  schema: Arc<ParsedGraphQLSchema>,
+  sdl: Arc<String>,
+}
+
+impl FilteredSourceRuntime {
+  pub fn new(inner: Arc<Box<dyn SourceRuntime>>, filter: SchemaFilterConfig) -> Self {
+    Self {
+      inner,
+      filter,
+      cache: RwLock::new(None),
+    }
+  }
+
+  fn filtered_schema(&self) -> Option<FilteredSchema> {
+    let source_schema = self.inner.schema()?;
+
+    // @expected: the lock is never held while panicking
+    if let Some(cached) = self.cache.read().unwrap().as_ref() {
+      if Arc::ptr_eq(&cached.source_schema, &source_schema) {
+        return Some(cached.clone());
+      }
+    }
+
+    let schema = filter_schema(&source_schema, &self.filter);
+    let filtered = FilteredSchema {
+      source_schema,
+      sdl: Arc::new(schema.to_string()),
+      schema: Arc::new(schema),
+    };
+
+    // @expected: the lock is never held while panicking
+    *self.cache.write().unwrap() = Some(filtered.clone());
+
+    Some(filtered)
+  }
+}
+
+impl SourceRuntime for FilteredSourceRuntime {
+  fn name(&self) -> &str {
+    self.inner.name()
+  }
+
+  fn schema(&self) -> Option<Arc<ParsedGraphQLSchema>> {
+    self.filtered_schema().map(|filtered| filtered.schema)
+  }
+
+  fn sdl(&self) -> Option<Arc<String>> {
+    self.filtered_schema().map(|filtered| filtered.sdl)
+  }
+
+  fn schema_health(&self) -> Option<SchemaHealth> {
+    self.inner.schema_health()
+  }
+
+  fn shutdown(&self) {
+    self.inner.shutdown()
+  }
+
+  fn subscribe_schema_changes(&self, listener: SchemaChangeListener) {
+    self.inner.subscribe_schema_changes(listener)
+  }
+
+  fn refresh_schema<'a>(&'a self, now: SystemTime) -> Pin<Box<(dyn Future<Output = ()> + 'a)>> {
+    self.inner.refresh_schema(now)
+  }
+
+  fn execute<'a>(
+    &'a self,
+    plugin_manager: Arc<Box<dyn PluginManager>>,
+    request_context: &'a mut RequestExecutionContext,
+  ) -> Pin<Box<(dyn Future<Output = Result<GraphQLResponse, SourceError>> + 'a)>> {
+    Box::pin(wasm_polyfills::call_async(async move {
+      let request = match request_context.downstream_graphql_request.as_ref() {
+        Some(request) => request,
+        None => {
+          return Ok(GraphQLResponse::new_error(
+            "source request isn't available at execution context!",
+          ))
+        }
+      };
+
+      // Without a schema, it's not possible to tell which operations are allowed, so all of them are rejected.
+      let filtered = match self.filtered_schema() {
+        Some(filtered) => filtered,
+        None => {
+          return Ok(GraphQLResponse::new_error(
+            "the schema of the source isn't available yet",
+          ))
+        }
+      };
+
+      let errors = validate_graphql_operation(&filtered.schema, &request.parsed_operation);
+
+      if !errors.is_empty() {
+        return Ok(errors.into());
+      }
+
+      self.inner.execute(plugin_manager, request_context).await
+    }))
+  }
+}
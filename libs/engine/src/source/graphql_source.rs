@@ -1,4 +1,4 @@
-use std::{future::Future, pin::Pin, sync::Arc};
+use std::{future::Future, pin::Pin, sync::Arc, time::SystemTime};
 
 use conductor_common::{
   execute::RequestExecutionContext,
@@ -11,9 +11,11 @@ use minitrace_reqwest::{traced_reqwest, TracedHttpClient};
 use reqwest::{header::HeaderValue, Method, StatusCode};
 use tracing::debug;
 
-use crate::schema_awareness::SchemaAwareness;
+use crate::{http_client::create_upstream_http_client, schema_awareness::SchemaAwareness};
 
-use conductor_common::source::{GraphQLSourceInitError, SourceError, SourceRuntime};
+use conductor_common::source::{
+  GraphQLSourceInitError, SchemaChangeListener, SchemaHealth, SourceError, SourceRuntime,
+};
 
 #[derive(Debug)]
 pub struct GraphQLSourceRuntime {
@@ -34,8 +36,7 @@ impl GraphQLSourceRuntime {
       config
     );
 
-    let client = wasm_polyfills::create_http_client()
-      .build()
+    let client = create_upstream_http_client(config.http_client.as_ref())
       .map_err(|source| GraphQLSourceInitError::FetcherError { source })?;
 
     let fetcher = traced_reqwest(client);
@@ -72,6 +73,30 @@ impl SourceRuntime for GraphQLSourceRuntime {
     None
   }
 
+  fn schema_health(&self) -> Option<SchemaHealth> {
+    self.schema_awareness.as_ref().map(|s| s.health())
+  }
+
+  fn shutdown(&self) {
+    if let Some(schema_awareness) = &self.schema_awareness {
+      schema_awareness.stop_polling();
+    }
+  }
+
+  fn subscribe_schema_changes(&self, listener: SchemaChangeListener) {
+    if let Some(schema_awareness) = &self.schema_awareness {
+      schema_awareness.subscribe_changes(listener);
+    }
+  }
+
+  fn refresh_schema<'a>(&'a self, now: SystemTime) -> Pin<Box<(dyn Future<Output = ()> + 'a)>> {
+    Box::pin(async move {
+      if let Some(schema_awareness) = &self.schema_awareness {
+        schema_awareness.refresh_if_stale(now).await;
+      }
+    })
+  }
+
   fn schema(&self) -> Option<Arc<ParsedGraphQLSchema>> {
     if let Some(schema_awareness) = &self.schema_awareness {
       return schema_awareness.schema();
@@ -0,0 +1,1275 @@
+use std::{
+  collections::{HashMap, HashSet},
+  future::Future,
+  pin::Pin,
+  sync::{Arc, RwLock},
+  time::SystemTime,
+};
+
+use conductor_common::{
+  execute::RequestExecutionContext,
+  graphql::{
+    validate_graphql_operation, GraphQLError, GraphQLRequest, GraphQLResponse,
+    ParsedGraphQLRequest, ParsedGraphQLSchema,
+  },
+  introspection::serde_value_to_sdl_value,
+  normalize::hash_signature,
+  plugin_manager::PluginManager,
+  source::{SchemaChangeListener, SchemaHealth, SourceError, SourceRuntime},
+};
+use conductor_config::MergeSourceConfig;
+use graphql_parser::query::{
+  Definition, Document, Field, FragmentDefinition, InlineFragment, Mutation, OperationDefinition,
+  Query, Selection, SelectionSet, Type, TypeCondition, Value as GraphQLValue, VariableDefinition,
+};
+use serde_json::{Map, Value};
+
+use crate::schema_merge::{merge_schemas, MergedSchema};
+
+type QuerySelectionSet = SelectionSet<'static, String>;
+type QuerySelection = Selection<'static, String>;
+type QueryField = Field<'static, String>;
+
+/// The alias used to request the key of a merged type, when some of its fields are fetched from another source.
+const MERGE_KEY_ALIAS: &str = "__merge_key";
+/// The alias used to request the type of an object, when some of its fields are fetched from another source.
+const MERGE_TYPENAME_ALIAS: &str = "__merge_typename";
+
+#[derive(Debug)]
+pub struct MergeSourceRuntime {
+  pub identifier: String,
+  pub config: MergeSourceConfig,
+  sources: Vec<Arc<Box<dyn SourceRuntime>>>,
+  type_merging: HashMap<String, TypeMerging>,
+  cache: RwLock<Option<CachedMergedSchema>>,
+}
+
+/// The resolved `type_merging` configuration of a type.
+#[derive(Debug)]
+struct TypeMerging {
+  key: String,
+  /// The root field and the argument used to fetch an object by its key, by source index.
+  resolvers: HashMap<usize, (String, String)>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedMergedSchema {
+  /// The schemas of the merged sources, used to detect schema reloads.
+  source_schemas: Vec<Arc<ParsedGraphQLSchema>>,
+  merged: Arc<MergedSchema>,
+  schema: Arc<ParsedGraphQLSchema>,
+  sdl: Arc<String>,
+}
+
+/// Fields of a merged type that are fetched from another source, after the objects they belong to are fetched.
+#[derive(Debug)]
+struct DeferredFetch {
+  /// The response keys leading to the objects, lists are traversed.
+  path: Vec<String>,
+  type_name: String,
+  source: usize,
+  selections: Vec<QuerySelection>,
+}
+
+impl MergeSourceRuntime {
+  /// Creates the runtime, `sources` must be the runtimes of the sources listed in `config.sources`, in the same order.
+  pub fn new(
+    identifier: String,
+    config: MergeSourceConfig,
+    sources: Vec<Arc<Box<dyn SourceRuntime>>>,
+  ) -> Self {
+    let type_merging = config
+      .type_merging
+      .iter()
+      .map(|rule| {
+        let resolvers = rule
+          .resolvers
+          .iter()
+          .filter_map(|resolver| {
+            let index = config
+              .sources
+              .iter()
+              .position(|merged| merged.id == resolver.source)?;
+            let argument = resolver.argument.clone().unwrap_or(rule.key.clone());
+
+            Some((index, (resolver.field.clone(), argument)))
+          })
+          .collect();
+
+        (
+          rule.type_name.clone(),
+          TypeMerging {
+            key: rule.key.clone(),
+            resolvers,
+          },
+        )
+      })
+      .collect();
+
+    Self {
+      identifier,
+      config,
+      sources,
+      type_merging,
+      cache: RwLock::new(None),
+    }
+  }
+
+  fn merged_schema(&self) -> Option<CachedMergedSchema> {
+    let source_schemas = self
+      .sources
+      .iter()
+      .map(|source| source.schema())
+      .collect::<Option<Vec<_>>>()?;
+
+    // @expected: the lock is never held while panicking
+    if let Some(cached) = self.cache.read().unwrap().as_ref() {
+      let is_up_to_date = cached
+        .source_schemas
+        .iter()
+        .zip(&source_schemas)
+        .all(|(cached, current)| Arc::ptr_eq(cached, current));
+
+      if is_up_to_date {
+        return Some(cached.clone());
+      }
+    }
+
+    let merged = merge_schemas(
+      &source_schemas
+        .iter()
+        .zip(&self.config.sources)
+        .map(|(schema, merged)| (schema.clone(), merged.type_prefix.clone()))
+        .collect::<Vec<_>>(),
+      &self.type_merging.keys().cloned().collect(),
+    );
+    let cached = CachedMergedSchema {
+      source_schemas,
+      sdl: Arc::new(merged.schema.to_string()),
+      schema: Arc::new(merged.schema.clone()),
+      merged: Arc::new(merged),
+    };
+
+    // @expected: the lock is never held while panicking
+    *self.cache.write().unwrap() = Some(cached.clone());
+
+    Some(cached)
+  }
+
+  /// The source that resolves a field of a merged type, when the current source doesn't declare it.
+  fn field_owner(
+    &self,
+    merged: &MergedSchema,
+    type_merging: &TypeMerging,
+    type_name: &str,
+    field_name: &str,
+  ) -> Option<usize> {
+    (0..merged.sources.len()).find(|index| {
+      type_merging.resolvers.contains_key(index)
+        && merged.sources[*index].has_field(type_name, field_name)
+    })
+  }
+
+  /// Rewrites a selection set of the merged schema for a single source.
+  ///
+  /// Type conditions are renamed to the names of the source schema, and the fields of merged types that are not
+  /// declared by the source are moved to `deferred`, to be fetched later from the source that declares them.
+  fn plan_selection_set(
+    &self,
+    merged: &MergedSchema,
+    source: usize,
+    type_name: &str,
+    selection_set: &QuerySelectionSet,
+    path: &mut Vec<String>,
+    deferred: &mut Vec<DeferredFetch>,
+  ) -> QuerySelectionSet {
+    let type_merging = self.type_merging.get(type_name);
+    let mut items = vec![];
+    let mut needs_key = false;
+
+    for selection in &selection_set.items {
+      match selection {
+        Selection::Field(field) => {
+          if let Some(type_merging) = type_merging {
+            if field.name != "__typename"
+              && !merged.sources[source].has_field(type_name, &field.name)
+            {
+              // Without a resolver, the field can't be fetched, and is resolved as `null`.
+              if let Some(owner) = self.field_owner(merged, type_merging, type_name, &field.name) {
+                add_deferred_selection(deferred, path, type_name, owner, selection.clone());
+                needs_key = true;
+              }
+
+              continue;
+            }
+          }
+
+          let mut field = field.clone();
+
+          if let Some(field_type) = merged.field_type(type_name, &field.name) {
+            if !field.selection_set.items.is_empty() {
+              path.push(response_key(&field).to_string());
+              field.selection_set = self.plan_selection_set(
+                merged,
+                source,
+                field_type,
+                &field.selection_set,
+                path,
+                deferred,
+              );
+              path.pop();
+            }
+          }
+
+          items.push(Selection::Field(field));
+        }
+        Selection::InlineFragment(fragment) => {
+          let condition = match &fragment.type_condition {
+            Some(TypeCondition::On(condition)) => condition.as_str(),
+            None => type_name,
+          };
+          let mut fragment = fragment.clone();
+
+          fragment.selection_set = self.plan_selection_set(
+            merged,
+            source,
+            condition,
+            &fragment.selection_set,
+            path,
+            deferred,
+          );
+          fragment.type_condition = fragment.type_condition.map(|TypeCondition::On(condition)| {
+            TypeCondition::On(merged.sources[source].original_name(&condition).to_string())
+          });
+
+          items.push(Selection::InlineFragment(fragment));
+        }
+        // Fragment spreads are replaced by inline fragments before planning.
+        Selection::FragmentSpread(_) => {}
+      }
+    }
+
+    if needs_key {
+      if let Some(type_merging) = type_merging {
+        items.push(Selection::Field(internal_field(
+          MERGE_KEY_ALIAS,
+          &type_merging.key,
+        )));
+        items.push(Selection::Field(internal_field(
+          MERGE_TYPENAME_ALIAS,
+          "__typename",
+        )));
+      }
+    }
+
+    SelectionSet {
+      span: selection_set.span,
+      items,
+    }
+  }
+
+  /// Sends a selection set, already planned for the source, and returns the data and errors of the response.
+  async fn fetch(
+    &self,
+    plugin_manager: Arc<Box<dyn PluginManager>>,
+    request_context: &mut RequestExecutionContext,
+    request: &ParsedGraphQLRequest,
+    source: usize,
+    operation: OperationDefinition<'static, String>,
+  ) -> (Option<Value>, Vec<GraphQLError>) {
+    let variables = request.request.variables.as_ref().map(|variables| {
+      let used_variables = operation_variables(&operation);
+
+      variables
+        .iter()
+        .filter(|(name, _)| used_variables.contains(name.as_str()))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect::<Map<String, Value>>()
+    });
+    let document = Document {
+      definitions: vec![Definition::Operation(operation)],
+    };
+    let source_request = ParsedGraphQLRequest {
+      request: GraphQLRequest {
+        operation: document.to_string(),
+        operation_name: None,
+        variables,
+        extensions: None,
+      },
+      parsed_operation: document,
+    };
+
+    let downstream_request = request_context
+      .downstream_graphql_request
+      .replace(source_request);
+    let result = self.sources[source]
+      .execute(plugin_manager, request_context)
+      .await;
+    request_context.downstream_graphql_request = downstream_request;
+
+    match result {
+      Ok(response) => (response.data, response.errors.unwrap_or_default()),
+      Err(e) => (None, vec![GraphQLError::new(&e.to_string())]),
+    }
+  }
+
+  /// Fetches the deferred fields of merged types, and adds them to the objects in `data`.
+  fn resolve_deferred<'a>(
+    &'a self,
+    merged: &'a MergedSchema,
+    plugin_manager: Arc<Box<dyn PluginManager>>,
+    request_context: &'a mut RequestExecutionContext,
+    request: &'a ParsedGraphQLRequest,
+    data: &'a mut Value,
+    deferred: Vec<DeferredFetch>,
+  ) -> Pin<Box<dyn Future<Output = Vec<GraphQLError>> + 'a>> {
+    Box::pin(async move {
+      let mut errors = vec![];
+
+      for fetch in deferred {
+        let mut targets = vec![];
+        collect_objects(data, &fetch.path, &fetch.type_name, &mut targets);
+
+        let Some((resolver_field, resolver_argument)) = self
+          .type_merging
+          .get(&fetch.type_name)
+          .and_then(|type_merging| type_merging.resolvers.get(&fetch.source))
+        else {
+          continue;
+        };
+
+        if targets.is_empty() {
+          continue;
+        }
+
+        let mut nested_deferred = vec![];
+        let selection_set = self.plan_selection_set(
+          merged,
+          fetch.source,
+          &fetch.type_name,
+          &SelectionSet {
+            span: Default::default(),
+            items: fetch.selections,
+          },
+          &mut vec![],
+          &mut nested_deferred,
+        );
+
+        // All objects are fetched with a single request, using an alias for each object.
+        let items = targets
+          .iter()
+          .enumerate()
+          .map(|(index, target)| {
+            let key = target.get(MERGE_KEY_ALIAS).cloned().unwrap_or(Value::Null);
+
+            Selection::Field(Field {
+              position: Default::default(),
+              alias: Some(format!("_{}", index)),
+              name: resolver_field.clone(),
+              arguments: vec![(resolver_argument.clone(), serde_value_to_sdl_value(key))],
+              directives: vec![],
+              selection_set: selection_set.clone(),
+            })
+          })
+          .collect::<Vec<_>>();
+        let selection_set = SelectionSet {
+          span: Default::default(),
+          items,
+        };
+
+        let (response_data, fetch_errors) = self
+          .fetch(
+            plugin_manager.clone(),
+            request_context,
+            request,
+            fetch.source,
+            source_operation(
+              "Query",
+              None,
+              request,
+              merged,
+              fetch.source,
+              selection_set.clone(),
+            ),
+          )
+          .await;
+        errors.extend(fetch_errors);
+
+        let Some(Value::Object(mut response_data)) = response_data else {
+          continue;
+        };
+
+        rename_typenames(
+          &mut response_data,
+          &selection_set,
+          &merged.sources[fetch.source].exposed_names,
+        );
+
+        let mut results = Value::Array(
+          (0..targets.len())
+            .map(|index| {
+              response_data
+                .remove(&format!("_{}", index))
+                .unwrap_or(Value::Null)
+            })
+            .collect(),
+        );
+
+        errors.extend(
+          self
+            .resolve_deferred(
+              merged,
+              plugin_manager.clone(),
+              request_context,
+              request,
+              &mut results,
+              nested_deferred,
+            )
+            .await,
+        );
+
+        if let Value::Array(results) = results {
+          for (target, result) in targets.into_iter().zip(results) {
+            if let Value::Object(result) = result {
+              target.extend(result);
+            }
+          }
+        }
+      }
+
+      errors
+    })
+  }
+
+  async fn execute_merged(
+    &self,
+    plugin_manager: Arc<Box<dyn PluginManager>>,
+    request_context: &mut RequestExecutionContext,
+    request: &ParsedGraphQLRequest,
+  ) -> GraphQLResponse {
+    let Some(cached) = self.merged_schema() else {
+      return GraphQLResponse::new_error("the schemas of the merged sources aren't available yet");
+    };
+
+    let validation_errors = validate_graphql_operation(&cached.schema, &request.parsed_operation);
+
+    if !validation_errors.is_empty() {
+      return validation_errors.into();
+    }
+
+    let (root_type, operation_name, selection_set) = match request.executable_operation() {
+      Some(Definition::Operation(OperationDefinition::Query(query))) => {
+        ("Query", query.name.clone(), &query.selection_set)
+      }
+      Some(Definition::Operation(OperationDefinition::SelectionSet(selection_set))) => {
+        ("Query", None, selection_set)
+      }
+      Some(Definition::Operation(OperationDefinition::Mutation(mutation))) => {
+        ("Mutation", mutation.name.clone(), &mutation.selection_set)
+      }
+      Some(Definition::Operation(OperationDefinition::Subscription(_))) => {
+        return GraphQLResponse::new_error("subscriptions are not supported by merge sources");
+      }
+      _ => return GraphQLResponse::new_error("failed to locate the operation to execute"),
+    };
+
+    let merged = &cached.merged;
+    let selection_set = inline_fragments(selection_set, request, &mut vec![]);
+    let mut results = Map::new();
+    let mut errors = vec![];
+
+    for key in root_typename_keys(&selection_set) {
+      results.insert(key, Value::String(root_type.to_string()));
+    }
+
+    // Each run of root fields resolved by the same source is executed after the previous one, so the mutations are
+    // executed serially, in the order they are selected.
+    for (source, root_selection_set) in split_root_fields(merged, root_type, &selection_set) {
+      let mut deferred = vec![];
+      let planned = self.plan_selection_set(
+        merged,
+        source,
+        root_type,
+        &root_selection_set,
+        &mut vec![],
+        &mut deferred,
+      );
+      let operation = source_operation(
+        root_type,
+        operation_name.clone(),
+        request,
+        merged,
+        source,
+        planned.clone(),
+      );
+
+      let (source_data, source_errors) = self
+        .fetch(
+          plugin_manager.clone(),
+          request_context,
+          request,
+          source,
+          operation,
+        )
+        .await;
+
+      let Some(Value::Object(mut source_data)) = source_data else {
+        // The fields of a failed fetch are resolved to `null`, and the errors of the source are reported for each of them.
+        let mut keys = vec![];
+        response_keys(&root_selection_set, &mut keys);
+
+        for error in source_errors {
+          match error.path {
+            Some(_) => errors.push(error),
+            None => errors.extend(keys.iter().map(|key| GraphQLError {
+              path: Some(vec![key.clone()]),
+              ..error.clone()
+            })),
+          }
+        }
+
+        for key in keys {
+          results.entry(key).or_insert(Value::Null);
+        }
+
+        continue;
+      };
+
+      errors.extend(source_errors);
+      rename_typenames(
+        &mut source_data,
+        &planned,
+        &merged.sources[source].exposed_names,
+      );
+
+      let mut source_data = Value::Object(source_data);
+      errors.extend(
+        self
+          .resolve_deferred(
+            merged,
+            plugin_manager.clone(),
+            request_context,
+            request,
+            &mut source_data,
+            deferred,
+          )
+          .await,
+      );
+
+      if let Value::Object(source_data) = source_data {
+        merge_objects(&mut results, source_data);
+      }
+    }
+
+    // The root fields are returned in the order they are selected, whatever the source that resolved them.
+    let mut keys = vec![];
+    response_keys(&selection_set, &mut keys);
+    let data = keys
+      .into_iter()
+      .filter_map(|key| results.remove(&key).map(|value| (key, value)))
+      .collect::<Map<String, Value>>();
+
+    let mut data = Value::Object(data);
+    remove_internal_fields(&mut data);
+
+    let mut response = GraphQLResponse::new_data(data);
+
+    if !errors.is_empty() {
+      response.errors = Some(errors);
+    }
+
+    response
+  }
+}
+
+impl SourceRuntime for MergeSourceRuntime {
+  fn name(&self) -> &str {
+    &self.identifier
+  }
+
+  fn schema(&self) -> Option<Arc<ParsedGraphQLSchema>> {
+    self.merged_schema().map(|cached| cached.schema)
+  }
+
+  fn sdl(&self) -> Option<Arc<String>> {
+    self.merged_schema().map(|cached| cached.sdl)
+  }
+
+  /// The merged schema is loaded once the schemas of all merged sources are loaded. When a source fails to reload
+  /// its schema, its error is reported.
+  fn schema_health(&self) -> Option<SchemaHealth> {
+    let mut health = SchemaHealth::default();
+    let mut schema_hashes = vec![];
+    let mut is_loaded = true;
+
+    for (source, source_health) in self
+      .sources
+      .iter()
+      .filter_map(|source| source.schema_health().map(|health| (source, health)))
+    {
+      match &source_health.schema_hash {
+        Some(schema_hash) => schema_hashes.push(schema_hash.clone()),
+        None => is_loaded = false,
+      }
+
+      if health.last_error.is_none() && source_health.last_error.is_some() {
+        health.last_error = source_health
+          .last_error
+          .map(|e| format!("source \"{}\": {}", source.name(), e));
+        health.last_failed_at = source_health.last_failed_at;
+        health.last_loaded_at = source_health.last_loaded_at;
+      } else if health.last_error.is_none() {
+        health.last_loaded_at = health.last_loaded_at.max(source_health.last_loaded_at);
+      }
+    }
+
+    health.schema_hash = is_loaded.then(|| hash_signature(&schema_hashes.join("\n")));
+
+    Some(health)
+  }
+
+  fn merged_sources(&self) -> Vec<Arc<Box<dyn SourceRuntime>>> {
+    self.sources.clone()
+  }
+
+  fn shutdown(&self) {
+    for source in &self.sources {
+      source.shutdown();
+    }
+  }
+
+  fn subscribe_schema_changes(&self, listener: SchemaChangeListener) {
+    for source in &self.sources {
+      source.subscribe_schema_changes(listener.clone());
+    }
+  }
+
+  fn refresh_schema<'a>(&'a self, now: SystemTime) -> Pin<Box<(dyn Future<Output = ()> + 'a)>> {
+    Box::pin(async move {
+      for source in &self.sources {
+        source.refresh_schema(now).await;
+      }
+    })
+  }
+
+  fn execute<'a>(
+    &'a self,
+    plugin_manager: Arc<Box<dyn PluginManager>>,
+    request_context: &'a mut RequestExecutionContext,
+  ) -> Pin<Box<(dyn Future<Output = Result<GraphQLResponse, SourceError>> + 'a)>> {
+    Box::pin(wasm_polyfills::call_async(async move {
+      // The downstream request is replaced by the request of each source while they are executed, and restored after.
+      let request = match request_context.downstream_graphql_request.take() {
+        Some(request) => request,
+        None => {
+          return Ok(GraphQLResponse::new_error(
+            "source request isn't available at execution context!",
+          ))
+        }
+      };
+
+      let response = self
+        .execute_merged(plugin_manager, request_context, &request)
+        .await;
+      request_context.downstream_graphql_request = Some(request);
+
+      Ok(response)
+    }))
+  }
+}
+
+fn response_key(field: &QueryField) -> &str {
+  field.alias.as_ref().unwrap_or(&field.name)
+}
+
+fn internal_field(alias: &str, name: &str) -> QueryField {
+  Field {
+    position: Default::default(),
+    alias: Some(alias.to_string()),
+    name: name.to_string(),
+    arguments: vec![],
+    directives: vec![],
+    selection_set: SelectionSet {
+      span: Default::default(),
+      items: vec![],
+    },
+  }
+}
+
+fn add_deferred_selection(
+  deferred: &mut Vec<DeferredFetch>,
+  path: &[String],
+  type_name: &str,
+  source: usize,
+  selection: QuerySelection,
+) {
+  let existing = deferred
+    .iter_mut()
+    .find(|fetch| fetch.path == path && fetch.type_name == type_name && fetch.source == source);
+
+  match existing {
+    Some(fetch) => fetch.selections.push(selection),
+    None => deferred.push(DeferredFetch {
+      path: path.to_vec(),
+      type_name: type_name.to_string(),
+      source,
+      selections: vec![selection],
+    }),
+  }
+}
+
+/// Builds an operation for a source, with the variable definitions of the downstream request that it uses.
+fn source_operation(
+  root_type: &str,
+  name: Option<String>,
+  request: &ParsedGraphQLRequest,
+  merged: &MergedSchema,
+  source: usize,
+  selection_set: QuerySelectionSet,
+) -> OperationDefinition<'static, String> {
+  let variable_definitions = request_variable_definitions(request)
+    .iter()
+    .filter(|definition| selection_set_uses_variable(&selection_set, &definition.name))
+    .map(|definition| {
+      let mut definition = definition.clone();
+      rename_variable_type(&mut definition.var_type, merged, source);
+
+      definition
+    })
+    .collect();
+
+  match root_type {
+    "Mutation" => OperationDefinition::Mutation(Mutation {
+      position: Default::default(),
+      name,
+      variable_definitions,
+      directives: vec![],
+      selection_set,
+    }),
+    _ => OperationDefinition::Query(Query {
+      position: Default::default(),
+      name,
+      variable_definitions,
+      directives: vec![],
+      selection_set,
+    }),
+  }
+}
+
+fn request_variable_definitions(
+  request: &ParsedGraphQLRequest,
+) -> &[VariableDefinition<'static, String>] {
+  match request.executable_operation() {
+    Some(Definition::Operation(OperationDefinition::Query(query))) => &query.variable_definitions,
+    Some(Definition::Operation(OperationDefinition::Mutation(mutation))) => {
+      &mutation.variable_definitions
+    }
+    _ => &[],
+  }
+}
+
+/// Renames the type of a variable to its name in the source schema.
+fn rename_variable_type(t: &mut Type<'static, String>, merged: &MergedSchema, source: usize) {
+  match t {
+    Type::NamedType(name) => {
+      *name = merged.sources[source].original_name(name).to_string();
+    }
+    Type::ListType(inner) | Type::NonNullType(inner) => rename_variable_type(inner, merged, source),
+  }
+}
+
+fn operation_variables(operation: &OperationDefinition<'static, String>) -> HashSet<&str> {
+  match operation {
+    OperationDefinition::Query(query) => query
+      .variable_definitions
+      .iter()
+      .map(|definition| definition.name.as_str())
+      .collect(),
+    OperationDefinition::Mutation(mutation) => mutation
+      .variable_definitions
+      .iter()
+      .map(|definition| definition.name.as_str())
+      .collect(),
+    _ => HashSet::new(),
+  }
+}
+
+fn value_uses_variable(value: &GraphQLValue<'static, String>, name: &str) -> bool {
+  match value {
+    GraphQLValue::Variable(variable) => variable == name,
+    GraphQLValue::List(values) => values.iter().any(|value| value_uses_variable(value, name)),
+    GraphQLValue::Object(fields) => fields
+      .values()
+      .any(|value| value_uses_variable(value, name)),
+    _ => false,
+  }
+}
+
+fn directives_use_variable(
+  directives: &[graphql_parser::query::Directive<'static, String>],
+  name: &str,
+) -> bool {
+  directives.iter().any(|directive| {
+    directive
+      .arguments
+      .iter()
+      .any(|(_, value)| value_uses_variable(value, name))
+  })
+}
+
+fn selection_set_uses_variable(selection_set: &QuerySelectionSet, name: &str) -> bool {
+  selection_set.items.iter().any(|selection| match selection {
+    Selection::Field(field) => {
+      field
+        .arguments
+        .iter()
+        .any(|(_, value)| value_uses_variable(value, name))
+        || directives_use_variable(&field.directives, name)
+        || selection_set_uses_variable(&field.selection_set, name)
+    }
+    Selection::InlineFragment(fragment) => {
+      directives_use_variable(&fragment.directives, name)
+        || selection_set_uses_variable(&fragment.selection_set, name)
+    }
+    Selection::FragmentSpread(spread) => directives_use_variable(&spread.directives, name),
+  })
+}
+
+/// Replaces the fragment spreads with inline fragments, so a selection set can be split between sources.
+fn inline_fragments<'a>(
+  selection_set: &QuerySelectionSet,
+  request: &'a ParsedGraphQLRequest,
+  active_fragments: &mut Vec<&'a str>,
+) -> QuerySelectionSet {
+  let items = selection_set
+    .items
+    .iter()
+    .filter_map(|selection| match selection {
+      Selection::Field(field) => {
+        let mut field = field.clone();
+        field.selection_set = inline_fragments(&field.selection_set, request, active_fragments);
+
+        Some(Selection::Field(field))
+      }
+      Selection::InlineFragment(fragment) => {
+        let mut fragment = fragment.clone();
+        fragment.selection_set =
+          inline_fragments(&fragment.selection_set, request, active_fragments);
+
+        Some(Selection::InlineFragment(fragment))
+      }
+      Selection::FragmentSpread(spread) => {
+        let fragment = find_fragment(request, &spread.fragment_name)?;
+
+        if active_fragments.contains(&fragment.name.as_str()) {
+          return None;
+        }
+
+        active_fragments.push(&fragment.name);
+        let selection_set = inline_fragments(&fragment.selection_set, request, active_fragments);
+        active_fragments.pop();
+
+        Some(Selection::InlineFragment(InlineFragment {
+          position: spread.position,
+          type_condition: Some(fragment.type_condition.clone()),
+          directives: spread.directives.clone(),
+          selection_set,
+        }))
+      }
+    })
+    .collect();
+
+  SelectionSet {
+    span: selection_set.span,
+    items,
+  }
+}
+
+fn find_fragment<'a>(
+  request: &'a ParsedGraphQLRequest,
+  name: &str,
+) -> Option<&'a FragmentDefinition<'static, String>> {
+  request
+    .parsed_operation
+    .definitions
+    .iter()
+    .find_map(|definition| match definition {
+      Definition::Fragment(fragment) if fragment.name == name => Some(fragment),
+      _ => None,
+    })
+}
+
+/// The response keys of the `__typename` fields selected on the root type, that are resolved by the gateway.
+fn root_typename_keys(selection_set: &QuerySelectionSet) -> Vec<String> {
+  selection_set
+    .items
+    .iter()
+    .flat_map(|selection| match selection {
+      Selection::Field(field) if field.name == "__typename" => {
+        vec![response_key(field).to_string()]
+      }
+      Selection::InlineFragment(fragment) => root_typename_keys(&fragment.selection_set),
+      _ => vec![],
+    })
+    .collect()
+}
+
+/// Splits the root fields into consecutive runs of fields resolved by the same source, in the order they are selected.
+/// Inline fragments are kept in each run they contribute to.
+fn split_root_fields(
+  merged: &MergedSchema,
+  root_type: &str,
+  selection_set: &QuerySelectionSet,
+) -> Vec<(usize, QuerySelectionSet)> {
+  let mut runs: Vec<(usize, QuerySelectionSet)> = vec![];
+  let mut add = |source: usize, selection: QuerySelection| match runs.last_mut() {
+    Some((run_source, run)) if *run_source == source => run.items.push(selection),
+    _ => runs.push((
+      source,
+      SelectionSet {
+        span: selection_set.span,
+        items: vec![selection],
+      },
+    )),
+  };
+
+  for selection in &selection_set.items {
+    match selection {
+      Selection::Field(field) => {
+        if let Some(source) = merged.root_field_source(root_type, &field.name) {
+          add(source, selection.clone());
+        }
+      }
+      Selection::InlineFragment(fragment) => {
+        for (source, run) in split_root_fields(merged, root_type, &fragment.selection_set) {
+          add(
+            source,
+            Selection::InlineFragment(InlineFragment {
+              selection_set: run,
+              ..fragment.clone()
+            }),
+          );
+        }
+      }
+      Selection::FragmentSpread(_) => {}
+    }
+  }
+
+  runs
+}
+
+/// The response keys of the fields of a selection set, in the order they are selected, without duplicates.
+fn response_keys(selection_set: &QuerySelectionSet, keys: &mut Vec<String>) {
+  for selection in &selection_set.items {
+    match selection {
+      Selection::Field(field) => {
+        if !keys.iter().any(|key| key == response_key(field)) {
+          keys.push(response_key(field).to_string());
+        }
+      }
+      Selection::InlineFragment(fragment) => response_keys(&fragment.selection_set, keys),
+      Selection::FragmentSpread(_) => {}
+    }
+  }
+}
+
+/// Adds the fields of `source` to `target`, merging the objects selected more than once.
+fn merge_objects(target: &mut Map<String, Value>, source: Map<String, Value>) {
+  for (key, value) in source {
+    match (target.get_mut(&key), value) {
+      (Some(Value::Object(target)), Value::Object(value)) => merge_objects(target, value),
+      (Some(Value::Array(target)), Value::Array(values)) if target.len() == values.len() => {
+        for (target, value) in target.iter_mut().zip(values) {
+          match (target, value) {
+            (Value::Object(target), Value::Object(value)) => merge_objects(target, value),
+            (target, value) => *target = value,
+          }
+        }
+      }
+      (_, value) => {
+        target.insert(key, value);
+      }
+    }
+  }
+}
+
+/// Renames the values of the `__typename` fields to the names of the merged schema.
+fn rename_typenames(
+  object: &mut Map<String, Value>,
+  selection_set: &QuerySelectionSet,
+  names: &HashMap<String, String>,
+) {
+  for selection in &selection_set.items {
+    match selection {
+      Selection::Field(field) => match object.get_mut(response_key(field)) {
+        Some(Value::String(typename)) if field.name == "__typename" => {
+          if let Some(exposed) = names.get(typename.as_str()) {
+            *typename = exposed.clone();
+          }
+        }
+        Some(value) if !field.selection_set.items.is_empty() => {
+          rename_value_typenames(value, &field.selection_set, names)
+        }
+        _ => {}
+      },
+      Selection::InlineFragment(fragment) => {
+        rename_typenames(object, &fragment.selection_set, names)
+      }
+      Selection::FragmentSpread(_) => {}
+    }
+  }
+}
+
+fn rename_value_typenames(
+  value: &mut Value,
+  selection_set: &QuerySelectionSet,
+  names: &HashMap<String, String>,
+) {
+  match value {
+    Value::Object(object) => rename_typenames(object, selection_set, names),
+    Value::Array(items) => {
+      for item in items {
+        rename_value_typenames(item, selection_set, names);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// Collects the objects of the given type at the end of a path of response keys.
+fn collect_objects<'a>(
+  value: &'a mut Value,
+  path: &[String],
+  type_name: &str,
+  objects: &mut Vec<&'a mut Map<String, Value>>,
+) {
+  match value {
+    Value::Array(items) => {
+      for item in items {
+        collect_objects(item, path, type_name, objects);
+      }
+    }
+    Value::Object(object) => match path.split_first() {
+      Some((key, rest)) => {
+        if let Some(value) = object.get_mut(key) {
+          collect_objects(value, rest, type_name, objects);
+        }
+      }
+      None => {
+        if object.get(MERGE_TYPENAME_ALIAS).and_then(|v| v.as_str()) == Some(type_name) {
+          objects.push(object);
+        }
+      }
+    },
+    _ => {}
+  }
+}
+
+fn remove_internal_fields(value: &mut Value) {
+  match value {
+    Value::Object(object) => {
+      object.remove(MERGE_KEY_ALIAS);
+      object.remove(MERGE_TYPENAME_ALIAS);
+      object.values_mut().for_each(remove_internal_fields);
+    }
+    Value::Array(items) => items.iter_mut().for_each(remove_internal_fields),
+    _ => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{collections::VecDeque, sync::Mutex};
+
+  use conductor_common::{
+    graphql::parse_graphql_schema,
+    http::{ConductorHttpRequest, StatusCode},
+  };
+  use serde_json::json;
+
+  use super::*;
+  use crate::plugin_manager::PluginManagerImpl;
+
+  /// A source that records the requests it receives, and replies with the given responses in order. A `None`
+  /// response fails the request.
+  #[derive(Debug)]
+  struct TestSource {
+    schema: Arc<ParsedGraphQLSchema>,
+    responses: Mutex<VecDeque<Option<Value>>>,
+    requests: Arc<Mutex<Vec<GraphQLRequest>>>,
+  }
+
+  impl SourceRuntime for TestSource {
+    fn name(&self) -> &str {
+      "test"
+    }
+
+    fn schema(&self) -> Option<Arc<ParsedGraphQLSchema>> {
+      Some(self.schema.clone())
+    }
+
+    fn sdl(&self) -> Option<Arc<String>> {
+      None
+    }
+
+    fn execute<'a>(
+      &'a self,
+      _plugin_manager: Arc<Box<dyn PluginManager>>,
+      request_context: &'a mut RequestExecutionContext,
+    ) -> Pin<Box<(dyn Future<Output = Result<GraphQLResponse, SourceError>> + 'a)>> {
+      let request = request_context.downstream_graphql_request.as_ref().unwrap();
+      self.requests.lock().unwrap().push(request.request.clone());
+      let response = self.responses.lock().unwrap().pop_front().unwrap();
+
+      Box::pin(async move {
+        response
+          .map(GraphQLResponse::new_data)
+          .ok_or(SourceError::UnexpectedHTTPStatusError(
+            StatusCode::BAD_GATEWAY,
+          ))
+      })
+    }
+  }
+
+  const USERS: &str = "
+    type Query { me: User user(id: ID!): User }
+    type Mutation { rename(name: String!): User }
+    type User { id: ID! name: String }
+  ";
+  const REVIEWS: &str = "
+    type Query { reviews(first: Int): [Review] userById(id: ID!): User }
+    type Mutation { addReview(body: String!): Review }
+    type Review { id: ID! body: String }
+    type User { id: ID! reviews: [Review] }
+  ";
+
+  type Requests = Arc<Mutex<Vec<GraphQLRequest>>>;
+
+  fn test_source(
+    sdl: &str,
+    responses: Vec<Option<Value>>,
+  ) -> (Arc<Box<dyn SourceRuntime>>, Requests) {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let source = TestSource {
+      schema: Arc::new(parse_graphql_schema(sdl).unwrap()),
+      responses: Mutex::new(responses.into()),
+      requests: requests.clone(),
+    };
+
+    (Arc::new(Box::new(source)), requests)
+  }
+
+  fn execute(
+    users: Vec<Option<Value>>,
+    reviews: Vec<Option<Value>>,
+    operation: &str,
+    variables: Value,
+  ) -> (GraphQLResponse, Vec<GraphQLRequest>, Vec<GraphQLRequest>) {
+    let (users, users_requests) = test_source(USERS, users);
+    let (reviews, reviews_requests) = test_source(REVIEWS, reviews);
+    let config = serde_json::from_value::<MergeSourceConfig>(json!({
+      "sources": [{ "id": "users" }, { "id": "reviews", "type_prefix": "Reviews" }],
+      "type_merging": [{
+        "type_name": "User",
+        "key": "id",
+        "resolvers": [
+          { "source": "users", "field": "user" },
+          { "source": "reviews", "field": "userById" }
+        ]
+      }]
+    }))
+    .unwrap();
+    let runtime = MergeSourceRuntime::new("merged".to_string(), config, vec![users, reviews]);
+
+    let mut request_context = RequestExecutionContext::new(ConductorHttpRequest::default());
+    request_context.downstream_graphql_request = Some(
+      ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
+        operation: operation.to_string(),
+        operation_name: None,
+        variables: variables.as_object().cloned(),
+        extensions: None,
+      })
+      .unwrap(),
+    );
+    let plugin_manager: Arc<Box<dyn PluginManager>> =
+      Arc::new(Box::new(PluginManagerImpl::default()));
+
+    let response =
+      futures::executor::block_on(runtime.execute(plugin_manager, &mut request_context)).unwrap();
+    let users_requests = users_requests.lock().unwrap().clone();
+    let reviews_requests = reviews_requests.lock().unwrap().clone();
+
+    (response, users_requests, reviews_requests)
+  }
+
+  #[test]
+  fn should_fetch_merged_type_fields_and_forward_variables() {
+    let (response, users_requests, reviews_requests) = execute(
+      vec![Some(
+        json!({ "me": { "name": "Ann", "__merge_key": "1", "__merge_typename": "User" } }),
+      )],
+      vec![
+        Some(json!({ "_0": { "reviews": [{ "body": "great" }] } })),
+        Some(json!({ "reviews": [{ "__typename": "Review", "body": "ok" }] })),
+      ],
+      "query Feed($first: Int) { me { name reviews { body } } reviews(first: $first) { __typename body } }",
+      json!({ "first": 2 }),
+    );
+
+    assert!(response.errors.is_none());
+    assert_eq!(
+      response.data,
+      Some(json!({
+        "me": { "name": "Ann", "reviews": [{ "body": "great" }] },
+        "reviews": [{ "__typename": "ReviewsReview", "body": "ok" }]
+      }))
+    );
+
+    // The key of the user is requested from the first source, to fetch its reviews from the second one.
+    assert_eq!(users_requests.len(), 1);
+    assert!(users_requests[0].operation.contains("__merge_key: id"));
+    assert!(!users_requests[0].operation.contains("reviews"));
+    assert!(!users_requests[0].operation.contains("$first"));
+    assert_eq!(users_requests[0].variables, Some(Map::new()));
+
+    assert_eq!(reviews_requests.len(), 2);
+    assert!(reviews_requests[0]
+      .operation
+      .contains("_0: userById(id: \"1\")"));
+    assert!(reviews_requests[1]
+      .operation
+      .contains("query Feed($first: Int)"));
+    assert_eq!(
+      reviews_requests[1].variables,
+      json!({ "first": 2 }).as_object().cloned()
+    );
+  }
+
+  #[test]
+  fn should_execute_mutations_in_order_and_null_failed_fields() {
+    let (response, users_requests, reviews_requests) = execute(
+      vec![
+        Some(json!({ "first": { "name": "a" } })),
+        Some(json!({ "second": { "name": "c" } })),
+      ],
+      vec![None],
+      r#"mutation { first: rename(name: "a") { name } review: addReview(body: "b") { body } second: rename(name: "c") { name } }"#,
+      Value::Null,
+    );
+
+    assert_eq!(
+      response.data,
+      Some(json!({ "first": { "name": "a" }, "review": null, "second": { "name": "c" } }))
+    );
+
+    let errors = response.errors.unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, Some(vec!["review".to_string()]));
+
+    assert_eq!(users_requests.len(), 2);
+    assert!(users_requests[0].operation.contains("first: rename"));
+    assert!(!users_requests[0].operation.contains("second"));
+    assert!(users_requests[1].operation.contains("second: rename"));
+    assert_eq!(reviews_requests.len(), 1);
+  }
+}
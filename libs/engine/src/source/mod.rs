@@ -1,3 +1,7 @@
 pub mod federation_source;
+pub mod filtered_source;
 pub mod graphql_source;
+pub mod merge_source;
 pub mod mock_source;
+pub mod openapi_source;
+pub mod schema_mock_source;
@@ -0,0 +1,869 @@
+use std::{collections::HashSet, future::Future, pin::Pin, sync::Arc};
+
+use conductor_common::{
+  execute::RequestExecutionContext,
+  graphql::{
+    validate_graphql_operation, GraphQLError, GraphQLResponse, ParsedGraphQLRequest,
+    ParsedGraphQLSchema,
+  },
+  http::{ConductorHttpRequest, HeaderName, HeaderValue, HttpHeadersMap, Url, CONTENT_TYPE},
+  plugin_manager::PluginManager,
+  source::{GraphQLSourceInitError, SourceError, SourceRuntime},
+};
+use conductor_config::{OpenApiSourceConfig, OpenApiSpecSource};
+use graphql_parser::query::{
+  Definition, Directive, Field, FragmentDefinition, OperationDefinition, Selection, SelectionSet,
+  TypeCondition, Value as GraphQLValue,
+};
+use minitrace_reqwest::{traced_reqwest, TracedHttpClient};
+use reqwest::Method;
+use serde_json::{Map, Value};
+use tracing::debug;
+
+use crate::{
+  http_client::create_upstream_http_client,
+  openapi::{
+    build_openapi_schema, OpenApiOperation, OpenApiSchema, ParameterLocation, BODY_ARGUMENT,
+  },
+};
+
+type QuerySelectionSet = SelectionSet<'static, String>;
+type QueryField = Field<'static, String>;
+
+/// A source that exposes a REST API, described by an OpenAPI document, as a GraphQL schema.
+#[derive(Debug)]
+pub struct OpenApiSourceRuntime {
+  pub fetcher: TracedHttpClient,
+  pub config: OpenApiSourceConfig,
+  pub identifier: String,
+  openapi: OpenApiSchema,
+  schema: Arc<ParsedGraphQLSchema>,
+  sdl: Arc<String>,
+  endpoint: Url,
+}
+
+impl OpenApiSourceRuntime {
+  pub async fn new(
+    identifier: String,
+    config: OpenApiSourceConfig,
+  ) -> Result<Self, GraphQLSourceInitError> {
+    tracing::info!(
+      "Initializing source '{}' of type 'openapi' with config: {:?}",
+      identifier,
+      config
+    );
+
+    let client = create_upstream_http_client(config.http_client.as_ref())
+      .map_err(|source| GraphQLSourceInitError::FetcherError { source })?;
+    let fetcher = traced_reqwest(client);
+
+    let document = match &config.spec {
+      OpenApiSpecSource::File { file } => file.contents.clone(),
+      OpenApiSpecSource::Remote { url, headers } => fetcher
+        .request(Method::GET, url)
+        .headers(headers.clone())
+        .send()
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|response| response.error_for_status().map_err(anyhow::Error::from))
+        .map_err(|e| GraphQLSourceInitError::SourceInitFailed {
+          source: anyhow::anyhow!("failed to fetch OpenAPI document from \"{}\": {}", url, e),
+        })?
+        .text()
+        .await
+        .map_err(|e| GraphQLSourceInitError::SourceInitFailed { source: e.into() })?,
+    };
+
+    let openapi = build_openapi_schema(&document)
+      .map_err(|e| GraphQLSourceInitError::SourceInitFailed { source: e.into() })?;
+
+    let endpoint = config
+      .endpoint
+      .as_deref()
+      .or(openapi.server_url.as_deref())
+      .ok_or_else(|| GraphQLSourceInitError::SourceInitFailed {
+        source: anyhow::anyhow!(
+          "the OpenAPI document does not declare any server, the \"endpoint\" field is required"
+        ),
+      })?;
+    let endpoint = Url::parse(endpoint)
+      .ok()
+      .filter(|url| !url.cannot_be_a_base())
+      .ok_or_else(|| GraphQLSourceInitError::SourceInitFailed {
+        source: anyhow::anyhow!("invalid REST API endpoint \"{}\"", endpoint),
+      })?;
+
+    Ok(Self {
+      fetcher,
+      config,
+      identifier,
+      schema: Arc::new(openapi.schema.clone()),
+      sdl: Arc::new(openapi.sdl.clone()),
+      openapi,
+      endpoint,
+    })
+  }
+
+  async fn execute_operation(
+    &self,
+    plugin_manager: Arc<Box<dyn PluginManager>>,
+    request_context: &mut RequestExecutionContext,
+    request: &ParsedGraphQLRequest,
+  ) -> Result<GraphQLResponse, SourceError> {
+    let validation_errors = validate_graphql_operation(&self.schema, &request.parsed_operation);
+
+    if !validation_errors.is_empty() {
+      return Ok(validation_errors.into());
+    }
+
+    let (root_type, selection_set) = match request.executable_operation() {
+      Some(Definition::Operation(OperationDefinition::Query(query))) => {
+        ("Query", &query.selection_set)
+      }
+      Some(Definition::Operation(OperationDefinition::SelectionSet(selection_set))) => {
+        ("Query", selection_set)
+      }
+      Some(Definition::Operation(OperationDefinition::Mutation(mutation))) => {
+        ("Mutation", &mutation.selection_set)
+      }
+      Some(Definition::Operation(OperationDefinition::Subscription(_))) => {
+        return Ok(GraphQLResponse::new_error(
+          "subscriptions are not supported by OpenAPI sources",
+        ));
+      }
+      _ => {
+        return Ok(GraphQLResponse::new_error(
+          "failed to locate the operation to execute",
+        ))
+      }
+    };
+
+    let executor = SelectionExecutor {
+      openapi: &self.openapi,
+      request,
+    };
+    let mut data = Map::new();
+    let mut errors = vec![];
+
+    // Root fields are resolved one after the other, so mutations are executed in the order they are selected.
+    for (key, fields) in executor.collect_fields(root_type, &[selection_set]) {
+      let field = fields[0];
+
+      if field.name == "__typename" {
+        data.insert(key, Value::String(root_type.to_string()));
+        continue;
+      }
+
+      let Some(operation) = self.openapi.operation(root_type, &field.name) else {
+        data.insert(key, Value::Null);
+        continue;
+      };
+
+      let value = match self
+        .call_operation(
+          plugin_manager.clone(),
+          request_context,
+          &executor,
+          operation,
+          field,
+        )
+        .await?
+      {
+        Ok(response) => {
+          let selection_sets = fields.iter().map(|f| &f.selection_set).collect::<Vec<_>>();
+
+          executor.project(&response, &operation.type_name, &selection_sets)
+        }
+        Err(message) => {
+          let mut error = GraphQLError::new(&message);
+          error.path = Some(vec![key.clone()]);
+          errors.push(error);
+
+          Value::Null
+        }
+      };
+
+      data.insert(key, value);
+    }
+
+    let mut response = GraphQLResponse::new_data(Value::Object(data));
+
+    if !errors.is_empty() {
+      response.errors = Some(errors);
+    }
+
+    Ok(response)
+  }
+
+  /// Calls the HTTP endpoint of an operation. Failures that only affect this field are returned as an error message.
+  async fn call_operation(
+    &self,
+    plugin_manager: Arc<Box<dyn PluginManager>>,
+    request_context: &mut RequestExecutionContext,
+    executor: &SelectionExecutor<'_>,
+    operation: &OpenApiOperation,
+    field: &QueryField,
+  ) -> Result<Result<Value, String>, SourceError> {
+    let argument = |name: &str| {
+      field
+        .arguments
+        .iter()
+        .find(|(argument, _)| argument == name)
+        .map(|(_, value)| executor.resolve_value(value))
+        .filter(|value| !value.is_null())
+    };
+
+    let mut url = self.endpoint.clone();
+    let mut path_values = vec![];
+    let mut query_pairs = vec![];
+    let mut headers = HttpHeadersMap::new();
+
+    for parameter in &operation.parameters {
+      let Some(value) = argument(&parameter.argument) else {
+        // Path parameters are always required, the URL can't be built without them.
+        if parameter.location == ParameterLocation::Path {
+          return Ok(Err(format!(
+            "missing value for path parameter \"{}\"",
+            parameter.name
+          )));
+        }
+
+        continue;
+      };
+
+      match parameter.location {
+        ParameterLocation::Path => {
+          path_values.push((format!("{{{}}}", parameter.name), parameter_value(&value)));
+        }
+        ParameterLocation::Query => match value {
+          Value::Array(items) => query_pairs.extend(
+            items
+              .iter()
+              .map(|item| (parameter.name.clone(), parameter_value(item))),
+          ),
+          value => query_pairs.push((parameter.name.clone(), parameter_value(&value))),
+        },
+        ParameterLocation::Header => {
+          match (
+            HeaderName::from_bytes(parameter.name.as_bytes()),
+            HeaderValue::from_str(&parameter_value(&value)),
+          ) {
+            (Ok(name), Ok(value)) => {
+              headers.insert(name, value);
+            }
+            _ => {
+              return Ok(Err(format!(
+                "invalid value for header \"{}\"",
+                parameter.name
+              )))
+            }
+          }
+        }
+      }
+    }
+
+    // The parameters are replaced in each segment of the path template, and the segments are percent-encoded, so a
+    // parameter value can't change the structure of the path.
+    if let Ok(mut segments) = url.path_segments_mut() {
+      segments.pop_if_empty().extend(
+        operation
+          .path
+          .split('/')
+          .filter(|segment| !segment.is_empty())
+          .map(|segment| {
+            path_values
+              .iter()
+              .fold(segment.to_string(), |segment, (placeholder, value)| {
+                segment.replace(placeholder, value)
+              })
+          }),
+      );
+    }
+
+    if !query_pairs.is_empty() {
+      url.query_pairs_mut().extend_pairs(query_pairs);
+    }
+
+    let body = match (&operation.body_type, argument(BODY_ARGUMENT)) {
+      (Some(body_type), Some(value)) => {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        executor.request_body(value, body_type).to_string().into()
+      }
+      _ => Default::default(),
+    };
+
+    for name in &self.config.forward_headers {
+      if let Some(value) = request_context.downstream_http_request.headers.get(name) {
+        if let Ok(name) = HeaderName::from_bytes(name.as_bytes()) {
+          headers.insert(name, value.clone());
+        }
+      }
+    }
+
+    headers.extend(self.config.headers.clone());
+
+    let mut conductor_http_request = ConductorHttpRequest {
+      query_string: url.query().unwrap_or_default().to_string(),
+      uri: url.to_string(),
+      method: operation.method.clone(),
+      headers,
+      body,
+    };
+
+    plugin_manager
+      .on_upstream_http_request(request_context, &mut conductor_http_request)
+      .await;
+
+    if request_context.is_short_circuit() {
+      return Err(SourceError::ShortCircuit);
+    }
+
+    debug!(
+      "dispatching upstream http request from the following input: {:?}",
+      conductor_http_request
+    );
+
+    let upstream_response = self
+      .fetcher
+      .request(conductor_http_request.method, conductor_http_request.uri)
+      .headers(conductor_http_request.headers)
+      .body(conductor_http_request.body)
+      .send()
+      .await;
+
+    plugin_manager
+      .on_upstream_http_response(request_context, &upstream_response)
+      .await;
+
+    let response = match upstream_response {
+      Ok(response) => response,
+      Err(e) => return Ok(Err(format!("failed to call the REST API: {}", e))),
+    };
+
+    let status = response.status();
+    let body = match response.bytes().await {
+      Ok(body) => body,
+      Err(e) => return Ok(Err(e.to_string())),
+    };
+
+    if !status.is_success() {
+      return Ok(Err(format!(
+        "the REST API responded with HTTP status {}",
+        status
+      )));
+    }
+
+    if body.is_empty() {
+      return Ok(Ok(Value::Null));
+    }
+
+    Ok(
+      serde_json::from_slice(&body)
+        .map_err(|e| format!("failed to parse the response of the REST API: {}", e)),
+    )
+  }
+}
+
+impl SourceRuntime for OpenApiSourceRuntime {
+  fn name(&self) -> &str {
+    &self.identifier
+  }
+
+  fn schema(&self) -> Option<Arc<ParsedGraphQLSchema>> {
+    Some(self.schema.clone())
+  }
+
+  fn sdl(&self) -> Option<Arc<String>> {
+    Some(self.sdl.clone())
+  }
+
+  fn execute<'a>(
+    &'a self,
+    plugin_manager: Arc<Box<dyn PluginManager>>,
+    request_context: &'a mut RequestExecutionContext,
+  ) -> Pin<Box<(dyn Future<Output = Result<GraphQLResponse, SourceError>> + 'a)>> {
+    Box::pin(wasm_polyfills::call_async(async move {
+      let request = match request_context.downstream_graphql_request.take() {
+        Some(request) => request,
+        None => {
+          return Ok(GraphQLResponse::new_error(
+            "source request isn't available at execution context!",
+          ))
+        }
+      };
+
+      let response = self
+        .execute_operation(plugin_manager, request_context, &request)
+        .await;
+      request_context.downstream_graphql_request = Some(request);
+
+      response
+    }))
+  }
+}
+
+/// Resolves the selection sets of an operation against the JSON responses of the REST API.
+struct SelectionExecutor<'a> {
+  openapi: &'a OpenApiSchema,
+  request: &'a ParsedGraphQLRequest,
+}
+
+impl<'a> SelectionExecutor<'a> {
+  /// Groups the selected fields by response key, following fragments and `@skip`/`@include`.
+  fn collect_fields(
+    &self,
+    type_name: &str,
+    selection_sets: &[&'a QuerySelectionSet],
+  ) -> Vec<(String, Vec<&'a QueryField>)> {
+    let mut result = vec![];
+    let mut visited_fragments = HashSet::new();
+
+    for selection_set in selection_sets {
+      self.collect_fields_into(
+        type_name,
+        selection_set,
+        &mut visited_fragments,
+        &mut result,
+      );
+    }
+
+    result
+  }
+
+  fn collect_fields_into(
+    &self,
+    type_name: &str,
+    selection_set: &'a QuerySelectionSet,
+    visited_fragments: &mut HashSet<&'a str>,
+    result: &mut Vec<(String, Vec<&'a QueryField>)>,
+  ) {
+    for selection in &selection_set.items {
+      match selection {
+        Selection::Field(field) if self.is_included(&field.directives) => {
+          let key = field.alias.as_ref().unwrap_or(&field.name);
+
+          match result.iter_mut().find(|(k, _)| k == key) {
+            Some((_, fields)) => fields.push(field),
+            None => result.push((key.clone(), vec![field])),
+          }
+        }
+        Selection::InlineFragment(fragment)
+          if self.is_included(&fragment.directives)
+            && applies_to(fragment.type_condition.as_ref(), type_name) =>
+        {
+          self.collect_fields_into(
+            type_name,
+            &fragment.selection_set,
+            visited_fragments,
+            result,
+          );
+        }
+        Selection::FragmentSpread(spread)
+          if self.is_included(&spread.directives)
+            && visited_fragments.insert(spread.fragment_name.as_str()) =>
+        {
+          if let Some(fragment) = self.find_fragment(&spread.fragment_name) {
+            if applies_to(Some(&fragment.type_condition), type_name) {
+              self.collect_fields_into(
+                type_name,
+                &fragment.selection_set,
+                visited_fragments,
+                result,
+              );
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  fn find_fragment(&self, name: &str) -> Option<&'a FragmentDefinition<'static, String>> {
+    self
+      .request
+      .parsed_operation
+      .definitions
+      .iter()
+      .find_map(|definition| match definition {
+        Definition::Fragment(fragment) if fragment.name == name => Some(fragment),
+        _ => None,
+      })
+  }
+
+  /// Checks the `@skip` and `@include` directives of a selection.
+  fn is_included(&self, directives: &[Directive<'static, String>]) -> bool {
+    directives.iter().all(|directive| {
+      let condition = directive
+        .arguments
+        .iter()
+        .find(|(name, _)| name == "if")
+        .and_then(|(_, value)| self.resolve_value(value).as_bool());
+
+      match directive.name.as_str() {
+        "skip" => condition != Some(true),
+        "include" => condition != Some(false),
+        _ => true,
+      }
+    })
+  }
+
+  /// Converts an argument value to JSON, using the request variables for variable references.
+  fn resolve_value(&self, value: &GraphQLValue<'static, String>) -> Value {
+    match value {
+      GraphQLValue::Variable(name) => self
+        .request
+        .request
+        .variables
+        .as_ref()
+        .and_then(|variables| variables.get(name))
+        .cloned()
+        .unwrap_or(Value::Null),
+      GraphQLValue::Int(n) => n.as_i64().map(Value::from).unwrap_or(Value::Null),
+      GraphQLValue::Float(f) => Value::from(*f),
+      GraphQLValue::String(s) | GraphQLValue::Enum(s) => Value::String(s.clone()),
+      GraphQLValue::Boolean(b) => Value::Bool(*b),
+      GraphQLValue::Null => Value::Null,
+      GraphQLValue::List(items) => items.iter().map(|item| self.resolve_value(item)).collect(),
+      GraphQLValue::Object(fields) => Value::Object(
+        fields
+          .iter()
+          .map(|(name, value)| (name.clone(), self.resolve_value(value)))
+          .collect(),
+      ),
+    }
+  }
+
+  /// Renames the fields of an input value to the properties of the request body.
+  fn request_body(&self, value: Value, type_name: &str) -> Value {
+    match value {
+      Value::Array(items) => items
+        .into_iter()
+        .map(|item| self.request_body(item, type_name))
+        .collect(),
+      Value::Object(fields) => Value::Object(
+        fields
+          .into_iter()
+          .map(
+            |(name, value)| match self.openapi.object_field(type_name, &name) {
+              Some(field) => (
+                field.property.clone(),
+                self.request_body(value, &field.type_name),
+              ),
+              None => (name, value),
+            },
+          )
+          .collect(),
+      ),
+      value => value,
+    }
+  }
+
+  /// Selects the requested fields from a JSON response.
+  fn project(
+    &self,
+    value: &Value,
+    type_name: &str,
+    selection_sets: &[&'a QuerySelectionSet],
+  ) -> Value {
+    match value {
+      Value::Array(items) => items
+        .iter()
+        .map(|item| self.project(item, type_name, selection_sets))
+        .collect(),
+      Value::Object(object) if selection_sets.iter().any(|s| !s.items.is_empty()) => {
+        let mut result = Map::new();
+
+        for (key, fields) in self.collect_fields(type_name, selection_sets) {
+          let field = fields[0];
+
+          if field.name == "__typename" {
+            result.insert(key, Value::String(type_name.to_string()));
+            continue;
+          }
+
+          let value = match self.openapi.object_field(type_name, &field.name) {
+            Some(object_field) => match object.get(&object_field.property) {
+              Some(value) => {
+                let selection_sets = fields.iter().map(|f| &f.selection_set).collect::<Vec<_>>();
+
+                self.project(value, &object_field.type_name, &selection_sets)
+              }
+              None => Value::Null,
+            },
+            None => Value::Null,
+          };
+
+          result.insert(key, value);
+        }
+
+        Value::Object(result)
+      }
+      value => value.clone(),
+    }
+  }
+}
+
+fn applies_to(type_condition: Option<&TypeCondition<'static, String>>, type_name: &str) -> bool {
+  match type_condition {
+    Some(TypeCondition::On(name)) => name == type_name,
+    None => true,
+  }
+}
+
+/// The value of a path, query or header parameter.
+fn parameter_value(value: &Value) -> String {
+  match value {
+    Value::String(s) => s.clone(),
+    value => value.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use conductor_common::{
+    graphql::GraphQLRequest, http::ConductorHttpRequest, serde_utils::LocalFileReference,
+  };
+  use httpmock::{Method::GET, Method::POST, MockServer};
+  use serde_json::json;
+
+  use super::*;
+  use crate::plugin_manager::PluginManagerImpl;
+
+  const DOCUMENT: &str = r##"
+openapi: 3.0.3
+paths:
+  /pets/{petId}:
+    get:
+      operationId: getPet
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: integer
+        - name: x-tenant
+          in: header
+          schema:
+            type: string
+      responses:
+        "200":
+          description: The pet
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+  /pets:
+    get:
+      operationId: listPets
+      parameters:
+        - name: tags
+          in: query
+          schema:
+            type: array
+            items:
+              type: string
+      responses:
+        "200":
+          description: The pets
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: "#/components/schemas/Pet"
+    post:
+      operationId: createPet
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Pet"
+      responses:
+        "201":
+          description: The created pet
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        id:
+          type: integer
+        name:
+          type: string
+        photo-urls:
+          type: array
+          items:
+            type: string
+"##;
+
+  async fn execute(
+    server: &MockServer,
+    operation: &str,
+    variables: Value,
+    headers: &[(&'static str, &'static str)],
+  ) -> GraphQLResponse {
+    let runtime = OpenApiSourceRuntime::new(
+      "pets".to_string(),
+      OpenApiSourceConfig {
+        spec: OpenApiSpecSource::File {
+          file: LocalFileReference {
+            path: "openapi.yaml".to_string(),
+            contents: DOCUMENT.to_string(),
+          },
+        },
+        endpoint: Some(server.url("/v1")),
+        forward_headers: vec!["authorization".to_string()],
+        headers: HttpHeadersMap::new(),
+        http_client: None,
+      },
+    )
+    .await
+    .unwrap();
+
+    let mut downstream_http_request = ConductorHttpRequest::default();
+
+    for (name, value) in headers {
+      downstream_http_request
+        .headers
+        .insert(*name, HeaderValue::from_static(value));
+    }
+
+    let mut request_context = RequestExecutionContext::new(downstream_http_request);
+    request_context.downstream_graphql_request = Some(
+      ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
+        operation: operation.to_string(),
+        operation_name: None,
+        variables: variables.as_object().cloned(),
+        extensions: None,
+      })
+      .unwrap(),
+    );
+
+    runtime
+      .execute(
+        Arc::new(Box::new(PluginManagerImpl::default())),
+        &mut request_context,
+      )
+      .await
+      .unwrap()
+  }
+
+  #[tokio::test]
+  async fn should_map_path_header_and_forwarded_headers() {
+    let server = MockServer::start_async().await;
+    let mock = server
+      .mock_async(|when, then| {
+        when
+          .method(GET)
+          .path("/v1/pets/7")
+          .header("x-tenant", "acme")
+          .header("authorization", "Bearer token")
+          .header_missing("cookie");
+        then
+          .status(200)
+          .json_body(json!({ "id": 7, "name": "Rex", "photo-urls": ["rex.png"] }));
+      })
+      .await;
+
+    let response = execute(
+      &server,
+      "query($id: Int!) { pet: getPet(petId: $id, x_tenant: \"acme\") { name photo_urls } }",
+      json!({ "id": 7 }),
+      &[("authorization", "Bearer token"), ("cookie", "session=1")],
+    )
+    .await;
+
+    mock.assert_async().await;
+    assert!(response.errors.is_none());
+    assert_eq!(
+      response.data,
+      Some(json!({ "pet": { "name": "Rex", "photo_urls": ["rex.png"] } }))
+    );
+  }
+
+  #[tokio::test]
+  async fn should_map_query_parameters_and_request_body() {
+    let server = MockServer::start_async().await;
+    let list = server
+      .mock_async(|when, then| {
+        when
+          .method(GET)
+          .path("/v1/pets")
+          .query_param("tags", "dog")
+          .query_param("tags", "small");
+        then
+          .status(200)
+          .json_body(json!([{ "id": 1, "name": "Rex" }]));
+      })
+      .await;
+    let create = server
+      .mock_async(|when, then| {
+        when
+          .method(POST)
+          .path("/v1/pets")
+          .header("content-type", "application/json")
+          .json_body(json!({ "name": "Tom", "photo-urls": ["tom.png"] }));
+        then
+          .status(201)
+          .json_body(json!({ "id": 2, "name": "Tom" }));
+      })
+      .await;
+
+    let response = execute(
+      &server,
+      "{ listPets(tags: [\"dog\", \"small\"]) { id name } }",
+      json!({}),
+      &[],
+    )
+    .await;
+    list.assert_async().await;
+    assert_eq!(
+      response.data,
+      Some(json!({ "listPets": [{ "id": 1, "name": "Rex" }] }))
+    );
+
+    let response = execute(
+      &server,
+      "mutation { createPet(input: { name: \"Tom\", photo_urls: [\"tom.png\"] }) { id } }",
+      json!({}),
+      &[],
+    )
+    .await;
+    create.assert_async().await;
+    assert_eq!(response.data, Some(json!({ "createPet": { "id": 2 } })));
+  }
+
+  #[tokio::test]
+  async fn should_fail_field_without_path_parameter() {
+    let server = MockServer::start_async().await;
+    let mock = server
+      .mock_async(|when, then| {
+        when.path_contains("/pets/");
+        then.status(200).json_body(json!({ "name": "Rex" }));
+      })
+      .await;
+
+    let response = execute(
+      &server,
+      "query($id: Int!) { getPet(petId: $id) { name } }",
+      json!({}),
+      &[],
+    )
+    .await;
+
+    assert_eq!(mock.hits_async().await, 0);
+    assert_eq!(response.data, Some(json!({ "getPet": null })));
+    let errors = response.errors.unwrap();
+    assert_eq!(
+      errors[0].message,
+      "missing value for path parameter \"petId\""
+    );
+    assert_eq!(errors[0].path, Some(vec!["getPet".to_string()]));
+  }
+}
@@ -0,0 +1,679 @@
+use std::{
+  collections::{HashMap, HashSet},
+  future::Future,
+  hash::{Hash, Hasher},
+  pin::Pin,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+  },
+  time::SystemTime,
+};
+
+use conductor_common::{
+  execute::RequestExecutionContext,
+  graphql::{
+    validate_graphql_operation, GraphQLResponse, ParsedGraphQLRequest, ParsedGraphQLSchema,
+  },
+  plugin_manager::PluginManager,
+  source::{
+    GraphQLSourceInitError, SchemaChangeListener, SchemaHealth, SourceError, SourceRuntime,
+  },
+};
+use conductor_config::SchemaMockSourceConfig;
+use graphql_parser::{
+  query::{
+    Definition, Directive, Field, FragmentDefinition, OperationDefinition, Selection, SelectionSet,
+    TypeCondition, Value as GraphQLValue,
+  },
+  schema::{Definition as SchemaDefinition, Type, TypeDefinition},
+};
+use serde_json::{Map, Value};
+
+use crate::{schema_awareness::SchemaAwareness, schema_filter::type_name};
+
+type QuerySelectionSet = SelectionSet<'static, String>;
+type QueryField = Field<'static, String>;
+type SchemaTypeDefinition = TypeDefinition<'static, String>;
+type SchemaType = Type<'static, String>;
+
+/// A source that generates mocked responses from a GraphQL schema, matching the selection set of each operation.
+#[derive(Debug)]
+pub struct SchemaMockSourceRuntime {
+  pub config: SchemaMockSourceConfig,
+  pub identifier: String,
+  pub schema_awareness: SchemaAwareness,
+  /// The number of operations executed so far, used to pick the operations that get an injected error.
+  executed_operations: AtomicU64,
+}
+
+impl SchemaMockSourceRuntime {
+  pub async fn new(
+    identifier: String,
+    config: SchemaMockSourceConfig,
+  ) -> Result<Self, GraphQLSourceInitError> {
+    tracing::info!(
+      "Initializing source '{}' of type 'schema_mock' with config: {:?}",
+      identifier,
+      config
+    );
+
+    #[cfg(target_arch = "wasm32")]
+    if config.latency.is_some() {
+      tracing::warn!(
+        "The \"latency\" of source '{}' is ignored, timers are not available on WASM runtime",
+        identifier
+      );
+    }
+
+    let schema_awareness =
+      SchemaAwareness::new(identifier.clone(), config.schema.clone(), |_, _| Ok(()))
+        .await
+        .map_err(|source| GraphQLSourceInitError::SourceInitFailed {
+          source: source.into(),
+        })?;
+
+    Ok(Self {
+      config,
+      identifier,
+      schema_awareness,
+      executed_operations: AtomicU64::new(0),
+    })
+  }
+
+  /// Decides if the next operation gets an error, based on `error_rate`. The sequence of errors only depends on the seed.
+  fn should_inject_error(&self) -> bool {
+    let Some(error_rate) = self.config.error_rate else {
+      return false;
+    };
+
+    let count = self.executed_operations.fetch_add(1, Ordering::Relaxed);
+    let mut hasher = StableHasher::default();
+    (self.config.seed, count).hash(&mut hasher);
+
+    ((hasher.finish() % 10_000) as f64 / 10_000.0) < error_rate
+  }
+}
+
+impl SourceRuntime for SchemaMockSourceRuntime {
+  fn name(&self) -> &str {
+    &self.identifier
+  }
+
+  fn schema(&self) -> Option<Arc<ParsedGraphQLSchema>> {
+    self.schema_awareness.schema()
+  }
+
+  fn sdl(&self) -> Option<Arc<String>> {
+    self.schema_awareness.raw()
+  }
+
+  fn schema_health(&self) -> Option<SchemaHealth> {
+    Some(self.schema_awareness.health())
+  }
+
+  fn shutdown(&self) {
+    self.schema_awareness.stop_polling();
+  }
+
+  fn subscribe_schema_changes(&self, listener: SchemaChangeListener) {
+    self.schema_awareness.subscribe_changes(listener);
+  }
+
+  fn refresh_schema<'a>(&'a self, now: SystemTime) -> Pin<Box<(dyn Future<Output = ()> + 'a)>> {
+    Box::pin(self.schema_awareness.refresh_if_stale(now))
+  }
+
+  fn execute<'a>(
+    &'a self,
+    _plugin_manager: Arc<Box<dyn PluginManager>>,
+    request_context: &'a mut RequestExecutionContext,
+  ) -> Pin<Box<(dyn Future<Output = Result<GraphQLResponse, SourceError>> + 'a)>> {
+    Box::pin(wasm_polyfills::call_async(async move {
+      // Timers are not available on WASM runtime, so the latency is only simulated on native runtime.
+      #[cfg(not(target_arch = "wasm32"))]
+      if let Some(latency) = self.config.latency {
+        tokio::time::sleep(latency).await;
+      }
+
+      let request = match request_context.downstream_graphql_request.as_ref() {
+        Some(request) => request,
+        None => {
+          return Ok(GraphQLResponse::new_error(
+            "source request isn't available at execution context!",
+          ))
+        }
+      };
+
+      let schema = match self.schema_awareness.schema() {
+        Some(schema) => schema,
+        None => {
+          return Ok(GraphQLResponse::new_error(
+            "the schema of the source isn't available yet",
+          ))
+        }
+      };
+
+      let errors = validate_graphql_operation(&schema, &request.parsed_operation);
+
+      if !errors.is_empty() {
+        return Ok(errors.into());
+      }
+
+      if self.should_inject_error() {
+        return Ok(GraphQLResponse::new_error(
+          "mocked error, injected by the \"error_rate\" of the source",
+        ));
+      }
+
+      Ok(MockGenerator::new(&self.config, &schema, request).execute())
+    }))
+  }
+}
+
+/// Generates the response of an operation, with a value of the right type for every selected field.
+struct MockGenerator<'a> {
+  config: &'a SchemaMockSourceConfig,
+  schema: &'a ParsedGraphQLSchema,
+  request: &'a ParsedGraphQLRequest,
+  types: HashMap<&'a str, &'a SchemaTypeDefinition>,
+}
+
+impl<'a> MockGenerator<'a> {
+  fn new(
+    config: &'a SchemaMockSourceConfig,
+    schema: &'a ParsedGraphQLSchema,
+    request: &'a ParsedGraphQLRequest,
+  ) -> Self {
+    let types = schema
+      .definitions
+      .iter()
+      .filter_map(|definition| match definition {
+        SchemaDefinition::TypeDefinition(t) => Some((type_name(t), t)),
+        _ => None,
+      })
+      .collect();
+
+    Self {
+      config,
+      schema,
+      request,
+      types,
+    }
+  }
+
+  fn execute(&self) -> GraphQLResponse {
+    let (root_type, selection_set) = match self.request.executable_operation() {
+      Some(Definition::Operation(OperationDefinition::Query(query))) => {
+        (self.root_type_name("Query"), &query.selection_set)
+      }
+      Some(Definition::Operation(OperationDefinition::SelectionSet(selection_set))) => {
+        (self.root_type_name("Query"), selection_set)
+      }
+      Some(Definition::Operation(OperationDefinition::Mutation(mutation))) => {
+        (self.root_type_name("Mutation"), &mutation.selection_set)
+      }
+      Some(Definition::Operation(OperationDefinition::Subscription(_))) => {
+        return GraphQLResponse::new_error(
+          "subscriptions are not supported by schema mock sources",
+        );
+      }
+      _ => return GraphQLResponse::new_error("failed to locate the operation to execute"),
+    };
+
+    GraphQLResponse::new_data(self.generate_object(root_type, &[selection_set], &mut vec![], None))
+  }
+
+  /// The name of a root type, as declared by the `schema` definition.
+  fn root_type_name(&self, root_type: &'a str) -> &'a str {
+    self
+      .schema
+      .definitions
+      .iter()
+      .find_map(|definition| match definition {
+        SchemaDefinition::SchemaDefinition(schema_definition) => match root_type {
+          "Mutation" => schema_definition.mutation.as_deref(),
+          _ => schema_definition.query.as_deref(),
+        },
+        _ => None,
+      })
+      .unwrap_or(root_type)
+  }
+
+  fn generate_object(
+    &self,
+    type_name: &str,
+    selection_sets: &[&'a QuerySelectionSet],
+    path: &mut Vec<String>,
+    override_value: Option<&'a Value>,
+  ) -> Value {
+    let mut result = Map::new();
+
+    for (key, fields) in self.collect_fields(type_name, selection_sets) {
+      let field = fields[0];
+
+      if field.name == "__typename" {
+        result.insert(key, Value::String(type_name.to_string()));
+        continue;
+      }
+
+      let Some(field_type) = self.field_type(type_name, &field.name) else {
+        result.insert(key, Value::Null);
+        continue;
+      };
+
+      // The value set for the parent object takes precedence over the field coordinate, that takes precedence over the type.
+      let field_override = override_value
+        .and_then(|value| value.get(&field.name))
+        .or_else(|| {
+          self
+            .config
+            .overrides
+            .get(&format!("{}.{}", type_name, field.name))
+        });
+      let selection_sets = fields.iter().map(|f| &f.selection_set).collect::<Vec<_>>();
+
+      path.push(key.clone());
+      let value = self.generate(field_type, &selection_sets, path, field_override);
+      path.pop();
+
+      result.insert(key, value);
+    }
+
+    Value::Object(result)
+  }
+
+  fn generate(
+    &self,
+    field_type: &'a SchemaType,
+    selection_sets: &[&'a QuerySelectionSet],
+    path: &mut Vec<String>,
+    override_value: Option<&'a Value>,
+  ) -> Value {
+    match field_type {
+      Type::NonNullType(inner) => self.generate(inner, selection_sets, path, override_value),
+      Type::ListType(inner) => {
+        let items = match override_value {
+          Some(Value::Array(items)) => items.iter().map(Some).collect::<Vec<_>>(),
+          _ => vec![None; self.config.list_length],
+        };
+
+        items
+          .into_iter()
+          .enumerate()
+          .map(|(index, item_override)| {
+            path.push(index.to_string());
+            let value = self.generate(inner, selection_sets, path, item_override);
+            path.pop();
+
+            value
+          })
+          .collect()
+      }
+      Type::NamedType(name) => {
+        let override_value = override_value.or_else(|| self.config.overrides.get(name));
+
+        match (self.types.get(name.as_str()), override_value) {
+          // A value that is not an object replaces the whole object (for example, `null`).
+          (Some(TypeDefinition::Object(_)), Some(value)) if !value.is_object() => value.clone(),
+          (Some(TypeDefinition::Object(_)), _) => {
+            self.generate_object(name, selection_sets, path, override_value)
+          }
+          (Some(TypeDefinition::Interface(_) | TypeDefinition::Union(_)), _) => {
+            let possible_types = self.possible_types(name);
+            let concrete_type = override_value
+              .and_then(|value| value.get("__typename"))
+              .and_then(Value::as_str)
+              .and_then(|typename| possible_types.iter().find(|t| **t == typename))
+              .or_else(|| {
+                possible_types.get((self.hash(path) % possible_types.len().max(1) as u64) as usize)
+              });
+
+            match concrete_type {
+              Some(concrete_type) => {
+                self.generate_object(concrete_type, selection_sets, path, override_value)
+              }
+              None => Value::Null,
+            }
+          }
+          (_, Some(value)) => value.clone(),
+          (t, None) => self.generate_leaf(name, t, path),
+        }
+      }
+    }
+  }
+
+  fn generate_leaf(
+    &self,
+    name: &str,
+    type_definition: Option<&&'a SchemaTypeDefinition>,
+    path: &[String],
+  ) -> Value {
+    let hash = self.hash(path);
+
+    match (name, type_definition) {
+      ("Int", _) => Value::from((hash % 1000) as i64),
+      ("Float", _) => Value::from((hash % 100_000) as f64 / 100.0),
+      ("Boolean", _) => Value::Bool(hash % 2 == 0),
+      ("ID", _) => Value::String(format!("{:x}", hash)),
+      ("String", _) => {
+        // List indexes are skipped, so the value is named after the field.
+        let field = path
+          .iter()
+          .rev()
+          .find(|segment| segment.parse::<usize>().is_err())
+          .map(String::as_str)
+          .unwrap_or("value");
+
+        Value::String(format!("{} {}", field, hash % 1000))
+      }
+      (_, Some(TypeDefinition::Enum(e))) if !e.values.is_empty() => Value::String(
+        e.values[(hash % e.values.len() as u64) as usize]
+          .name
+          .clone(),
+      ),
+      _ => Value::String(format!("{} {}", name, hash % 1000)),
+    }
+  }
+
+  /// A hash of the response path and the seed, so the same field always gets the same value.
+  fn hash(&self, path: &[String]) -> u64 {
+    let mut hasher = StableHasher::default();
+    (self.config.seed, path).hash(&mut hasher);
+
+    hasher.finish()
+  }
+
+  fn field_type(&self, type_name: &str, field_name: &str) -> Option<&'a SchemaType> {
+    let fields = match self.types.get(type_name)? {
+      TypeDefinition::Object(o) => &o.fields,
+      TypeDefinition::Interface(i) => &i.fields,
+      _ => return None,
+    };
+
+    fields
+      .iter()
+      .find(|field| field.name == field_name)
+      .map(|field| &field.field_type)
+  }
+
+  /// The object types that implement an interface or belong to a union.
+  fn possible_types(&self, name: &str) -> Vec<&'a str> {
+    let mut possible_types = self
+      .schema
+      .definitions
+      .iter()
+      .filter_map(|definition| match definition {
+        SchemaDefinition::TypeDefinition(TypeDefinition::Object(o))
+          if o.implements_interfaces.iter().any(|i| i == name) =>
+        {
+          Some(o.name.as_str())
+        }
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+
+    if let Some(TypeDefinition::Union(u)) = self.types.get(name) {
+      possible_types.extend(u.types.iter().map(String::as_str));
+    }
+
+    possible_types
+  }
+
+  /// Checks if a fragment applies to an object type, directly or through one of its interfaces or unions.
+  fn applies_to(
+    &self,
+    type_condition: Option<&TypeCondition<'static, String>>,
+    type_name: &str,
+  ) -> bool {
+    match type_condition {
+      Some(TypeCondition::On(name)) => {
+        name == type_name || self.possible_types(name).contains(&type_name)
+      }
+      None => true,
+    }
+  }
+
+  /// Groups the selected fields by response key, following fragments and `@skip`/`@include`.
+  fn collect_fields(
+    &self,
+    type_name: &str,
+    selection_sets: &[&'a QuerySelectionSet],
+  ) -> Vec<(String, Vec<&'a QueryField>)> {
+    let mut result = vec![];
+    let mut visited_fragments = HashSet::new();
+
+    for selection_set in selection_sets {
+      self.collect_fields_into(
+        type_name,
+        selection_set,
+        &mut visited_fragments,
+        &mut result,
+      );
+    }
+
+    result
+  }
+
+  fn collect_fields_into(
+    &self,
+    type_name: &str,
+    selection_set: &'a QuerySelectionSet,
+    visited_fragments: &mut HashSet<&'a str>,
+    result: &mut Vec<(String, Vec<&'a QueryField>)>,
+  ) {
+    for selection in &selection_set.items {
+      match selection {
+        Selection::Field(field) if self.is_included(&field.directives) => {
+          let key = field.alias.as_ref().unwrap_or(&field.name);
+
+          match result.iter_mut().find(|(k, _)| k == key) {
+            Some((_, fields)) => fields.push(field),
+            None => result.push((key.clone(), vec![field])),
+          }
+        }
+        Selection::InlineFragment(fragment)
+          if self.is_included(&fragment.directives)
+            && self.applies_to(fragment.type_condition.as_ref(), type_name) =>
+        {
+          self.collect_fields_into(
+            type_name,
+            &fragment.selection_set,
+            visited_fragments,
+            result,
+          );
+        }
+        Selection::FragmentSpread(spread)
+          if self.is_included(&spread.directives)
+            && visited_fragments.insert(spread.fragment_name.as_str()) =>
+        {
+          if let Some(fragment) = self.find_fragment(&spread.fragment_name) {
+            if self.applies_to(Some(&fragment.type_condition), type_name) {
+              self.collect_fields_into(
+                type_name,
+                &fragment.selection_set,
+                visited_fragments,
+                result,
+              );
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  fn find_fragment(&self, name: &str) -> Option<&'a FragmentDefinition<'static, String>> {
+    self
+      .request
+      .parsed_operation
+      .definitions
+      .iter()
+      .find_map(|definition| match definition {
+        Definition::Fragment(fragment) if fragment.name == name => Some(fragment),
+        _ => None,
+      })
+  }
+
+  /// Checks the `@skip` and `@include` directives of a selection.
+  fn is_included(&self, directives: &[Directive<'static, String>]) -> bool {
+    directives.iter().all(|directive| {
+      let condition = directive
+        .arguments
+        .iter()
+        .find(|(name, _)| name == "if")
+        .and_then(|(_, value)| match value {
+          GraphQLValue::Boolean(b) => Some(*b),
+          GraphQLValue::Variable(name) => self
+            .request
+            .request
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.get(name))
+            .and_then(Value::as_bool),
+          _ => None,
+        });
+
+      match directive.name.as_str() {
+        "skip" => condition != Some(true),
+        "include" => condition != Some(false),
+        _ => true,
+      }
+    })
+  }
+}
+
+/// A 64-bit FNV-1a hasher. Unlike `DefaultHasher`, its algorithm is fixed across Rust releases and platforms, so a
+/// seed always generates the same values.
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+  fn default() -> Self {
+    Self(0xcbf29ce484222325)
+  }
+}
+
+impl Hasher for StableHasher {
+  fn finish(&self) -> u64 {
+    self.0
+  }
+
+  fn write(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x100000001b3);
+    }
+  }
+
+  // Lengths are hashed as `u64`, so 32-bit (WASM) and 64-bit platforms get the same values.
+  fn write_usize(&mut self, i: usize) {
+    self.write_u64(i as u64);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use conductor_common::graphql::{parse_graphql_schema, GraphQLRequest};
+  use conductor_config::{
+    SchemaAwarenessConfig, SchemaAwarenessConfigOnError, SchemaAwarenessFormat,
+    SchemaAwarenessSource,
+  };
+  use serde_json::json;
+
+  use super::*;
+
+  const SDL: &str = r#"
+    type Query { me: User, search: [SearchResult!]! }
+    type User { id: ID!, name: String, age: Int, role: Role, createdAt: DateTime }
+    type Post { title: String }
+    union SearchResult = User | Post
+    enum Role { ADMIN, USER }
+    scalar DateTime
+  "#;
+
+  fn generate(config: &SchemaMockSourceConfig, operation: &str) -> Value {
+    let schema = parse_graphql_schema(SDL).unwrap();
+    let request = ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
+      operation: operation.to_string(),
+      operation_name: None,
+      variables: None,
+      extensions: None,
+    })
+    .unwrap();
+
+    MockGenerator::new(config, &schema, &request)
+      .execute()
+      .data
+      .unwrap()
+  }
+
+  fn config(overrides: HashMap<String, Value>) -> SchemaMockSourceConfig {
+    SchemaMockSourceConfig {
+      schema: SchemaAwarenessConfig {
+        format: SchemaAwarenessFormat::Sdl,
+        source: SchemaAwarenessSource::Inline {
+          content: SDL.to_string(),
+        },
+        polling_interval: None,
+        on_error: SchemaAwarenessConfigOnError::Terminate,
+      },
+      seed: 0,
+      list_length: 3,
+      overrides,
+      error_rate: None,
+      latency: None,
+    }
+  }
+
+  #[test]
+  fn should_generate_values_for_the_selection_set() {
+    let config = config(HashMap::from([
+      ("DateTime".to_string(), json!("2024-01-01T00:00:00Z")),
+      ("User.name".to_string(), json!("Dotan")),
+    ]));
+    let operation = "{ me { __typename id user: name age role createdAt } search { __typename ... on Post { title } } }";
+    let data = generate(&config, operation);
+
+    assert_eq!(data, generate(&config, operation));
+    assert_eq!(data["me"]["__typename"], "User");
+    assert!(data["me"]["id"].is_string());
+    assert_eq!(data["me"]["user"], "Dotan");
+    assert!(data["me"]["age"].is_i64());
+    assert!(["ADMIN", "USER"].contains(&data["me"]["role"].as_str().unwrap()));
+    assert_eq!(data["me"]["createdAt"], "2024-01-01T00:00:00Z");
+
+    let search = data["search"].as_array().unwrap();
+    assert_eq!(search.len(), 3);
+
+    for item in search {
+      match item["__typename"].as_str().unwrap() {
+        "Post" => assert!(item["title"].is_string()),
+        _ => assert!(item.get("title").is_none()),
+      }
+    }
+  }
+
+  #[test]
+  fn should_merge_object_overrides_with_generated_fields() {
+    let config = config(HashMap::from([(
+      "Query.me".to_string(),
+      json!({ "name": "Kamil" }),
+    )]));
+    let data = generate(&config, "{ me { id name } }");
+
+    assert_eq!(data["me"]["name"], "Kamil");
+    assert!(data["me"]["id"].is_string());
+  }
+
+  #[test]
+  fn should_hash_with_fnv() {
+    let mut hasher = StableHasher::default();
+    hasher.write(b"a");
+    assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+
+    let mut hasher = StableHasher::default();
+    hasher.write_usize(1);
+    let mut expected = StableHasher::default();
+    expected.write_u64(1);
+    assert_eq!(hasher.finish(), expected.finish());
+  }
+}
@@ -3,11 +3,15 @@ use std::{ops::Index, sync::Arc};
 use anyhow::{anyhow, Error, Ok as anyhowOk};
 use conductor_common::http::ConductorHttpRequest;
 use conductor_common::{execute::RequestExecutionContext, plugin_manager::PluginManager};
+use conductor_tracing::otel_attrs::{
+  CONDUCTOR_FEDERATION_ENTITY_COUNT, CONDUCTOR_FEDERATION_QUERY_PLAN,
+  CONDUCTOR_FEDERATION_SUBGRAPH, GRAPHQL_DOCUMENT,
+};
 use constants::CONDUCTOR_INTERNAL_SERVICE_RESOLVER;
 use executor::{
   dynamically_build_schema_from_supergraph, find_objects_matching_criteria, QueryResponse,
 };
-use fastrace::Span;
+use fastrace::{future::FutureExt, trace, Span};
 use futures::future::join_all;
 use futures::lock::Mutex;
 use graphql_parser::query::Document;
@@ -33,6 +37,8 @@ pub struct FederationExecutor<'a> {
   pub client: &'a minitrace_reqwest::TracedHttpClient,
   pub plugin_manager: Arc<Box<dyn PluginManager>>,
   pub supergraph: &'a Supergraph,
+  /// Attaches the query plan to the span of the execution.
+  pub trace_query_plan: bool,
 }
 
 impl<'a> FederationExecutor<'a> {
@@ -42,21 +48,31 @@ impl<'a> FederationExecutor<'a> {
     parsed_user_query: Document<'static, String>,
   ) -> Result<(String, QueryPlan), Error> {
     // println!("parsed_user_query: {:#?}", user_query);
-    let mut user_query = parse_user_query(parsed_user_query)?;
-    let query_plan = plan_for_user_query(self.supergraph, &mut user_query)?;
+    let query_plan = {
+      let _span = Span::enter_with_local_parent("federation_query_planning");
+      let mut user_query = parse_user_query(parsed_user_query)?;
+
+      plan_for_user_query(self.supergraph, &mut user_query)?
+    };
 
     // println!("query plan: {:#?}", query_plan);
 
+    let mut execution_span = Span::enter_with_local_parent("federation_execution");
+
+    if self.trace_query_plan {
+      let query_plan_json = serde_json::to_string(&query_plan)?;
+      execution_span =
+        execution_span.with_properties(|| [(CONDUCTOR_FEDERATION_QUERY_PLAN, query_plan_json)]);
+    }
+
     let response_vec = self
       .execute_query_plan(&query_plan, request_context)
+      .in_span(execution_span)
       .await?;
 
     // println!("response: {:#?}", json!(response_vec).to_string());
 
-    anyhowOk((
-      json!(response_vec.index(0).index(0).1).to_string(),
-      query_plan,
-    ))
+    anyhowOk((merge_responses(response_vec), query_plan))
   }
 
   pub async fn execute_query_plan(
@@ -92,12 +108,31 @@ impl<'a> FederationExecutor<'a> {
     let mut entity_arguments: Option<SerdeValue> = None;
 
     for (i, query_step) in query_steps.iter().enumerate() {
+      let entity_count = entity_arguments
+        .as_ref()
+        .and_then(|arguments| arguments.as_array())
+        .map(|arguments| arguments.len())
+        .unwrap_or_default();
+      let step_span =
+        Span::enter_with_local_parent(format!("subgraph {}", query_step.service_name))
+          .with_properties(|| {
+            [
+              (
+                CONDUCTOR_FEDERATION_SUBGRAPH,
+                query_step.service_name.clone(),
+              ),
+              (GRAPHQL_DOCUMENT, query_step.query.clone()),
+              (CONDUCTOR_FEDERATION_ENTITY_COUNT, entity_count.to_string()),
+            ]
+          });
+
       let data = self
         .execute_query_step(
           query_step,
           entity_arguments.clone(),
           request_context.lock().await,
         )
+        .in_span(step_span)
         .await;
 
       match data {
@@ -168,6 +203,8 @@ impl<'a> FederationExecutor<'a> {
     let is_introspection = query_step.service_name == CONDUCTOR_INTERNAL_SERVICE_RESOLVER;
 
     if is_introspection {
+      // The gateway resolves introspection fields from the API schema before the operation reaches the executor,
+      // so this is only used when the executor is called directly.
       let schema = dynamically_build_schema_from_supergraph(self.supergraph);
 
       // Execute the introspection query
@@ -188,13 +225,6 @@ impl<'a> FederationExecutor<'a> {
         extensions: None,
       })
     } else {
-      let _span = Span::enter_with_local_parent(format!("subgraph {}", query_step.service_name))
-        .with_properties(|| {
-          [
-            ("service_name", query_step.service_name.clone()),
-            ("graphql.document", query_step.query.clone()),
-          ]
-        });
       let url = self
         .supergraph
         .subgraphs
@@ -282,10 +312,17 @@ impl<'a> FederationExecutor<'a> {
   }
 }
 
+#[trace(name = "federation_merge_responses")]
+fn merge_responses(responses: Vec<Vec<((String, String), QueryResponse)>>) -> String {
+  json!(responses.index(0).index(0).1).to_string()
+}
+
 #[cfg(test)]
 mod tests {
   use conductor_common::graphql::parse_graphql_schema;
 
+  use super::*;
+
   #[tokio::test]
   async fn generates_query_plan() {
     use crate::{
@@ -579,4 +616,141 @@ type User
     // TODO: fix ordering, it fails bc ordering of fields in a query plan is dynamic
     // insta::assert_json_snapshot!(query_plan);
   }
+
+  #[derive(Debug)]
+  struct NoopPluginManager;
+
+  #[async_trait::async_trait(?Send)]
+  impl PluginManager for NoopPluginManager {
+    async fn on_downstream_http_request(&self, _context: &mut RequestExecutionContext) {}
+    fn on_downstream_http_response(
+      &self,
+      _context: &mut RequestExecutionContext,
+      _response: &mut conductor_common::http::ConductorHttpResponse,
+    ) {
+    }
+    async fn on_downstream_graphql_request(
+      &self,
+      _source_runtime: Arc<Box<dyn conductor_common::source::SourceRuntime>>,
+      _context: &mut RequestExecutionContext,
+    ) {
+    }
+    async fn on_upstream_graphql_request<'a>(
+      &self,
+      _req: &mut conductor_common::graphql::GraphQLRequest,
+    ) {
+    }
+    async fn on_upstream_http_request<'a>(
+      &self,
+      _ctx: &mut RequestExecutionContext,
+      _request: &mut ConductorHttpRequest,
+    ) {
+    }
+    async fn on_upstream_http_response<'a>(
+      &self,
+      _ctx: &mut RequestExecutionContext,
+      _response: &Result<reqwest::Response, reqwest_middleware::Error>,
+    ) {
+    }
+    fn on_schema_change(
+      &self,
+      _source_id: &str,
+      _diff: &conductor_common::schema_diff::SchemaDiff,
+    ) {
+    }
+    async fn on_flush(&self) {}
+    async fn on_shutdown(&self) {}
+  }
+
+  #[tokio::test]
+  async fn traces_planning_subgraph_fetches_and_merging() {
+    use conductor_tracing::routed_reporter::test_utils::TestReporter;
+    use fastrace::collector::{Config, SpanContext};
+    use httpmock::MockServer;
+
+    use crate::supergraph::parse_supergraph;
+
+    let server = MockServer::start_async().await;
+    server
+      .mock_async(|when, then| {
+        when.path("/graphql");
+        then
+          .status(200)
+          .header("content-type", "application/json")
+          .body(r#"{"data":{"users":[{"id":"1","name":"Ada"}]}}"#);
+      })
+      .await;
+
+    let supergraph_schema = format!(
+      r#"
+      enum join__Graph {{
+        ACCOUNTS @join__graph(name: "accounts", url: "{}")
+      }}
+
+      type Query @join__type(graph: ACCOUNTS) {{
+        users: [User] @join__field(graph: ACCOUNTS)
+      }}
+
+      type User @join__type(graph: ACCOUNTS, key: "id") {{
+        id: ID!
+        name: String @join__field(graph: ACCOUNTS)
+      }}
+      "#,
+      server.url("/graphql")
+    );
+    let supergraph = parse_supergraph(&parse_graphql_schema(&supergraph_schema).unwrap()).unwrap();
+
+    let (spans, reporter) = TestReporter::new();
+    fastrace::set_reporter(reporter, Config::default());
+
+    let client = minitrace_reqwest::traced_reqwest(reqwest::Client::new());
+    let executor = FederationExecutor {
+      client: &client,
+      plugin_manager: Arc::new(Box::new(NoopPluginManager)),
+      supergraph: &supergraph,
+      trace_query_plan: true,
+    };
+    let mut request_context = RequestExecutionContext::new(ConductorHttpRequest::default());
+    let root = Span::root("root", SpanContext::random());
+
+    let (response, _) = executor
+      .execute_federation(
+        Arc::new(Mutex::new(&mut request_context)),
+        graphql_parser::parse_query::<String>("{ users { id name } }")
+          .unwrap()
+          .into_static(),
+      )
+      .in_span(root)
+      .await
+      .unwrap();
+    assert!(response.contains("Ada"));
+
+    fastrace::flush();
+
+    let spans = spans.lock().unwrap();
+    let span = |name: &str| {
+      spans
+        .iter()
+        .find(|span| span.name == name)
+        .unwrap_or_else(|| panic!("failed to find span {}", name))
+    };
+
+    span("federation_query_planning");
+    span("federation_merge_responses");
+    let execution = span("federation_execution");
+    assert!(execution
+      .properties
+      .iter()
+      .any(|(key, _)| key == CONDUCTOR_FEDERATION_QUERY_PLAN));
+
+    let subgraph = spans
+      .iter()
+      .find(|span| span.name.starts_with("subgraph "))
+      .expect("failed to find subgraph span");
+    assert_eq!(subgraph.parent_id, execution.span_id);
+    assert!(subgraph
+      .properties
+      .iter()
+      .any(|(key, value)| key == CONDUCTOR_FEDERATION_SUBGRAPH && value == "ACCOUNTS"));
+  }
 }
@@ -3,7 +3,7 @@
 #[macro_use]
 extern crate napi_derive;
 
-use conductor::run_services;
+use conductor::{run_services, shutdown::request_shutdown};
 #[actix_web::main]
 #[napi]
 pub async fn execute_conductor(config_file_path: String) -> Result<(), napi::Error> {
@@ -12,8 +12,8 @@ pub async fn execute_conductor(config_file_path: String) -> Result<(), napi::Err
     .map_err(|e| napi::Error::new(napi::Status::GenericFailure, e.to_string()))
 }
 
+/// Gracefully stops the server started by `execute_conductor`: in-flight requests are drained, and telemetry is flushed.
 #[napi]
 pub fn shutdown_server() {
-  // @expected: we need this
-  panic!("Exited process!")
+  request_shutdown();
 }
@@ -1,8 +1,6 @@
 use fastrace::collector::Reporter;
 
-use crate::{
-  reporters::TracingReporter, routed_reporter::RoutedReporter, trace_id::extract_tenant_id,
-};
+use crate::{reporters::TracingReporter, routed_reporter::RoutedReporter, sampling::TraceSampler};
 use std::{
   collections::HashMap,
   sync::{Arc, Mutex},
@@ -11,6 +9,7 @@ use std::{
 #[derive(Default)]
 pub struct FastraceManager {
   reporters: HashMap<u32, Arc<Mutex<TracingReporter>>>,
+  samplers: HashMap<u32, TraceSampler>,
 }
 
 impl std::fmt::Debug for FastraceManager {
@@ -26,8 +25,17 @@ impl FastraceManager {
       .insert(tenant_id, Arc::new(Mutex::new(reporter)));
   }
 
+  /// Sets the sampler deciding which requests of a tenant are recorded.
+  pub fn set_sampler(&mut self, tenant_id: u32, sampler: TraceSampler) {
+    self.samplers.insert(tenant_id, sampler);
+  }
+
+  pub fn sampler(&self, tenant_id: u32) -> Option<&TraceSampler> {
+    self.samplers.get(&tenant_id)
+  }
+
   pub fn build_root_reporter(&self) -> impl Reporter {
-    let mut routed_reporter = RoutedReporter::new(|span| Some(extract_tenant_id(span.trace_id)));
+    let mut routed_reporter = RoutedReporter::new();
 
     for (tenant_id, reporter) in &self.reporters {
       routed_reporter = routed_reporter.with_reporter(*tenant_id, reporter.clone());
@@ -36,17 +44,27 @@ impl FastraceManager {
     routed_reporter
   }
 
-  #[allow(clippy::await_holding_lock)]
   pub async fn shutdown(self) {
     tracing::info!("Shutting down tracing reporters...");
+    self.flush().await;
+  }
+
+  /// Sends the spans collected so far with all reporters, that remain usable afterwards.
+  pub async fn flush(&self) {
     fastrace::flush();
 
-    for (_, reporter) in self.reporters {
-      let mut reporter = reporter
+    for reporter in self.reporters.values() {
+      // The spans are taken while the lock is held, and shipped after it's released: concurrent requests keep
+      // reporting spans meanwhile.
+      let pending = reporter
         .lock()
-        .expect("failed to acquire lock for tracing reporter");
+        // @expected: the lock is only poisoned if a reporter panicked while reporting spans.
+        .expect("failed to acquire lock for tracing reporter")
+        .take_flush();
 
-      reporter.flush().await
+      if let Some(pending) = pending {
+        pending.await;
+      }
     }
   }
 }
@@ -1,6 +1,8 @@
 pub mod fastrace_mgr;
 pub mod otel_attrs;
 pub mod otel_utils;
+pub mod propagation;
 pub mod reporters;
 pub mod routed_reporter;
+pub mod sampling;
 pub mod trace_id;
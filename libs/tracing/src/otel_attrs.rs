@@ -35,4 +35,11 @@ pub const GRAPHQL_ERROR_COUNT: &str = "graphql.error.count";
 
 // Conductor-specific
 pub const CONDUCTOR_ENDPOINT: &str = "conductor.endpoint";
+pub const CONDUCTOR_TENANT_ID: &str = "conductor.tenant_id";
+pub const CONDUCTOR_PARENT_SAMPLED: &str = "conductor.parent_sampled";
 pub const CONDUCTOR_SOURCE: &str = "conductor.source";
+pub const CONDUCTOR_CLIENT_NAME: &str = "conductor.client.name";
+pub const CONDUCTOR_CLIENT_VERSION: &str = "conductor.client.version";
+pub const CONDUCTOR_FEDERATION_SUBGRAPH: &str = "conductor.federation.subgraph";
+pub const CONDUCTOR_FEDERATION_ENTITY_COUNT: &str = "conductor.federation.entity_count";
+pub const CONDUCTOR_FEDERATION_QUERY_PLAN: &str = "conductor.federation.query_plan";
@@ -1,3 +1,4 @@
+use conductor_common::execute::ClientInfo;
 use conductor_common::graphql::GraphQLError;
 use conductor_common::graphql::ParsedGraphQLRequest;
 use conductor_common::Definition;
@@ -8,7 +9,7 @@ use crate::otel_attrs::*;
 
 // Based on https://opentelemetry.io/docs/specs/semconv/database/graphql/
 #[inline]
-pub fn create_graphql_span(request: &ParsedGraphQLRequest) -> Span {
+pub fn create_graphql_span(request: &ParsedGraphQLRequest, client: Option<&ClientInfo>) -> Span {
   let excutable_op = request.executable_operation();
 
   let (op_type, op_name): (Option<&str>, Option<&String>) = match excutable_op {
@@ -38,6 +39,14 @@ pub fn create_graphql_span(request: &ParsedGraphQLRequest) -> Span {
     properties.push((GRAPHQL_OPERATION_NAME, op_name.to_string()));
   }
 
+  if let Some(client) = client {
+    properties.push((CONDUCTOR_CLIENT_NAME, client.name.clone()));
+
+    if let Some(version) = &client.version {
+      properties.push((CONDUCTOR_CLIENT_VERSION, version.clone()));
+    }
+  }
+
   Span::enter_with_local_parent(otel_name).with_properties(|| properties)
 }
 
@@ -0,0 +1,385 @@
+use conductor_common::{
+  execute::RequestExecutionContext,
+  http::{ConductorHttpRequest, HeaderName, HeaderValue},
+};
+use fastrace::{
+  collector::{SpanContext, SpanId, TraceId},
+  Span,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  otel_attrs::CONDUCTOR_PARENT_SAMPLED,
+  sampling::TraceSampler,
+  trace_id::{generate_trace_id, tenant_root_span},
+};
+
+pub static TRACEPARENT_HEADER: &str = "traceparent";
+pub static TRACESTATE_HEADER: &str = "tracestate";
+pub static B3_HEADER: &str = "b3";
+pub static B3_TRACE_ID_HEADER: &str = "x-b3-traceid";
+pub static B3_SPAN_ID_HEADER: &str = "x-b3-spanid";
+pub static B3_SAMPLED_HEADER: &str = "x-b3-sampled";
+
+/// Configuration for the correlation of requests across Conductor and the upstream services.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema, PartialEq)]
+pub struct PropagationConfig {
+  /// The header containing the id of the incoming request. When the header is missing, or its value is longer than 128 characters or contains characters other than letters, digits, `-`, `_`, `.` and `:`, an id is generated for the request.
+  ///
+  /// The id is returned in the same header of the response, forwarded to the upstream services, and added to the logs of the request.
+  #[serde(default = "default_request_id_header")]
+  pub request_id_header: String,
+  /// Continues the trace of the incoming request when it has a [W3C `traceparent` header](https://www.w3.org/TR/trace-context/), and sends the `traceparent` and `tracestate` headers to the upstream services.
+  #[serde(default = "default_trace_context")]
+  pub trace_context: bool,
+  /// Continues the trace of the incoming request when it has [B3 headers](https://github.com/openzipkin/b3-propagation) (`b3`, or `X-B3-TraceId` and `X-B3-SpanId`), and sends the `b3` header to the upstream services.
+  #[serde(default)]
+  pub b3: bool,
+}
+
+impl Default for PropagationConfig {
+  fn default() -> Self {
+    Self {
+      request_id_header: default_request_id_header(),
+      trace_context: default_trace_context(),
+      b3: false,
+    }
+  }
+}
+
+fn default_request_id_header() -> String {
+  "x-request-id".to_string()
+}
+
+fn default_trace_context() -> bool {
+  true
+}
+
+/// The maximum length of a request id received from a client.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Generates a unique id for a request that doesn't have one.
+pub fn generate_request_id() -> String {
+  format!("{:032x}", rand::random::<u128>())
+}
+
+/// Returns the request id received from a client when it's safe to echo back and log: at most 128 characters, made of
+/// letters, digits, `-`, `_`, `.` and `:`. Other ids are ignored, and replaced by a generated one.
+pub fn accept_request_id(value: &str) -> Option<&str> {
+  let valid = !value.is_empty()
+    && value.len() <= MAX_REQUEST_ID_LENGTH
+    && value
+      .bytes()
+      .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'));
+
+  valid.then_some(value)
+}
+
+impl PropagationConfig {
+  /// Extracts the trace context of an incoming request, using the enabled propagation formats.
+  pub fn extract_span_context(
+    &self,
+    header: impl Fn(&str) -> Option<String>,
+  ) -> Option<SpanContext> {
+    self
+      .extract_parent(header)
+      .map(|(span_context, _)| span_context)
+  }
+
+  /// Extracts the trace context of an incoming request, with its sampling decision when it has one.
+  fn extract_parent(
+    &self,
+    header: impl Fn(&str) -> Option<String>,
+  ) -> Option<(SpanContext, Option<bool>)> {
+    if self.trace_context {
+      if let Some(traceparent) = header(TRACEPARENT_HEADER) {
+        if let Some(span_context) = SpanContext::decode_w3c_traceparent(&traceparent) {
+          // The last part of the header contains the trace flags, the lowest bit being the "sampled" flag.
+          let sampled = traceparent
+            .split('-')
+            .nth(3)
+            .and_then(|flags| u8::from_str_radix(flags, 16).ok())
+            .map(|flags| flags & 1 == 1);
+
+          return Some((span_context, sampled));
+        }
+      }
+    }
+
+    if self.b3 {
+      if let Some(b3) = header(B3_HEADER) {
+        if let Some(span_context) = decode_b3(&b3) {
+          return Some((
+            span_context,
+            b3.split('-').nth(2).and_then(decode_b3_sampled),
+          ));
+        }
+      }
+
+      if let (Some(trace_id), Some(span_id)) =
+        (header(B3_TRACE_ID_HEADER), header(B3_SPAN_ID_HEADER))
+      {
+        let sampled = header(B3_SAMPLED_HEADER).and_then(|value| decode_b3_sampled(&value));
+
+        return decode_b3_ids(&trace_id, &span_id).map(|span_context| (span_context, sampled));
+      }
+    }
+
+    None
+  }
+
+  /// Creates the root span of a request handled by a tenant: the trace of the incoming request is continued when there
+  /// is one, otherwise a new trace is started.
+  ///
+  /// When the request isn't sampled by the head decision of the sampler, and the sampler doesn't need the outcome of
+  /// the request, the root span isn't recorded, and neither are its children.
+  pub fn root_span(
+    &self,
+    name: String,
+    tenant_id: u32,
+    endpoint: &str,
+    sampler: Option<&TraceSampler>,
+    header: impl Fn(&str) -> Option<String>,
+  ) -> Span {
+    let (span_context, parent_sampled) = match self.extract_parent(header) {
+      Some((parent, sampled)) => (parent, sampled),
+      None => (
+        SpanContext::new(generate_trace_id(tenant_id), SpanId::default()),
+        None,
+      ),
+    };
+
+    if let Some(sampler) = sampler {
+      if !sampler.decides_on_outcome()
+        && !sampler.sample_head(endpoint, span_context.trace_id, parent_sampled)
+      {
+        return Span::noop();
+      }
+    }
+
+    let span = tenant_root_span(name, span_context, tenant_id);
+
+    match parent_sampled {
+      Some(sampled) => span.with_property(|| (CONDUCTOR_PARENT_SAMPLED, sampled.to_string())),
+      None => span,
+    }
+  }
+
+  /// Returns the trace context of an incoming request, or a new one when it doesn't have one. It's forwarded to the
+  /// upstream services when the request isn't sampled, so they follow the same decision.
+  pub fn request_span_context(
+    &self,
+    tenant_id: u32,
+    header: impl Fn(&str) -> Option<String>,
+  ) -> SpanContext {
+    self
+      .extract_span_context(header)
+      .unwrap_or_else(|| SpanContext::new(generate_trace_id(tenant_id), SpanId(rand::random())))
+  }
+
+  /// Adds the request id and the trace context of the current span to a request sent to an upstream service.
+  ///
+  /// When the request isn't traced, the trace context of the request (`ctx.trace_context`) is sent instead, with the
+  /// sampled flag cleared.
+  pub fn inject_upstream_headers(
+    &self,
+    ctx: &RequestExecutionContext,
+    request: &mut ConductorHttpRequest,
+  ) {
+    if let Some(request_id) = &ctx.request_id {
+      if let (Ok(name), Ok(value)) = (
+        HeaderName::from_bytes(self.request_id_header.as_bytes()),
+        HeaderValue::from_str(request_id),
+      ) {
+        request.headers.insert(name, value);
+      }
+    }
+
+    let (span_context, sampled) = match SpanContext::current_local_parent() {
+      Some(span_context) => (span_context, true),
+      None => match ctx.trace_context {
+        Some(span_context) => (span_context, false),
+        None => return,
+      },
+    };
+
+    if self.trace_context {
+      let traceparent = format!(
+        "00-{:032x}-{:016x}-{:02x}",
+        span_context.trace_id.0, span_context.span_id.0, sampled as u8
+      );
+
+      if let Ok(value) = HeaderValue::from_str(&traceparent) {
+        request.headers.insert(TRACEPARENT_HEADER, value);
+      }
+
+      if let Some(tracestate) = ctx.downstream_http_request.headers.get(TRACESTATE_HEADER) {
+        request
+          .headers
+          .insert(TRACESTATE_HEADER, tracestate.clone());
+      }
+    }
+
+    if self.b3 {
+      let b3 = format!(
+        "{:032x}-{:016x}-{}",
+        span_context.trace_id.0, span_context.span_id.0, sampled as u8
+      );
+
+      if let Ok(value) = HeaderValue::from_str(&b3) {
+        request.headers.insert(B3_HEADER, value);
+      }
+    }
+  }
+}
+
+/// Decodes the single `b3` header: `{TraceId}-{SpanId}-{SamplingState}-{ParentSpanId}`, where the last two parts are
+/// optional.
+fn decode_b3(value: &str) -> Option<SpanContext> {
+  let mut parts = value.split('-');
+
+  decode_b3_ids(parts.next()?, parts.next()?)
+}
+
+/// Decodes the B3 sampling state: `1` (or `d`, for debug) when sampled, `0` when not.
+fn decode_b3_sampled(value: &str) -> Option<bool> {
+  match value {
+    "1" | "d" | "true" => Some(true),
+    "0" | "false" => Some(false),
+    _ => None,
+  }
+}
+
+fn decode_b3_ids(trace_id: &str, span_id: &str) -> Option<SpanContext> {
+  // B3 trace ids can be 64 or 128 bits long.
+  if !matches!(trace_id.len(), 16 | 32) || span_id.len() != 16 {
+    return None;
+  }
+
+  let trace_id = u128::from_str_radix(trace_id, 16).ok()?;
+  let span_id = u64::from_str_radix(span_id, 16).ok()?;
+
+  Some(SpanContext::new(TraceId(trace_id), SpanId(span_id)))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use super::*;
+
+  fn headers(values: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let values: HashMap<String, String> = values
+      .iter()
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+      .collect();
+
+    move |name| values.get(name).cloned()
+  }
+
+  #[test]
+  fn should_extract_span_context() {
+    let config = PropagationConfig {
+      b3: true,
+      ..Default::default()
+    };
+
+    let span_context = config
+      .extract_span_context(headers(&[(
+        "traceparent",
+        "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+      )]))
+      .unwrap();
+    assert_eq!(
+      span_context.trace_id,
+      TraceId(0x0af7651916cd43dd8448eb211c80319c)
+    );
+    assert_eq!(span_context.span_id, SpanId(0xb7ad6b7169203331));
+
+    let span_context = config
+      .extract_span_context(headers(&[("b3", "80f198ee56343ba8-e457b5a2e4d86bd1-1")]))
+      .unwrap();
+    assert_eq!(span_context.trace_id, TraceId(0x80f198ee56343ba8));
+    assert_eq!(span_context.span_id, SpanId(0xe457b5a2e4d86bd1));
+
+    let span_context = config
+      .extract_span_context(headers(&[
+        ("x-b3-traceid", "80f198ee56343ba864fe8b2a57d3eff7"),
+        ("x-b3-spanid", "e457b5a2e4d86bd1"),
+      ]))
+      .unwrap();
+    assert_eq!(
+      span_context.trace_id,
+      TraceId(0x80f198ee56343ba864fe8b2a57d3eff7)
+    );
+
+    assert!(config
+      .extract_span_context(headers(&[("b3", "invalid")]))
+      .is_none());
+    assert!(PropagationConfig::default()
+      .extract_span_context(headers(&[("b3", "80f198ee56343ba8-e457b5a2e4d86bd1-1")]))
+      .is_none());
+  }
+
+  #[test]
+  fn should_forward_trace_context_of_unsampled_requests() {
+    let config = PropagationConfig {
+      b3: true,
+      ..Default::default()
+    };
+    let incoming = headers(&[
+      (
+        "traceparent",
+        "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-00",
+      ),
+      ("tracestate", "vendor=value"),
+    ]);
+
+    let mut downstream_request = ConductorHttpRequest {
+      headers: Default::default(),
+      method: Default::default(),
+      uri: "/graphql".to_string(),
+      query_string: String::new(),
+      body: Default::default(),
+    };
+    downstream_request
+      .headers
+      .insert(TRACESTATE_HEADER, HeaderValue::from_static("vendor=value"));
+
+    let mut ctx = RequestExecutionContext::new(downstream_request.clone());
+    ctx.trace_context = Some(config.request_span_context(0, incoming));
+
+    // No span is entered, like when the root span of the request is not sampled.
+    let mut upstream_request = downstream_request;
+    config.inject_upstream_headers(&ctx, &mut upstream_request);
+
+    assert_eq!(
+      upstream_request.headers.get(TRACEPARENT_HEADER).unwrap(),
+      "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-00"
+    );
+    assert_eq!(
+      upstream_request.headers.get(TRACESTATE_HEADER).unwrap(),
+      "vendor=value"
+    );
+    assert_eq!(
+      upstream_request.headers.get(B3_HEADER).unwrap(),
+      "0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-0"
+    );
+
+    let span_context = config.request_span_context(0, headers(&[]));
+    assert_ne!(span_context.span_id, SpanId::default());
+  }
+
+  #[test]
+  fn should_accept_only_safe_request_ids() {
+    assert_eq!(
+      accept_request_id("01HQ7Z-abc_def.1:2"),
+      Some("01HQ7Z-abc_def.1:2")
+    );
+    assert_eq!(accept_request_id(""), None);
+    assert_eq!(accept_request_id("id with spaces"), None);
+    assert_eq!(accept_request_id("id\r\nx-injected: 1"), None);
+    assert_eq!(accept_request_id(&"a".repeat(129)), None);
+  }
+}
@@ -1,4 +1,9 @@
+use std::sync::Arc;
+
 use fastrace::collector::{Reporter as MinitraceSyncReporter, SpanRecord};
+use futures::{future::LocalBoxFuture, lock::Mutex};
+
+use crate::sampling::TraceSampler;
 
 #[async_trait::async_trait(?Send)]
 pub trait AsyncReporter: Send + 'static {
@@ -7,19 +12,48 @@ pub trait AsyncReporter: Send + 'static {
 
 pub struct AggregatingReporter {
   collected_spans: Vec<SpanRecord>,
-  reporter: Box<dyn AsyncReporter>,
+  // The reporter is shared with the pending flushes, so spans can be shipped without holding the lock of this reporter.
+  reporter: Arc<Mutex<Box<dyn AsyncReporter>>>,
+  sampler: Option<TraceSampler>,
 }
 
 impl AggregatingReporter {
   pub fn new(reporter: Box<dyn AsyncReporter>) -> Self {
     Self {
       collected_spans: Vec::new(),
-      reporter,
+      reporter: Arc::new(Mutex::new(reporter)),
+      sampler: None,
     }
   }
 
+  /// Only ships the spans of the requests kept by the sampler. The decision is made when the spans are flushed, once the
+  /// requests are complete, so the rules based on their outcome have access to their duration and errors.
+  pub fn with_sampler(mut self, sampler: TraceSampler) -> Self {
+    self.sampler = Some(sampler);
+
+    self
+  }
+
+  /// Takes the spans collected so far, and returns a future shipping them. The returned future doesn't borrow this
+  /// reporter, so it can be awaited after releasing any lock held on it.
+  pub fn take_flush(&mut self) -> LocalBoxFuture<'static, ()> {
+    let mut spans = std::mem::take(&mut self.collected_spans);
+
+    if let Some(sampler) = &self.sampler {
+      spans = sampler.sample(spans);
+    }
+
+    let reporter = self.reporter.clone();
+
+    Box::pin(async move {
+      if !spans.is_empty() {
+        reporter.lock().await.flush(&spans).await;
+      }
+    })
+  }
+
   pub async fn flush(&mut self) {
-    self.reporter.flush(&self.collected_spans).await;
+    self.take_flush().await
   }
 }
 
@@ -45,10 +79,18 @@ impl TracingReporter {
     }
   }
 
-  pub async fn flush(&mut self) {
+  /// Takes the spans waiting to be shipped, and returns a future shipping them.
+  pub fn take_flush(&mut self) -> Option<LocalBoxFuture<'static, ()>> {
     // Only the AggregatingReporter needs to flush the spans at this point.
-    if let TracingReporter::Aggregating(reporter) = self {
-      reporter.flush().await;
+    match self {
+      TracingReporter::Aggregating(reporter) => Some(reporter.take_flush()),
+      TracingReporter::Simple(_) => None,
+    }
+  }
+
+  pub async fn flush(&mut self) {
+    if let Some(flush) = self.take_flush() {
+      flush.await;
     }
   }
 }
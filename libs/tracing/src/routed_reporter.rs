@@ -5,21 +5,21 @@ use std::{
 
 use fastrace::collector::{Reporter, SpanRecord};
 
-use crate::reporters::TracingReporter;
-
-pub type RouterFn = fn(&SpanRecord) -> Option<u32>;
-
+use crate::{otel_attrs::CONDUCTOR_TENANT_ID, reporters::TracingReporter};
+
+/// Reports the spans of each request to the reporter of its tenant.
+///
+/// The tenant is taken from the root span of the request (see `tenant_root_span`), found by following the parents of
+/// the spans: fastrace reports the spans of a trace together, once its root span is finished. Spans without a known
+/// tenant are dropped.
+#[derive(Default)]
 pub struct RoutedReporter {
   reporters: HashMap<u32, Arc<Mutex<TracingReporter>>>,
-  router_fn: RouterFn,
 }
 
 impl RoutedReporter {
-  pub fn new(router_fn: RouterFn) -> Self {
-    Self {
-      reporters: HashMap::new(),
-      router_fn,
-    }
+  pub fn new() -> Self {
+    Self::default()
   }
 
   pub fn with_reporter(mut self, tenant_id: u32, reporter: Arc<Mutex<TracingReporter>>) -> Self {
@@ -30,16 +30,19 @@ impl RoutedReporter {
 
   pub fn make_report(&mut self, spans: &[SpanRecord]) {
     let mut chunks: HashMap<u32, Vec<SpanRecord>> = HashMap::new();
+    let mut dropped = 0;
 
-    for span in spans {
-      if let Some(key) = (self.router_fn)(span) {
-        let chunk = chunks.entry(key).or_default();
-        chunk.push(span.clone());
-      } else {
-        tracing::warn!("no key for span: {:?}, dropping span", span);
+    for (span, tenant_id) in spans.iter().zip(resolve_tenants(spans)) {
+      match tenant_id {
+        Some(tenant_id) => chunks.entry(tenant_id).or_default().push(span.clone()),
+        None => dropped += 1,
       }
     }
 
+    if dropped > 0 {
+      tracing::warn!("dropping {} span(s) without a known tenant", dropped);
+    }
+
     for (key, chunk) in chunks {
       if let Some(reporter) = self.reporters.get_mut(&key) {
         let mut r = reporter.lock().unwrap();
@@ -49,6 +52,50 @@ impl RoutedReporter {
   }
 }
 
+/// Resolves the tenant of every span, from the root span of its request.
+fn resolve_tenants(spans: &[SpanRecord]) -> Vec<Option<u32>> {
+  find_request_roots(spans)
+    .into_iter()
+    .map(|root| root.and_then(|root| tenant_id(&spans[root])))
+    .collect()
+}
+
+/// Finds the root span of the request of every span, as an index in `spans`: the closest ancestor carrying a tenant.
+pub(crate) fn find_request_roots(spans: &[SpanRecord]) -> Vec<Option<usize>> {
+  let by_id: HashMap<(u128, u64), usize> = spans
+    .iter()
+    .enumerate()
+    .map(|(index, span)| ((span.trace_id.0, span.span_id.0), index))
+    .collect();
+
+  (0..spans.len())
+    .map(|index| {
+      let mut current = index;
+
+      // Bounded by the number of spans, in case the parents form a cycle.
+      for _ in 0..spans.len() {
+        let span = &spans[current];
+
+        if tenant_id(span).is_some() {
+          return Some(current);
+        }
+
+        current = *by_id.get(&(span.trace_id.0, span.parent_id.0))?;
+      }
+
+      None
+    })
+    .collect()
+}
+
+fn tenant_id(span: &SpanRecord) -> Option<u32> {
+  span
+    .properties
+    .iter()
+    .find(|(k, _)| k == CONDUCTOR_TENANT_ID)
+    .and_then(|(_, v)| v.parse().ok())
+}
+
 impl Reporter for RoutedReporter {
   #[cfg(target_arch = "wasm32")]
   fn report(&mut self, spans: &[SpanRecord]) {
@@ -96,73 +143,47 @@ pub mod test_utils {
   }
 }
 
-// #[cfg(test)]
-// mod test {
-//   use super::*;
-
-//   #[tokio::test]
-//   async fn routed_reporter() {
-//     let (spans0, reporter0) = test_utils::TestReporter::new();
-//     let (spans1, reporter1) = test_utils::TestReporter::new();
-//     let mut routed_reporter =
-//       RoutedReporter::new(|span| Some(MinitraceManager::extract_tenant_id(span.trace_id)))
-//         .with_reporter(0, Box::new(reporter0).into())
-//         .with_reporter(1, Box::new(reporter1).into());
-
-//     routed_reporter
-//       .report(&vec![
-//         // This one goes to tenant 2, it does not exists
-//         SpanRecord {
-//           trace_id: MinitraceManager::generate_trace_id(2),
-//           ..Default::default()
-//         },
-//         // This one goes to tenant 0
-//         SpanRecord {
-//           trace_id: MinitraceManager::generate_trace_id(0),
-//           ..Default::default()
-//         },
-//         // This one goes to tenant 0
-//         SpanRecord {
-//           trace_id: MinitraceManager::generate_trace_id(0),
-//           ..Default::default()
-//         },
-//         // This one goes to tenant 1
-//         SpanRecord {
-//           trace_id: MinitraceManager::generate_trace_id(1),
-//           ..Default::default()
-//         },
-//         // This one goes to tenant 2, it does not exists
-//         SpanRecord {
-//           trace_id: MinitraceManager::generate_trace_id(2),
-//           ..Default::default()
-//         },
-//       ])
-//       .await;
-
-//     routed_reporter
-//       .report(&vec![
-//         // This one goes to tenant 1
-//         SpanRecord {
-//           trace_id: MinitraceManager::generate_trace_id(1),
-//           ..Default::default()
-//         },
-//         // This one goes to tenant 1
-//         SpanRecord {
-//           trace_id: MinitraceManager::generate_trace_id(1),
-//           ..Default::default()
-//         },
-//         // This one goes to tenant 2
-//         SpanRecord {
-//           trace_id: MinitraceManager::generate_trace_id(2),
-//           ..Default::default()
-//         },
-//       ])
-//       .await;
-
-//     let spans0 = spans0.lock().unwrap();
-//     let spans1 = spans1.lock().unwrap();
-
-//     assert_eq!(spans0.len(), 2);
-//     assert_eq!(spans1.len(), 3);
-//   }
-// }
+#[cfg(test)]
+mod tests {
+  use std::borrow::Cow;
+
+  use fastrace::collector::{SpanId, TraceId};
+
+  use super::*;
+
+  fn span(trace_id: u128, span_id: u64, parent_id: u64, tenant_id: Option<u32>) -> SpanRecord {
+    SpanRecord {
+      trace_id: TraceId(trace_id),
+      span_id: SpanId(span_id),
+      parent_id: SpanId(parent_id),
+      properties: tenant_id
+        .map(|tenant_id| {
+          vec![(
+            Cow::Borrowed(CONDUCTOR_TENANT_ID),
+            Cow::Owned(tenant_id.to_string()),
+          )]
+        })
+        .unwrap_or_default(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn should_resolve_tenant_from_root_span() {
+    let tenants = resolve_tenants(&[
+      // Two requests continuing the same client trace, handled by different tenants.
+      span(7, 1, 99, Some(0)),
+      span(7, 2, 1, None),
+      span(7, 3, 2, None),
+      span(7, 4, 99, Some(1)),
+      span(7, 5, 4, None),
+      // A span whose root isn't reported.
+      span(8, 6, 42, None),
+    ]);
+
+    assert_eq!(
+      tenants,
+      vec![Some(0), Some(0), Some(0), Some(1), Some(1), None]
+    );
+  }
+}
@@ -0,0 +1,268 @@
+use std::{collections::HashMap, time::Duration};
+
+use fastrace::collector::{SpanRecord, TraceId};
+
+use crate::{
+  otel_attrs::{
+    CONDUCTOR_ENDPOINT, CONDUCTOR_PARENT_SAMPLED, ERROR_INDICATOR, GRAPHQL_ERROR_COUNT,
+    GRAPHQL_OPERATION_NAME, OTEL_STATUS_CODE,
+  },
+  routed_reporter::find_request_roots,
+};
+
+/// Decides which traces are exported by a reporter.
+///
+/// The head decision is made when the root span of a request is created (see `PropagationConfig::root_span`): the
+/// spans of requests that aren't sampled are not recorded. Rules based on the outcome of the request (errors, tail
+/// sampling and operation ratios) need the complete trace: they're applied by the aggregating reporters when the spans
+/// are shipped, and the requests are recorded until then.
+#[derive(Debug, Clone)]
+pub struct TraceSampler {
+  /// The ratio of traces to keep, between `0.0` and `1.0`.
+  pub ratio: f64,
+  /// Follows the sampling decision of the incoming trace context, when there is one.
+  pub parent_based: bool,
+  /// Keeps the traces of failed requests, regardless of the ratio.
+  pub always_sample_errors: bool,
+  /// Ratios for specific endpoints, by endpoint path.
+  pub endpoint_ratios: HashMap<String, f64>,
+  /// Ratios for specific GraphQL operations, by operation name.
+  pub operation_ratios: HashMap<String, f64>,
+  /// Keeps traces that weren't sampled, based on their outcome.
+  pub tail: Option<TailSampling>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TailSampling {
+  /// Keeps the traces of requests that took longer than the threshold.
+  pub latency_threshold: Option<Duration>,
+  /// Keeps the traces containing GraphQL errors.
+  pub graphql_errors: bool,
+}
+
+impl Default for TraceSampler {
+  fn default() -> Self {
+    Self {
+      ratio: 1.0,
+      parent_based: true,
+      always_sample_errors: true,
+      endpoint_ratios: HashMap::new(),
+      operation_ratios: HashMap::new(),
+      tail: None,
+    }
+  }
+}
+
+impl TraceSampler {
+  /// Returns the same sampler, without the rules based on the outcome of the request.
+  pub fn head_only(self) -> Self {
+    Self {
+      always_sample_errors: false,
+      operation_ratios: HashMap::new(),
+      tail: None,
+      ..self
+    }
+  }
+
+  /// Whether some rules need the outcome of the request, so requests must be recorded even if they aren't sampled by
+  /// the head decision.
+  pub fn decides_on_outcome(&self) -> bool {
+    self.always_sample_errors || self.tail.is_some() || !self.operation_ratios.is_empty()
+  }
+
+  /// Decides whether a request is sampled, when its root span is created.
+  pub fn sample_head(
+    &self,
+    endpoint: &str,
+    trace_id: TraceId,
+    parent_sampled: Option<bool>,
+  ) -> bool {
+    if self.parent_based {
+      if let Some(sampled) = parent_sampled {
+        return sampled;
+      }
+    }
+
+    let ratio = self
+      .endpoint_ratios
+      .get(endpoint)
+      .copied()
+      .unwrap_or(self.ratio);
+
+    ratio_position(trace_id.0) < ratio
+  }
+
+  /// Returns the spans of the sampled requests. The decision is made once per request, based on all its spans.
+  ///
+  /// The spans of a request are reported together, once its root span is finished. Spans whose root span is missing
+  /// are dropped.
+  pub fn sample(&self, spans: Vec<SpanRecord>) -> Vec<SpanRecord> {
+    let roots = find_request_roots(&spans);
+    let mut requests: HashMap<usize, Vec<&SpanRecord>> = HashMap::new();
+
+    for (span, root) in spans.iter().zip(&roots) {
+      if let Some(root) = root {
+        requests.entry(*root).or_default().push(span);
+      }
+    }
+
+    let sampled: HashMap<usize, bool> = requests
+      .into_iter()
+      .map(|(root, request)| (root, self.should_sample(&spans[root], &request)))
+      .collect();
+
+    spans
+      .into_iter()
+      .zip(roots)
+      .filter(|(_, root)| root.is_some_and(|root| sampled[&root]))
+      .map(|(span, _)| span)
+      .collect()
+  }
+
+  fn should_sample(&self, root: &SpanRecord, spans: &[&SpanRecord]) -> bool {
+    if self.always_sample_errors && spans.iter().any(|span| is_error(span)) {
+      return true;
+    }
+
+    if let Some(tail) = &self.tail {
+      if tail.should_keep(root, spans) {
+        return true;
+      }
+    }
+
+    let parent_sampled = find_property(&[root], CONDUCTOR_PARENT_SAMPLED).map(|v| v == "true");
+
+    if !(self.parent_based && parent_sampled.is_some()) {
+      if let Some(ratio) = find_property(spans, GRAPHQL_OPERATION_NAME)
+        .and_then(|name| self.operation_ratios.get(name))
+      {
+        return ratio_position(root.trace_id.0) < *ratio;
+      }
+    }
+
+    let endpoint = find_property(&[root], CONDUCTOR_ENDPOINT).unwrap_or_default();
+
+    self.sample_head(endpoint, root.trace_id, parent_sampled)
+  }
+}
+
+impl TailSampling {
+  fn should_keep(&self, root: &SpanRecord, spans: &[&SpanRecord]) -> bool {
+    if self.graphql_errors
+      && spans
+        .iter()
+        .any(|span| find_property(&[*span], GRAPHQL_ERROR_COUNT).is_some())
+    {
+      return true;
+    }
+
+    match self.latency_threshold {
+      Some(threshold) => root.duration_ns > threshold.as_nanos() as u64,
+      None => false,
+    }
+  }
+}
+
+fn find_property<'a>(spans: &[&'a SpanRecord], key: &str) -> Option<&'a str> {
+  spans.iter().find_map(|span| {
+    span
+      .properties
+      .iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, v)| v.as_ref())
+  })
+}
+
+fn is_error(span: &SpanRecord) -> bool {
+  span
+    .properties
+    .iter()
+    .any(|(k, v)| (k == ERROR_INDICATOR && v == "true") || (k == OTEL_STATUS_CODE && v == "ERROR"))
+}
+
+/// Maps a trace id to a stable position in `[0.0, 1.0)`, so all the spans of a trace get the same decision.
+fn ratio_position(trace_id: u128) -> f64 {
+  // splitmix64 finalizer, to spread the ids evenly.
+  let mut x = (trace_id as u64) ^ ((trace_id >> 64) as u64);
+  x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+  x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+  x ^= x >> 31;
+
+  (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+  use std::borrow::Cow;
+
+  use fastrace::collector::SpanId;
+
+  use super::*;
+  use crate::otel_attrs::CONDUCTOR_TENANT_ID;
+
+  /// The spans of a request: its root span, and a child span with the given properties.
+  fn request(
+    trace_id: u128,
+    duration_ns: u64,
+    properties: &[(&'static str, &'static str)],
+  ) -> Vec<SpanRecord> {
+    let root = SpanRecord {
+      trace_id: TraceId(trace_id),
+      span_id: SpanId(1),
+      duration_ns,
+      properties: vec![(Cow::Borrowed(CONDUCTOR_TENANT_ID), Cow::Borrowed("0"))],
+      ..Default::default()
+    };
+    let child = SpanRecord {
+      trace_id: TraceId(trace_id),
+      span_id: SpanId(2),
+      parent_id: SpanId(1),
+      properties: properties
+        .iter()
+        .map(|(k, v)| (Cow::Borrowed(*k), Cow::Borrowed(*v)))
+        .collect(),
+      ..Default::default()
+    };
+
+    vec![root, child]
+  }
+
+  #[test]
+  fn should_sample_traces() {
+    let sampler = TraceSampler {
+      ratio: 0.0,
+      operation_ratios: HashMap::from([("GetUser".to_string(), 1.0)]),
+      tail: Some(TailSampling {
+        latency_threshold: Some(Duration::from_millis(500)),
+        graphql_errors: true,
+      }),
+      ..Default::default()
+    };
+
+    let sampled = sampler.sample(
+      [
+        request(1, 0, &[]),
+        request(2, 0, &[(GRAPHQL_OPERATION_NAME, "GetUser")]),
+        request(3, 0, &[(GRAPHQL_ERROR_COUNT, "1")]),
+        request(4, 600_000_000, &[]),
+        request(5, 0, &[(OTEL_STATUS_CODE, "ERROR")]),
+      ]
+      .concat(),
+    );
+
+    let trace_ids: Vec<u128> = sampled.iter().map(|span| span.trace_id.0).collect();
+    assert_eq!(trace_ids, vec![2, 2, 3, 3, 4, 4, 5, 5]);
+
+    let sampler = TraceSampler {
+      ratio: 0.5,
+      endpoint_ratios: HashMap::from([("/all".to_string(), 1.0)]),
+      ..Default::default()
+    };
+    let sampled = (0..1000)
+      .filter(|i| sampler.sample_head("/graphql", TraceId(*i), None))
+      .count();
+    assert!(sampled > 400 && sampled < 600);
+    assert!(sampler.sample_head("/all", TraceId(1), None));
+    assert!(!sampler.sample_head("/all", TraceId(1), Some(false)));
+  }
+}
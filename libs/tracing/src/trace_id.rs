@@ -1,4 +1,11 @@
-use fastrace::collector::TraceId;
+use std::borrow::Cow;
+
+use fastrace::{
+  collector::{SpanContext, TraceId},
+  Span,
+};
+
+use crate::otel_attrs::CONDUCTOR_TENANT_ID;
 
 pub fn generate_trace_id(tenant_id: u32) -> TraceId {
   let uniq: u32 = rand::random();
@@ -9,3 +16,15 @@ pub fn generate_trace_id(tenant_id: u32) -> TraceId {
 pub fn extract_tenant_id(trace_id: TraceId) -> u32 {
   (trace_id.0 >> 32) as u32
 }
+
+/// Creates the root span of a request handled by a tenant.
+///
+/// The tenant is carried by the root span, instead of being derived from the trace id: the trace id of a continued
+/// trace comes from the client. The spans of the request are reported to the tenant of their root span.
+pub fn tenant_root_span(
+  name: impl Into<Cow<'static, str>>,
+  span_context: SpanContext,
+  tenant_id: u32,
+) -> Span {
+  Span::root(name, span_context).with_property(|| (CONDUCTOR_TENANT_ID, tenant_id.to_string()))
+}
@@ -0,0 +1,123 @@
+use conductor_common::{
+  serde_utils::{JsonSchemaExample, JsonSchemaExampleMetadata, JsonSchemaExampleWrapperType},
+  vrl_utils::VrlConfigReference,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The `client_awareness` plugin identifies the client application that sent a request, using its name and version.
+///
+/// The identified client is attached to the traces (as `conductor.client.name` and `conductor.client.version` attributes of the GraphQL operation span) and to the logs of the request. It's also available to the plugins that follow, for example `usage_reporting`.
+///
+/// The name and the version can be read from an HTTP header, from a claim of the JWT authenticated by the `jwt_auth` plugin, or computed with a VRL expression. If the name can't be resolved, the request is handled without a client.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[schemars(example = "client_awareness_example_1")]
+#[schemars(example = "client_awareness_example_2")]
+#[schemars(example = "client_awareness_example_3")]
+pub struct ClientAwarenessPluginConfig {
+  /// Where to find the name of the client.
+  #[serde(default = "default_name")]
+  pub name: ClientAwarenessSource,
+  /// Where to find the version of the client.
+  #[serde(default = "default_version")]
+  pub version: ClientAwarenessSource,
+}
+
+impl Default for ClientAwarenessPluginConfig {
+  fn default() -> Self {
+    Self {
+      name: default_name(),
+      version: default_version(),
+    }
+  }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[serde(tag = "from")]
+pub enum ClientAwarenessSource {
+  /// Reads the value from a header of the incoming HTTP request.
+  #[serde(rename = "header")]
+  #[schemars(title = "header")]
+  Header { name: String },
+  /// Reads the value from a claim of the JWT authenticated by the `jwt_auth` plugin. Nested claims can be read using a dot-separated path, e.g. `app.name`.
+  ///
+  /// The `jwt_auth` plugin needs to be declared before this plugin.
+  #[serde(rename = "jwt_claim")]
+  #[schemars(title = "jwt_claim")]
+  JwtClaim { claim: String },
+  /// Computes the value with a VRL expression, that has access to the metadata field `%downstream_http_req` (fields: `body`, `uri`, `query_string`, `method`, `headers`).
+  ///
+  /// The expression must return a string, or `null` if the value is missing.
+  #[serde(rename = "vrl")]
+  #[schemars(title = "vrl")]
+  Vrl { expression: VrlConfigReference },
+}
+
+fn default_name() -> ClientAwarenessSource {
+  ClientAwarenessSource::Header {
+    name: "apollographql-client-name".to_string(),
+  }
+}
+
+fn default_version() -> ClientAwarenessSource {
+  ClientAwarenessSource::Header {
+    name: "apollographql-client-version".to_string(),
+  }
+}
+
+fn client_awareness_example_1() -> JsonSchemaExample<ClientAwarenessPluginConfig> {
+  JsonSchemaExample {
+    metadata: JsonSchemaExampleMetadata::new(
+      "Headers",
+      Some("This example identifies the client using the `apollographql-client-name` and `apollographql-client-version` headers."),
+    ),
+    wrapper: Some(JsonSchemaExampleWrapperType::Plugin {
+      name: "client_awareness".to_string(),
+    }),
+    example: ClientAwarenessPluginConfig::default(),
+  }
+}
+
+fn client_awareness_example_2() -> JsonSchemaExample<ClientAwarenessPluginConfig> {
+  JsonSchemaExample {
+    metadata: JsonSchemaExampleMetadata::new(
+      "JWT Claims",
+      Some("This example identifies the client using the `client_id` claim of the authenticated JWT, and its version using the `x-client-version` header."),
+    ),
+    wrapper: Some(JsonSchemaExampleWrapperType::Plugin {
+      name: "client_awareness".to_string(),
+    }),
+    example: ClientAwarenessPluginConfig {
+      name: ClientAwarenessSource::JwtClaim {
+        claim: "client_id".to_string(),
+      },
+      version: ClientAwarenessSource::Header {
+        name: "x-client-version".to_string(),
+      },
+    },
+  }
+}
+
+fn client_awareness_example_3() -> JsonSchemaExample<ClientAwarenessPluginConfig> {
+  JsonSchemaExample {
+    metadata: JsonSchemaExampleMetadata::new(
+      "VRL",
+      Some("This example identifies the client from the `User-Agent` header, using VRL."),
+    ),
+    wrapper: Some(JsonSchemaExampleWrapperType::Plugin {
+      name: "client_awareness".to_string(),
+    }),
+    example: ClientAwarenessPluginConfig {
+      name: ClientAwarenessSource::Vrl {
+        expression: VrlConfigReference::Inline {
+          content: "parsed, err = parse_regex(%downstream_http_req.headers.\"user-agent\", r'^(?P<name>[^/]+)/(?P<version>\\S+)')\nparsed.name".to_string(),
+        },
+      },
+      version: ClientAwarenessSource::Vrl {
+        expression: VrlConfigReference::Inline {
+          content: "parsed, err = parse_regex(%downstream_http_req.headers.\"user-agent\", r'^(?P<name>[^/]+)/(?P<version>\\S+)')\nparsed.version".to_string(),
+        },
+      },
+    },
+  }
+}
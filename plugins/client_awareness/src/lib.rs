@@ -0,0 +1,6 @@
+mod config;
+mod plugin;
+
+pub use config::ClientAwarenessPluginConfig as Config;
+pub use config::ClientAwarenessSource as Source;
+pub use plugin::ClientAwarenessPlugin as Plugin;
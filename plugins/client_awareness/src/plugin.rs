@@ -0,0 +1,196 @@
+use conductor_common::{
+  execute::{ClientInfo, RequestExecutionContext},
+  plugin::{CreatablePlugin, Plugin, PluginError},
+  vrl_utils::{conductor_request_to_value, VrlProgramProxy},
+};
+use serde_json::Value;
+use tracing::{debug, error, warn};
+use vrl::value;
+
+use crate::config::{ClientAwarenessPluginConfig, ClientAwarenessSource};
+
+#[derive(Debug)]
+pub struct ClientAwarenessPlugin {
+  name: ValueResolver,
+  version: ValueResolver,
+}
+
+#[derive(Debug)]
+enum ValueResolver {
+  Header(String),
+  JwtClaim(Vec<String>),
+  Vrl(VrlProgramProxy),
+}
+
+impl ValueResolver {
+  fn new(source: &ClientAwarenessSource) -> Result<Self, PluginError> {
+    Ok(match source {
+      ClientAwarenessSource::Header { name } => ValueResolver::Header(name.clone()),
+      ClientAwarenessSource::JwtClaim { claim } => {
+        ValueResolver::JwtClaim(claim.split('.').map(ToString::to_string).collect())
+      }
+      ClientAwarenessSource::Vrl { expression } => match expression.program() {
+        Ok(program) => ValueResolver::Vrl(program),
+        Err(e) => {
+          return Err(PluginError::InitError {
+            source: anyhow::anyhow!("vrl compiler error: {:?}", e),
+          })
+        }
+      },
+    })
+  }
+
+  fn resolve(&self, ctx: &mut RequestExecutionContext) -> Option<String> {
+    let value = match self {
+      ValueResolver::Header(name) => ctx
+        .downstream_http_request
+        .headers
+        .get(name.as_str())
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string),
+      ValueResolver::JwtClaim(path) => {
+        let claims = ctx.ctx_get(jwt_auth_plugin::CLAIMS_CONTEXT_KEY)?;
+
+        match path.iter().try_fold(claims, |value, key| value.get(key))? {
+          Value::String(value) => Some(value.clone()),
+          Value::Number(value) => Some(value.to_string()),
+          _ => None,
+        }
+      }
+      ValueResolver::Vrl(program) => {
+        let downstream_http_req = conductor_request_to_value(&ctx.downstream_http_request);
+
+        match program.resolve_with_state(
+          value::Value::Null,
+          value!({
+            downstream_http_req: downstream_http_req,
+          }),
+          ctx.vrl_shared_state(),
+        ) {
+          Ok(value::Value::Bytes(value)) => Some(String::from_utf8_lossy(&value).to_string()),
+          Ok(value::Value::Null) => None,
+          Ok(ret) => {
+            warn!("ClientAwarenessPlugin::vrl::expression must return a string, but returned a non-string value: {:?}, ignoring...", ret);
+
+            None
+          }
+          Err(err) => {
+            error!(
+              "ClientAwarenessPlugin::vrl::expression resolve error: {:?}",
+              err
+            );
+
+            None
+          }
+        }
+      }
+    };
+
+    value.filter(|value| !value.is_empty())
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl CreatablePlugin for ClientAwarenessPlugin {
+  type Config = ClientAwarenessPluginConfig;
+
+  async fn create(config: Self::Config) -> Result<Box<Self>, PluginError> {
+    Ok(Box::new(Self {
+      name: ValueResolver::new(&config.name)?,
+      version: ValueResolver::new(&config.version)?,
+    }))
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Plugin for ClientAwarenessPlugin {
+  async fn on_downstream_http_request(&self, ctx: &mut RequestExecutionContext) {
+    if let Some(name) = self.name.resolve(ctx) {
+      let version = self.version.resolve(ctx);
+      debug!("identified client \"{}\" (version: {:?})", name, version);
+
+      ctx.client = Some(ClientInfo { name, version });
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use conductor_common::{
+    http::{ConductorHttpRequest, HeaderValue},
+    vrl_utils::VrlConfigReference,
+  };
+  use serde_json::json;
+
+  use super::*;
+
+  async fn identify(config: ClientAwarenessPluginConfig, ctx: &mut RequestExecutionContext) {
+    let plugin = ClientAwarenessPlugin::create(config).await.unwrap();
+
+    plugin.on_downstream_http_request(ctx).await;
+  }
+
+  #[tokio::test]
+  async fn should_identify_client_from_headers() {
+    let mut request = ConductorHttpRequest::default();
+    request
+      .headers
+      .insert("apollographql-client-name", HeaderValue::from_static("web"));
+    request.headers.insert(
+      "apollographql-client-version",
+      HeaderValue::from_static("1.2.0"),
+    );
+    let mut ctx = RequestExecutionContext::new(request);
+
+    identify(Default::default(), &mut ctx).await;
+
+    assert_eq!(
+      ctx.client,
+      Some(ClientInfo {
+        name: "web".to_string(),
+        version: Some("1.2.0".to_string()),
+      })
+    );
+
+    let mut ctx = RequestExecutionContext::new(ConductorHttpRequest::default());
+    identify(Default::default(), &mut ctx).await;
+    assert_eq!(ctx.client, None);
+  }
+
+  #[tokio::test]
+  async fn should_identify_client_from_jwt_claims_and_vrl() {
+    let mut request = ConductorHttpRequest::default();
+    request
+      .headers
+      .insert("user-agent", HeaderValue::from_static("ios-app/3.1.4"));
+    let mut ctx = RequestExecutionContext::new(request);
+    ctx.ctx_insert(
+      jwt_auth_plugin::CLAIMS_CONTEXT_KEY,
+      json!({ "app": { "name": "ios" } }),
+    );
+
+    identify(
+      ClientAwarenessPluginConfig {
+        name: ClientAwarenessSource::JwtClaim {
+          claim: "app.name".to_string(),
+        },
+        version: ClientAwarenessSource::Vrl {
+          expression: VrlConfigReference::Inline {
+            content: "split(string!(%downstream_http_req.headers.\"user-agent\"), \"/\")[1]"
+              .to_string(),
+          },
+        },
+      },
+      &mut ctx,
+    )
+    .await;
+
+    assert_eq!(
+      ctx.client,
+      Some(ClientInfo {
+        name: "ios".to_string(),
+        version: Some("3.1.4".to_string()),
+      })
+    );
+  }
+}
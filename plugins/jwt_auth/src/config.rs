@@ -15,12 +15,15 @@ use std::time::Duration;
 /// By default, the plugin will look for the JWT token in the `Authorization` header, with the `Bearer` prefix.
 ///
 /// You can also configure the plugin to reject requests that don't have a valid JWT token.
+///
+/// The claims of a valid token are always stored in the request context, so the plugins declared after this one can use them (for example, `client_awareness` with a `jwt_claim` source).
 #[derive(Deserialize, Serialize, Debug, Clone, JsonSchema, Default)]
 #[schemars(example = "jwt_auth_example_1")]
 #[schemars(example = "jwt_auth_example_2")]
 #[schemars(example = "jwt_auth_example_3")]
 #[schemars(example = "jwt_auth_example_4")]
 #[schemars(example = "jwt_auth_example_5")]
+#[schemars(example = "jwt_auth_example_6")]
 pub struct JwtAuthPluginConfig {
   /// A list of JWKS providers to use for verifying the JWT signature.
   /// Can be either a path to a local JSON of the file-system, or a URL to a remote JWKS provider.
@@ -128,6 +131,23 @@ pub enum JwksProviderSourceConfig {
     /// If set to `true`, the JWKS will be fetched on startup and cached. In case of invalid JWKS, the error will be ignored and the plugin will try to fetch again when server receives the first request.
     /// If set to `false`, the JWKS will be fetched on-demand, when the first request comes in.
     prefetch: Option<bool>,
+    /// A cache shared by the instances of the gateway, where the fetched JWKS is stored for `cache_duration`.
+    /// When set, a new instance of the gateway reads the JWKS from this cache, instead of fetching it again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cache: Option<JwksCacheConfig>,
+  },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[serde(tag = "source")]
+pub enum JwksCacheConfig {
+  /// Stores the JWKS using the [Cache API](https://developers.cloudflare.com/workers/runtime-apis/cache/), available on Cloudflare Worker runtime.
+  #[serde(rename = "cache_api")]
+  #[schemars(title = "cache_api")]
+  CacheApi {
+    /// The name of the cache to open. If not specified, the default cache is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cache_name: Option<String>,
   },
 }
 fn default_polling_interval() -> Option<Duration> {
@@ -178,6 +198,7 @@ fn jwt_auth_example_2() -> JsonSchemaExample<JwtAuthPluginConfig> {
         url: "https://example.com/jwks.json".to_string(),
         cache_duration: Some(Duration::from_secs(10 * 60)),
         prefetch: Some(true),
+        cache: None,
       }],
       lookup_locations: vec![JwtAuthPluginLookupLocation::Header {
         name: "Authorization".to_string(),
@@ -204,6 +225,7 @@ fn jwt_auth_example_3() -> JsonSchemaExample<JwtAuthPluginConfig> {
         url: "https://example.com/jwks.json".to_string(),
         cache_duration: Some(Duration::from_secs(10 * 60)),
         prefetch: Some(true),
+        cache: None,
       }],
       lookup_locations: vec![JwtAuthPluginLookupLocation::Cookie {
         name: "auth".to_string(),
@@ -230,6 +252,7 @@ fn jwt_auth_example_4() -> JsonSchemaExample<JwtAuthPluginConfig> {
         url: "https://example.com/jwks.json".to_string(),
         cache_duration: Some(Duration::from_secs(10 * 60)),
         prefetch: Some(true),
+        cache: None,
       }],
       lookup_locations: vec![JwtAuthPluginLookupLocation::Cookie {
         name: "jwt".to_string(),
@@ -258,6 +281,7 @@ fn jwt_auth_example_5() -> JsonSchemaExample<JwtAuthPluginConfig> {
         url: "https://example.com/jwks.json".to_string(),
         cache_duration: Some(Duration::from_secs(10 * 60)),
         prefetch: None,
+        cache: None,
       }],
       lookup_locations: vec![JwtAuthPluginLookupLocation::Cookie {
         name: "jwt".to_string(),
@@ -268,3 +292,30 @@ fn jwt_auth_example_5() -> JsonSchemaExample<JwtAuthPluginConfig> {
     },
   }
 }
+
+fn jwt_auth_example_6() -> JsonSchemaExample<JwtAuthPluginConfig> {
+  JsonSchemaExample {
+    metadata: JsonSchemaExampleMetadata::new(
+      "Cached Remote JWKS",
+      Some(
+        "This example is loading a remote JWKS on Cloudflare Worker runtime, and stores it using the Cache API, so new instances of the Worker don't fetch it again.",
+      ),
+    ),
+    wrapper: Some(JsonSchemaExampleWrapperType::Plugin {
+      name: "jwt_auth".to_string(),
+    }),
+    example: JwtAuthPluginConfig {
+      jwks_providers: vec![JwksProviderSourceConfig::Remote {
+        url: "https://example.com/jwks.json".to_string(),
+        cache_duration: Some(Duration::from_secs(10 * 60)),
+        prefetch: None,
+        cache: Some(JwksCacheConfig::CacheApi { cache_name: None }),
+      }],
+      lookup_locations: vec![JwtAuthPluginLookupLocation::Header {
+        name: "Authorization".to_string(),
+        prefix: Some("Bearer".to_string()),
+      }],
+      ..Default::default()
+    },
+  }
+}
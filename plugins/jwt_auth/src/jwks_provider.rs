@@ -5,14 +5,17 @@ use std::{
 
 use web_time::SystemTime;
 
+use conductor_common::kv::{resolve_key_value_store, KeyValueBinding, KeyValueStore};
 use jsonwebtoken::jwk::JwkSet;
 
-use crate::config::JwksProviderSourceConfig;
+use crate::config::{JwksCacheConfig, JwksProviderSourceConfig};
 
 #[derive(Debug)]
 pub struct JwksProvider {
   config: JwksProviderSourceConfig,
   jwk: RwLock<Option<Arc<TimedJwtSet>>>,
+  /// The store of the `cache` configured for a remote JWKS, if it's available on the runtime.
+  cache: Option<Arc<dyn KeyValueStore>>,
 }
 
 #[derive(Debug)]
@@ -39,12 +42,21 @@ pub enum JwksProviderError {
 
 impl JwksProvider {
   async fn load_jwks(&self) -> Result<&Self, JwksProviderError> {
-    let new_jwk = Some(Arc::new(match &self.config {
+    let new_jwk = match &self.config {
       JwksProviderSourceConfig::Remote {
         url,
         cache_duration,
-        prefetch: _,
+        ..
       } => {
+        let cache_duration = cache_duration.unwrap_or(Duration::from_secs(10 * 60));
+        let expiration = SystemTime::now().checked_add(cache_duration);
+
+        if let Some(set) = self.load_cached_jwks(url).await {
+          tracing::debug!("loaded jwks for a remote source from cache: {}", url);
+
+          return self.store_jwks(TimedJwtSet { expiration, set });
+        }
+
         // @expected: if initiating an http client fails, then we have to exit.
         let client = wasm_polyfills::create_http_client().build().unwrap();
         tracing::debug!("loading jwks for a remote source: {}", url);
@@ -57,11 +69,15 @@ impl JwksProvider {
           .text()
           .await
           .map_err(JwksProviderError::RemoteJwksNetworkError)?;
-        let expiration =
-          SystemTime::now().checked_add(cache_duration.unwrap_or(Duration::from_secs(10 * 60)));
         let set = serde_json::from_str::<JwkSet>(&response_text)
           .map_err(JwksProviderError::JwksContentInvalidStructure)?;
 
+        if let Some(cache) = &self.cache {
+          if let Err(e) = cache.put(url, &response_text, Some(cache_duration)).await {
+            tracing::warn!("failed to store jwks in cache: {}", e);
+          }
+        }
+
         TimedJwtSet { expiration, set }
       }
       JwksProviderSourceConfig::Local { file } => TimedJwtSet {
@@ -69,19 +85,59 @@ impl JwksProvider {
         set: serde_json::from_str::<JwkSet>(&file.contents)
           .map_err(JwksProviderError::JwksContentInvalidStructure)?,
       },
-    }));
+    };
+
+    self.store_jwks(new_jwk)
+  }
 
+  fn store_jwks(&self, jwk: TimedJwtSet) -> Result<&Self, JwksProviderError> {
     if let Ok(mut w_jwk) = self.jwk.write() {
-      *w_jwk = new_jwk;
+      *w_jwk = Some(Arc::new(jwk));
     }
 
     Ok(self)
   }
 
+  async fn load_cached_jwks(&self, url: &str) -> Option<JwkSet> {
+    let cached = match self.cache.as_ref()?.get(url).await {
+      Ok(cached) => cached?,
+      Err(e) => {
+        tracing::warn!("failed to read jwks from cache: {}", e);
+
+        return None;
+      }
+    };
+
+    match serde_json::from_str::<JwkSet>(&cached) {
+      Ok(set) => Some(set),
+      Err(e) => {
+        tracing::warn!("ignoring invalid jwks stored in cache: {}", e);
+
+        None
+      }
+    }
+  }
+
   pub fn new(config: JwksProviderSourceConfig) -> Self {
+    let cache = match &config {
+      JwksProviderSourceConfig::Remote {
+        cache: Some(JwksCacheConfig::CacheApi { cache_name }),
+        ..
+      } => match resolve_key_value_store(&KeyValueBinding::CacheApi(cache_name.clone())) {
+        Ok(cache) => Some(cache),
+        Err(e) => {
+          tracing::warn!("jwks cache is ignored: {}", e);
+
+          None
+        }
+      },
+      _ => None,
+    };
+
     Self {
       config,
       jwk: RwLock::new(None),
+      cache,
     }
   }
 
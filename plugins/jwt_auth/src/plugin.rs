@@ -30,7 +30,8 @@ pub struct JwtAuthPlugin {
   providers: Vec<JwksProvider>,
 }
 
-static CLAIMS_CONTEXT_KEY: &str = "jwt_auth:upstream:claims";
+/// The key of the request context containing the claims of the authenticated JWT.
+pub static CLAIMS_CONTEXT_KEY: &str = "jwt_auth:upstream:claims";
 static TOKEN_CONTEXT_KEY: &str = "jwt_auth:upstream:token";
 
 #[derive(Debug, thiserror::Error)]
@@ -362,9 +363,8 @@ impl Plugin for JwtAuthPlugin {
 
     match self.authenticate(&valid_jwks, &ctx.downstream_http_request) {
       Ok((token_data, token)) => {
-        if self.config.forward_claims_to_upstream_header.is_some() {
-          ctx.ctx_insert(CLAIMS_CONTEXT_KEY, token_data.claims);
-        }
+        // The claims are also used by other plugins, e.g. `client_awareness`.
+        ctx.ctx_insert(CLAIMS_CONTEXT_KEY, token_data.claims);
         if self.config.forward_token_to_upstream_header.is_some() {
           ctx.ctx_insert(TOKEN_CONTEXT_KEY, token);
         }
@@ -768,4 +768,45 @@ pub mod jwt_plugin {
         .is_ok_and(|v| v.keys[0].common.key_id.as_ref().unwrap().eq("test_id")));
     }
   }
+
+  pub mod jwks_cache {
+    use super::*;
+    use crate::{
+      config::{JwksCacheConfig, JwksProviderSourceConfig},
+      jwks_provider::JwksProvider,
+    };
+    use conductor_common::kv::{
+      register_key_value_store, InMemoryKeyValueStore, KeyValueBinding, KeyValueStore,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    pub fn remote_jwks_from_cache() {
+      let cache = Arc::new(InMemoryKeyValueStore::default());
+      register_key_value_store(
+        KeyValueBinding::CacheApi(Some("jwks_tests".to_string())),
+        cache.clone(),
+      );
+      // The URL is never fetched, since the JWKS is available in the cache.
+      let url = "http://jwks.invalid/jwks.json";
+      futures::executor::block_on(cache.put(
+        url,
+        &serde_json::to_string(&*JWKS_RSA512_2045_PUBLIC_KEY).unwrap(),
+        None,
+      ))
+      .unwrap();
+
+      let provider = JwksProvider::new(JwksProviderSourceConfig::Remote {
+        url: url.to_string(),
+        cache_duration: None,
+        prefetch: None,
+        cache: Some(JwksCacheConfig::CacheApi {
+          cache_name: Some("jwks_tests".to_string()),
+        }),
+      });
+      let set = futures::executor::block_on(provider.retrieve_jwk_set()).unwrap();
+
+      assert!(set.get_jwk().find("test_id").is_some());
+    }
+  }
 }
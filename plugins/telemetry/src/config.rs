@@ -1,5 +1,6 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
+use conductor_tracing::sampling::{TailSampling, TraceSampler};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
@@ -31,12 +32,89 @@ pub struct TelemetryPluginConfig {
   ///
   /// The telemtry data is scoped per endpoint, and you can specify multiple targets if you need to export stats to multiple backends.
   pub targets: Vec<TelemetryTarget>,
+  /// Controls which traces are exported to the targets. When not set, all traces are exported.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sampling: Option<TelemetrySamplingConfig>,
 }
 
 fn default_service_name() -> String {
   "conductor".to_string()
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+/// Head sampling decides whether a trace is recorded when the request is received, using a ratio of the traces. The decision is stable for a trace, and the spans of requests that aren't sampled are not recorded.
+///
+/// On WASM runtime (CloudFlare Worker), the rules based on the outcome of the request (`always_sample_errors`, `operations` and `tail`) are applied when the spans are shipped, at the end of the request: requests are recorded until then. These rules are not supported on other runtimes.
+pub struct TelemetrySamplingConfig {
+  /// The ratio of traces to export, between `0` (no traces) and `1` (all traces).
+  #[serde(default = "default_sampling_ratio")]
+  pub ratio: f64,
+  /// Follows the sampling decision of the incoming request, when it continues an existing trace (the `sampled` flag of the `traceparent` header, or the B3 sampling state).
+  ///
+  /// See the `propagation` configuration for the trace context formats accepted by Conductor.
+  #[serde(default = "default_sampling_parent_based")]
+  pub parent_based: bool,
+  /// Always exports the traces of requests that failed, regardless of the sampling ratio. Only supported on WASM runtime (CloudFlare Worker).
+  #[serde(default = "default_sampling_always_sample_errors")]
+  pub always_sample_errors: bool,
+  /// Sampling ratios for specific endpoints, by endpoint path (e.g. `/graphql`). Overrides `ratio`.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub endpoints: HashMap<String, f64>,
+  /// Sampling ratios for specific GraphQL operations, by operation name. Overrides `ratio` and `endpoints`. Only supported on WASM runtime (CloudFlare Worker).
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub operations: HashMap<String, f64>,
+  /// Tail sampling exports the traces that were not sampled by the ratio, based on the outcome of the request. Only supported on WASM runtime (CloudFlare Worker), where the decision is made when the spans are shipped, at the end of the request.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub tail: Option<TelemetryTailSamplingConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct TelemetryTailSamplingConfig {
+  #[serde(
+    deserialize_with = "humantime_serde::deserialize",
+    serialize_with = "humantime_serde::serialize",
+    default
+  )]
+  #[schemars(with = "Option<String>")]
+  /// Exports the traces of requests that took longer than this duration. You can use the human-readable format in this field, e.g. `500ms`.
+  pub latency_threshold: Option<Duration>,
+  /// Exports the traces that contain GraphQL errors.
+  #[serde(default = "default_tail_sampling_graphql_errors")]
+  pub graphql_errors: bool,
+}
+
+fn default_sampling_ratio() -> f64 {
+  1.0
+}
+
+fn default_sampling_parent_based() -> bool {
+  true
+}
+
+fn default_sampling_always_sample_errors() -> bool {
+  true
+}
+
+fn default_tail_sampling_graphql_errors() -> bool {
+  true
+}
+
+impl From<&TelemetrySamplingConfig> for TraceSampler {
+  fn from(config: &TelemetrySamplingConfig) -> Self {
+    TraceSampler {
+      ratio: config.ratio,
+      parent_based: config.parent_based,
+      always_sample_errors: config.always_sample_errors,
+      endpoint_ratios: config.endpoints.clone(),
+      operation_ratios: config.operations.clone(),
+      tail: config.tail.as_ref().map(|tail| TailSampling {
+        latency_threshold: tail.latency_threshold,
+        graphql_errors: tail.graphql_errors,
+      }),
+    }
+  }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
 #[serde(tag = "type")]
 pub enum TelemetryTarget {
@@ -8,5 +8,7 @@ pub mod wasm_reporter;
 pub mod reporter;
 
 pub use config::TelemetryPluginConfig as Config;
+pub use config::TelemetrySamplingConfig as SamplingConfig;
+pub use config::TelemetryTailSamplingConfig as TailSamplingConfig;
 pub use config::TelemetryTarget as Target;
 pub use plugin::TelemetryPlugin as Plugin;
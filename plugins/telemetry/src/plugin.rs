@@ -2,6 +2,7 @@ use crate::config::{TelemetryPluginConfig, TelemetryTarget};
 use conductor_common::plugin::{CreatablePlugin, Plugin, PluginError};
 use conductor_tracing::fastrace_mgr::FastraceManager;
 use conductor_tracing::reporters::TracingReporter;
+use conductor_tracing::sampling::TraceSampler;
 use opentelemetry::trace::SpanKind;
 use opentelemetry::trace::TraceError;
 use opentelemetry::InstrumentationScope;
@@ -184,6 +185,41 @@ impl TelemetryPlugin {
     Ok(TracingReporter::Simple(reporter))
   }
 
+  /// Sets the sampler of the tenant, used when the root span of a request is created, and returns the reporter.
+  ///
+  /// The rules based on the outcome of a request need its complete trace: they're only applied by the aggregating
+  /// reporters of the WASM runtime.
+  fn with_sampling(
+    &self,
+    tenant_id: u32,
+    reporter: TracingReporter,
+    tracing_manager: &mut FastraceManager,
+  ) -> TracingReporter {
+    let Some(config) = &self.config.sampling else {
+      return reporter;
+    };
+    let sampler = TraceSampler::from(config);
+
+    match reporter {
+      TracingReporter::Aggregating(reporter) => {
+        tracing_manager.set_sampler(tenant_id, sampler.clone());
+
+        TracingReporter::Aggregating(reporter.with_sampler(sampler))
+      }
+      reporter @ TracingReporter::Simple(_) => {
+        if config.tail.is_some() || !config.operations.is_empty() {
+          tracing::warn!(
+            "Tail sampling and operation sampling ratios are only supported on WASM runtime. Ignoring."
+          );
+        }
+
+        tracing_manager.set_sampler(tenant_id, sampler.head_only());
+
+        reporter
+      }
+    }
+  }
+
   #[cfg(feature = "test_utils")]
   pub fn configure_tracing_for_test(
     &self,
@@ -191,6 +227,7 @@ impl TelemetryPlugin {
     reporter: TracingReporter,
     tracing_manager: &mut FastraceManager,
   ) {
+    let reporter = self.with_sampling(tenant_id, reporter, tracing_manager);
     tracing_manager.add_reporter(tenant_id, reporter);
   }
 
@@ -202,6 +239,7 @@ impl TelemetryPlugin {
     for target in &self.config.targets {
       let reporter = Self::compose_reporter(&self.config.service_name, target)
         .map_err(|e| PluginError::InitError { source: e.into() })?;
+      let reporter = self.with_sampling(tenant_id, reporter, tracing_manager);
       tracing_manager.add_reporter(tenant_id, reporter);
     }
 
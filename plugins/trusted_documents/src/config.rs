@@ -26,6 +26,8 @@ pub struct ApolloPersistedQueryManifestRecord {
 #[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
 #[schemars(example = "trusted_documents_example_1")]
 #[schemars(example = "trusted_documents_example_2")]
+#[schemars(example = "trusted_documents_example_3")]
+#[schemars(example = "trusted_documents_example_4")]
 pub struct TrustedDocumentsPluginConfig {
   /// The store defines the source of trusted documents.
   /// The store contents is a list of hashes and GraphQL documents that are allowed to be executed.
@@ -37,6 +39,10 @@ pub struct TrustedDocumentsPluginConfig {
   /// This is a security measure to prevent accidental exposure of operations that are not persisted.
   #[serde(skip_serializing_if = "Option::is_none")]
   pub allow_untrusted: Option<bool>,
+  /// Records the untrusted operations executed by the gateway (when `allow_untrusted` is enabled), and exposes them as a store file.
+  /// This can be used to build the list of trusted documents from the traffic, before disabling untrusted operations.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub learn: Option<TrustedDocumentsLearnConfig>,
 }
 
 fn trusted_documents_example_1() -> JsonSchemaExample<TrustedDocumentsPluginConfig> {
@@ -54,6 +60,7 @@ fn trusted_documents_example_1() -> JsonSchemaExample<TrustedDocumentsPluginConf
                 format: TrustedDocumentsFileFormat::JsonKeyValue,
             },
             allow_untrusted: None,
+            learn: None,
             protocols: vec![TrustedDocumentsProtocolConfig::DocumentId {
                 field_name: "documentId".to_string(),
             }],
@@ -76,6 +83,7 @@ fn trusted_documents_example_2() -> JsonSchemaExample<TrustedDocumentsPluginConf
                 format: TrustedDocumentsFileFormat::JsonKeyValue,
             },
             allow_untrusted: None,
+            learn: None,
             protocols: vec![TrustedDocumentsProtocolConfig::HttpGet {
                 document_id_from: TrustedDocumentHttpGetParameterLocation::document_id_default(),
                 variables_from: TrustedDocumentHttpGetParameterLocation::variables_default(),
@@ -86,6 +94,99 @@ fn trusted_documents_example_2() -> JsonSchemaExample<TrustedDocumentsPluginConf
     }
 }
 
+fn trusted_documents_example_3() -> JsonSchemaExample<TrustedDocumentsPluginConfig> {
+  JsonSchemaExample {
+    metadata: JsonSchemaExampleMetadata::new("Workers KV Store", Some("This example reads the trusted documents from the Workers KV namespace bound as `TRUSTED_DOCUMENTS`, on Cloudflare Worker runtime. The protocol exposed is based on HTTP `POST`, using the `documentId` parameter from the request body.")),
+    wrapper: Some(JsonSchemaExampleWrapperType::Plugin {
+      name: "trusted_documents".to_string(),
+    }),
+    example: TrustedDocumentsPluginConfig {
+      store: TrustedDocumentsPluginStoreConfig::WorkersKv {
+        binding: "TRUSTED_DOCUMENTS".to_string(),
+        key_prefix: "".to_string(),
+      },
+      allow_untrusted: None,
+      learn: None,
+      protocols: vec![TrustedDocumentsProtocolConfig::DocumentId {
+        field_name: "documentId".to_string(),
+      }],
+    },
+  }
+}
+
+fn trusted_documents_example_4() -> JsonSchemaExample<TrustedDocumentsPluginConfig> {
+  JsonSchemaExample {
+    metadata: JsonSchemaExampleMetadata::new("Learn Mode", Some("This example allows untrusted operations, and records them so they can be exported from `GET /graphql/trusted-documents`, with the token set in the `TRUSTED_DOCUMENTS_EXPORT_TOKEN` environment variable, as an Apollo Persisted Query Manifest. The exported file can be used later as the `file` store, with `allow_untrusted` disabled.")),
+    wrapper: Some(JsonSchemaExampleWrapperType::Plugin {
+      name: "trusted_documents".to_string(),
+    }),
+    example: TrustedDocumentsPluginConfig {
+      store: TrustedDocumentsPluginStoreConfig::File {
+        file: LocalFileReference {
+          path: "trusted_documents.json".to_string(),
+          contents: "".to_string(),
+        },
+        format: TrustedDocumentsFileFormat::JsonKeyValue,
+      },
+      allow_untrusted: Some(true),
+      learn: Some(TrustedDocumentsLearnConfig {
+        export_path: "/graphql/trusted-documents".to_string(),
+        export_token: "${TRUSTED_DOCUMENTS_EXPORT_TOKEN}".to_string(),
+        format: TrustedDocumentsFileFormat::ApolloPersistedQueryManifest,
+        max_operations: learn_max_operations_default(),
+        client_name_header: learn_client_name_header_default(),
+        client_version_header: learn_client_version_header_default(),
+      }),
+      protocols: vec![TrustedDocumentsProtocolConfig::DocumentId {
+        field_name: "documentId".to_string(),
+      }],
+    },
+  }
+}
+
+/// Configuration of the learn mode: untrusted operations are recorded in memory, and can be exported with a `GET` request.
+///
+/// Operations are normalized (comments, formatting and the order of fields are ignored), so the same operation is recorded once. The exported document is the first one received for the operation, as sent by the client, and its id is the SHA-256 hash of that document, like the hash computed by clients using persisted queries.
+/// Note that each instance of the gateway records the operations it executed, and the records are lost when the gateway restarts.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct TrustedDocumentsLearnConfig {
+  /// The path of the `GET` endpoint exposing the recorded operations, for example `/graphql/trusted-documents`.
+  /// The path must be handled by the endpoint of this plugin, so it should start with the path of the endpoint.
+  ///
+  /// The exported file contains all the operations sent by clients, so you should not expose this path publicly.
+  pub export_path: String,
+  /// The token required to export the recorded operations, sent in the `Authorization` header with the `Bearer` prefix.
+  /// Requests without this token get a `401 Unauthorized` response.
+  ///
+  /// You can use an environment variable for this field, for example `${TRUSTED_DOCUMENTS_EXPORT_TOKEN}`.
+  pub export_token: String,
+  /// The format of the exported file. Both formats can be loaded by the `file` store.
+  ///
+  /// The Apollo Persisted Query Manifest format also includes the number of executions of each operation, and the clients that sent it.
+  pub format: TrustedDocumentsFileFormat,
+  /// The name of the header containing the name of the client that sent the operation.
+  #[serde(default = "learn_client_name_header_default")]
+  pub client_name_header: String,
+  /// The name of the header containing the version of the client that sent the operation.
+  #[serde(default = "learn_client_version_header_default")]
+  pub client_version_header: String,
+  /// The maximum number of distinct operations recorded. Once it's reached, new operations are not recorded, and the executions of the operations already recorded are still counted.
+  #[serde(default = "learn_max_operations_default")]
+  pub max_operations: usize,
+}
+
+fn learn_max_operations_default() -> usize {
+  1000
+}
+
+fn learn_client_name_header_default() -> String {
+  "apollographql-client-name".to_string()
+}
+
+fn learn_client_version_header_default() -> String {
+  "apollographql-client-version".to_string()
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
 #[serde(tag = "source")]
 pub enum TrustedDocumentsPluginStoreConfig {
@@ -101,6 +202,17 @@ pub enum TrustedDocumentsPluginStoreConfig {
     /// The format and the expected structure of the loaded store file.
     format: TrustedDocumentsFileFormat,
   },
+  #[serde(rename = "workers_kv")]
+  #[schemars(title = "workers_kv")]
+  /// Workers KV store configuration, available on Cloudflare Worker runtime. Each document is stored as a value, under the key of its hash.
+  /// Documents are read from the namespace when requested, so new documents can be added without redeploying the gateway.
+  WorkersKv {
+    /// The name of the KV namespace binding, as configured for the Worker.
+    binding: String,
+    /// A prefix added to the document hash to build the key of the document in the namespace.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    key_prefix: String,
+  },
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
@@ -0,0 +1,332 @@
+use std::{
+  collections::{BTreeMap, HashMap},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+  },
+};
+
+use conductor_common::{
+  graphql::ParsedGraphQLRequest,
+  http::{header::AUTHORIZATION, ConductorHttpRequest, HttpHeadersMap, Method},
+  normalize::{hash_signature, normalize_request},
+};
+use graphql_parser::query::{Definition, OperationDefinition};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::config::{TrustedDocumentsFileFormat, TrustedDocumentsLearnConfig};
+
+/// Records the untrusted operations executed by the gateway, so they can be exported as a trusted documents store file.
+#[derive(Debug)]
+pub struct TrustedDocumentsLearner {
+  config: TrustedDocumentsLearnConfig,
+  /// The recorded operations, by the hash of their normalized signature.
+  operations: Mutex<BTreeMap<String, LearnedOperation>>,
+  /// Set once an operation wasn't recorded because `max_operations` was reached, so it's only logged once.
+  limit_reached: AtomicBool,
+}
+
+#[derive(Debug)]
+struct LearnedOperation {
+  /// The SHA-256 hash of the document, as sent by the client.
+  id: String,
+  body: String,
+  name: String,
+  operation_type: &'static str,
+  count: u64,
+  clients: BTreeMap<(String, String), u64>,
+}
+
+#[derive(Serialize, Debug)]
+struct LearnedManifest<'a> {
+  format: &'static str,
+  version: i32,
+  operations: Vec<LearnedManifestRecord<'a>>,
+}
+
+/// A record of the Apollo Persisted Query Manifest, with the usage of the operation as additional fields.
+#[derive(Serialize, Debug)]
+struct LearnedManifestRecord<'a> {
+  id: &'a str,
+  body: &'a str,
+  name: &'a str,
+  #[serde(rename = "type")]
+  operation_type: &'a str,
+  count: u64,
+  clients: Vec<LearnedClient<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+struct LearnedClient<'a> {
+  name: &'a str,
+  version: &'a str,
+  count: u64,
+}
+
+impl TrustedDocumentsLearner {
+  pub fn new(config: TrustedDocumentsLearnConfig) -> Self {
+    Self {
+      config,
+      operations: Mutex::new(BTreeMap::new()),
+      limit_reached: AtomicBool::new(false),
+    }
+  }
+
+  /// Checks if the incoming request should be answered with the recorded operations.
+  pub fn is_export_request(&self, request: &ConductorHttpRequest) -> bool {
+    request.method == Method::GET && request_path(&request.uri) == self.config.export_path
+  }
+
+  /// Checks if the export request has the configured token, in the `Authorization` header.
+  pub fn is_export_authorized(&self, request: &ConductorHttpRequest) -> bool {
+    request
+      .headers
+      .get(AUTHORIZATION)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.strip_prefix("Bearer "))
+      .is_some_and(|token| constant_time_eq(token.as_bytes(), self.config.export_token.as_bytes()))
+  }
+
+  /// Records an execution of the operation of the request.
+  ///
+  /// Operations are identified by their normalized signature, so the same operation sent with a different formatting is
+  /// recorded once. The exported document is the first one received, as sent by the client, and its id is the SHA-256
+  /// hash of that document: the hash computed by clients using persisted queries.
+  pub fn record(&self, request: &ParsedGraphQLRequest, headers: &HttpHeadersMap) {
+    let Some(normalized) = normalize_request(request, &Default::default()) else {
+      return;
+    };
+    let header_value = |name: &str| {
+      headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+    };
+    let client = (
+      header_value(&self.config.client_name_header),
+      header_value(&self.config.client_version_header),
+    );
+
+    let mut operations = self.operations.lock().unwrap_or_else(|e| e.into_inner());
+
+    if !operations.contains_key(&normalized.hash) && operations.len() >= self.config.max_operations
+    {
+      if !self.limit_reached.swap(true, Ordering::Relaxed) {
+        warn!(
+          "trusted documents learn mode recorded {} operations, new operations are not recorded anymore",
+          self.config.max_operations
+        );
+      }
+
+      return;
+    }
+
+    let operation = operations.entry(normalized.hash).or_insert_with(|| {
+      let (name, operation_type) = operation_info(request);
+      let body = request.request.operation.clone();
+
+      LearnedOperation {
+        id: hash_signature(&body),
+        body,
+        name,
+        operation_type,
+        count: 0,
+        clients: BTreeMap::new(),
+      }
+    });
+
+    operation.count += 1;
+    *operation.clients.entry(client).or_insert(0) += 1;
+  }
+
+  /// Serializes the recorded operations, in the configured store file format.
+  pub fn export(&self) -> Result<String, serde_json::Error> {
+    let operations = self.operations.lock().unwrap_or_else(|e| e.into_inner());
+
+    match self.config.format {
+      TrustedDocumentsFileFormat::JsonKeyValue => serde_json::to_string_pretty(
+        &operations
+          .values()
+          .map(|operation| (&operation.id, &operation.body))
+          .collect::<HashMap<_, _>>(),
+      ),
+      TrustedDocumentsFileFormat::ApolloPersistedQueryManifest => {
+        serde_json::to_string_pretty(&LearnedManifest {
+          format: "apollo-persisted-query-manifest",
+          version: 1,
+          operations: operations
+            .values()
+            .map(|operation| LearnedManifestRecord {
+              id: &operation.id,
+              body: &operation.body,
+              name: &operation.name,
+              operation_type: operation.operation_type,
+              count: operation.count,
+              clients: operation
+                .clients
+                .iter()
+                .map(|((name, version), count)| LearnedClient {
+                  name,
+                  version,
+                  count: *count,
+                })
+                .collect(),
+            })
+            .collect(),
+        })
+      }
+    }
+  }
+}
+
+/// Compares the bytes of two strings in a constant time, so the comparison of a token doesn't leak its prefix.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+  a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn operation_info(request: &ParsedGraphQLRequest) -> (String, &'static str) {
+  match request.executable_operation() {
+    Some(Definition::Operation(OperationDefinition::Query(query))) => {
+      (query.name.clone().unwrap_or_default(), "query")
+    }
+    Some(Definition::Operation(OperationDefinition::Mutation(mutation))) => {
+      (mutation.name.clone().unwrap_or_default(), "mutation")
+    }
+    Some(Definition::Operation(OperationDefinition::Subscription(subscription))) => (
+      subscription.name.clone().unwrap_or_default(),
+      "subscription",
+    ),
+    _ => (String::new(), "query"),
+  }
+}
+
+/// Extracts the path from the URI of the request, that can be either a full URL or a path with a query string.
+fn request_path(uri: &str) -> &str {
+  let path = match uri.split_once("://") {
+    Some((_, rest)) => rest.find('/').map(|index| &rest[index..]).unwrap_or("/"),
+    None => uri,
+  };
+
+  path.split(['?', '#']).next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+  use conductor_common::graphql::GraphQLRequest;
+  use conductor_common::http::HeaderValue;
+
+  use crate::store::{fs::TrustedDocumentsFilesystemStore, TrustedDocumentsStore};
+
+  use super::*;
+
+  fn learner(format: TrustedDocumentsFileFormat) -> TrustedDocumentsLearner {
+    TrustedDocumentsLearner::new(TrustedDocumentsLearnConfig {
+      export_path: "/graphql/trusted-documents".to_string(),
+      export_token: "secret".to_string(),
+      format,
+      client_name_header: "apollographql-client-name".to_string(),
+      client_version_header: "apollographql-client-version".to_string(),
+      max_operations: 2,
+    })
+  }
+
+  fn request(operation: &str) -> ParsedGraphQLRequest {
+    ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
+      operation: operation.to_string(),
+      operation_name: None,
+      variables: None,
+      extensions: None,
+    })
+    .unwrap()
+  }
+
+  #[test]
+  fn matches_export_requests() {
+    let learner = learner(TrustedDocumentsFileFormat::JsonKeyValue);
+    let request = |method: Method, uri: &str| ConductorHttpRequest {
+      method,
+      uri: uri.to_string(),
+      query_string: "".to_string(),
+      headers: Default::default(),
+      body: Default::default(),
+    };
+    let authorized = |authorization: &'static str| {
+      let mut request = request(Method::GET, "/graphql/trusted-documents");
+      request
+        .headers
+        .insert(AUTHORIZATION, HeaderValue::from_static(authorization));
+
+      learner.is_export_authorized(&request)
+    };
+
+    assert!(learner.is_export_request(&request(Method::GET, "/graphql/trusted-documents")));
+    assert!(learner.is_export_request(&request(
+      Method::GET,
+      "https://example.com/graphql/trusted-documents?a=1"
+    )));
+    assert!(!learner.is_export_request(&request(Method::POST, "/graphql/trusted-documents")));
+    assert!(!learner.is_export_request(&request(Method::GET, "/graphql")));
+
+    assert!(authorized("Bearer secret"));
+    assert!(!authorized("Bearer secret2"));
+    assert!(!authorized("secret"));
+    assert!(!learner.is_export_authorized(&request(Method::GET, "/graphql/trusted-documents")));
+  }
+
+  #[tokio::test]
+  async fn exports_recorded_operations() {
+    let learner = learner(TrustedDocumentsFileFormat::ApolloPersistedQueryManifest);
+    let mut headers = HttpHeadersMap::new();
+    headers.insert("apollographql-client-name", HeaderValue::from_static("web"));
+    headers.insert(
+      "apollographql-client-version",
+      HeaderValue::from_static("1.0.0"),
+    );
+
+    learner.record(&request("query test { __typename }"), &headers);
+    // Same operation, with a different formatting and a comment.
+    learner.record(
+      &request("# comment\nquery test {\n  __typename\n}"),
+      &Default::default(),
+    );
+    learner.record(&request("mutation { __typename }"), &headers);
+
+    let exported: serde_json::Value = serde_json::from_str(&learner.export().unwrap()).unwrap();
+    let operations = exported["operations"].as_array().unwrap();
+    let query = operations
+      .iter()
+      .find(|operation| operation["name"] == "test")
+      .unwrap();
+
+    assert_eq!(operations.len(), 2);
+    assert_eq!(query["type"], "query");
+    assert_eq!(query["count"], 2);
+    assert_eq!(
+      query["clients"],
+      serde_json::json!([
+        { "name": "", "version": "", "count": 1 },
+        { "name": "web", "version": "1.0.0", "count": 1 }
+      ])
+    );
+
+    // The limit of recorded operations is reached, but known operations are still counted.
+    learner.record(&request("query other { __typename }"), &headers);
+    learner.record(&request("query test { __typename }"), &headers);
+    let exported: serde_json::Value = serde_json::from_str(&learner.export().unwrap()).unwrap();
+    assert_eq!(exported["operations"].as_array().unwrap().len(), 2);
+
+    // The exported file can be loaded by the file store.
+    let store = TrustedDocumentsFilesystemStore::new_from_file_contents(
+      &learner.export().unwrap(),
+      &TrustedDocumentsFileFormat::ApolloPersistedQueryManifest,
+    )
+    .unwrap();
+    // The id is the hash of the document sent by the client.
+    let id = query["id"].as_str().unwrap();
+    assert_eq!(query["body"], "query test { __typename }");
+    assert_eq!(id, hash_signature("query test { __typename }"));
+    assert!(store.has_document(id).await);
+  }
+}
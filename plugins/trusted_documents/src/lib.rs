@@ -1,9 +1,11 @@
 mod config;
+mod learn;
 mod plugin;
 mod protocols;
 mod store;
 
 pub use config::TrustedDocumentsFileFormat as FileFormat;
+pub use config::TrustedDocumentsLearnConfig as LearnConfig;
 pub use config::TrustedDocumentsPluginConfig as Config;
 pub use config::TrustedDocumentsPluginStoreConfig as Store;
 pub use config::TrustedDocumentsProtocolConfig as Protocol;
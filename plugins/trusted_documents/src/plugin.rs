@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
 use crate::{
+  learn::TrustedDocumentsLearner,
   protocols::{
     apollo_manifest::ApolloManifestPersistedDocumentsProtocol,
     document_id::DocumentIdTrustedDocumentsProtocol, get_handler::TrustedDocumentsGetHandler,
   },
-  store::fs::TrustedDocumentsFilesystemStore,
+  store::{fs::TrustedDocumentsFilesystemStore, workers_kv::TrustedDocumentsWorkersKvStore},
 };
 
 use super::{protocols::TrustedDocumentsProtocol, store::TrustedDocumentsStore};
@@ -15,7 +16,8 @@ use crate::config::{
 use conductor_common::{
   execute::RequestExecutionContext,
   graphql::{ExtractGraphQLOperationError, GraphQLRequest, GraphQLResponse, ParsedGraphQLRequest},
-  http::StatusCode,
+  http::{ConductorHttpResponse, HeaderValue, HttpHeadersMap, StatusCode, CONTENT_TYPE},
+  kv::{resolve_key_value_store, KeyValueBinding},
   plugin::{CreatablePlugin, Plugin, PluginError},
   source::SourceRuntime,
 };
@@ -26,8 +28,12 @@ pub struct TrustedDocumentsPlugin {
   config: TrustedDocumentsPluginConfig,
   incoming_message_handlers: Vec<Box<dyn TrustedDocumentsProtocol>>,
   store: Box<dyn TrustedDocumentsStore>,
+  learner: Option<TrustedDocumentsLearner>,
 }
 
+/// Set when the operation of the request was loaded from the store, so it's not recorded by the learn mode.
+static TRUSTED_DOCUMENT_CONTEXT_KEY: &str = "trusted_documents:document_id";
+
 #[derive(Debug, thiserror::Error)]
 pub enum TrustedDocumentsPluginError {
   #[error("failed to create store: {0}")]
@@ -52,6 +58,20 @@ impl CreatablePlugin for TrustedDocumentsPlugin {
 
         Box::new(fs_store)
       }
+      TrustedDocumentsPluginStoreConfig::WorkersKv {
+        binding,
+        key_prefix,
+      } => {
+        let kv_store = resolve_key_value_store(&KeyValueBinding::WorkersKv(binding.clone()))
+          .map_err(|e| PluginError::InitError {
+            source: TrustedDocumentsPluginError::StoreCreationError(e.to_string()).into(),
+          })?;
+
+        Box::new(TrustedDocumentsWorkersKvStore::new(
+          kv_store,
+          key_prefix.clone(),
+        ))
+      }
     };
 
     let incoming_message_handlers: Vec<Box<dyn TrustedDocumentsProtocol>> = config
@@ -90,10 +110,20 @@ impl CreatablePlugin for TrustedDocumentsPlugin {
             })
             .collect();
 
+    let learner = config.learn.clone().map(|learn_config| {
+      debug!(
+        "trusted documents learn mode is enabled, exporting from path: {}",
+        learn_config.export_path
+      );
+
+      TrustedDocumentsLearner::new(learn_config)
+    });
+
     Ok(Box::new(Self {
       config,
       store,
       incoming_message_handlers,
+      learner,
     }))
   }
 }
@@ -101,6 +131,23 @@ impl CreatablePlugin for TrustedDocumentsPlugin {
 #[async_trait::async_trait(?Send)]
 impl Plugin for TrustedDocumentsPlugin {
   async fn on_downstream_http_request(&self, ctx: &mut RequestExecutionContext) {
+    if let Some(learner) = &self.learner {
+      if learner.is_export_request(&ctx.downstream_http_request) {
+        if !learner.is_export_authorized(&ctx.downstream_http_request) {
+          warn!("unauthorized request to export the learned trusted documents");
+
+          ctx.short_circuit(
+            GraphQLResponse::new_error("unauthorized")
+              .into_with_status_code(StatusCode::UNAUTHORIZED),
+          );
+          return;
+        }
+
+        ctx.short_circuit(export_learned_documents(learner));
+        return;
+      }
+    }
+
     if ctx.downstream_graphql_request.is_some() {
       return;
     }
@@ -120,7 +167,7 @@ impl Plugin for TrustedDocumentsPlugin {
           debug!("found trusted document with id {:?}", extracted.hash);
 
           match ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
-            operation: op.clone(),
+            operation: op,
             operation_name: extracted.operation_name,
             variables: extracted.variables,
             extensions: extracted.extensions,
@@ -132,6 +179,7 @@ impl Plugin for TrustedDocumentsPlugin {
               );
 
               ctx.downstream_graphql_request = Some(parsed);
+              ctx.ctx_insert(TRUSTED_DOCUMENT_CONTEXT_KEY, extracted.hash);
               return;
             }
             Err(e) => {
@@ -177,5 +225,34 @@ impl Plugin for TrustedDocumentsPlugin {
         ctx.short_circuit(response);
       }
     }
+
+    if let Some(learner) = &self.learner {
+      if !ctx.is_short_circuit() && ctx.ctx_get(TRUSTED_DOCUMENT_CONTEXT_KEY).is_none() {
+        if let Some(request) = &ctx.downstream_graphql_request {
+          learner.record(request, &ctx.downstream_http_request.headers);
+        }
+      }
+    }
+  }
+}
+
+fn export_learned_documents(learner: &TrustedDocumentsLearner) -> ConductorHttpResponse {
+  match learner.export() {
+    Ok(body) => {
+      let mut headers = HttpHeadersMap::new();
+      headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+      ConductorHttpResponse {
+        body: body.into(),
+        status: StatusCode::OK,
+        headers,
+      }
+    }
+    Err(e) => {
+      error!("failed to export learned trusted documents: {}", e);
+
+      GraphQLResponse::new_error("failed to export learned trusted documents")
+        .into_with_status_code(StatusCode::INTERNAL_SERVER_ERROR)
+    }
   }
 }
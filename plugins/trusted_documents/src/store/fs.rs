@@ -17,8 +17,8 @@ impl TrustedDocumentsStore for TrustedDocumentsFilesystemStore {
     self.known_documents.contains_key(hash)
   }
 
-  async fn get_document(&self, hash: &str) -> Option<&String> {
-    self.known_documents.get(hash)
+  async fn get_document(&self, hash: &str) -> Option<String> {
+    self.known_documents.get(hash).cloned()
   }
 }
 
@@ -103,7 +103,7 @@ pub mod tests {
       assert_eq!(store.known_documents.len(), 1);
       assert!(store.has_document("key1").await);
       assert_eq!(
-        store.get_document("key1").await.cloned(),
+        store.get_document("key1").await,
         Some("query test { __typename }".to_string())
       );
     }
@@ -1,9 +1,10 @@
 use std::fmt::Debug;
 
 pub mod fs;
+pub mod workers_kv;
 
 #[async_trait::async_trait(?Send)]
 pub trait TrustedDocumentsStore: Sync + Send + Debug {
   async fn has_document(&self, hash: &str) -> bool;
-  async fn get_document(&self, hash: &str) -> Option<&String>;
+  async fn get_document(&self, hash: &str) -> Option<String>;
 }
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use conductor_common::kv::KeyValueStore;
+use tracing::{debug, error};
+
+use super::TrustedDocumentsStore;
+
+/// A store that reads trusted documents from a Workers KV namespace, where every document is stored under its hash.
+///
+/// Documents are read on demand, so documents added to the namespace are available without restarting the gateway.
+#[derive(Debug)]
+pub struct TrustedDocumentsWorkersKvStore {
+  store: Arc<dyn KeyValueStore>,
+  key_prefix: String,
+}
+
+impl TrustedDocumentsWorkersKvStore {
+  pub fn new(store: Arc<dyn KeyValueStore>, key_prefix: String) -> Self {
+    debug!(
+      "creating trusted documents store from a Workers KV namespace, key prefix: {:?}",
+      key_prefix
+    );
+
+    Self { store, key_prefix }
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl TrustedDocumentsStore for TrustedDocumentsWorkersKvStore {
+  async fn has_document(&self, hash: &str) -> bool {
+    self.get_document(hash).await.is_some()
+  }
+
+  async fn get_document(&self, hash: &str) -> Option<String> {
+    let key = format!("{}{}", self.key_prefix, hash);
+
+    match self.store.get(&key).await {
+      Ok(document) => document,
+      Err(e) => {
+        error!("failed to read trusted document from Workers KV: {}", e);
+
+        None
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use conductor_common::kv::InMemoryKeyValueStore;
+
+  #[tokio::test]
+  async fn workers_kv_store_prefixed_keys() {
+    let store = TrustedDocumentsWorkersKvStore::new(
+      Arc::new(InMemoryKeyValueStore::with_values([
+        (
+          "documents:key1".to_string(),
+          "query test { __typename }".to_string(),
+        ),
+        ("key2".to_string(), "query { __typename }".to_string()),
+      ])),
+      "documents:".to_string(),
+    );
+
+    assert!(store.has_document("key1").await);
+    assert_eq!(
+      store.get_document("key1").await,
+      Some("query test { __typename }".to_string())
+    );
+    assert!(!store.has_document("key2").await);
+    assert_eq!(store.get_document("documents:key1").await, None);
+  }
+}
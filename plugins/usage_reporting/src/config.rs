@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use conductor_common::serde_utils::{
+  JsonSchemaExample, JsonSchemaExampleMetadata, JsonSchemaExampleWrapperType,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The `usage_reporting` plugin collects the usage of the GraphQL operations executed by the gateway, and reports it to a schema registry.
+///
+/// The reports are compatible with the [GraphQL Hive](https://the-guild.dev/graphql/hive) usage API: for each operation, the plugin reports the schema coordinates it uses, the client that sent it, its latency and its errors.
+///
+/// The schema coordinates are resolved from the schema of the source, so the source should have a schema (for example, using `schema_awareness`). Otherwise, the operations are reported without coordinates.
+///
+/// Reports are collected in memory, and sent in batches in the background. The pending report is sent as well when the gateway shuts down, or is replaced after a configuration reload.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[schemars(example = "usage_reporting_example_1")]
+#[schemars(example = "usage_reporting_example_2")]
+pub struct UsageReportingPluginConfig {
+  /// The URL of the usage reporting endpoint.
+  #[serde(default = "default_endpoint")]
+  pub endpoint: String,
+  /// The access token used to authenticate with the registry, sent as a `Bearer` token in the `Authorization` header.
+  ///
+  /// You can use environment variables interpolation to avoid storing the token in the configuration file, e.g. `${HIVE_TOKEN}`.
+  pub token: String,
+  /// The ratio of operations to report, between `0` (none) and `1` (all operations).
+  #[serde(default = "default_sample_rate")]
+  pub sample_rate: f64,
+  /// A list of operation names that are never reported.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub exclude: Vec<String>,
+  /// The maximum number of operations sent in a single report. A report is sent as soon as this number is reached.
+  #[serde(default = "default_max_batch_size")]
+  pub max_batch_size: usize,
+  /// The maximum time to wait before sending the collected operations. You can use the human-readable format in this field, e.g. `5s`.
+  ///
+  /// > On WASM runtime (CloudFlare Worker), background tasks are not available, so the reports are only sent when a request is handled after this interval.
+  #[serde(
+    deserialize_with = "humantime_serde::deserialize",
+    serialize_with = "humantime_serde::serialize",
+    default = "default_flush_interval"
+  )]
+  #[schemars(with = "String")]
+  pub flush_interval: Duration,
+  /// The name of the header containing the name of the client that sent the operation, used when the client isn't identified by the `client_awareness` plugin.
+  #[serde(default = "default_client_name_header")]
+  pub client_name_header: String,
+  /// The name of the header containing the version of the client that sent the operation, used when the client isn't identified by the `client_awareness` plugin.
+  #[serde(default = "default_client_version_header")]
+  pub client_version_header: String,
+}
+
+fn default_endpoint() -> String {
+  "https://app.graphql-hive.com/usage".to_string()
+}
+
+fn default_sample_rate() -> f64 {
+  1.0
+}
+
+fn default_max_batch_size() -> usize {
+  1000
+}
+
+fn default_flush_interval() -> Duration {
+  Duration::from_secs(5)
+}
+
+fn default_client_name_header() -> String {
+  "apollographql-client-name".to_string()
+}
+
+fn default_client_version_header() -> String {
+  "apollographql-client-version".to_string()
+}
+
+fn usage_reporting_example_1() -> JsonSchemaExample<UsageReportingPluginConfig> {
+  JsonSchemaExample {
+    metadata: JsonSchemaExampleMetadata::new(
+      "GraphQL Hive",
+      Some("This example reports the usage of all operations to GraphQL Hive, using the token from the `HIVE_TOKEN` environment variable."),
+    ),
+    wrapper: Some(JsonSchemaExampleWrapperType::Plugin {
+      name: "usage_reporting".to_string(),
+    }),
+    example: UsageReportingPluginConfig {
+      endpoint: default_endpoint(),
+      token: "${HIVE_TOKEN}".to_string(),
+      sample_rate: default_sample_rate(),
+      exclude: vec![],
+      max_batch_size: default_max_batch_size(),
+      flush_interval: default_flush_interval(),
+      client_name_header: default_client_name_header(),
+      client_version_header: default_client_version_header(),
+    },
+  }
+}
+
+fn usage_reporting_example_2() -> JsonSchemaExample<UsageReportingPluginConfig> {
+  JsonSchemaExample {
+    metadata: JsonSchemaExampleMetadata::new(
+      "Sampling",
+      Some("This example reports 10% of the operations to a self-hosted registry, except the `HealthCheck` operation."),
+    ),
+    wrapper: Some(JsonSchemaExampleWrapperType::Plugin {
+      name: "usage_reporting".to_string(),
+    }),
+    example: UsageReportingPluginConfig {
+      endpoint: "https://hive.example.com/usage".to_string(),
+      token: "${HIVE_TOKEN}".to_string(),
+      sample_rate: 0.1,
+      exclude: vec!["HealthCheck".to_string()],
+      max_batch_size: default_max_batch_size(),
+      flush_interval: default_flush_interval(),
+      client_name_header: default_client_name_header(),
+      client_version_header: default_client_version_header(),
+    },
+  }
+}
@@ -0,0 +1,247 @@
+use std::collections::{BTreeSet, HashSet};
+
+use conductor_common::graphql::{ParsedGraphQLRequest, ParsedGraphQLSchema};
+use graphql_parser::{
+  query::{
+    Definition as QueryDefinition, OperationDefinition, Selection, SelectionSet, TypeCondition,
+  },
+  schema::{Definition, Field, Type, TypeDefinition},
+};
+
+/// Collects the schema coordinates used by the executed operation: the types, fields (`Type.field`) and arguments (`Type.field.argument`) it selects,
+/// and the input types of its arguments.
+///
+/// Selections that can't be resolved from the schema are ignored, along with the introspection fields.
+pub fn collect_schema_coordinates(
+  schema: &ParsedGraphQLSchema,
+  request: &ParsedGraphQLRequest,
+) -> BTreeSet<String> {
+  let mut collector = CoordinatesCollector {
+    schema,
+    request,
+    coordinates: BTreeSet::new(),
+    visited_fragments: HashSet::new(),
+  };
+
+  let (root_type, selection_set) = match request.executable_operation() {
+    Some(QueryDefinition::Operation(OperationDefinition::SelectionSet(selection_set))) => {
+      (collector.root_type("query"), selection_set)
+    }
+    Some(QueryDefinition::Operation(OperationDefinition::Query(query))) => {
+      (collector.root_type("query"), &query.selection_set)
+    }
+    Some(QueryDefinition::Operation(OperationDefinition::Mutation(mutation))) => {
+      (collector.root_type("mutation"), &mutation.selection_set)
+    }
+    Some(QueryDefinition::Operation(OperationDefinition::Subscription(subscription))) => (
+      collector.root_type("subscription"),
+      &subscription.selection_set,
+    ),
+    _ => return BTreeSet::new(),
+  };
+
+  collector.collect(&root_type, selection_set);
+
+  collector.coordinates
+}
+
+struct CoordinatesCollector<'a> {
+  schema: &'a ParsedGraphQLSchema,
+  request: &'a ParsedGraphQLRequest,
+  coordinates: BTreeSet<String>,
+  visited_fragments: HashSet<&'a str>,
+}
+
+impl<'a> CoordinatesCollector<'a> {
+  fn root_type(&self, operation_type: &str) -> String {
+    let schema_definition =
+      self
+        .schema
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+          Definition::SchemaDefinition(schema_definition) => Some(schema_definition),
+          _ => None,
+        });
+
+    let declared = schema_definition.and_then(|schema_definition| match operation_type {
+      "mutation" => schema_definition.mutation.clone(),
+      "subscription" => schema_definition.subscription.clone(),
+      _ => schema_definition.query.clone(),
+    });
+
+    declared.unwrap_or_else(|| match operation_type {
+      "mutation" => "Mutation".to_string(),
+      "subscription" => "Subscription".to_string(),
+      _ => "Query".to_string(),
+    })
+  }
+
+  fn fields(&self, type_name: &str) -> Option<&'a [Field<'static, String>]> {
+    let schema: &'a ParsedGraphQLSchema = self.schema;
+
+    schema
+      .definitions
+      .iter()
+      .find_map(|definition| match definition {
+        Definition::TypeDefinition(TypeDefinition::Object(t)) if t.name == type_name => {
+          Some(&t.fields[..])
+        }
+        Definition::TypeDefinition(TypeDefinition::Interface(t)) if t.name == type_name => {
+          Some(&t.fields[..])
+        }
+        _ => None,
+      })
+  }
+
+  fn collect(&mut self, type_name: &str, selection_set: &'a SelectionSet<'static, String>) {
+    for selection in &selection_set.items {
+      match selection {
+        Selection::Field(field) => {
+          if field.name.starts_with("__") {
+            continue;
+          }
+
+          let Some(field_definition) = self
+            .fields(type_name)
+            .and_then(|fields| fields.iter().find(|f| f.name == field.name))
+          else {
+            continue;
+          };
+
+          let coordinate = format!("{}.{}", type_name, field.name);
+          self.coordinates.insert(type_name.to_string());
+
+          for (argument, _) in &field.arguments {
+            if let Some(argument_definition) = field_definition
+              .arguments
+              .iter()
+              .find(|a| &a.name == argument)
+            {
+              self
+                .coordinates
+                .insert(format!("{}.{}", coordinate, argument));
+              self
+                .coordinates
+                .insert(named_type(&argument_definition.value_type).to_string());
+            }
+          }
+
+          self.coordinates.insert(coordinate);
+          self.collect(
+            named_type(&field_definition.field_type),
+            &field.selection_set,
+          );
+        }
+        Selection::InlineFragment(fragment) => {
+          let type_name = match &fragment.type_condition {
+            Some(TypeCondition::On(type_condition)) => type_condition.as_str(),
+            None => type_name,
+          };
+
+          self.collect(type_name, &fragment.selection_set);
+        }
+        Selection::FragmentSpread(spread) => {
+          if !self.visited_fragments.insert(&spread.fragment_name) {
+            continue;
+          }
+
+          let request = self.request;
+          let fragment = request
+            .parsed_operation
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+              QueryDefinition::Fragment(fragment) if fragment.name == spread.fragment_name => {
+                Some(fragment)
+              }
+              _ => None,
+            });
+
+          if let Some(fragment) = fragment {
+            let TypeCondition::On(type_condition) = &fragment.type_condition;
+
+            self.collect(type_condition, &fragment.selection_set);
+          }
+        }
+      }
+    }
+  }
+}
+
+fn named_type(t: &Type<'static, String>) -> &str {
+  match t {
+    Type::NamedType(name) => name,
+    Type::ListType(inner) | Type::NonNullType(inner) => named_type(inner),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use conductor_common::graphql::{parse_graphql_schema, GraphQLRequest};
+
+  use super::*;
+
+  #[test]
+  fn should_collect_coordinates_from_schema() {
+    let schema = parse_graphql_schema(
+      r#"
+        type Query {
+          user(id: ID!, locale: String): User
+          node(id: ID!): Node
+        }
+
+        interface Node { id: ID! }
+
+        type User implements Node {
+          id: ID!
+          name: String
+          friends(first: Int): [User!]!
+        }
+      "#,
+    )
+    .unwrap();
+    let request = ParsedGraphQLRequest::create_and_parse(GraphQLRequest {
+      operation: r#"
+        query ($id: ID!) {
+          __typename
+          user(id: $id) { ...UserFields }
+          node(id: "1") { id ... on User { name } }
+          unknown { id }
+        }
+
+        fragment UserFields on User {
+          name
+          friends(first: 10) { id ...UserFields }
+        }
+      "#
+      .to_string(),
+      operation_name: None,
+      variables: None,
+      extensions: None,
+    })
+    .unwrap();
+
+    assert_eq!(
+      collect_schema_coordinates(&schema, &request)
+        .into_iter()
+        .collect::<Vec<_>>(),
+      vec![
+        "ID",
+        "Int",
+        "Node",
+        "Node.id",
+        "Query",
+        "Query.node",
+        "Query.node.id",
+        "Query.user",
+        "Query.user.id",
+        "User",
+        "User.friends",
+        "User.friends.first",
+        "User.id",
+        "User.name",
+      ]
+    );
+  }
+}
@@ -0,0 +1,8 @@
+mod config;
+mod coordinates;
+mod plugin;
+mod report;
+
+pub use config::UsageReportingPluginConfig as Config;
+pub use plugin::UsageReportingPlugin as Plugin;
+pub use report::UsageReporter as Reporter;
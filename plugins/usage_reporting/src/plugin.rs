@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use conductor_common::{
+  execute::{RequestExecutionContext, GRAPHQL_ERRORS_COUNT_CONTEXT_KEY},
+  graphql::ParsedGraphQLRequest,
+  http::ConductorHttpResponse,
+  normalize::{normalize_request, NormalizationOptions},
+  plugin::{CreatablePlugin, Plugin, PluginError},
+  source::SourceRuntime,
+};
+use graphql_parser::query::{Definition, OperationDefinition};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+  config::UsageReportingPluginConfig,
+  coordinates::collect_schema_coordinates,
+  report::{
+    ClientInfo, OperationExecution, OperationMapRecord, OperationMetadata, OperationRecord,
+    UsageReporter,
+  },
+};
+
+static START_TIME_CONTEXT_KEY: &str = "usage_reporting:start_time";
+static OPERATION_CONTEXT_KEY: &str = "usage_reporting:operation";
+
+#[derive(Debug)]
+pub struct UsageReportingPlugin {
+  config: UsageReportingPluginConfig,
+  reporter: Arc<UsageReporter>,
+}
+
+/// The operation of a request that was selected for reporting, kept in the request context until the response is sent.
+#[derive(Serialize, Deserialize, Debug)]
+struct PendingOperation {
+  key: String,
+  operation: OperationMapRecord,
+}
+
+#[async_trait::async_trait(?Send)]
+impl CreatablePlugin for UsageReportingPlugin {
+  type Config = UsageReportingPluginConfig;
+
+  async fn create(config: Self::Config) -> Result<Box<Self>, PluginError> {
+    let reporter = UsageReporter::new(
+      config.endpoint.clone(),
+      config.token.clone(),
+      config.sample_rate,
+      config.max_batch_size,
+      config.flush_interval,
+    )
+    .map_err(|e| PluginError::InitError { source: e.into() })?;
+    let reporter = Arc::new(reporter);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(flush_periodically(Arc::downgrade(&reporter)));
+
+    Ok(Box::new(Self { config, reporter }))
+  }
+}
+
+impl UsageReportingPlugin {
+  /// The reporter collecting the usage of this plugin, that can be used to send the pending report on demand.
+  pub fn reporter(&self) -> Arc<UsageReporter> {
+    self.reporter.clone()
+  }
+
+  fn is_excluded(&self, request: &ParsedGraphQLRequest) -> bool {
+    operation_name(request).is_some_and(|name| self.config.exclude.contains(&name))
+  }
+
+  fn client_info(&self, ctx: &RequestExecutionContext) -> Option<ClientInfo> {
+    if let Some(client) = &ctx.client {
+      return Some(ClientInfo {
+        name: client.name.clone(),
+        version: client.version.clone().unwrap_or_default(),
+      });
+    }
+
+    let headers = &ctx.downstream_http_request.headers;
+    let header_value = |name: &str| {
+      headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+    };
+
+    header_value(&self.config.client_name_header).map(|name| ClientInfo {
+      name,
+      version: header_value(&self.config.client_version_header).unwrap_or_default(),
+    })
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Plugin for UsageReportingPlugin {
+  async fn on_downstream_http_request(&self, ctx: &mut RequestExecutionContext) {
+    ctx.ctx_insert(START_TIME_CONTEXT_KEY, now_nanos());
+  }
+
+  async fn on_downstream_graphql_request(
+    &self,
+    source_runtime: Arc<Box<dyn SourceRuntime>>,
+    ctx: &mut RequestExecutionContext,
+  ) {
+    let Some(request) = &ctx.downstream_graphql_request else {
+      return;
+    };
+
+    if self.is_excluded(request) || !self.reporter.should_sample() {
+      return;
+    }
+
+    let Some(normalized) = normalize_request(
+      request,
+      &NormalizationOptions {
+        hide_literals: true,
+      },
+    ) else {
+      return;
+    };
+
+    let fields = source_runtime
+      .schema()
+      .map(|schema| {
+        collect_schema_coordinates(&schema, request)
+          .into_iter()
+          .collect()
+      })
+      .unwrap_or_default();
+
+    let pending = PendingOperation {
+      key: normalized.hash,
+      operation: OperationMapRecord {
+        operation: normalized.signature,
+        operation_name: operation_name(request),
+        fields,
+      },
+    };
+
+    match serde_json::to_value(pending) {
+      Ok(value) => {
+        ctx.ctx_insert(OPERATION_CONTEXT_KEY, value);
+      }
+      Err(e) => warn!("failed to store the operation for usage reporting: {}", e),
+    }
+  }
+
+  fn on_downstream_http_response(
+    &self,
+    ctx: &mut RequestExecutionContext,
+    response: &mut ConductorHttpResponse,
+  ) {
+    let Some(pending) = ctx
+      .ctx_get(OPERATION_CONTEXT_KEY)
+      .and_then(|value| serde_json::from_value::<PendingOperation>(value.clone()).ok())
+    else {
+      return;
+    };
+
+    let now = now_nanos();
+    let start_time = ctx
+      .ctx_get(START_TIME_CONTEXT_KEY)
+      .and_then(|value| value.as_u64())
+      .unwrap_or(now);
+    // The count is only missing when a plugin short-circuited the request, which is reported as a failed operation.
+    let errors_total = ctx
+      .ctx_get(GRAPHQL_ERRORS_COUNT_CONTEXT_KEY)
+      .and_then(|value| value.as_u64())
+      .map(|count| count as usize);
+
+    let record = OperationRecord {
+      operation_map_key: pending.key.clone(),
+      timestamp: start_time / 1_000_000,
+      execution: OperationExecution {
+        ok: response.status.is_success() && errors_total == Some(0),
+        duration: now.saturating_sub(start_time),
+        errors_total: errors_total.unwrap_or(1),
+      },
+      metadata: self
+        .client_info(ctx)
+        .map(|client| OperationMetadata { client }),
+    };
+
+    self.reporter.add(pending.key, pending.operation, record);
+
+    // On WASM runtime, background tasks are not available, so the report is sent by `on_flush` instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    if self.reporter.is_flush_due() {
+      tracing::debug!("usage report is ready, sending it in the background");
+      let reporter = self.reporter.clone();
+      tokio::spawn(async move { reporter.flush().await });
+    }
+  }
+
+  async fn on_flush(&self) {
+    if self.reporter.is_flush_due() {
+      self.reporter.flush().await;
+    }
+  }
+
+  async fn on_shutdown(&self) {
+    self.reporter.flush().await;
+  }
+}
+
+fn operation_name(request: &ParsedGraphQLRequest) -> Option<String> {
+  match request.executable_operation() {
+    Some(Definition::Operation(OperationDefinition::Query(query))) => query.name.clone(),
+    Some(Definition::Operation(OperationDefinition::Mutation(mutation))) => mutation.name.clone(),
+    Some(Definition::Operation(OperationDefinition::Subscription(subscription))) => {
+      subscription.name.clone()
+    }
+    _ => None,
+  }
+}
+
+fn now_nanos() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_nanos() as u64)
+    .unwrap_or_default()
+}
+
+/// Sends the pending report at every `flush_interval`, until the plugin is dropped.
+#[cfg(not(target_arch = "wasm32"))]
+async fn flush_periodically(reporter: std::sync::Weak<UsageReporter>) {
+  loop {
+    let Some(flush_interval) = reporter.upgrade().map(|reporter| reporter.flush_interval()) else {
+      return;
+    };
+
+    tokio::time::sleep(flush_interval).await;
+
+    match reporter.upgrade() {
+      Some(reporter) => reporter.flush().await,
+      None => return,
+    }
+  }
+}
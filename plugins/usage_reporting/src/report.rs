@@ -0,0 +1,241 @@
+use std::{
+  collections::HashMap,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+  },
+  time::Duration,
+};
+
+use conductor_common::http::{HeaderValue, CONTENT_TYPE};
+use reqwest::header::AUTHORIZATION;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+use web_time::Instant;
+
+/// A usage report, based on the format of the [GraphQL Hive usage API](https://the-guild.dev/graphql/hive/docs/api-reference/usage-reporting).
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct UsageReport {
+  pub size: usize,
+  /// The operations of the report, by their key.
+  pub map: HashMap<String, OperationMapRecord>,
+  pub operations: Vec<OperationRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationMapRecord {
+  /// The normalized operation, without its literal values.
+  pub operation: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub operation_name: Option<String>,
+  /// The schema coordinates used by the operation.
+  pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationRecord {
+  pub operation_map_key: String,
+  /// The time the operation was received, in milliseconds since the Unix epoch.
+  pub timestamp: u64,
+  pub execution: OperationExecution,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub metadata: Option<OperationMetadata>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationExecution {
+  pub ok: bool,
+  /// The duration of the execution, in nanoseconds.
+  pub duration: u64,
+  pub errors_total: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OperationMetadata {
+  pub client: ClientInfo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClientInfo {
+  pub name: String,
+  pub version: String,
+}
+
+/// Collects the usage of operations, and sends it in batches to the registry.
+#[derive(Debug)]
+pub struct UsageReporter {
+  client: reqwest::Client,
+  endpoint: String,
+  token: String,
+  sample_rate: f64,
+  max_batch_size: usize,
+  flush_interval: Duration,
+  sampled_count: AtomicU64,
+  pending: Mutex<PendingReport>,
+}
+
+#[derive(Debug)]
+struct PendingReport {
+  report: UsageReport,
+  last_flush_at: Instant,
+}
+
+impl UsageReporter {
+  pub fn new(
+    endpoint: String,
+    token: String,
+    sample_rate: f64,
+    max_batch_size: usize,
+    flush_interval: Duration,
+  ) -> Result<Self, reqwest::Error> {
+    Ok(Self {
+      client: wasm_polyfills::create_http_client().build()?,
+      endpoint,
+      token,
+      sample_rate: sample_rate.clamp(0.0, 1.0),
+      max_batch_size: max_batch_size.max(1),
+      flush_interval,
+      sampled_count: AtomicU64::new(0),
+      pending: Mutex::new(PendingReport {
+        report: UsageReport::default(),
+        last_flush_at: Instant::now(),
+      }),
+    })
+  }
+
+  pub fn flush_interval(&self) -> Duration {
+    self.flush_interval
+  }
+
+  /// Decides if the next operation should be reported, based on the sample rate.
+  ///
+  /// Operations are sampled evenly: with a rate of `0.25`, one operation out of four is reported.
+  pub fn should_sample(&self) -> bool {
+    let count = self.sampled_count.fetch_add(1, Ordering::Relaxed) as f64;
+
+    ((count + 1.0) * self.sample_rate).floor() > (count * self.sample_rate).floor()
+  }
+
+  /// Adds an operation to the pending report.
+  pub fn add(&self, key: String, operation: OperationMapRecord, record: OperationRecord) {
+    let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+
+    pending.report.map.entry(key).or_insert(operation);
+    pending.report.operations.push(record);
+    pending.report.size = pending.report.operations.len();
+  }
+
+  /// Returns `true` when the pending report should be sent: the batch is full, or the `flush_interval` elapsed.
+  pub fn is_flush_due(&self) -> bool {
+    let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+
+    pending.report.size > 0
+      && (pending.report.size >= self.max_batch_size
+        || pending.last_flush_at.elapsed() >= self.flush_interval)
+  }
+
+  /// Sends the pending report to the registry. Failed reports are dropped.
+  pub async fn flush(&self) {
+    let report = {
+      let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+      pending.last_flush_at = Instant::now();
+
+      std::mem::take(&mut pending.report)
+    };
+
+    if report.operations.is_empty() {
+      return;
+    }
+
+    let body = match serde_json::to_string(&report) {
+      Ok(body) => body,
+      Err(e) => {
+        error!("failed to serialize usage report: {}", e);
+        return;
+      }
+    };
+
+    debug!(
+      "sending usage report with {} operations to {}",
+      report.size, self.endpoint
+    );
+
+    let result = self
+      .client
+      .post(&self.endpoint)
+      .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+      .header(AUTHORIZATION, format!("Bearer {}", self.token))
+      .header("X-Usage-API-Version", "2")
+      .body(body)
+      .send()
+      .await
+      .and_then(|response| response.error_for_status());
+
+    if let Err(e) = result {
+      error!("failed to send usage report: {}", e);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn create_reporter(sample_rate: f64, max_batch_size: usize) -> UsageReporter {
+    UsageReporter::new(
+      "http://localhost/usage".to_string(),
+      "token".to_string(),
+      sample_rate,
+      max_batch_size,
+      Duration::from_secs(60),
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn should_sample_evenly() {
+    let reporter = create_reporter(0.25, 1);
+    let sampled = (0..100).filter(|_| reporter.should_sample()).count();
+    assert_eq!(sampled, 25);
+
+    let reporter = create_reporter(0.0, 1);
+    assert!(!(0..100).any(|_| reporter.should_sample()));
+  }
+
+  #[test]
+  fn should_request_flush_when_batch_is_full() {
+    let reporter = create_reporter(1.0, 2);
+    let add = || {
+      reporter.add(
+        "key".to_string(),
+        OperationMapRecord {
+          operation: "query{a}".to_string(),
+          operation_name: None,
+          fields: vec!["Query.a".to_string()],
+        },
+        OperationRecord {
+          operation_map_key: "key".to_string(),
+          timestamp: 0,
+          execution: OperationExecution {
+            ok: true,
+            duration: 1,
+            errors_total: 0,
+          },
+          metadata: None,
+        },
+      )
+    };
+
+    add();
+    assert!(!reporter.is_flush_due());
+    add();
+    assert!(reporter.is_flush_due());
+
+    let pending = reporter.pending.lock().unwrap();
+    assert_eq!(pending.report.size, 2);
+    assert_eq!(pending.report.map.len(), 1);
+  }
+}
@@ -7,4 +7,6 @@ export default {
   'disable-introspection': 'Disable Introspection',
   'trusted-documents': 'Trusted Documents',
   'http-get': 'HTTP GET',
+  'usage-reporting': 'Usage Reporting',
+  'client-awareness': 'Client Awareness',
 };